@@ -0,0 +1,410 @@
+//! Operator CLI for the CrossChainExecutor.
+//!
+//! Wraps the executor's administrative methods in subcommands whose
+//! arguments are typed flags rather than hand-written JSON, and signs the
+//! resulting transactions locally with the operator's key. Views need no
+//! key.
+//!
+//! ```text
+//! intents --contract executor.testnet --key-file ~/.near-credentials/testnet/executor.testnet.json \
+//!     deploy --wasm res/near_intents.wasm --required-signatures 2 --agent agent.testnet
+//! intents --contract executor.testnet config update --unbonding-period-secs 604800
+//! intents --contract executor.testnet intent show 0x5c..e1
+//! ```
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Args, Parser, Subcommand};
+use near_crypto::{InMemorySigner, SecretKey};
+use near_primitives::transaction::{Action, DeployContractAction};
+use near_primitives::types::{AccountId, Balance, Gas};
+use serde_json::{json, Map, Value};
+
+mod near;
+
+use near::{function_call, NearClient};
+
+const ONE_NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+const TGAS: Gas = 1_000_000_000_000;
+
+/// Gas attached to administrative calls
+const DEFAULT_GAS: Gas = 30 * TGAS;
+
+#[derive(Parser)]
+#[command(name = "intents", about = "Operate a CrossChainExecutor deployment")]
+struct Cli {
+    /// RPC endpoint of the network the executor runs on
+    #[arg(
+        long,
+        env = "NEAR_NODE_URL",
+        default_value = "https://rpc.testnet.near.org"
+    )]
+    node_url: String,
+    /// Account the executor is deployed on
+    #[arg(long, env = "NEAR_EXECUTOR_ID")]
+    contract: AccountId,
+    /// near-cli credentials file of the signing account
+    #[arg(long, env = "NEAR_KEY_FILE")]
+    key_file: Option<PathBuf>,
+    /// Signing account, when not using a key file
+    #[arg(long, env = "NEAR_ACCOUNT_ID", requires = "private_key")]
+    account: Option<AccountId>,
+    /// Private key of the signing account, when not using a key file
+    #[arg(long, env = "NEAR_PRIVATE_KEY", hide_env_values = true)]
+    private_key: Option<SecretKey>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Deploy the executor to its account and initialize it
+    Deploy {
+        /// Compiled contract, see contract/build.sh
+        #[arg(long)]
+        wasm: PathBuf,
+        /// Validator approvals needed to execute an intent
+        #[arg(long)]
+        required_signatures: u32,
+        /// Initial agent account
+        #[arg(long)]
+        agent: AccountId,
+        /// Owner account, the executor's own account by default
+        #[arg(long)]
+        owner: Option<AccountId>,
+    },
+    /// Manage bridge validators
+    #[command(subcommand)]
+    Validator(ValidatorCommand),
+    /// Halt intent creation and execution
+    Pause,
+    /// Resume normal operation
+    Unpause,
+    /// Inspect or change the configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Inspect intents
+    #[command(subcommand)]
+    Intent(IntentCommand),
+    /// Refund a failed intent to its sender on the source chain
+    Refund { intent_id: String },
+}
+
+#[derive(Subcommand)]
+enum ValidatorCommand {
+    /// Register the signing account as a validator, bonding NEAR
+    Add {
+        /// ed25519 key the validator signs approvals with
+        #[arg(long)]
+        public_key: String,
+        /// NEAR to bond, e.g. 100 or 12.5
+        #[arg(long, value_parser = parse_near)]
+        bond: Balance,
+    },
+    /// Remove a validator and start unbonding its bond
+    Remove { validator: AccountId },
+    /// List the members of the validator role
+    List,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print the current configuration
+    Show,
+    /// Change configuration fields, leaving unset ones as they are
+    Update(ConfigUpdate),
+}
+
+#[derive(Args)]
+struct ConfigUpdate {
+    #[arg(long)]
+    required_signatures: Option<u32>,
+    #[arg(long)]
+    ft_transfer_tgas: Option<Gas>,
+    /// yoctoNEAR attached to `ft_transfer` calls
+    #[arg(long)]
+    ft_transfer_deposit: Option<Balance>,
+    #[arg(long)]
+    transfer_callback_tgas: Option<Gas>,
+    /// NEAR a validator must bond
+    #[arg(long, value_parser = parse_near)]
+    min_validator_bond: Option<Balance>,
+    #[arg(long)]
+    unbonding_period_secs: Option<u64>,
+    #[arg(long)]
+    require_sender_signature: Option<bool>,
+    #[arg(long)]
+    ft_transfer_call_tgas: Option<Gas>,
+    /// NEAR a solver must bond
+    #[arg(long, value_parser = parse_near)]
+    min_solver_bond: Option<Balance>,
+    #[arg(long)]
+    solver_claim_period_secs: Option<u64>,
+}
+
+#[derive(Subcommand)]
+enum IntentCommand {
+    /// Print an intent with its lifecycle history
+    Show { intent_id: String },
+    /// List intents in creation order
+    List {
+        /// Only intents with this status, e.g. Pending or Failed
+        #[arg(long)]
+        status: Option<String>,
+        #[arg(long, default_value_t = 0)]
+        from_index: u64,
+        #[arg(long, default_value_t = 50)]
+        limit: u64,
+    },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let signer = match (&cli.key_file, cli.account, cli.private_key) {
+        (Some(path), _, _) => Some(
+            InMemorySigner::from_file(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?,
+        ),
+        (None, Some(account), Some(private_key)) => {
+            Some(InMemorySigner::from_secret_key(account, private_key))
+        }
+        _ => None,
+    };
+    let near = NearClient::new(&cli.node_url, cli.contract, signer);
+
+    let result = match cli.command {
+        Command::Deploy {
+            wasm,
+            required_signatures,
+            agent,
+            owner,
+        } => {
+            let code = std::fs::read(&wasm)
+                .with_context(|| format!("Failed to read {}", wasm.display()))?;
+            near.transact(vec![
+                Action::DeployContract(DeployContractAction { code }),
+                function_call(
+                    "new",
+                    &json!({
+                        "required_signatures": required_signatures,
+                        "agent_account": agent,
+                        "owner_id": owner,
+                    }),
+                    0,
+                    DEFAULT_GAS,
+                ),
+            ])
+            .await?
+        }
+        Command::Validator(ValidatorCommand::Add { public_key, bond }) => {
+            near.call(
+                "register_validator",
+                json!({ "public_key": public_key }),
+                bond,
+                DEFAULT_GAS,
+            )
+            .await?
+        }
+        Command::Validator(ValidatorCommand::Remove { validator }) => {
+            near.call(
+                "remove_validator",
+                json!({ "validator": validator }),
+                0,
+                DEFAULT_GAS,
+            )
+            .await?
+        }
+        Command::Validator(ValidatorCommand::List) => {
+            near.view("get_role_members", json!({ "role": "Validator" }))
+                .await?
+        }
+        Command::Pause => near.call("pause", json!({}), 0, DEFAULT_GAS).await?,
+        Command::Unpause => near.call("unpause", json!({}), 0, DEFAULT_GAS).await?,
+        Command::Config(ConfigCommand::Show) => near.view("get_config", json!({})).await?,
+        Command::Config(ConfigCommand::Update(update)) => {
+            let update = config_update(&update);
+            anyhow::ensure!(!update.is_empty(), "Set at least one field to update");
+            near.call("update_config", json!({ "update": update }), 0, DEFAULT_GAS)
+                .await?
+        }
+        Command::Intent(IntentCommand::Show { intent_id }) => {
+            let intent = near
+                .view("get_intent", json!({ "intent_id": intent_id }))
+                .await?;
+            anyhow::ensure!(!intent.is_null(), "Intent {} not found", intent_id);
+            let history = near
+                .view("get_intent_history", json!({ "intent_id": intent_id }))
+                .await?;
+            json!({ "intent": intent, "history": history })
+        }
+        Command::Intent(IntentCommand::List {
+            status,
+            from_index,
+            limit,
+        }) => {
+            // U128 indexes are passed as strings
+            let from_index = from_index.to_string();
+            match status {
+                Some(status) => {
+                    // Failed carries its reason, any of which matches
+                    let status = match status.as_str() {
+                        "Failed" => json!({ "Failed": "" }),
+                        _ => json!(status),
+                    };
+                    near.view(
+                        "get_intents_by_status",
+                        json!({ "status": status, "from_index": from_index, "limit": limit }),
+                    )
+                    .await?
+                }
+                None => {
+                    near.view(
+                        "get_intents",
+                        json!({ "from_index": from_index, "limit": limit }),
+                    )
+                    .await?
+                }
+            }
+        }
+        Command::Refund { intent_id } => {
+            near.call(
+                "refund_intent",
+                json!({ "intent_id": intent_id }),
+                0,
+                DEFAULT_GAS,
+            )
+            .await?
+        }
+    };
+
+    if !result.is_null() {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    }
+    Ok(())
+}
+
+/// `ConfigUpdate` argument of `update_config`, holding the set fields only.
+/// Gas, U128 and U64 values are passed as strings.
+fn config_update(update: &ConfigUpdate) -> Map<String, Value> {
+    let secs_to_ns = |secs: u64| (secs * 1_000_000_000).to_string();
+    let tgas = |tgas: Gas| (tgas * TGAS).to_string();
+    let fields = [
+        (
+            "required_signatures",
+            update.required_signatures.map(Value::from),
+        ),
+        (
+            "ft_transfer_gas",
+            update.ft_transfer_tgas.map(tgas).map(Value::from),
+        ),
+        (
+            "ft_transfer_deposit",
+            update
+                .ft_transfer_deposit
+                .map(|deposit| Value::from(deposit.to_string())),
+        ),
+        (
+            "transfer_callback_gas",
+            update.transfer_callback_tgas.map(tgas).map(Value::from),
+        ),
+        (
+            "min_validator_bond",
+            update
+                .min_validator_bond
+                .map(|bond| Value::from(bond.to_string())),
+        ),
+        (
+            "unbonding_period",
+            update
+                .unbonding_period_secs
+                .map(secs_to_ns)
+                .map(Value::from),
+        ),
+        (
+            "require_sender_signature",
+            update.require_sender_signature.map(Value::from),
+        ),
+        (
+            "ft_transfer_call_gas",
+            update.ft_transfer_call_tgas.map(tgas).map(Value::from),
+        ),
+        (
+            "min_solver_bond",
+            update
+                .min_solver_bond
+                .map(|bond| Value::from(bond.to_string())),
+        ),
+        (
+            "solver_claim_period",
+            update
+                .solver_claim_period_secs
+                .map(secs_to_ns)
+                .map(Value::from),
+        ),
+    ];
+    fields
+        .into_iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value?)))
+        .collect()
+}
+
+/// Parse a NEAR amount with up to 24 decimals into yoctoNEAR
+fn parse_near(amount: &str) -> Result<Balance, String> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > 24 || (whole.is_empty() && fraction.is_empty()) {
+        return Err(format!("Invalid NEAR amount {}", amount));
+    }
+    let parse = |digits: &str| -> Result<Balance, String> {
+        if digits.is_empty() {
+            return Ok(0);
+        }
+        digits
+            .parse::<Balance>()
+            .map_err(|_| format!("Invalid NEAR amount {}", amount))
+    };
+    let fraction = parse(fraction)? * 10u128.pow(24 - fraction.len() as u32);
+    parse(whole)?
+        .checked_mul(ONE_NEAR)
+        .and_then(|whole| whole.checked_add(fraction))
+        .ok_or_else(|| format!("NEAR amount {} is too large", amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_near_amounts() {
+        assert_eq!(parse_near("100"), Ok(100 * ONE_NEAR));
+        assert_eq!(parse_near("12.5"), Ok(12 * ONE_NEAR + ONE_NEAR / 2));
+        assert_eq!(parse_near("0.000000000000000000000001"), Ok(1));
+        assert!(parse_near("1.0000000000000000000000001").is_err());
+        assert!(parse_near("ten").is_err());
+    }
+
+    #[test]
+    fn config_updates_hold_set_fields_only() {
+        let update = ConfigUpdate {
+            required_signatures: Some(3),
+            ft_transfer_tgas: Some(10),
+            ft_transfer_deposit: None,
+            transfer_callback_tgas: None,
+            min_validator_bond: None,
+            unbonding_period_secs: Some(60),
+            require_sender_signature: None,
+            ft_transfer_call_tgas: None,
+            min_solver_bond: None,
+            solver_claim_period_secs: None,
+        };
+        assert_eq!(
+            Value::Object(config_update(&update)),
+            json!({
+                "required_signatures": 3,
+                "ft_transfer_gas": "10000000000000",
+                "unbonding_period": "60000000000",
+            })
+        );
+    }
+}
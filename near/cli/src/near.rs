@@ -0,0 +1,112 @@
+//! Views and locally signed transactions on the executor.
+
+use anyhow::{bail, Context};
+use near_crypto::InMemorySigner;
+use near_jsonrpc_client::{methods, JsonRpcClient};
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::transaction::{Action, FunctionCallAction, Transaction};
+use near_primitives::types::{AccountId, Balance, BlockReference, Finality, FunctionArgs, Gas};
+use near_primitives::views::{FinalExecutionStatus, QueryRequest};
+use serde_json::Value;
+
+pub struct NearClient {
+    client: JsonRpcClient,
+    contract_id: AccountId,
+    signer: Option<InMemorySigner>,
+}
+
+impl NearClient {
+    pub fn new(rpc_url: &str, contract_id: AccountId, signer: Option<InMemorySigner>) -> Self {
+        Self {
+            client: JsonRpcClient::connect(rpc_url),
+            contract_id,
+            signer,
+        }
+    }
+
+    /// Call a view method of the executor on final state
+    pub async fn view(&self, method: &str, args: Value) -> anyhow::Result<Value> {
+        let response = self
+            .client
+            .call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+                request: QueryRequest::CallFunction {
+                    account_id: self.contract_id.clone(),
+                    method_name: method.to_string(),
+                    args: FunctionArgs::from(args.to_string().into_bytes()),
+                },
+            })
+            .await?;
+        match response.kind {
+            QueryResponseKind::CallResult(result) if result.result.is_empty() => Ok(Value::Null),
+            QueryResponseKind::CallResult(result) => Ok(serde_json::from_slice(&result.result)?),
+            _ => bail!("Unexpected response to {}", method),
+        }
+    }
+
+    /// Call a method of the executor, returning its JSON result
+    pub async fn call(
+        &self,
+        method: &str,
+        args: Value,
+        deposit: Balance,
+        gas: Gas,
+    ) -> anyhow::Result<Value> {
+        self.transact(vec![function_call(method, &args, deposit, gas)])
+            .await
+    }
+
+    /// Sign and send a transaction to the executor's account, waiting for
+    /// its outcome
+    pub async fn transact(&self, actions: Vec<Action>) -> anyhow::Result<Value> {
+        let signer = self
+            .signer
+            .as_ref()
+            .context("Pass --key-file, or --account and --private-key, to sign transactions")?;
+        let access_key = self
+            .client
+            .call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+                request: QueryRequest::ViewAccessKey {
+                    account_id: signer.account_id.clone(),
+                    public_key: signer.public_key.clone(),
+                },
+            })
+            .await?;
+        let nonce = match access_key.kind {
+            QueryResponseKind::AccessKey(key) => key.nonce,
+            _ => bail!("Unexpected access key response"),
+        };
+
+        let transaction = Transaction {
+            signer_id: signer.account_id.clone(),
+            public_key: signer.public_key.clone(),
+            nonce: nonce + 1,
+            receiver_id: self.contract_id.clone(),
+            block_hash: access_key.block_hash,
+            actions,
+        };
+        let outcome = self
+            .client
+            .call(methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest {
+                signed_transaction: transaction.sign(signer),
+            })
+            .await?;
+        eprintln!("Transaction {}", outcome.transaction.hash);
+        match outcome.status {
+            FinalExecutionStatus::SuccessValue(value) if value.is_empty() => Ok(Value::Null),
+            FinalExecutionStatus::SuccessValue(value) => Ok(serde_json::from_slice(&value)?),
+            FinalExecutionStatus::Failure(error) => bail!("Transaction failed: {}", error),
+            status => bail!("Transaction did not complete: {:?}", status),
+        }
+    }
+}
+
+pub fn function_call(method: &str, args: &Value, deposit: Balance, gas: Gas) -> Action {
+    Action::FunctionCall(FunctionCallAction {
+        method_name: method.to_string(),
+        args: args.to_string().into_bytes(),
+        gas,
+        deposit,
+    })
+}
@@ -3,16 +3,22 @@
 # Exit on error
 set -e
 
-echo "Building NEAR contract..."
+if ! command -v cargo-near > /dev/null; then
+    echo "cargo-near is required to embed the ABI, install it with: cargo install cargo-near"
+    exit 1
+fi
 
-# Build the contract
-RUSTFLAGS='-C link-arg=-s' cargo build --target wasm32-unknown-unknown --release
+# Network whose defaults are compiled in: mainnet (default) or testnet
+NETWORK="${1:-mainnet}"
+if [ "$NETWORK" != "mainnet" ] && [ "$NETWORK" != "testnet" ]; then
+    echo "Usage: $0 [mainnet|testnet]"
+    exit 1
+fi
 
-# Create res directory if it doesn't exist
-mkdir -p res
+echo "Building NEAR contract for $NETWORK..."
 
-# Copy wasm file to res directory
-cp target/wasm32-unknown-unknown/release/near_intents.wasm res/
+# Build the contract with its ABI embedded, served by the __contract_abi view
+cargo near build --release --embed-abi --out-dir res --features "$NETWORK"
 
-echo "Build complete! Contract binary is in res/near_intents.wasm"
-echo "To deploy, run: near deploy --accountId your.testnet.near --wasmFile res/near_intents.wasm"
\ No newline at end of file
+echo "Build complete! Contract binary is in res/near_intents.wasm, its ABI in res/near_intents_abi.json"
+echo "To deploy, run: near deploy --accountId your.testnet.near --wasmFile res/near_intents.wasm"
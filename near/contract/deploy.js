@@ -39,13 +39,27 @@ async function main() {
       contractId: process.env.NEAR_ACCOUNT_ID,
       methodName: 'new',
       args: {
-        token_contract: process.env.NEAR_TOKEN_CONTRACT,
         required_signatures: 1, // Set to appropriate value for production
-        agent_account: process.env.CDP_AGENT_ACCOUNT
+        agent_account: process.env.CDP_AGENT_ACCOUNT,
+        owner_id: process.env.NEAR_OWNER_ACCOUNT // Defaults to the contract account
       },
       gas: '300000000000000' // 300 TGas
     });
 
+    // Register the bridged token
+    if (process.env.BASE_TOKEN_ADDRESS && process.env.NEAR_TOKEN_CONTRACT) {
+      console.log('\nRegistering token...');
+      await account.functionCall({
+        contractId: process.env.NEAR_ACCOUNT_ID,
+        methodName: 'register_token',
+        args: {
+          base_token: process.env.BASE_TOKEN_ADDRESS,
+          near_contract: process.env.NEAR_TOKEN_CONTRACT
+        },
+        gas: '30000000000000' // 30 TGas
+      });
+    }
+
     console.log('\nDeployment and initialization complete!');
     console.log('Contract ID:', process.env.NEAR_ACCOUNT_ID);
     console.log('\nMake sure to update your .env file with the contract address');
@@ -0,0 +1,11 @@
+//! Arbitrary `{"method": .., "args": ..}` calls into the executor.
+//!
+//! Run from `contract/` with `cargo +nightly fuzz run method_args`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    near_intents::fuzzing::fuzz_method_args(data);
+});
@@ -0,0 +1,368 @@
+//! Multi-action intents.
+//!
+//! An intent may split the tokens it delivers across up to four actions:
+//! plain transfers, Ref Finance swaps paying out to the receiver, and
+//! `ft_transfer_call`s into contracts. The actions are part of the intent's
+//! typed hash and run one after another, each started from the callback of
+//! the one before once it went through. Every action pays the protocol fee
+//! on its own amount.
+//!
+//! An action failing stops the chain. While nothing was delivered yet the
+//! intent fails and is queued for refund on the source chain, as a single
+//! delivery would. Delivered actions cannot be recalled, so once one went
+//! through, the tokens of the failed action and of every action after it
+//! are sent on to the receiver instead, and the intent fails once that
+//! compensating transfer landed. One that did not land leaves the intent
+//! executing with the amount it owes the receiver queued, and anyone can
+//! send it again with `retry_compensation` until it lands. Multi-action
+//! intents move a single fungible token.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    env, near_bindgen, serde_json, AccountId, Gas, Promise, PromiseOrValue, PromiseResult,
+};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::swap::{SwapAction, ON_SWAPPED_GAS};
+use crate::tokens::is_native_token;
+use crate::{
+    CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, DeliveryKind, IntentStatus,
+};
+
+/// Largest number of actions an intent carries
+const MAX_ACTIONS: usize = 4;
+
+/// Most gas running an intent's actions may take, leaving room for the
+/// execution checks within a transaction's gas
+const MAX_ACTIONS_GAS: Gas = Gas(250_000_000_000_000);
+
+/// Step of a multi-action intent, its amount in source decimals
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde", tag = "kind", rename_all = "snake_case")]
+pub enum IntentAction {
+    /// Send tokens to an account
+    Transfer {
+        receiver_id: AccountId,
+        amount: U128,
+    },
+    /// Swap tokens on Ref Finance, paying the output to the intent's receiver
+    Swap { amount: U128, swap: SwapAction },
+    /// Send tokens to a contract with `ft_transfer_call`, which must use
+    /// all of them
+    Call {
+        receiver_id: AccountId,
+        amount: U128,
+        msg: String,
+    },
+}
+
+impl IntentAction {
+    pub fn amount(&self) -> U128 {
+        match self {
+            IntentAction::Transfer { amount, .. }
+            | IntentAction::Swap { amount, .. }
+            | IntentAction::Call { amount, .. } => *amount,
+        }
+    }
+}
+
+impl CrossChainIntent {
+    /// Accounts the intent's transfers and calls pay, other than its receiver
+    pub(crate) fn action_receivers(&self) -> impl Iterator<Item = &AccountId> {
+        self.actions.iter().filter_map(|action| match action {
+            IntentAction::Transfer { receiver_id, .. } | IntentAction::Call { receiver_id, .. } => {
+                Some(receiver_id)
+            }
+            IntentAction::Swap { .. } => None,
+        })
+    }
+
+    /// Swaps made by the intent's actions
+    pub(crate) fn action_swaps(&self) -> impl Iterator<Item = &SwapAction> {
+        self.actions.iter().filter_map(|action| match action {
+            IntentAction::Swap { swap, .. } => Some(swap),
+            _ => None,
+        })
+    }
+
+    /// Earliest deadline of the swaps the intent makes on arrival or in its
+    /// actions
+    pub(crate) fn swap_deadline(&self) -> Option<u64> {
+        self.swap
+            .iter()
+            .chain(self.action_swaps())
+            .map(|swap| swap.deadline.0)
+            .min()
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Start the next action of an intent once the one at `index`
+    /// delivered `amount`, or stop and compensate the receiver
+    #[private]
+    pub fn on_action_complete(
+        &mut self,
+        intent_id: String,
+        index: u32,
+        fee: U128,
+        amount: U128,
+    ) -> PromiseOrValue<bool> {
+        let mut intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        let index = index as usize;
+        let used = match env::promise_result(0) {
+            // Tokens a contract did not use are refunded to the executor
+            PromiseResult::Successful(result)
+                if matches!(intent.actions[index], IntentAction::Call { .. }) =>
+            {
+                serde_json::from_slice::<U128>(&result)
+                    .map_or(amount.0, |used| used.0.min(amount.0))
+            }
+            PromiseResult::Successful(_) => amount.0,
+            _ => 0,
+        };
+
+        if used < amount.0 {
+            self.settle_execution_gas(&intent_id, false);
+            if index == 0 && used == 0 {
+                self.fail_intent(&mut intent, "action failed");
+                return PromiseOrValue::Value(false);
+            }
+            return PromiseOrValue::Promise(self.compensate_actions(&intent, index, used));
+        }
+
+        if fee.0 > 0 {
+            let protocol_fee = self.split_referral_fee(&intent, &intent.token, fee.0);
+            self.accrue_fee(&intent.token, protocol_fee);
+            IntentEvent::FeeCollected {
+                intent_id: intent_id.clone(),
+                token: intent.token.clone(),
+                amount: fee,
+            }
+            .emit();
+        }
+        if index + 1 < intent.actions.len() {
+            return PromiseOrValue::Promise(self.start_action(&intent, index + 1));
+        }
+
+        self.settle_execution_gas(&intent_id, true);
+        self.count_volume(&intent, &intent.token, intent.amount.0);
+        self.transition(&mut intent, IntentStatus::Completed);
+        IntentEvent::IntentCompleted { intent_id }.emit();
+        PromiseOrValue::Value(true)
+    }
+
+    /// Fail a multi-action intent once its compensating transfer of
+    /// `compensated` to the receiver resolved
+    #[private]
+    pub fn on_actions_compensated(
+        &mut self,
+        intent_id: String,
+        failed_action: u32,
+        compensated: U128,
+    ) -> bool {
+        let mut intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        let returned = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        if returned {
+            self.mark_failed(&mut intent, "actions partially executed");
+            IntentEvent::ActionsRolledBack {
+                intent_id,
+                failed_action,
+                compensated,
+            }
+            .emit();
+        } else {
+            // The executor still holds the tokens, so they wait for a retry
+            self.failed_compensations
+                .insert(intent_id.clone(), (failed_action, compensated));
+            IntentEvent::CompensationFailed {
+                intent_id,
+                failed_action,
+                compensated,
+            }
+            .emit();
+        }
+        returned
+    }
+
+    /// Send the compensating transfer of a multi-action intent again after
+    /// it failed. Callable by anyone.
+    pub fn retry_compensation(&mut self, intent_id: String) -> Promise {
+        self.assert_not_paused();
+        let needed = self.config.ft_transfer_gas.0 + self.config.transfer_callback_gas.0;
+        require(
+            env::prepaid_gas().0.saturating_sub(env::used_gas().0) >= needed,
+            ContractError::NotEnoughGas,
+        );
+        let (failed_action, compensated) = self
+            .failed_compensations
+            .remove(&intent_id)
+            .unwrap_or_else(|| ContractError::NoFailedCompensation.panic());
+        let intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        self.send_compensation(&intent, failed_action, compensated)
+    }
+
+    /// List multi-action intents whose compensating transfer failed, with
+    /// the amount each still owes its receiver
+    pub fn get_failed_compensations(&self) -> Vec<(String, U128)> {
+        self.failed_compensations
+            .iter()
+            .map(|(intent_id, (_, compensated))| (intent_id.clone(), *compensated))
+            .collect()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Check the actions of a new intent split its amount and can run
+    pub(crate) fn check_actions(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        if intent.actions.is_empty() {
+            return Ok(());
+        }
+        if intent.actions.len() > MAX_ACTIONS {
+            return Err(ContractError::TooManyActions);
+        }
+        if intent.delivery_kind() != DeliveryKind::Actions
+            || intent.execute_after.is_some()
+            || intent.depends_on.is_some()
+        {
+            return Err(ContractError::ActionDelivery);
+        }
+        if is_native_token(&intent.token) {
+            return Err(ContractError::NativeCannotSplit);
+        }
+
+        let mut total: u128 = 0;
+        for action in &intent.actions {
+            if action.amount().0 == 0 {
+                return Err(ContractError::ActionAmountNotPositive);
+            }
+            total = total
+                .checked_add(action.amount().0)
+                .ok_or(ContractError::ActionAmountsMismatch)?;
+            if let IntentAction::Swap { swap, .. } = action {
+                if self.ref_contract.is_none() {
+                    return Err(ContractError::RefNotConfigured);
+                }
+                if swap.deadline.0 < env::block_timestamp() {
+                    return Err(ContractError::SwapDeadlinePassed);
+                }
+            }
+        }
+        if total != intent.amount.0 {
+            return Err(ContractError::ActionAmountsMismatch);
+        }
+        if self.actions_gas(intent).0 > MAX_ACTIONS_GAS.0 {
+            return Err(ContractError::ActionsOutOfGas);
+        }
+        Ok(())
+    }
+
+    /// Start the action at `index` of an executing intent, withholding its
+    /// fee, with the callback moving on to the next one
+    pub(crate) fn start_action(&self, intent: &CrossChainIntent, index: usize) -> Promise {
+        let action = &intent.actions[index];
+        let amount = self
+            .rescale_amount(intent.chain_id, &intent.token, action.amount().0)
+            .unwrap_or_else(|| ContractError::AmountOverflow.panic());
+        let fee = self.compute_fee(&intent.token, amount);
+        let delivered = U128(amount - fee);
+        let transfer = match action {
+            IntentAction::Transfer { receiver_id, .. } => {
+                self.deliver_token(intent.chain_id, &intent.token, receiver_id, delivered)
+            }
+            IntentAction::Swap { swap, .. } => self.swap_on_ref(intent, swap, delivered),
+            IntentAction::Call {
+                receiver_id, msg, ..
+            } => self.transfer_call_token(
+                intent.chain_id,
+                &intent.token,
+                receiver_id,
+                delivered,
+                msg.clone(),
+            ),
+        };
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(self.action_callback_gas(intent, index))
+                .on_action_complete(intent.id.clone(), index as u32, U128(fee), delivered),
+        )
+    }
+
+    /// Gas the actions of an intent take with the callbacks between them
+    pub(crate) fn actions_gas(&self, intent: &CrossChainIntent) -> Gas {
+        match intent.actions.first() {
+            Some(action) => {
+                Gas(self.action_gas(intent, action).0 + self.action_callback_gas(intent, 0).0)
+            }
+            None => Gas(0),
+        }
+    }
+
+    /// Send the receiver the tokens of the failed action at `index` its
+    /// contract left unused, fee included, and of every action after it
+    fn compensate_actions(&self, intent: &CrossChainIntent, index: usize, used: u128) -> Promise {
+        let compensated = intent.actions[index..]
+            .iter()
+            .map(|action| {
+                self.rescale_amount(intent.chain_id, &intent.token, action.amount().0)
+                    .unwrap_or_else(|| ContractError::AmountOverflow.panic())
+            })
+            .sum::<u128>()
+            - used;
+        self.send_compensation(intent, index as u32, U128(compensated))
+    }
+
+    /// Send `compensated` of an intent's token to its receiver for the
+    /// actions from `failed_action` on
+    fn send_compensation(
+        &self,
+        intent: &CrossChainIntent,
+        failed_action: u32,
+        compensated: U128,
+    ) -> Promise {
+        self.deliver_token(
+            intent.chain_id,
+            &intent.token,
+            &intent.receiver,
+            compensated,
+        )
+        .then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(self.config.transfer_callback_gas)
+                .on_actions_compensated(intent.id.clone(), failed_action, compensated),
+        )
+    }
+
+    /// Gas the callback of the action at `index` needs to settle it and
+    /// start every later action, or the compensating transfer
+    fn action_callback_gas(&self, intent: &CrossChainIntent, index: usize) -> Gas {
+        let settle_gas = 2 * self.config.transfer_callback_gas.0
+            + self.token_transfer_gas(intent.chain_id, &intent.token).0;
+        let later: u64 = intent.actions[index + 1..]
+            .iter()
+            .map(|action| self.action_gas(intent, action).0 + settle_gas)
+            .sum();
+        Gas(settle_gas + later)
+    }
+
+    /// Gas attached to the transfers of one action
+    fn action_gas(&self, intent: &CrossChainIntent, action: &IntentAction) -> Gas {
+        let transfer_gas = self.token_transfer_gas(intent.chain_id, &intent.token).0;
+        let transfer_call_gas = transfer_gas + self.config.ft_transfer_call_gas.0;
+        Gas(match action {
+            IntentAction::Transfer { .. } => transfer_gas,
+            IntentAction::Swap { .. } => transfer_call_gas + ON_SWAPPED_GAS.0,
+            IntentAction::Call { .. } => transfer_call_gas,
+        })
+    }
+}
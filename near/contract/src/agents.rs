@@ -0,0 +1,148 @@
+//! CDP agent registry.
+//!
+//! Any account holding the Agent role may operate intents. The owner can
+//! additionally deactivate an agent without revoking it, or cap the volume
+//! it executes per UTC day. Volume is summed in raw token units across all
+//! tokens, so quotas are a coarse blast-radius limit rather than a value cap.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Balance};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+const NANOS_PER_DAY: u64 = 86_400 * 1_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AgentConfig {
+    pub active: bool,
+    /// Maximum volume executed per day, unlimited when unset
+    pub daily_quota: Option<U128>,
+    /// Day (since the Unix epoch) `used_today` was accumulated in
+    pub day: u64,
+    pub used_today: U128,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            active: true,
+            daily_quota: None,
+            day: 0,
+            used_today: U128(0),
+        }
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Register an agent account with an optional daily volume quota
+    pub fn add_agent(&mut self, account_id: AccountId, daily_quota: Option<U128>) {
+        self.assert_role(&[Role::Owner]);
+        if !self.has_role(Role::Agent, account_id.clone()) {
+            self.grant(Role::Agent, &account_id);
+        }
+        let config = AgentConfig {
+            daily_quota,
+            ..self.agents.get(&account_id).cloned().unwrap_or_default()
+        };
+        self.set_agent_config(&account_id, config);
+    }
+
+    /// Revoke an agent and drop its configuration
+    pub fn remove_agent(&mut self, account_id: AccountId) {
+        self.assert_role(&[Role::Owner]);
+        self.revoke(Role::Agent, &account_id);
+        self.agents.remove(&account_id);
+    }
+
+    /// Activate or deactivate an agent without revoking it
+    pub fn set_agent_active(&mut self, account_id: AccountId, active: bool) {
+        self.assert_role(&[Role::Owner]);
+        let config = AgentConfig {
+            active,
+            ..self.agent_config(&account_id)
+        };
+        self.set_agent_config(&account_id, config);
+    }
+
+    /// Change an agent's daily volume quota
+    pub fn set_agent_quota(&mut self, account_id: AccountId, daily_quota: Option<U128>) {
+        self.assert_role(&[Role::Owner]);
+        let config = AgentConfig {
+            daily_quota,
+            ..self.agent_config(&account_id)
+        };
+        self.set_agent_config(&account_id, config);
+    }
+
+    /// Get an agent's configuration and today's usage
+    pub fn get_agent(&self, account_id: AccountId) -> Option<AgentConfig> {
+        self.has_role(Role::Agent, account_id.clone())
+            .then(|| self.agents.get(&account_id).cloned().unwrap_or_default())
+    }
+
+    /// List registered agent accounts
+    pub fn get_agents(&self) -> Vec<AccountId> {
+        self.get_role_members(Role::Agent)
+    }
+}
+
+impl CrossChainExecutor {
+    /// Assert the caller is an active agent, returning the caller
+    pub(crate) fn assert_agent(&self) -> AccountId {
+        let agent = self.assert_role(&[Role::Agent]);
+        require(
+            self.agents.get(&agent).is_none_or(|config| config.active),
+            ContractError::AgentInactive,
+        );
+        agent
+    }
+
+    /// Count `amount` against the agent's quota for the current day
+    pub(crate) fn consume_agent_quota(&mut self, agent: &AccountId, amount: Balance) {
+        let config = match self.agents.get_mut(agent) {
+            Some(config) => config,
+            None => return,
+        };
+        let today = env::block_timestamp() / NANOS_PER_DAY;
+        if config.day != today {
+            config.day = today;
+            config.used_today = U128(0);
+        }
+
+        let used = config
+            .used_today
+            .0
+            .checked_add(amount)
+            .unwrap_or_else(|| ContractError::AgentVolumeOverflow.panic());
+        if let Some(quota) = config.daily_quota {
+            require(used <= quota.0, ContractError::AgentQuotaExceeded);
+        }
+        config.used_today = U128(used);
+    }
+
+    fn agent_config(&self, account_id: &AccountId) -> AgentConfig {
+        require(
+            self.has_role(Role::Agent, account_id.clone()),
+            ContractError::NotAgent,
+        );
+        self.agents.get(account_id).cloned().unwrap_or_default()
+    }
+
+    fn set_agent_config(&mut self, account_id: &AccountId, config: AgentConfig) {
+        IntentEvent::AgentConfigured {
+            agent: account_id.clone(),
+            active: config.active,
+            daily_quota: config.daily_quota,
+        }
+        .emit();
+        self.agents.insert(account_id.clone(), config);
+    }
+}
@@ -0,0 +1,137 @@
+//! Aggregated validator attestations.
+//!
+//! Verifying one ed25519 signature per validator gets expensive as the set
+//! grows. Validators can instead produce a single aggregated ed25519 (MuSig)
+//! signature, which verifies like any other ed25519 signature under the sum
+//! of their public keys. An aggregate key stands in for a chain's quorum,
+//! so it is registered per chain and epoch only once that quorum has signed
+//! it: the validators attesting for the chain in the current epoch,
+//! reaching the chain's threshold. The owner may still revoke a key.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, CurveType, PublicKey};
+use schemars::JsonSchema;
+
+use crate::chains::chain_or_base;
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::history::IntentStep;
+use crate::roles::Role;
+use crate::{
+    verify_ed25519, CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, IntentStatus,
+    ValidatorSignature,
+};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AggregateSignature {
+    /// Epoch whose aggregate key produced the signature
+    pub epoch: u64,
+    pub signature: Base64VecU8, // 64-byte aggregated ed25519 signature
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Register the aggregate validator key of a chain, Base by default, for
+    /// the current epoch. `signatures` are over [`aggregate_key_hash`] and
+    /// must reach the chain's quorum. Callable by anyone, typically the
+    /// relayer.
+    pub fn set_aggregate_key(
+        &mut self,
+        public_key: PublicKey,
+        signatures: Vec<ValidatorSignature>,
+        chain_id: Option<u64>,
+    ) {
+        let chain_id = chain_or_base(chain_id);
+        let chain = self.chain(chain_id);
+        require(
+            public_key.curve_type() == CurveType::ED25519,
+            ContractError::AggregateKeyNotEd25519,
+        );
+        let epoch = self.current_epoch;
+        let message = aggregate_key_hash(chain_id, epoch, &public_key);
+        let signers = self.count_epoch_signatures(epoch, &message, &signatures, |validator| {
+            self.attests_for_chain(&chain, validator)
+        });
+        require(
+            signers >= self.chain_threshold(&chain, epoch),
+            ContractError::NotEnoughSignatures,
+        );
+        self.aggregate_keys.insert((chain_id, epoch), public_key);
+    }
+
+    /// Revoke the aggregate validator key of a chain's epoch, Base by default
+    pub fn remove_aggregate_key(&mut self, epoch: u64, chain_id: Option<u64>) {
+        self.assert_role(&[Role::Owner]);
+        self.aggregate_keys
+            .remove(&(chain_or_base(chain_id), epoch));
+    }
+
+    /// Get the aggregate validator key of a chain's epoch, Base by default
+    pub fn get_aggregate_key(&self, epoch: u64, chain_id: Option<u64>) -> Option<PublicKey> {
+        self.aggregate_keys
+            .get(&(chain_or_base(chain_id), epoch))
+            .cloned()
+    }
+
+    /// Attest a pending intent with an aggregated validator signature over
+    /// its signing hash. Callable by anyone, typically the relayer.
+    pub fn attest_intent(&mut self, intent_id: String, attestation: AggregateSignature) {
+        let intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        require(
+            matches!(intent.status, IntentStatus::Pending),
+            ContractError::AttestationNotPending,
+        );
+        require(
+            self.verify_aggregate(intent.chain_id, &intent.signing_hash(), &attestation),
+            ContractError::InvalidAggregateSignature,
+        );
+        let previous = self
+            .aggregate_attestations
+            .insert(intent_id.clone(), attestation.epoch);
+        if previous != Some(attestation.epoch) {
+            self.record_step(&intent_id, IntentStep::Attested(attestation.epoch));
+        }
+
+        IntentEvent::IntentAttested {
+            intent_id,
+            epoch: attestation.epoch,
+        }
+        .emit();
+    }
+}
+
+impl CrossChainExecutor {
+    /// Whether `attestation` is a valid signature on `message` under an
+    /// aggregate key registered for the chain
+    pub(crate) fn verify_aggregate(
+        &self,
+        chain_id: u64,
+        message: &[u8],
+        attestation: &AggregateSignature,
+    ) -> bool {
+        self.aggregate_keys
+            .get(&(chain_id, attestation.epoch))
+            .is_some_and(|public_key| verify_ed25519(public_key, message, &attestation.signature.0))
+    }
+
+    /// Whether the intent was attested under an aggregate key of its chain
+    /// that is still registered
+    pub(crate) fn has_aggregate_attestation(&self, intent: &CrossChainIntent) -> bool {
+        self.aggregate_attestations
+            .get(&intent.id)
+            .is_some_and(|epoch| self.aggregate_keys.contains_key(&(intent.chain_id, *epoch)))
+    }
+}
+
+/// Hash validators sign to register the aggregate key of a chain's epoch
+pub fn aggregate_key_hash(chain_id: u64, epoch: u64, public_key: &PublicKey) -> Vec<u8> {
+    let payload = ("set_aggregate_key", chain_id, epoch, public_key)
+        .try_to_vec()
+        .expect("Failed to serialize aggregate key");
+    env::sha256(&payload)
+}
@@ -0,0 +1,74 @@
+//! Automatic circuit breaker on anomalous volume.
+//!
+//! Once the owner sets a multiple, every execution compares the volume it
+//! would bring a token to in the current hour against the token's average
+//! hourly volume over the rest of the rolling 24-hour window, using the same
+//! buckets as the volume caps. An execution exceeding the multiple pauses
+//! the contract and emits an `anomaly_detected` alert before any transfer
+//! starts, so a compromised agent or validator set cannot drain a token
+//! faster than its history suggests. The intent stays pending until the
+//! owner, having investigated, unpauses. Tokens without trailing volume
+//! have no baseline and are left to their volume caps.
+
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, Balance};
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::tokens::normalize_address;
+use crate::volume_caps::BUCKETS_PER_WINDOW;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the multiple of a token's trailing average hourly volume at which
+    /// executions pause the contract, or disable the guard
+    pub fn set_anomaly_multiple(&mut self, multiple: Option<u32>) {
+        self.assert_role(&[Role::Owner]);
+        require(
+            multiple.is_none_or(|multiple| multiple > 1),
+            ContractError::InvalidMultiple,
+        );
+        self.anomaly_multiple = multiple;
+    }
+
+    /// Multiple of the trailing average hourly volume that trips the guard
+    pub fn get_anomaly_multiple(&self) -> Option<u32> {
+        self.anomaly_multiple
+    }
+}
+
+impl CrossChainExecutor {
+    /// Pause the contract if executing `intent` would make the hourly volume
+    /// of any of its tokens anomalous, returning whether it did
+    pub(crate) fn trip_anomaly_guard(&mut self, intent: &CrossChainIntent) -> bool {
+        let Some(multiple) = self.anomaly_multiple else {
+            return false;
+        };
+        let anomaly = intent.assets().find_map(|(token, amount)| {
+            let (current, trailing) = self.hourly_volumes(token);
+            let average = trailing / Balance::from(BUCKETS_PER_WINDOW - 1);
+            let volume = current.saturating_add(amount.0);
+            (average > 0 && volume > average.saturating_mul(Balance::from(multiple)))
+                .then(|| (normalize_address(token), volume, average))
+        });
+        let Some((token, volume, average)) = anomaly else {
+            return false;
+        };
+
+        self.paused = true;
+        IntentEvent::AnomalyDetected {
+            intent_id: intent.id.clone(),
+            token,
+            volume: U128(volume),
+            average: U128(average),
+        }
+        .emit();
+        IntentEvent::Paused {
+            by: env::current_account_id(),
+        }
+        .emit();
+        true
+    }
+}
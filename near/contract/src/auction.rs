@@ -0,0 +1,125 @@
+//! Dutch auctions for unclaimed intents.
+//!
+//! An agent can put a pending intent no solver has claimed up for auction.
+//! The minimum output a quote must offer the receiver starts at the full
+//! deliverable amount and worsens linearly to a floor at the deadline,
+//! staying there after it. The floor is never below the minimum output the
+//! sender signed, and intents without one cannot be auctioned. Auctioned intents can no longer be claimed;
+//! solvers quote instead, and quotes that fall below the current minimum
+//! are neither accepted nor selected.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, Balance};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, IntentStatus};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Auction {
+    /// Minimum output when the auction starts, the full deliverable amount
+    pub start_out: U128,
+    /// Minimum output from the deadline on
+    pub floor_out: U128,
+    pub starts_at: U64,
+    pub deadline: U64,
+}
+
+impl Auction {
+    /// Minimum output a quote must offer at `timestamp`
+    pub fn min_out_at(&self, timestamp: u64) -> Balance {
+        let duration = Balance::from(self.deadline.0 - self.starts_at.0);
+        let elapsed =
+            Balance::from(timestamp.clamp(self.starts_at.0, self.deadline.0) - self.starts_at.0);
+        let decay = self.start_out.0 - self.floor_out.0;
+        // Split the multiplication so large amounts cannot overflow
+        let decayed = decay / duration * elapsed + decay % duration * elapsed / duration;
+        self.start_out.0 - decayed
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Auction a pending, unclaimed intent, lowering the minimum output
+    /// from the full deliverable amount to `floor_out` by `deadline`
+    pub fn start_auction(&mut self, intent_id: String, floor_out: U128, deadline: U64) -> Auction {
+        self.assert_agent();
+        let intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        require(
+            matches!(intent.status, IntentStatus::Pending),
+            ContractError::NotPending,
+        );
+        require(
+            self.live_claim(&intent_id).is_none(),
+            ContractError::AlreadyClaimed,
+        );
+        require(
+            self.auctions.get(&intent_id).is_none(),
+            ContractError::AlreadyAuctioned,
+        );
+        let now = env::block_timestamp();
+        require(
+            deadline.0 > now && deadline.0 <= intent.expires_at.0,
+            ContractError::InvalidAuctionDeadline,
+        );
+        let min_out = intent
+            .min_out
+            .unwrap_or_else(|| ContractError::TakesNoQuotes.panic());
+        require(floor_out.0 >= min_out.0, ContractError::FloorBelowMinimum);
+        let start_out = self.deliverable_amount(&intent);
+        require(
+            floor_out.0 <= start_out,
+            ContractError::FloorAboveDeliverable,
+        );
+
+        let auction = Auction {
+            start_out: U128(start_out),
+            floor_out,
+            starts_at: U64(now),
+            deadline,
+        };
+        self.auctions.insert(intent_id.clone(), auction.clone());
+
+        IntentEvent::AuctionStarted {
+            intent_id,
+            start_out: auction.start_out,
+            floor_out,
+            deadline,
+        }
+        .emit();
+        auction
+    }
+
+    /// Get the auction of an intent
+    pub fn get_auction(&self, intent_id: String) -> Option<Auction> {
+        self.auctions.get(&intent_id).cloned()
+    }
+
+    /// Minimum output a quote for an auctioned intent must currently offer
+    pub fn get_min_out(&self, intent_id: String) -> Option<U128> {
+        self.auctions
+            .get(&intent_id)
+            .map(|auction| U128(auction.min_out_at(env::block_timestamp())))
+    }
+}
+
+impl CrossChainExecutor {
+    /// Minimum output quotes for an intent must currently offer, zero when
+    /// the intent is not auctioned
+    pub(crate) fn current_min_out(&self, intent_id: &String) -> Balance {
+        self.auctions
+            .get(intent_id)
+            .map_or(0, |auction| auction.min_out_at(env::block_timestamp()))
+    }
+
+    /// Drop the auction of an intent that no longer takes quotes
+    pub(crate) fn clear_auction(&mut self, intent_id: &String) {
+        self.auctions.remove(intent_id);
+    }
+}
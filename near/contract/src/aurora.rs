@@ -0,0 +1,79 @@
+//! Aurora EVM delivery.
+//!
+//! Intents may deliver to an address on Aurora instead of a NEAR account.
+//! Their receiver is the Aurora engine and `aurora_recipient` names the EVM
+//! address, which the typed hash covers. On execution the delivered tokens
+//! are deposited into the engine with `ft_transfer_call`, whose message is
+//! the recipient address in the hex form the engine expects, and land as
+//! the token's ERC-20 on Aurora. The token must be bridged to Aurora
+//! already, or the engine refunds the deposit and the intent fails.
+
+use near_sdk::json_types::U128;
+use near_sdk::{near_bindgen, AccountId, Promise};
+
+use crate::errors::ContractError;
+use crate::proof::parse_hex;
+use crate::roles::Role;
+use crate::tokens::{is_native_token, normalize_address};
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, DeliveryKind};
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the Aurora engine contract, or stop accepting Aurora recipients
+    pub fn set_aurora_engine(&mut self, contract_id: Option<AccountId>) {
+        self.assert_role(&[Role::Owner]);
+        self.aurora_engine = contract_id;
+    }
+
+    /// Get the Aurora engine contract
+    pub fn get_aurora_engine(&self) -> Option<AccountId> {
+        self.aurora_engine.clone()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Check a new intent's Aurora recipient can be delivered to
+    pub(crate) fn check_aurora(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        let Some(recipient) = &intent.aurora_recipient else {
+            return Ok(());
+        };
+        let Some(engine) = &self.aurora_engine else {
+            return Err(ContractError::AuroraNotConfigured);
+        };
+        if &intent.receiver != engine {
+            return Err(ContractError::AuroraReceiver);
+        }
+        if parse_hex(recipient).is_none_or(|bytes| bytes.len() != 20) {
+            return Err(ContractError::InvalidAuroraRecipient);
+        }
+        if intent.delivery_kind() != DeliveryKind::Aurora {
+            return Err(ContractError::AuroraDelivery);
+        }
+        if is_native_token(&intent.token) {
+            return Err(ContractError::NativeCannotGoToAurora);
+        }
+        Ok(())
+    }
+
+    /// Deposit `amount` of an intent's token into the Aurora engine for
+    /// `recipient`. Fails unless the engine kept all of it.
+    pub(crate) fn deposit_to_aurora(
+        &self,
+        intent: &CrossChainIntent,
+        recipient: &str,
+        amount: U128,
+    ) -> Promise {
+        let engine = self
+            .aurora_engine
+            .clone()
+            .unwrap_or_else(|| ContractError::AuroraNotConfigured.panic());
+        let recipient = normalize_address(recipient);
+        self.deposit_token(
+            intent.chain_id,
+            &intent.token,
+            &engine,
+            amount,
+            recipient.trim_start_matches("0x").to_string(),
+        )
+    }
+}
@@ -0,0 +1,127 @@
+//! Multi-asset basket intents.
+//!
+//! An intent can deliver further assets to its receiver alongside its
+//! primary token, for example USDC plus a NEAR gas top-up. Each extra asset
+//! is locked by its own `TokensLocked` log in the same source chain receipt
+//! as the primary deposit and is bound into the intent's typed hash.
+//! Execution starts one transfer per asset, joins them and settles the
+//! intent from a single callback that sees the result of every transfer.
+//!
+//! Delivered transfers cannot be recalled. An intent only some of whose
+//! transfers failed is therefore failed without being queued for refund,
+//! and its undelivered assets have to be released by the owner out of band.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, Promise, PromiseResult};
+use schemars::JsonSchema;
+
+use crate::errors::ContractError;
+use crate::tokens::{is_native_token, normalize_address};
+use crate::{CrossChainExecutor, CrossChainIntent};
+
+/// Largest number of assets an intent delivers besides its primary token
+const MAX_EXTRA_ASSETS: usize = 4;
+
+/// Asset an intent delivers besides its primary token
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BasketAsset {
+    /// Token address on the source chain
+    pub token: String,
+    pub amount: U128,
+    /// Index of the asset's `TokensLocked` log in the deposit receipt
+    pub log_index: u64,
+}
+
+impl CrossChainIntent {
+    /// Whether the intent delivers more than its primary token
+    pub fn is_basket(&self) -> bool {
+        !self.extra_assets.is_empty()
+    }
+
+    /// Tokens and amounts of every asset the intent delivers, primary first
+    pub(crate) fn assets(&self) -> impl Iterator<Item = (&String, U128)> {
+        std::iter::once((&self.token, self.amount)).chain(
+            self.extra_assets
+                .iter()
+                .map(|asset| (&asset.token, asset.amount)),
+        )
+    }
+}
+
+impl CrossChainExecutor {
+    /// Check the extra assets of a new intent can be delivered
+    pub(crate) fn check_basket(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        if !intent.is_basket() {
+            return Ok(());
+        }
+        if intent.extra_assets.len() > MAX_EXTRA_ASSETS {
+            return Err(ContractError::TooManyBasketAssets);
+        }
+        if intent.settle_in_defuse {
+            return Err(ContractError::BasketCannotSettleInDefuse);
+        }
+
+        let mut tokens = vec![normalize_address(&intent.token)];
+        let mut log_indices = vec![intent.proof.log_index];
+        for asset in &intent.extra_assets {
+            let token = normalize_address(&asset.token);
+            if tokens.contains(&token) {
+                return Err(ContractError::BasketTokensNotDistinct);
+            }
+            if log_indices.contains(&asset.log_index) {
+                return Err(ContractError::BasketLogsNotDistinct);
+            }
+            if asset.amount.0 == 0 {
+                return Err(ContractError::BasketAmountNotPositive);
+            }
+            self.check_token_enabled(intent.chain_id, &token)?;
+            if self
+                .rescale_amount(intent.chain_id, &token, asset.amount.0)
+                .is_none()
+            {
+                return Err(ContractError::AmountOverflow);
+            }
+            tokens.push(token);
+            log_indices.push(asset.log_index);
+        }
+        Ok(())
+    }
+
+    /// Join a transfer of every extra asset to the primary transfer,
+    /// returning the joined transfers and the fee withheld from each asset
+    pub(crate) fn join_basket_transfers(
+        &mut self,
+        intent: &CrossChainIntent,
+        transfer: Promise,
+    ) -> (Promise, Vec<U128>) {
+        let mut fees = Vec::with_capacity(intent.extra_assets.len());
+        let mut transfers = transfer;
+        for asset in &intent.extra_assets {
+            let amount = self
+                .rescale_amount(intent.chain_id, &asset.token, asset.amount.0)
+                .unwrap_or_else(|| ContractError::AmountOverflow.panic());
+            let fee = self.compute_fee(&asset.token, amount);
+            let delivered = U128(amount - fee);
+            let asset_transfer = if is_native_token(&asset.token) {
+                self.assert_native_liquidity(delivered.0);
+                Promise::new(intent.receiver.clone()).transfer(delivered.0)
+            } else {
+                self.deliver_token(intent.chain_id, &asset.token, &intent.receiver, delivered)
+            };
+            transfers = transfers.and(asset_transfer);
+            fees.push(U128(fee));
+        }
+        (transfers, fees)
+    }
+}
+
+/// Whether each of `count` joined transfers starting at promise result
+/// `first` succeeded
+pub(crate) fn transfer_results(first: u64, count: usize) -> Vec<bool> {
+    (first..first + count as u64)
+        .map(|index| matches!(env::promise_result(index), PromiseResult::Successful(_)))
+        .collect()
+}
@@ -0,0 +1,173 @@
+//! Batched intent creation and execution.
+//!
+//! The relayer can submit every deposit of a Base block in one transaction.
+//! Each intent is validated on its own and the result for every item is
+//! returned, so one bad deposit does not hold back the rest of the batch.
+//!
+//! Batch execution starts one transfer per intent, joins them and settles
+//! every intent from a single callback. Any intent failing its execution
+//! checks reverts the whole batch. Basket intents join one transfer per
+//! asset, so the callback walks the results intent by intent.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::{env, near_bindgen, Gas, Promise, PromiseOrValue};
+use schemars::JsonSchema;
+
+use crate::basket::transfer_results;
+use crate::errors::{require, ContractError};
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// Largest number of intents accepted in one batch
+const MAX_BATCH_SIZE: usize = 50;
+
+/// Gas kept back per intent so the batch can finish and charge storage
+const GAS_PER_CREATE: Gas = Gas(5_000_000_000_000);
+
+/// Largest number of intents executed in one batch
+const MAX_EXECUTE_BATCH_SIZE: usize = 10;
+
+/// Gas spent checking one intent before its transfer starts
+pub(crate) const GAS_PER_EXECUTION_CHECK: Gas = Gas(10_000_000_000_000);
+
+/// Outcome of one intent in a batch
+#[derive(Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchItemResult {
+    pub intent_id: String,
+    /// Code of the reason the intent was skipped, `None` when it was accepted
+    pub code: Option<ContractError>,
+    /// Why the intent was skipped, `None` when it was accepted
+    pub error: Option<String>,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Create several intents at once, reporting which were accepted.
+    /// Items left once the attached gas runs low are skipped and can be resubmitted.
+    /// The attached deposit must cover the storage of every accepted intent.
+    #[payable]
+    pub fn create_intents(&mut self, intents: Vec<CrossChainIntent>) -> Vec<BatchItemResult> {
+        self.assert_not_paused();
+
+        let agent = self.assert_agent();
+        require(!intents.is_empty(), ContractError::BatchEmpty);
+        require(
+            intents.len() <= MAX_BATCH_SIZE,
+            ContractError::BatchTooLarge,
+        );
+
+        let mut cost = 0;
+        let results = intents
+            .into_iter()
+            .map(|intent| {
+                let intent_id = intent.id.clone();
+                let remaining = env::prepaid_gas().0.saturating_sub(env::used_gas().0);
+                let result = if remaining < GAS_PER_CREATE.0 {
+                    Err(ContractError::BatchOutOfGas)
+                } else {
+                    self.screen_intent(&intent, "create")
+                        .and_then(|()| self.validate_new_intent(&intent))
+                };
+                if result.is_ok() {
+                    let initial_storage = self.storage_usage();
+                    self.store_new_intent(intent);
+                    cost += self.record_intent_deposit(&intent_id, &agent, initial_storage);
+                }
+                BatchItemResult {
+                    intent_id,
+                    code: result.err(),
+                    error: result.err().map(|error| error.to_string()),
+                }
+            })
+            .collect();
+        self.settle_attached_deposit(cost);
+        results
+    }
+
+    /// Execute several intents, settling all of their transfers in one
+    /// callback that returns whether each started transfer succeeded
+    pub fn execute_intents(&mut self, intent_ids: Vec<String>) -> PromiseOrValue<Vec<bool>> {
+        self.assert_not_paused();
+
+        let agent = self.assert_agent();
+        require(!intent_ids.is_empty(), ContractError::BatchEmpty);
+        require(
+            intent_ids.len() <= MAX_EXECUTE_BATCH_SIZE,
+            ContractError::BatchTooLarge,
+        );
+        let needed: u64 = intent_ids
+            .iter()
+            .map(|intent_id| {
+                let delivery_gas = match self.load_intent(intent_id) {
+                    Some(intent) => {
+                        require(intent.actions.is_empty(), ContractError::BatchMultiAction);
+                        self.delivery_gas(&intent)
+                    }
+                    None => self.config.ft_transfer_gas,
+                };
+                GAS_PER_EXECUTION_CHECK.0 + delivery_gas.0 + self.config.transfer_callback_gas.0
+            })
+            .sum();
+        require(
+            env::prepaid_gas().0.saturating_sub(env::used_gas().0) >= needed,
+            ContractError::BatchNotEnoughGas,
+        );
+
+        let mut started = Vec::new();
+        let mut fees = Vec::new();
+        let mut extra_fees = Vec::new();
+        let mut transfers: Option<Promise> = None;
+        for intent_id in intent_ids {
+            // Intents held back by their volume cap stay pending
+            let started_execution = self
+                .start_execution(&agent, &intent_id)
+                .unwrap_or_else(|reason| reason.panic());
+            if let Some((transfer, fee, basket_fees)) = started_execution {
+                transfers = Some(match transfers {
+                    Some(joined) => joined.and(transfer),
+                    None => transfer,
+                });
+                started.push(intent_id);
+                fees.push(fee);
+                extra_fees.push(basket_fees);
+            }
+        }
+
+        match transfers {
+            Some(transfers) => {
+                let callback_gas = Gas(self.config.transfer_callback_gas.0 * started.len() as u64);
+                PromiseOrValue::Promise(
+                    transfers.then(
+                        Self::ext(env::current_account_id())
+                            .with_static_gas(callback_gas)
+                            .on_batch_transfers_complete(started, fees, Some(extra_fees)),
+                    ),
+                )
+            }
+            None => PromiseOrValue::Value(vec![]),
+        }
+    }
+
+    /// Finalize every intent of a batch once the joined transfers resolved
+    #[private]
+    pub fn on_batch_transfers_complete(
+        &mut self,
+        intent_ids: Vec<String>,
+        fees: Vec<U128>,
+        extra_fees: Option<Vec<Vec<U128>>>,
+    ) -> Vec<bool> {
+        let mut extra_fees = extra_fees.unwrap_or_default().into_iter();
+        let mut next_result = 0;
+        intent_ids
+            .into_iter()
+            .zip(fees)
+            .map(|(intent_id, fee)| {
+                let basket_fees = extra_fees.next().unwrap_or_default();
+                let delivered = transfer_results(next_result, 1 + basket_fees.len());
+                next_result += delivered.len() as u64;
+                self.finish_execution(intent_id, fee, basket_fees, delivered)
+            })
+            .collect()
+    }
+}
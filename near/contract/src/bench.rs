@@ -0,0 +1,158 @@
+//! State fixtures for the gas benchmarks in `gas-bench/`.
+//!
+//! Populating a sandbox with a million intents through transactions would
+//! take days, so the benchmarks build the executor's state natively instead,
+//! in a mocked blockchain, and patch the resulting records into the sandbox
+//! account. Borsh layouts and storage prefixes are the same on every target,
+//! so the deployed wasm reads the state as its own.
+
+use std::collections::HashMap;
+
+use near_contract_standards::storage_management::StorageManagement;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::test_utils::VMContextBuilder;
+use near_sdk::{
+    env, AccountId, Balance, MockedBlockchain, PublicKey, RuntimeFeesConfig, VMConfig, ONE_NEAR,
+};
+
+pub use crate::checkpoints::checkpoint_hash;
+
+use crate::chains::BASE_CHAIN_ID;
+use crate::tokens::{TokenDecimals, TokenMapping};
+use crate::{BridgeProof, CrossChainExecutor, CrossChainIntent, IntentStatus};
+
+/// Seeded intents are spread over this many receivers, the first being
+/// `SeedParams::receiver`
+pub const SEEDED_RECEIVERS: u32 = 100;
+
+/// Intents stored per mocked execution, keeping each under the log limits
+const INTENTS_PER_BATCH: u32 = 20;
+
+/// Accounts and settings of the deployment the state is built for
+pub struct SeedParams {
+    pub executor: AccountId,
+    pub agent: AccountId,
+    pub validator: AccountId,
+    pub validator_key: PublicKey,
+    /// NEAR token `base_token` maps to
+    pub token: AccountId,
+    pub base_token: String,
+    pub base_bridge: String,
+    pub receiver: AccountId,
+    /// Pending intents to store
+    pub intents: u32,
+}
+
+fn set_blockchain(
+    params: &SeedParams,
+    caller: &AccountId,
+    deposit: Balance,
+    storage: HashMap<Vec<u8>, Vec<u8>>,
+) {
+    env::set_blockchain_interface(MockedBlockchain::new(
+        VMContextBuilder::new()
+            .current_account_id(params.executor.clone())
+            .predecessor_account_id(caller.clone())
+            .attached_deposit(deposit)
+            .build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        vec![],
+        storage,
+        Default::default(),
+        None,
+    ));
+}
+
+fn set_caller(params: &SeedParams, caller: &AccountId, deposit: Balance) {
+    let storage = near_sdk::mock::with_mocked_blockchain(|b| b.take_storage());
+    set_blockchain(params, caller, deposit, storage);
+}
+
+/// Pending intent `index` of a distinct sender, sized like a real one
+fn seeded_intent(params: &SeedParams, index: u32) -> CrossChainIntent {
+    let receiver = match index % SEEDED_RECEIVERS {
+        0 => params.receiver.clone(),
+        n => format!("receiver-{}.{}", n, params.executor).parse().unwrap(),
+    };
+    CrossChainIntent {
+        id: format!("0x{:064x}", index),
+        sender: format!("0x{:040x}", index),
+        receiver,
+        token: params.base_token.clone(),
+        amount: U128(1_000_000),
+        proof: BridgeProof {
+            // Clear of block 1, which the benchmarked deposit is proven in
+            block_number: u64::from(index) + 2,
+            timestamp: 0,
+            transaction_hash: format!("0x{:064x}", index),
+            receipt: Base64VecU8(vec![0; 400]),
+            receipt_index: 0,
+            proof_nodes: vec![Base64VecU8(vec![0; 450])],
+            log_index: 0,
+        },
+        status: IntentStatus::Pending,
+        signatures: vec![],
+        expires_at: U64(u64::MAX),
+        epoch: 0,
+        nonce: 1,
+        sender_signature: None,
+        chain_id: BASE_CHAIN_ID,
+        settle_in_defuse: false,
+        extra_assets: Vec::new(),
+        nft_token_id: None,
+        referrer: None,
+        swap: None,
+        to_stablecoin: false,
+        aurora_recipient: None,
+        mt_token_id: None,
+        execute_after: None,
+        depends_on: None,
+        actions: Vec::new(),
+        min_out: None,
+    }
+}
+
+/// Storage records of an executor initialized for `params`, with the
+/// validator bonded, the agent registered for storage and
+/// `params.intents` pending intents stored
+pub fn seeded_state(params: &SeedParams) -> Vec<(Vec<u8>, Vec<u8>)> {
+    set_blockchain(params, &params.executor, 0, HashMap::new());
+    let mut contract = CrossChainExecutor::new(1, params.agent.clone(), None);
+    contract.register_token(
+        TokenMapping {
+            base_address: params.base_token.clone(),
+            near_contract: params.token.clone(),
+            decimals: TokenDecimals { source: 6, near: 6 },
+            symbol: "USDC".to_string(),
+            enabled: true,
+            mintable: false,
+            min_amount: None,
+            max_amount: None,
+            stablecoin_route: None,
+            multi_token: false,
+            transfer_gas: None,
+        },
+        None,
+    );
+    contract.set_base_bridge(params.base_bridge.clone(), None);
+    set_caller(params, &params.validator, 100 * ONE_NEAR);
+    contract.register_validator(params.validator_key.clone());
+    set_caller(params, &params.agent, ONE_NEAR);
+    contract.storage_deposit(None, None);
+
+    for index in 0..params.intents {
+        if index % INTENTS_PER_BATCH == 0 {
+            contract.flush_collections();
+            set_caller(params, &params.agent, 0);
+        }
+        contract.store_new_intent(seeded_intent(params, index));
+    }
+
+    env::state_write(&contract);
+    // Dropping the contract writes out its collections
+    drop(contract);
+    near_sdk::mock::with_mocked_blockchain(|b| b.take_storage())
+        .into_iter()
+        .collect()
+}
@@ -0,0 +1,141 @@
+//! Sanctioned address blacklists.
+//!
+//! The owner and guardians can block source chain sender addresses and NEAR
+//! receiver accounts, whether an intent pays them as its receiver or as the
+//! receiver of one of its actions. Intents naming a blocked party are
+//! screened both when
+//! they are created and when they execute, so a party blocked after an
+//! intent was created cannot be paid by it either. Blocked attempts never
+//! panic, so the `intent_blocked` event recording each one reaches the
+//! audit trail: creation drops the intent and returns the attached deposit,
+//! while execution leaves the intent pending for the owner to resolve.
+
+use near_sdk::{near_bindgen, AccountId};
+
+use crate::errors::ContractError;
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::tokens::normalize_address;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Block source chain sender addresses
+    pub fn block_senders(&mut self, senders: Vec<String>) {
+        let by = self.assert_role(&[Role::Owner, Role::Guardian]);
+        for sender in senders {
+            let sender = normalize_address(&sender);
+            if self.blocked_senders.insert(sender.clone()) {
+                IntentEvent::SenderBlocked {
+                    sender,
+                    by: by.clone(),
+                }
+                .emit();
+            }
+        }
+    }
+
+    /// Unblock source chain sender addresses
+    pub fn unblock_senders(&mut self, senders: Vec<String>) {
+        let by = self.assert_role(&[Role::Owner, Role::Guardian]);
+        for sender in senders {
+            let sender = normalize_address(&sender);
+            if self.blocked_senders.remove(&sender) {
+                IntentEvent::SenderUnblocked {
+                    sender,
+                    by: by.clone(),
+                }
+                .emit();
+            }
+        }
+    }
+
+    /// Block NEAR receiver accounts
+    pub fn block_receivers(&mut self, receivers: Vec<AccountId>) {
+        let by = self.assert_role(&[Role::Owner, Role::Guardian]);
+        for receiver in receivers {
+            if self.blocked_receivers.insert(receiver.clone()) {
+                IntentEvent::ReceiverBlocked {
+                    receiver,
+                    by: by.clone(),
+                }
+                .emit();
+            }
+        }
+    }
+
+    /// Unblock NEAR receiver accounts
+    pub fn unblock_receivers(&mut self, receivers: Vec<AccountId>) {
+        let by = self.assert_role(&[Role::Owner, Role::Guardian]);
+        for receiver in receivers {
+            if self.blocked_receivers.remove(&receiver) {
+                IntentEvent::ReceiverUnblocked {
+                    receiver,
+                    by: by.clone(),
+                }
+                .emit();
+            }
+        }
+    }
+
+    /// Whether a source chain sender address is blocked
+    pub fn is_sender_blocked(&self, sender: String) -> bool {
+        self.blocked_senders.contains(&normalize_address(&sender))
+    }
+
+    /// Whether a NEAR receiver account is blocked
+    pub fn is_receiver_blocked(&self, receiver: AccountId) -> bool {
+        self.blocked_receivers.contains(&receiver)
+    }
+}
+
+impl CrossChainExecutor {
+    /// Check no party of an intent is blocked, emitting an
+    /// `intent_blocked` event for the attempt at `stage` if one is
+    pub(crate) fn screen_intent(
+        &self,
+        intent: &CrossChainIntent,
+        stage: &str,
+    ) -> Result<(), ContractError> {
+        let Err(reason) = self.check_parties_unblocked(intent) else {
+            return Ok(());
+        };
+        IntentEvent::IntentBlocked {
+            intent_id: intent.id.clone(),
+            stage: stage.to_string(),
+            sender: intent.sender.clone(),
+            receiver: self
+                .blocked_receiver(intent)
+                .unwrap_or(&intent.receiver)
+                .clone(),
+            reason: reason.to_string(),
+        }
+        .emit();
+        Err(reason)
+    }
+
+    /// Check no party of an intent is blocked
+    pub(crate) fn check_parties_unblocked(
+        &self,
+        intent: &CrossChainIntent,
+    ) -> Result<(), ContractError> {
+        if self
+            .blocked_senders
+            .contains(&normalize_address(&intent.sender))
+        {
+            Err(ContractError::SenderBlocked)
+        } else if self.blocked_receiver(intent).is_some() {
+            Err(ContractError::ReceiverBlocked)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// First blocked account an intent pays, its receiver or the receiver
+    /// of one of its actions
+    fn blocked_receiver<'a>(&self, intent: &'a CrossChainIntent) -> Option<&'a AccountId> {
+        std::iter::once(&intent.receiver)
+            .chain(intent.action_receivers())
+            .find(|receiver| self.blocked_receivers.contains(*receiver))
+    }
+}
@@ -0,0 +1,235 @@
+//! NEAR Chain Signatures for Base releases.
+//!
+//! Instead of the relayer holding a hot Base key, the executor can have the
+//! MPC signer contract (`v1.signer`) sign the Base transaction releasing an
+//! attested outbound intent. The agent only supplies the nonce and fees: the
+//! executor builds the EIP-1559 transaction calling the bridge's `release`
+//! itself, so the signer never signs anything but releases of attested
+//! intents. The signature is recorded and emitted for the relayer to
+//! assemble and broadcast the transaction. The Base bridge must accept
+//! releases from the address the signer derives for the executor under
+//! [`RELEASE_KEY_PATH`].
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{env, near_bindgen, AccountId, Gas, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::chains::BASE_CHAIN_ID;
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::outbound::OutboundStatus;
+use crate::proof::parse_hex;
+use crate::rlp::encode;
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+/// Derivation path of the key signing Base releases
+pub const RELEASE_KEY_PATH: &str = "base-release";
+
+/// Bridge function releasing locked funds on Base
+const RELEASE_SIGNATURE: &str = "release(address,address,uint256,bytes32)";
+
+/// EIP-2718 type of EIP-1559 transactions
+const EIP1559_TX_TYPE: u8 = 0x02;
+
+/// Gas forwarded to the signer, which resolves the request over several blocks
+const SIGN_GAS: Gas = Gas(250_000_000_000_000);
+
+/// Gas reserved for recording the signature
+const ON_SIGNED_GAS: Gas = Gas(10_000_000_000_000);
+
+/// Nonce and fees of a Base release transaction, chosen by the agent
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReleaseTxParams {
+    pub nonce: U64,
+    pub max_priority_fee_per_gas: U128,
+    pub max_fee_per_gas: U128,
+    pub gas_limit: U64,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ReleaseSignature {
+    /// Unsigned EIP-1559 transaction, 0x-prefixed hex
+    pub transaction: String,
+    /// Compressed signature point R, hex; its x coordinate is `r`
+    pub big_r: String,
+    /// Signature scalar `s`, hex
+    pub s: String,
+    /// Parity of R, the transaction's `y_parity`
+    pub recovery_id: u8,
+}
+
+/// Response of the signer's `sign` method
+#[derive(Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignerResponse {
+    pub big_r: AffinePoint,
+    pub s: Scalar,
+    pub recovery_id: u8,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AffinePoint {
+    pub affine_point: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Scalar {
+    pub scalar: String,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the MPC signer contract, or stop signing Base releases
+    pub fn set_signer_contract(&mut self, contract_id: Option<AccountId>) {
+        self.assert_role(&[Role::Owner]);
+        self.signer_contract = contract_id;
+    }
+
+    /// Get the MPC signer contract
+    pub fn get_signer_contract(&self) -> Option<AccountId> {
+        self.signer_contract.clone()
+    }
+
+    /// Have the signer sign the Base transaction releasing an attested
+    /// outbound intent, forwarding the attached deposit as its fee. A new
+    /// request replaces the recorded signature, e.g. to bump the fees.
+    #[payable]
+    pub fn request_release_signature(
+        &mut self,
+        intent_id: String,
+        params: ReleaseTxParams,
+    ) -> Promise {
+        self.assert_agent();
+        let signer = self
+            .signer_contract
+            .clone()
+            .unwrap_or_else(|| ContractError::SignerNotConfigured.panic());
+        let deposit = env::attached_deposit();
+        require(deposit > 0, ContractError::SignerFeeMissing);
+
+        let intent = self
+            .outbound_intents
+            .get(&intent_id)
+            .unwrap_or_else(|| ContractError::OutboundNotFound.panic());
+        require(
+            matches!(intent.status, OutboundStatus::Pending),
+            ContractError::OutboundNotPending,
+        );
+        self.assert_outbound_attested(&intent_id);
+
+        let bridge = self
+            .chain(BASE_CHAIN_ID)
+            .bridge
+            .unwrap_or_else(|| ContractError::BridgeNotConfigured.panic());
+        let amount = self.unscale_amount(BASE_CHAIN_ID, &intent.base_token, intent.amount.0);
+        let calldata = [
+            env::keccak256(RELEASE_SIGNATURE.as_bytes())[..4].to_vec(),
+            address_word(&intent.base_token),
+            address_word(&intent.recipient),
+            left_pad(&amount.to_be_bytes()),
+            env::keccak256(intent_id.as_bytes()),
+        ]
+        .concat();
+        let transaction = unsigned_eip1559_tx(&params, &parse_address(&bridge), &calldata);
+        let payload = env::keccak256(&transaction);
+
+        Promise::new(signer)
+            .function_call(
+                "sign".to_string(),
+                json!({
+                    "request": {
+                        "payload": payload,
+                        "path": RELEASE_KEY_PATH,
+                        "key_version": 0,
+                    }
+                })
+                .to_string()
+                .into_bytes(),
+                deposit,
+                SIGN_GAS,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(ON_SIGNED_GAS)
+                    .on_release_signed(intent_id, format!("0x{}", hex::encode(transaction))),
+            )
+    }
+
+    /// Record the signature of a release transaction
+    #[private]
+    pub fn on_release_signed(
+        &mut self,
+        intent_id: String,
+        transaction: String,
+        #[callback_result] result: Result<SignerResponse, PromiseError>,
+    ) -> bool {
+        let response = match result {
+            Ok(response) => response,
+            Err(_) => return false,
+        };
+        let signature = ReleaseSignature {
+            transaction,
+            big_r: response.big_r.affine_point,
+            s: response.s.scalar,
+            recovery_id: response.recovery_id,
+        };
+        self.release_signatures
+            .insert(intent_id.clone(), signature.clone());
+
+        IntentEvent::ReleaseSigned {
+            intent_id,
+            transaction: signature.transaction,
+            big_r: signature.big_r,
+            s: signature.s,
+            recovery_id: signature.recovery_id,
+        }
+        .emit();
+        true
+    }
+
+    /// Get the recorded signature of an outbound intent's release transaction
+    pub fn get_release_signature(&self, intent_id: String) -> Option<ReleaseSignature> {
+        self.release_signatures.get(&intent_id).cloned()
+    }
+}
+
+/// `0x02 || rlp([chain_id, nonce, max_priority_fee, max_fee, gas_limit, to,
+/// value, data, access_list])` with no value and an empty access list
+fn unsigned_eip1559_tx(params: &ReleaseTxParams, to: &[u8], data: &[u8]) -> Vec<u8> {
+    let fields = encode::list(&[
+        encode::uint(BASE_CHAIN_ID.into()),
+        encode::uint(params.nonce.0.into()),
+        encode::uint(params.max_priority_fee_per_gas.0),
+        encode::uint(params.max_fee_per_gas.0),
+        encode::uint(params.gas_limit.0.into()),
+        encode::bytes(to),
+        encode::uint(0),
+        encode::bytes(data),
+        encode::list(&[]),
+    ]);
+    [vec![EIP1559_TX_TYPE], fields].concat()
+}
+
+fn parse_address(address: &str) -> Vec<u8> {
+    parse_hex(address)
+        .filter(|bytes| bytes.len() == 20)
+        .unwrap_or_else(|| ContractError::InvalidBaseAddress.panic())
+}
+
+fn address_word(address: &str) -> Vec<u8> {
+    left_pad(&parse_address(address))
+}
+
+fn left_pad(bytes: &[u8]) -> Vec<u8> {
+    let mut word = vec![0u8; 32 - bytes.len()];
+    word.extend_from_slice(bytes);
+    word
+}
@@ -0,0 +1,161 @@
+//! Source chain registry.
+//!
+//! Intents may originate from any registered EVM chain, keyed by its EIP-155
+//! chain ID. Each chain has its own bridge contract, light client, token
+//! mappings and confirmation depth, and may narrow which validators attest
+//! for it. Base, Optimism, Arbitrum and Ethereum, or their Sepolia testnets
+//! in testnet builds, are registered at initialization and accept intents
+//! once their bridge and light client are set up. Base keeps the light client storage prefixes it used before
+//! chains were registered and is the default wherever a chain ID is optional.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::{LookupMap, UnorderedMap};
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::light_client::BaseHeader;
+use crate::proof::parse_hex;
+use crate::roles::Role;
+use crate::tokens::{normalize_address, TokenMapping};
+use crate::{CrossChainExecutor, CrossChainExecutorExt, StorageKey};
+
+pub use crate::network::BASE_CHAIN_ID;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ChainConfig {
+    pub name: String,
+    /// Bridge contract emitting the deposit logs, 0x-prefixed hex
+    pub bridge: Option<String>,
+    /// Blocks a header must be buried under before proofs against it are accepted
+    pub confirmations: u64,
+    /// Validators attesting for the chain, or empty for the whole validator set
+    pub validators: Vec<AccountId>,
+    /// Quorum for the chain's intents and headers, overriding the validator set's
+    pub required_signatures: Option<u32>,
+}
+
+impl ChainConfig {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            bridge: None,
+            confirmations: 0,
+            validators: vec![],
+            required_signatures: None,
+        }
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Register or update a source chain, or remove it
+    pub fn set_chain(&mut self, chain_id: u64, config: Option<ChainConfig>) {
+        self.assert_role(&[Role::Owner]);
+        match config {
+            Some(mut config) => {
+                if let Some(bridge) = &config.bridge {
+                    require(
+                        parse_hex(bridge).is_some_and(|bytes| bytes.len() == 20),
+                        ContractError::InvalidBridgeAddress,
+                    );
+                    config.bridge = Some(normalize_address(bridge));
+                }
+                require(
+                    config.required_signatures != Some(0),
+                    ContractError::InvalidRequiredSignatures,
+                );
+                IntentEvent::ChainConfigured {
+                    chain_id,
+                    name: config.name.clone(),
+                }
+                .emit();
+                self.chains.insert(chain_id, config);
+            }
+            None => {
+                require(
+                    self.chains.remove(&chain_id).is_some(),
+                    ContractError::ChainNotSupported,
+                );
+                IntentEvent::ChainRemoved { chain_id }.emit();
+            }
+        }
+    }
+
+    /// Get the configuration of a source chain
+    pub fn get_chain(&self, chain_id: u64) -> Option<ChainConfig> {
+        self.chains.get(&chain_id).cloned()
+    }
+
+    /// List registered source chains
+    pub fn get_chains(&self) -> Vec<(u64, ChainConfig)> {
+        self.chains
+            .iter()
+            .map(|(chain_id, config)| (*chain_id, config.clone()))
+            .collect()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Configuration of a registered chain, panicking if unsupported
+    pub(crate) fn chain(&self, chain_id: u64) -> ChainConfig {
+        self.chains
+            .get(&chain_id)
+            .unwrap_or_else(|| ContractError::ChainNotSupported.panic())
+            .clone()
+    }
+
+    /// Block headers of a chain keyed by block hash
+    pub(crate) fn chain_headers(&self, chain_id: u64) -> LookupMap<String, BaseHeader> {
+        match chain_id {
+            BASE_CHAIN_ID => LookupMap::new(StorageKey::BaseHeaders),
+            _ => LookupMap::new(StorageKey::ChainHeaders { chain_id }),
+        }
+    }
+
+    /// Canonical block hash at each block number of a chain
+    pub(crate) fn chain_canonical_headers(&self, chain_id: u64) -> LookupMap<u64, String> {
+        match chain_id {
+            BASE_CHAIN_ID => LookupMap::new(StorageKey::CanonicalHeaders),
+            _ => LookupMap::new(StorageKey::ChainCanonicalHeaders { chain_id }),
+        }
+    }
+
+    /// Mapping registered for a token of a chain
+    pub(crate) fn token_mapping(&self, chain_id: u64, token: &str) -> Option<&TokenMapping> {
+        self.token_mappings
+            .get(&chain_id)?
+            .get(&normalize_address(token))
+    }
+
+    /// Token mappings of a chain keyed by token address, created empty on
+    /// first use
+    pub(crate) fn chain_token_mappings_mut(
+        &mut self,
+        chain_id: u64,
+    ) -> &mut UnorderedMap<String, TokenMapping> {
+        self.token_mappings
+            .entry(chain_id)
+            .or_insert_with(|| UnorderedMap::new(StorageKey::ChainTokenMappings { chain_id }))
+    }
+
+    /// Whether the chain lets a validator attest for it
+    pub(crate) fn attests_for_chain(&self, chain: &ChainConfig, validator: &AccountId) -> bool {
+        chain.validators.is_empty() || chain.validators.contains(validator)
+    }
+
+    /// Approvals needed for an intent or header of a chain attested in `epoch`
+    pub(crate) fn chain_threshold(&self, chain: &ChainConfig, epoch: u64) -> u32 {
+        chain
+            .required_signatures
+            .unwrap_or_else(|| self.epoch_threshold(epoch))
+    }
+}
+
+/// Chain an optional chain ID argument refers to
+pub(crate) fn chain_or_base(chain_id: Option<u64>) -> u64 {
+    chain_id.unwrap_or(BASE_CHAIN_ID)
+}
@@ -0,0 +1,117 @@
+//! Validator-signed source chain checkpoints.
+//!
+//! On a regular cadence a quorum of the validators attesting for a chain
+//! signs the state root of one of its finalized blocks. The latest
+//! checkpoint of each chain bounds how far the contract trusts the chain:
+//! intents are only created and executed against proofs of blocks at or
+//! before it, however far its light client has run ahead. Chains without a
+//! checkpoint back no intents.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+use schemars::JsonSchema;
+
+use crate::chains::chain_or_base;
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::proof::parse_hex;
+use crate::tokens::normalize_address;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, ValidatorSignature};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Checkpoint {
+    pub block_number: u64,
+    /// State root of the block, 0x-prefixed hex
+    pub state_root: String,
+    /// Validator set epoch whose quorum signed the checkpoint
+    pub epoch: u64,
+    /// Block timestamp (ns) the checkpoint was submitted at
+    pub submitted_at: U64,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Submit a checkpoint of a chain, Base by default. `signatures` are
+    /// over [`checkpoint_hash`] and must reach the chain's quorum in the
+    /// current epoch. Callable by anyone, typically the relayer.
+    pub fn submit_checkpoint(
+        &mut self,
+        block_number: u64,
+        state_root: String,
+        signatures: Vec<ValidatorSignature>,
+        chain_id: Option<u64>,
+    ) {
+        let chain_id = chain_or_base(chain_id);
+        let chain = self.chain(chain_id);
+        let root = parse_hex(&state_root)
+            .filter(|bytes| bytes.len() == 32)
+            .unwrap_or_else(|| ContractError::InvalidStateRoot.panic());
+        let state_root = normalize_address(&state_root);
+        if let Some(latest) = self.checkpoints.get(&chain_id) {
+            require(
+                block_number > latest.block_number,
+                ContractError::CheckpointNotAdvancing,
+            );
+        }
+
+        let message = checkpoint_hash(chain_id, block_number, &root);
+        let signers =
+            self.count_epoch_signatures(self.current_epoch, &message, &signatures, |validator| {
+                self.attests_for_chain(&chain, validator)
+            });
+        require(
+            signers >= self.chain_threshold(&chain, self.current_epoch),
+            ContractError::NotEnoughSignatures,
+        );
+
+        IntentEvent::CheckpointSubmitted {
+            chain_id,
+            block_number,
+            state_root: state_root.clone(),
+        }
+        .emit();
+        self.checkpoints.insert(
+            chain_id,
+            Checkpoint {
+                block_number,
+                state_root,
+                epoch: self.current_epoch,
+                submitted_at: U64(env::block_timestamp()),
+            },
+        );
+    }
+
+    /// Get the latest checkpoint of a chain, Base by default
+    pub fn get_checkpoint(&self, chain_id: Option<u64>) -> Option<Checkpoint> {
+        self.checkpoints.get(&chain_or_base(chain_id)).cloned()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Check an intent's proof is of a block at or before its chain's
+    /// latest checkpoint
+    pub(crate) fn check_checkpointed(
+        &self,
+        intent: &CrossChainIntent,
+    ) -> Result<(), ContractError> {
+        let checkpoint = self
+            .checkpoints
+            .get(&intent.chain_id)
+            .ok_or(ContractError::ChainNotCheckpointed)?;
+        if intent.proof.block_number > checkpoint.block_number {
+            return Err(ContractError::ProofNotCheckpointed);
+        }
+        Ok(())
+    }
+}
+
+/// Hash validators sign to attest the state root of a chain's block
+pub fn checkpoint_hash(chain_id: u64, block_number: u64, state_root: &[u8]) -> Vec<u8> {
+    let payload = ("submit_checkpoint", chain_id, block_number, state_root)
+        .try_to_vec()
+        .expect("Failed to serialize checkpoint");
+    env::sha256(&payload)
+}
@@ -0,0 +1,149 @@
+//! Receiver compliance gating.
+//!
+//! Institutional integrators may only be allowed to deliver to vetted
+//! accounts. In `Allowlist` mode intents only execute for receivers the
+//! owner registered. In `Registry` mode receivers may also be vetted by a
+//! compliance registry contract: anyone can ask the contract to call the
+//! registry's `is_compliant({"account_id"})` view for a receiver, and a
+//! `true` verdict allows the receiver for [`RECEIVER_VERDICT_TTL`]. Checks
+//! run when intents execute, so intents for receivers vetted after
+//! creation can execute once vetted. `Open`, the default, allows any
+//! receiver.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{env, near_bindgen, AccountId, Gas, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::errors::ContractError;
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+/// Nanoseconds a compliant verdict of the registry allows a receiver for
+const RECEIVER_VERDICT_TTL: u64 = 24 * 3_600 * 1_000_000_000;
+
+/// Gas for the registry's `is_compliant` view
+const IS_COMPLIANT_GAS: Gas = Gas(10_000_000_000_000);
+
+/// Gas reserved for recording the registry's verdict
+const ON_RECEIVER_CHECKED_GAS: Gas = Gas(5_000_000_000_000);
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ComplianceMode {
+    /// Any receiver may be delivered to
+    Open,
+    /// Only receivers the owner registered may be delivered to
+    Allowlist,
+    /// Receivers the owner registered or the registry vetted may be
+    /// delivered to
+    Registry { contract: AccountId },
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set how receivers are vetted before their intents execute
+    pub fn set_compliance_mode(&mut self, mode: ComplianceMode) {
+        self.assert_role(&[Role::Owner]);
+        self.compliance_mode = mode;
+    }
+
+    /// How receivers are vetted before their intents execute
+    pub fn get_compliance_mode(&self) -> ComplianceMode {
+        self.compliance_mode.clone()
+    }
+
+    /// Allow receivers until they are unregistered
+    pub fn register_receivers(&mut self, receivers: Vec<AccountId>) {
+        self.assert_role(&[Role::Owner]);
+        for receiver in receivers {
+            self.allowed_receivers.insert(receiver.clone(), u64::MAX);
+            IntentEvent::ReceiverAllowed {
+                account_id: receiver,
+                until: None,
+            }
+            .emit();
+        }
+    }
+
+    /// Stop allowing receivers, whether registered or vetted by the registry
+    pub fn unregister_receivers(&mut self, receivers: Vec<AccountId>) {
+        self.assert_role(&[Role::Owner]);
+        for receiver in receivers {
+            if self.allowed_receivers.remove(&receiver).is_some() {
+                IntentEvent::ReceiverDisallowed {
+                    account_id: receiver,
+                }
+                .emit();
+            }
+        }
+    }
+
+    /// Ask the compliance registry to vet a receiver. Callable by anyone.
+    pub fn verify_receiver(&mut self, account_id: AccountId) -> Promise {
+        let ComplianceMode::Registry { contract } = &self.compliance_mode else {
+            ContractError::ComplianceNotConfigured.panic();
+        };
+        Promise::new(contract.clone())
+            .function_call(
+                "is_compliant".to_string(),
+                json!({ "account_id": account_id }).to_string().into_bytes(),
+                0,
+                IS_COMPLIANT_GAS,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(ON_RECEIVER_CHECKED_GAS)
+                    .on_receiver_verified(account_id),
+            )
+    }
+
+    /// Allow or disallow a receiver on the registry's verdict, treating a
+    /// failed call as non-compliant
+    #[private]
+    pub fn on_receiver_verified(
+        &mut self,
+        account_id: AccountId,
+        #[callback_result] result: Result<bool, PromiseError>,
+    ) -> bool {
+        let compliant = result.unwrap_or(false);
+        if compliant {
+            let until = env::block_timestamp().saturating_add(RECEIVER_VERDICT_TTL);
+            // Never shorten an owner registration
+            let allowed_until = self
+                .allowed_receivers
+                .get(&account_id)
+                .map_or(until, |current| until.max(*current));
+            self.allowed_receivers
+                .insert(account_id.clone(), allowed_until);
+            IntentEvent::ReceiverAllowed {
+                account_id,
+                until: (allowed_until != u64::MAX).then_some(allowed_until.into()),
+            }
+            .emit();
+        } else if self.allowed_receivers.remove(&account_id).is_some() {
+            IntentEvent::ReceiverDisallowed { account_id }.emit();
+        }
+        compliant
+    }
+
+    /// Whether intents delivering to a receiver may currently execute
+    pub fn is_receiver_allowed(&self, account_id: AccountId) -> bool {
+        self.check_receiver_allowed(&account_id).is_ok()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Check the compliance mode allows delivering to a receiver
+    pub(crate) fn check_receiver_allowed(&self, receiver: &AccountId) -> Result<(), ContractError> {
+        if matches!(self.compliance_mode, ComplianceMode::Open) {
+            return Ok(());
+        }
+        match self.allowed_receivers.get(receiver) {
+            Some(until) if *until > env::block_timestamp() => Ok(()),
+            _ => Err(ContractError::ReceiverNotAllowed),
+        }
+    }
+}
@@ -0,0 +1,224 @@
+//! Operational parameters kept in state so they can be tuned without a
+//! redeploy. Bonds and periods start from the defaults of the network the
+//! contract is built for.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, Gas};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::network;
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+/// Most gas any single configured call may reserve
+pub(crate) const MAX_CONFIGURED_GAS: Gas = Gas(100_000_000_000_000);
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Config {
+    /// Validator approvals needed to execute an intent or accept a header
+    pub required_signatures: u32,
+    /// Gas attached to `ft_transfer` calls delivering tokens
+    pub ft_transfer_gas: Gas,
+    /// Deposit attached to `ft_transfer` calls, 1 yoctoNEAR per NEP-141
+    pub ft_transfer_deposit: U128,
+    /// Gas reserved for the callback settling each transfer
+    pub transfer_callback_gas: Gas,
+    /// Bond a validator needs for its attestations to count
+    pub min_validator_bond: U128,
+    /// Nanoseconds unbonded NEAR stays locked before it can be withdrawn
+    pub unbonding_period: U64,
+    /// Whether new intents must carry the Base sender's signature
+    pub require_sender_signature: bool,
+    /// Gas attached to `ft_transfer_call` deposits into NEAR Intents
+    pub ft_transfer_call_gas: Gas,
+    /// Bond a solver needs to claim intents
+    pub min_solver_bond: U128,
+    /// Nanoseconds a solver's claim reserves an intent's execution for it
+    pub solver_claim_period: U64,
+    /// Nanoseconds after its source chain block a deposit proof can still
+    /// back a new intent
+    pub max_proof_age: U64,
+}
+
+impl Config {
+    pub fn new(required_signatures: u32) -> Self {
+        Self {
+            required_signatures,
+            ft_transfer_gas: Gas(5_000_000_000_000),
+            ft_transfer_deposit: U128(1),
+            transfer_callback_gas: Gas(10_000_000_000_000),
+            min_validator_bond: U128(network::MIN_VALIDATOR_BOND),
+            unbonding_period: U64(network::UNBONDING_PERIOD),
+            require_sender_signature: false,
+            ft_transfer_call_gas: Gas(50_000_000_000_000),
+            min_solver_bond: U128(network::MIN_SOLVER_BOND),
+            solver_claim_period: U64(network::SOLVER_CLAIM_PERIOD),
+            max_proof_age: U64(network::MAX_PROOF_AGE),
+        }
+    }
+
+    fn validate(&self) {
+        require(
+            self.required_signatures > 0,
+            ContractError::InvalidRequiredSignatures,
+        );
+        require(
+            self.ft_transfer_deposit.0 > 0,
+            ContractError::DepositNotPositive,
+        );
+        require(self.max_proof_age.0 > 0, ContractError::ProofAgeNotPositive);
+        for gas in [
+            self.ft_transfer_gas,
+            self.transfer_callback_gas,
+            self.ft_transfer_call_gas,
+        ] {
+            require(
+                gas.0 > 0 && gas <= MAX_CONFIGURED_GAS,
+                ContractError::InvalidGas,
+            );
+        }
+    }
+}
+
+/// Fields of [`Config`] to change, unset fields are left as they are
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default, Debug, JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConfigUpdate {
+    pub required_signatures: Option<u32>,
+    pub ft_transfer_gas: Option<Gas>,
+    pub ft_transfer_deposit: Option<U128>,
+    pub transfer_callback_gas: Option<Gas>,
+    pub min_validator_bond: Option<U128>,
+    pub unbonding_period: Option<U64>,
+    pub require_sender_signature: Option<bool>,
+    pub ft_transfer_call_gas: Option<Gas>,
+    pub min_solver_bond: Option<U128>,
+    pub solver_claim_period: Option<U64>,
+    pub max_proof_age: Option<U64>,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Get the current configuration
+    pub fn get_config(&self) -> Config {
+        self.config.clone()
+    }
+
+    /// Change configuration fields, emitting an event for each changed field
+    pub fn update_config(&mut self, update: ConfigUpdate) {
+        self.assert_role(&[Role::Owner]);
+        let old = self.config.clone();
+        let mut config = old.clone();
+        if let Some(required_signatures) = update.required_signatures {
+            config.required_signatures = required_signatures;
+        }
+        if let Some(ft_transfer_gas) = update.ft_transfer_gas {
+            config.ft_transfer_gas = ft_transfer_gas;
+        }
+        if let Some(ft_transfer_deposit) = update.ft_transfer_deposit {
+            config.ft_transfer_deposit = ft_transfer_deposit;
+        }
+        if let Some(transfer_callback_gas) = update.transfer_callback_gas {
+            config.transfer_callback_gas = transfer_callback_gas;
+        }
+        if let Some(min_validator_bond) = update.min_validator_bond {
+            config.min_validator_bond = min_validator_bond;
+        }
+        if let Some(unbonding_period) = update.unbonding_period {
+            config.unbonding_period = unbonding_period;
+        }
+        if let Some(require_sender_signature) = update.require_sender_signature {
+            config.require_sender_signature = require_sender_signature;
+        }
+        if let Some(ft_transfer_call_gas) = update.ft_transfer_call_gas {
+            config.ft_transfer_call_gas = ft_transfer_call_gas;
+        }
+        if let Some(min_solver_bond) = update.min_solver_bond {
+            config.min_solver_bond = min_solver_bond;
+        }
+        if let Some(solver_claim_period) = update.solver_claim_period {
+            config.solver_claim_period = solver_claim_period;
+        }
+        if let Some(max_proof_age) = update.max_proof_age {
+            config.max_proof_age = max_proof_age;
+        }
+        config.validate();
+
+        let changes = [
+            (
+                "required_signatures",
+                old.required_signatures.to_string(),
+                config.required_signatures.to_string(),
+            ),
+            (
+                "ft_transfer_gas",
+                old.ft_transfer_gas.0.to_string(),
+                config.ft_transfer_gas.0.to_string(),
+            ),
+            (
+                "ft_transfer_deposit",
+                old.ft_transfer_deposit.0.to_string(),
+                config.ft_transfer_deposit.0.to_string(),
+            ),
+            (
+                "transfer_callback_gas",
+                old.transfer_callback_gas.0.to_string(),
+                config.transfer_callback_gas.0.to_string(),
+            ),
+            (
+                "min_validator_bond",
+                old.min_validator_bond.0.to_string(),
+                config.min_validator_bond.0.to_string(),
+            ),
+            (
+                "unbonding_period",
+                old.unbonding_period.0.to_string(),
+                config.unbonding_period.0.to_string(),
+            ),
+            (
+                "require_sender_signature",
+                old.require_sender_signature.to_string(),
+                config.require_sender_signature.to_string(),
+            ),
+            (
+                "ft_transfer_call_gas",
+                old.ft_transfer_call_gas.0.to_string(),
+                config.ft_transfer_call_gas.0.to_string(),
+            ),
+            (
+                "min_solver_bond",
+                old.min_solver_bond.0.to_string(),
+                config.min_solver_bond.0.to_string(),
+            ),
+            (
+                "solver_claim_period",
+                old.solver_claim_period.0.to_string(),
+                config.solver_claim_period.0.to_string(),
+            ),
+            (
+                "max_proof_age",
+                old.max_proof_age.0.to_string(),
+                config.max_proof_age.0.to_string(),
+            ),
+        ];
+        self.config = config;
+
+        for (field, old_value, new_value) in changes {
+            if old_value != new_value {
+                IntentEvent::ConfigChanged {
+                    field: field.to_string(),
+                    old_value,
+                    new_value,
+                }
+                .emit();
+            }
+        }
+    }
+}
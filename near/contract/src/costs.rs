@@ -0,0 +1,81 @@
+//! Cost estimates.
+//!
+//! Before an intent is created the MCP agent shows the user what it costs:
+//! the NEAR `create_intent` needs attached for the intent's storage, the gas
+//! an executor attaches to deliver it and the protocol fees the delivery
+//! withholds. Storage is estimated from the records creation writes and errs
+//! high, the unused part of the attached deposit being refunded. Fees follow
+//! the current fee settings and leave out the spread of any solver quote
+//! bound at execution.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
+use near_sdk::{env, near_bindgen, Balance, Gas};
+use schemars::JsonSchema;
+
+use crate::batch::GAS_PER_EXECUTION_CHECK;
+use crate::errors::ContractError;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// Expected costs of creating and executing an intent
+#[derive(Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CostEstimate {
+    /// Gas to attach when executing the intent
+    pub execution_gas: Gas,
+    /// Bytes of state the intent occupies at most until it is pruned
+    pub storage_bytes: u64,
+    /// NEAR to attach to `create_intent` for the intent's storage
+    pub storage_deposit: U128,
+    /// Fee withheld from the primary token, in its NEAR decimals
+    pub fee: U128,
+    /// Fee withheld from each extra basket asset, in its NEAR decimals
+    pub extra_fees: Vec<U128>,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Estimate the gas, storage deposit and fees of an intent before it is
+    /// created
+    pub fn estimate_execution(&self, intent: CrossChainIntent) -> CostEstimate {
+        let storage_bytes = self.estimate_intent_storage(&intent);
+        let fee = if intent.is_nft() || intent.mt_token_id.is_some() {
+            0
+        } else if intent.actions.is_empty() {
+            self.estimate_fee(intent.chain_id, &intent.token, intent.amount.0)
+        } else {
+            // Each action withholds its own fee
+            intent
+                .actions
+                .iter()
+                .map(|action| self.estimate_fee(intent.chain_id, &intent.token, action.amount().0))
+                .sum()
+        };
+        let extra_fees = intent
+            .extra_assets
+            .iter()
+            .map(|asset| U128(self.estimate_fee(intent.chain_id, &asset.token, asset.amount.0)))
+            .collect();
+
+        CostEstimate {
+            execution_gas: Gas(GAS_PER_EXECUTION_CHECK.0
+                + self.delivery_gas(&intent).0
+                + self.config.transfer_callback_gas.0),
+            storage_bytes,
+            storage_deposit: U128(Balance::from(storage_bytes) * env::storage_byte_cost()),
+            fee: U128(fee),
+            extra_fees,
+        }
+    }
+}
+
+impl CrossChainExecutor {
+    /// Fee withheld from delivering `amount` of a token in source decimals,
+    /// in its NEAR decimals
+    fn estimate_fee(&self, chain_id: u64, token: &str, amount: Balance) -> Balance {
+        let amount = self
+            .rescale_amount(chain_id, token, amount)
+            .unwrap_or_else(|| ContractError::AmountOverflow.panic());
+        self.compute_fee(token, amount)
+    }
+}
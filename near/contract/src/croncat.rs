@@ -0,0 +1,178 @@
+//! Scheduled execution through Croncat.
+//!
+//! Intents normally execute when the agent calls for them, so an agent that
+//! is down holds up every delivery. Once the owner points the executor at a
+//! Croncat manager and schedules a recurring task with it, Croncat's agents
+//! call `tick` on the task's cadence. Each tick looks through a page of the
+//! intents created since the scheduler was set, resuming where the last
+//! tick stopped and starting over past the last one, and executes the ones
+//! that are pending, unexpired, past any deferral or dependency, unclaimed
+//! and attested by a quorum.
+//!
+//! Every execution runs in a receipt of its own, so one intent failing its
+//! execution checks does not hold back the rest of the tick. The task's
+//! deposit pays for the gas of scheduled executions, so they are not
+//! reimbursed from the gas fund.
+
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::serde_json::json;
+use near_sdk::{env, near_bindgen, AccountId, Gas, Promise, PromiseOrValue};
+
+use crate::batch::GAS_PER_EXECUTION_CHECK;
+use crate::errors::{require, ContractError};
+use crate::roles::Role;
+use crate::views::{DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT};
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, IntentStatus};
+
+/// Largest number of intents one tick executes
+const MAX_TICK_EXECUTIONS: usize = 10;
+
+/// Gas attached to calls managing the executor's Croncat task
+const CRONCAT_CALL_GAS: Gas = Gas(20_000_000_000_000);
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the Croncat manager allowed to tick, or stop scheduled execution
+    pub fn set_croncat_manager(&mut self, manager: Option<AccountId>) {
+        self.assert_role(&[Role::Owner]);
+        self.croncat_manager = manager;
+    }
+
+    /// Get the Croncat manager allowed to tick
+    pub fn get_croncat_manager(&self) -> Option<AccountId> {
+        self.croncat_manager.clone()
+    }
+
+    /// Create a recurring Croncat task calling `tick` on a cron `cadence`
+    /// with `gas` attached, funding it with the attached NEAR. Resolves to
+    /// the task's hash.
+    #[payable]
+    pub fn schedule_croncat_task(&mut self, cadence: String, gas: Gas) -> Promise {
+        self.assert_role(&[Role::Owner]);
+        Promise::new(self.croncat()).function_call(
+            "create_task".to_string(),
+            json!({
+                "contract_id": env::current_account_id(),
+                "function_id": "tick",
+                "cadence": cadence,
+                "recurring": true,
+                "deposit": U128(0),
+                "gas": gas.0,
+                "arguments": Base64VecU8(b"{}".to_vec()),
+            })
+            .to_string()
+            .into_bytes(),
+            env::attached_deposit(),
+            CRONCAT_CALL_GAS,
+        )
+    }
+
+    /// Remove the executor's Croncat task, refunding what is left of its
+    /// deposit to the executor
+    pub fn remove_croncat_task(&mut self, task_hash: Base64VecU8) -> Promise {
+        self.assert_role(&[Role::Owner]);
+        Promise::new(self.croncat()).function_call(
+            "remove_task".to_string(),
+            json!({ "task_hash": task_hash }).to_string().into_bytes(),
+            0,
+            CRONCAT_CALL_GAS,
+        )
+    }
+
+    /// Execute the ready intents among the next `limit` scheduled ones, as
+    /// far as the attached gas allows. Called by the Croncat manager.
+    /// Returns the IDs of the intents whose execution was started.
+    pub fn tick(&mut self, limit: Option<u64>) -> Vec<String> {
+        require(
+            self.croncat_manager.as_ref() == Some(&env::predecessor_account_id()),
+            ContractError::NotScheduler,
+        );
+        if self.paused {
+            return Vec::new();
+        }
+
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+        if self.tick_cursor >= u64::from(self.scheduled_intents.len()) {
+            self.tick_cursor = 0;
+        }
+        let page: Vec<String> = self
+            .scheduled_intents
+            .iter()
+            .skip(usize::try_from(self.tick_cursor).unwrap_or(usize::MAX))
+            .take(limit as usize)
+            .cloned()
+            .collect();
+        let mut started = Vec::new();
+        // Intents looked at and still scheduled, which the cursor moves past
+        let mut passed = 0;
+        for intent_id in page {
+            if started.len() == MAX_TICK_EXECUTIONS {
+                break;
+            }
+            let Some(intent) = self.load_intent(&intent_id) else {
+                self.scheduled_intents.remove(&intent_id);
+                continue;
+            };
+            if !matches!(intent.status, IntentStatus::Pending)
+                || env::block_timestamp() >= intent.expires_at.0
+            {
+                self.scheduled_intents.remove(&intent_id);
+                continue;
+            }
+            if !self.is_ready_to_execute(&intent) {
+                passed += 1;
+                continue;
+            }
+            let gas = Gas(GAS_PER_EXECUTION_CHECK.0
+                + self.delivery_gas(&intent).0
+                + self.config.transfer_callback_gas.0);
+            if env::prepaid_gas().0.saturating_sub(env::used_gas().0) < gas.0 {
+                break;
+            }
+            Self::ext(env::current_account_id())
+                .with_static_gas(gas)
+                .execute_scheduled(intent_id.clone());
+            started.push(intent_id);
+            passed += 1;
+        }
+        self.tick_cursor += passed;
+        started
+    }
+
+    /// Execute an intent on behalf of the scheduler
+    #[private]
+    pub fn execute_scheduled(&mut self, intent_id: String) -> PromiseOrValue<bool> {
+        self.assert_not_paused();
+        let executed = self.execute(&env::current_account_id(), intent_id.clone());
+        // The scheduler's task already paid for the gas
+        self.execution_gas.remove(&intent_id);
+        executed
+    }
+}
+
+impl CrossChainExecutor {
+    /// Track a new intent for scheduled execution while a scheduler is set
+    pub(crate) fn schedule_intent(&mut self, intent_id: &str) {
+        if self.croncat_manager.is_some() {
+            self.scheduled_intents.insert(intent_id.to_string());
+        }
+    }
+
+    /// Whether a pending intent is past any deferral and dependency, carries
+    /// the attestations to execute and is not reserved for a solver
+    fn is_ready_to_execute(&self, intent: &CrossChainIntent) -> bool {
+        intent.is_eligible()
+            && self.check_dependency_completed(intent).is_ok()
+            && self.live_claim(&intent.id).is_none()
+            && (self.has_aggregate_attestation(intent)
+                || self.count_approvals(intent)
+                    >= self.chain_threshold(&self.chain(intent.chain_id), intent.epoch))
+            && self.check_checkpointed(intent).is_ok()
+    }
+
+    fn croncat(&self) -> AccountId {
+        self.croncat_manager
+            .clone()
+            .unwrap_or_else(|| ContractError::CroncatNotConfigured.panic())
+    }
+}
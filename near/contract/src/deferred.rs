@@ -0,0 +1,43 @@
+//! Deferred execution.
+//!
+//! An intent may name a time before which it may not execute, so a sender
+//! can lock a delivery in now and have it land later, for vesting-style
+//! releases or to settle deposits together. The time is part of the
+//! intent's typed hash. Executions before it are rejected and the intent
+//! stays pending, still expiring at its usual time, which must come after.
+//! Deferred intents move a single token as is.
+
+use near_sdk::env;
+
+use crate::errors::ContractError;
+use crate::{CrossChainExecutor, CrossChainIntent, DeliveryKind};
+
+impl CrossChainExecutor {
+    /// Check a new intent's deferral leaves it a window to execute in
+    pub(crate) fn check_execute_after(
+        &self,
+        intent: &CrossChainIntent,
+    ) -> Result<(), ContractError> {
+        let Some(execute_after) = intent.execute_after else {
+            return Ok(());
+        };
+        if execute_after.0 >= intent.expires_at.0 {
+            return Err(ContractError::ExpiresBeforeEligible);
+        }
+        if !matches!(
+            intent.delivery_kind(),
+            DeliveryKind::Transfer | DeliveryKind::Defuse | DeliveryKind::Actions
+        ) {
+            return Err(ContractError::DeferredDelivery);
+        }
+        Ok(())
+    }
+}
+
+impl CrossChainIntent {
+    /// Whether the intent's deferral, if any, has run out
+    pub(crate) fn is_eligible(&self) -> bool {
+        self.execute_after
+            .is_none_or(|execute_after| env::block_timestamp() >= execute_after.0)
+    }
+}
@@ -0,0 +1,88 @@
+//! NEAR Intents settlement.
+//!
+//! Intents may settle into the NEAR Intents verifier contract (`intents.near`)
+//! instead of the receiver's wallet: the delivered tokens are deposited with
+//! `ft_transfer_call` and credited to the receiver inside the verifier, where
+//! they can be swapped by `token_diff` intents without another hop. The
+//! intent only completes if the verifier kept the whole deposit; one it
+//! refunded fails the intent.
+//!
+//! The executor also holds a balance of its own in the verifier. The owner
+//! registers public keys for it there, and the agent submits `token_diff`
+//! intents signed with those keys to settle against that balance.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::{self, json};
+use near_sdk::{near_bindgen, AccountId, Gas, Promise, PublicKey};
+
+use crate::errors::{require, ContractError};
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+/// Gas forwarded to the verifier's `execute_intents`
+const EXECUTE_INTENTS_GAS: Gas = Gas(100_000_000_000_000);
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the NEAR Intents verifier contract, or stop settling into it
+    pub fn set_defuse_contract(&mut self, contract_id: Option<AccountId>) {
+        self.assert_role(&[Role::Owner]);
+        self.defuse_contract = contract_id;
+    }
+
+    /// Get the NEAR Intents verifier contract
+    pub fn get_defuse_contract(&self) -> Option<AccountId> {
+        self.defuse_contract.clone()
+    }
+
+    /// Register a key allowed to sign intents for the executor's verifier balance
+    pub fn add_defuse_public_key(&mut self, public_key: PublicKey) -> Promise {
+        self.assert_role(&[Role::Owner]);
+        Promise::new(self.defuse()).function_call(
+            "add_public_key".to_string(),
+            json!({ "public_key": public_key }).to_string().into_bytes(),
+            1,
+            self.config.ft_transfer_gas,
+        )
+    }
+
+    /// Submit signed `token_diff` intents to the verifier, settling them
+    /// against the executor's and the counterparties' verifier balances
+    pub fn execute_defuse_intents(&mut self, signed: Vec<serde_json::Value>) -> Promise {
+        self.assert_agent();
+        require(!signed.is_empty(), ContractError::NoIntentsToExecute);
+        Promise::new(self.defuse()).function_call(
+            "execute_intents".to_string(),
+            json!({ "signed": signed }).to_string().into_bytes(),
+            0,
+            EXECUTE_INTENTS_GAS,
+        )
+    }
+}
+
+impl CrossChainExecutor {
+    /// Deposit `amount` of a source chain token's NEAR counterpart into the
+    /// verifier on behalf of `receiver`, minting it first if the mapping is
+    /// mintable. Fails unless the verifier kept all of it.
+    pub(crate) fn deposit_to_defuse(
+        &self,
+        chain_id: u64,
+        base_token: &str,
+        receiver: &AccountId,
+        amount: U128,
+    ) -> Promise {
+        self.deposit_token(
+            chain_id,
+            base_token,
+            &self.defuse(),
+            amount,
+            receiver.to_string(),
+        )
+    }
+
+    fn defuse(&self) -> AccountId {
+        self.defuse_contract
+            .clone()
+            .unwrap_or_else(|| ContractError::DefuseNotConfigured.panic())
+    }
+}
@@ -0,0 +1,246 @@
+//! NEP-366 delegate actions.
+//!
+//! Receivers can have an intent executed or cancelled without holding NEAR
+//! for gas: they sign a delegate action calling `execute_intent` or
+//! `cancel_intent` on the executor, and the agent relays it. The signature
+//! is checked on-chain over the NEP-366 hash of the delegate action, so the
+//! same payload a wallet signs for a protocol-level meta transaction is
+//! accepted. Contracts cannot read access keys, so the signing key must be
+//! the one an implicit account is derived from, or a key the account
+//! registered for delegation.
+
+use near_sdk::borsh::BorshSerialize;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, serde_json, AccountId, CurveType, PromiseOrValue, PublicKey};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::{verify_ed25519, CrossChainExecutor, CrossChainExecutorExt};
+
+/// NEP-461 prefix tagging the signed payload as an NEP-366 delegate action
+const DELEGATE_ACTION_PREFIX: u32 = (1 << 30) + 366;
+
+/// Borsh tag of the function call action
+const FUNCTION_CALL_ACTION: u8 = 2;
+
+/// Most delegation keys an account can register
+const MAX_DELEGATE_KEYS: usize = 4;
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DelegateFunctionCall {
+    pub method_name: String,
+    /// JSON arguments of the call
+    pub args: Base64VecU8,
+    pub gas: U64,
+    pub deposit: U128,
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DelegateAction {
+    /// Account authorizing the calls, the intent's receiver
+    pub sender_id: AccountId,
+    /// Contract the calls are made on, the executor
+    pub receiver_id: AccountId,
+    pub actions: Vec<DelegateFunctionCall>,
+    /// Above any nonce the sender delegated with before
+    pub nonce: U64,
+    /// Last block height the action can be relayed at
+    pub max_block_height: U64,
+    pub public_key: PublicKey,
+}
+
+#[derive(Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SignedDelegateAction {
+    pub delegate_action: DelegateAction,
+    /// 64-byte ed25519 signature over the NEP-366 hash
+    pub signature: Base64VecU8,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct IntentArgs {
+    intent_id: String,
+}
+
+impl DelegateAction {
+    /// SHA-256 of the NEP-461 prefix followed by the borsh-serialized
+    /// action, as wallets sign it
+    pub fn signing_hash(&self) -> Vec<u8> {
+        let mut payload = (
+            DELEGATE_ACTION_PREFIX,
+            &self.sender_id,
+            &self.receiver_id,
+            self.actions.len() as u32,
+        )
+            .try_to_vec()
+            .expect("Failed to serialize delegate action");
+        for call in &self.actions {
+            payload.extend(
+                (
+                    FUNCTION_CALL_ACTION,
+                    &call.method_name,
+                    &call.args.0,
+                    call.gas.0,
+                    call.deposit.0,
+                )
+                    .try_to_vec()
+                    .expect("Failed to serialize delegate action"),
+            );
+        }
+        payload.extend(
+            (self.nonce.0, self.max_block_height.0)
+                .try_to_vec()
+                .expect("Failed to serialize delegate action"),
+        );
+        // Keys serialize as their curve tag followed by the raw key
+        payload.extend(self.public_key.as_bytes());
+        env::sha256(&payload)
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Allow a key to sign delegate actions for the caller
+    pub fn add_delegate_key(&mut self, public_key: PublicKey) {
+        require(
+            public_key.curve_type() == CurveType::ED25519,
+            ContractError::DelegateKeyNotEd25519,
+        );
+        let account_id = env::predecessor_account_id();
+        let mut keys = self
+            .delegate_keys
+            .get(&account_id)
+            .cloned()
+            .unwrap_or_default();
+        if !keys.contains(&public_key) {
+            require(
+                keys.len() < MAX_DELEGATE_KEYS,
+                ContractError::TooManyDelegateKeys,
+            );
+            keys.push(public_key);
+            self.delegate_keys.insert(account_id.clone(), keys);
+        }
+    }
+
+    /// Stop a key from signing delegate actions for the caller
+    pub fn remove_delegate_key(&mut self, public_key: PublicKey) {
+        let account_id = env::predecessor_account_id();
+        let mut keys = self
+            .delegate_keys
+            .get(&account_id)
+            .cloned()
+            .unwrap_or_default();
+        keys.retain(|key| key != &public_key);
+        if keys.is_empty() {
+            self.delegate_keys.remove(&account_id);
+        } else {
+            self.delegate_keys.insert(account_id.clone(), keys);
+        }
+    }
+
+    /// Get the keys an account registered for delegation
+    pub fn get_delegate_keys(&self, account_id: AccountId) -> Vec<PublicKey> {
+        self.delegate_keys
+            .get(&account_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Get the last nonce an account delegated with
+    pub fn get_delegate_nonce(&self, account_id: AccountId) -> U64 {
+        U64(self.delegate_nonces.get(&account_id).copied().unwrap_or(0))
+    }
+
+    /// Relay a receiver's signed delegate action executing or cancelling
+    /// one of its intents
+    pub fn execute_delegate_action(
+        &mut self,
+        signed: SignedDelegateAction,
+    ) -> PromiseOrValue<bool> {
+        self.assert_not_paused();
+        self.assert_agent();
+
+        let action = signed.delegate_action;
+        require(
+            action.receiver_id == env::current_account_id(),
+            ContractError::DelegateReceiverMismatch,
+        );
+        require(
+            env::block_height() <= action.max_block_height.0,
+            ContractError::DelegateExpired,
+        );
+        require(
+            self.is_delegate_key(&action.sender_id, &action.public_key),
+            ContractError::DelegateKeyNotAllowed,
+        );
+        require(
+            verify_ed25519(
+                &action.public_key,
+                &action.signing_hash(),
+                &signed.signature.0,
+            ),
+            ContractError::InvalidDelegateSignature,
+        );
+        require(
+            action.nonce.0
+                > self
+                    .delegate_nonces
+                    .get(&action.sender_id)
+                    .copied()
+                    .unwrap_or(0),
+            ContractError::DelegateNonceUsed,
+        );
+        self.delegate_nonces
+            .insert(action.sender_id.clone(), action.nonce.0);
+
+        let call = match action.actions.as_slice() {
+            [call] => call,
+            _ => ContractError::DelegateNotSingleCall.panic(),
+        };
+        require(call.deposit.0 == 0, ContractError::DelegateDeposit);
+        let args: IntentArgs = serde_json::from_slice(&call.args.0)
+            .unwrap_or_else(|_| ContractError::InvalidCallArguments.panic());
+        let intent = self
+            .load_intent(&args.intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        require(
+            intent.receiver == action.sender_id,
+            ContractError::DelegateNotReceiver,
+        );
+
+        IntentEvent::DelegateActionRelayed {
+            sender_id: action.sender_id.clone(),
+            method_name: call.method_name.clone(),
+            nonce: action.nonce,
+        }
+        .emit();
+
+        match call.method_name.as_str() {
+            "execute_intent" => self.execute(&action.sender_id, args.intent_id),
+            "cancel_intent" => {
+                self.cancel(args.intent_id);
+                PromiseOrValue::Value(true)
+            }
+            _ => ContractError::MethodNotDelegable.panic(),
+        }
+    }
+}
+
+impl CrossChainExecutor {
+    /// Whether a key may sign delegate actions for an account: the key an
+    /// implicit account is derived from, or a registered delegation key
+    fn is_delegate_key(&self, account_id: &AccountId, public_key: &PublicKey) -> bool {
+        let implicit = public_key.curve_type() == CurveType::ED25519
+            && account_id.as_str() == hex::encode(&public_key.as_bytes()[1..]);
+        implicit
+            || self
+                .delegate_keys
+                .get(account_id)
+                .is_some_and(|keys| keys.contains(public_key))
+    }
+}
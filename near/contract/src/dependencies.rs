@@ -0,0 +1,54 @@
+//! Intent dependencies.
+//!
+//! Multi-leg flows, such as bridging funds and then paying a counterparty,
+//! need one intent to execute only after another delivered. An intent may
+//! name the intent it depends on, which the typed hash covers, and may not
+//! execute until that intent completed. A dependency that fails or is
+//! cancelled leaves the intent pending until it expires and is refunded.
+//! Dependent intents move a single token as is.
+
+use crate::errors::ContractError;
+use crate::{CrossChainExecutor, CrossChainIntent, DeliveryKind, IntentStatus};
+
+impl CrossChainExecutor {
+    /// Check a new intent depends on an intent that can still complete
+    pub(crate) fn check_dependency(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        let Some(depends_on) = &intent.depends_on else {
+            return Ok(());
+        };
+        if depends_on == &intent.id {
+            return Err(ContractError::SelfDependency);
+        }
+        let dependency = self
+            .load_intent(depends_on)
+            .ok_or(ContractError::UnknownDependency)?;
+        if matches!(
+            dependency.status,
+            IntentStatus::Failed(_) | IntentStatus::Cancelled | IntentStatus::Refunded
+        ) {
+            return Err(ContractError::DependencyCannotComplete);
+        }
+        if !matches!(
+            intent.delivery_kind(),
+            DeliveryKind::Transfer | DeliveryKind::Defuse | DeliveryKind::Actions
+        ) || intent.execute_after.is_some()
+        {
+            return Err(ContractError::DependentDelivery);
+        }
+        Ok(())
+    }
+
+    /// Check the intent an intent depends on, if any, has completed
+    pub(crate) fn check_dependency_completed(
+        &self,
+        intent: &CrossChainIntent,
+    ) -> Result<(), ContractError> {
+        match &intent.depends_on {
+            Some(depends_on) => match self.load_intent(depends_on) {
+                Some(dependency) if matches!(dependency.status, IntentStatus::Completed) => Ok(()),
+                _ => Err(ContractError::DependencyNotCompleted),
+            },
+            None => Ok(()),
+        }
+    }
+}
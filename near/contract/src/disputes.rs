@@ -0,0 +1,104 @@
+//! Intent disputes.
+//!
+//! Arbiters, guardians and validators can dispute a pending intent they
+//! believe is fraudulent, freezing it in the `Disputed` status so it cannot
+//! execute. An arbiter then rules on it: releasing it back to `Pending`, or
+//! cancelling it so its funds never move.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, IntentStatus};
+
+/// Longest evidence string a dispute may carry
+const MAX_EVIDENCE_LEN: usize = 1_024;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Dispute {
+    pub disputer: AccountId,
+    /// Free-form evidence, typically a link or transaction hash
+    pub evidence: String,
+    pub opened_at: U64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum DisputeRuling {
+    /// The dispute is dismissed and the intent may execute
+    Release,
+    /// The intent is fraudulent and is cancelled
+    Cancel,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Freeze a pending intent until an arbiter rules on it
+    pub fn dispute_intent(&mut self, intent_id: String, evidence: String) {
+        let disputer = self.assert_role(&[Role::Arbiter, Role::Guardian, Role::Validator]);
+        if evidence.len() > MAX_EVIDENCE_LEN {
+            ContractError::EvidenceTooLarge
+                .panic_with(&format!("Evidence exceeds {} bytes", MAX_EVIDENCE_LEN));
+        }
+
+        let mut intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        self.transition(&mut intent, IntentStatus::Disputed);
+        self.disputes.insert(
+            intent_id.clone(),
+            Dispute {
+                disputer: disputer.clone(),
+                evidence: evidence.clone(),
+                opened_at: U64(env::block_timestamp()),
+            },
+        );
+
+        IntentEvent::IntentDisputed {
+            intent_id,
+            disputer,
+            evidence,
+        }
+        .emit();
+    }
+
+    /// Rule on a disputed intent
+    pub fn resolve_dispute(&mut self, intent_id: String, ruling: DisputeRuling) {
+        let arbiter = self.assert_role(&[Role::Arbiter]);
+        let mut intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        require(
+            matches!(intent.status, IntentStatus::Disputed),
+            ContractError::NotDisputed,
+        );
+
+        match ruling {
+            DisputeRuling::Release => self.transition(&mut intent, IntentStatus::Pending),
+            DisputeRuling::Cancel => {
+                self.transition(&mut intent, IntentStatus::Cancelled);
+                self.approvals.remove(&intent_id);
+                self.aggregate_attestations.remove(&intent_id);
+            }
+        }
+        self.disputes.remove(&intent_id);
+
+        IntentEvent::DisputeResolved {
+            intent_id,
+            arbiter,
+            ruling,
+        }
+        .emit();
+    }
+
+    /// Get the open dispute on an intent
+    pub fn get_dispute(&self, intent_id: String) -> Option<Dispute> {
+        self.disputes.get(&intent_id).cloned()
+    }
+}
@@ -0,0 +1,496 @@
+//! Typed intent hashing and intent IDs.
+//!
+//! An intent's terms are hashed as an EIP-712 digest, so wallets and the
+//! bridge can compute it before the deposit and sign it with
+//! `eth_signTypedData`. The domain names the intent's source chain and is
+//! salted with the executor's account ID, so digests never collide across
+//! chains or deployments. The proof, status, epoch and
+//! signatures are not terms of the intent and are left out.
+//!
+//! The deposit commits to the terms: the bridge's `TokensLocked` log names
+//! the typed hash of the intent it backs. The contract then derives the ID
+//! the intent is created under from the deposit itself,
+//! `sha256(chain_id || tx_hash || log_index || sender || token || amount)`,
+//! so every submission of one deposit lands on the same ID and only the
+//! first is accepted.
+//!
+//! The sender may sign the digest itself, letting the contract check the
+//! sender authorized the intent without trusting the agent that submitted it.
+//!
+//! ```text
+//! EIP712Domain(string name,string version,uint256 chainId,bytes32 salt)
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse)
+//! ```
+//!
+//! Basket intents append their extra assets, leaving the digest of
+//! single-asset intents unchanged:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,BasketAsset[] extraAssets)BasketAsset(string token,uint256 amount)
+//! ```
+//!
+//! NFT intents append the token ID of the NFT they move:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,uint256 nftTokenId)
+//! ```
+//!
+//! Intents swapping on arrival append their swap terms:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,SwapAction swap)SwapAction(uint256 poolId,string tokenOut,uint256 minAmountOut,uint256 deadline)
+//! ```
+//!
+//! Intents delivered as a stablecoin append the flag asking for it:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,bool toStablecoin)
+//! ```
+//!
+//! Intents delivered to Aurora append the EVM address they credit:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,address auroraRecipient)
+//! ```
+//!
+//! Multi-token intents append the NEP-245 token ID they deliver:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,string mtTokenId)
+//! ```
+//!
+//! Deferred intents append the time from which they may execute:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,uint256 executeAfter)
+//! ```
+//!
+//! Intents waiting on another append the ID of the intent they depend on:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,bytes32 dependsOn)
+//! ```
+//!
+//! Multi-action intents append their actions, each encoded with every field
+//! of its kind and the fields of other kinds left empty:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,IntentAction[] actions)IntentAction(string kind,string receiverId,uint256 amount,string msg,uint256 poolId,string tokenOut,uint256 minAmountOut,uint256 deadline)
+//! ```
+//!
+//! Intents open to solver quotes append the least output they accept:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,uint256 minOut)
+//! ```
+//!
+//! Referred intents end whichever of these types they use with the account
+//! referring them, so signing the intent also signs who earns its referral
+//! share:
+//!
+//! ```text
+//! CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,string referrer)
+//! ```
+
+use near_sdk::{env, near_bindgen};
+
+use crate::actions::IntentAction;
+use crate::errors::ContractError;
+use crate::proof::parse_hex;
+use crate::tokens::normalize_address;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+pub const DOMAIN_NAME: &str = "NEAR Intents";
+pub const DOMAIN_VERSION: &str = "1";
+
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,bytes32 salt)";
+const INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse)";
+const BASKET_INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,BasketAsset[] extraAssets)BasketAsset(string token,uint256 amount)";
+const BASKET_ASSET_TYPE: &str = "BasketAsset(string token,uint256 amount)";
+const NFT_INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,uint256 nftTokenId)";
+const SWAP_INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,SwapAction swap)SwapAction(uint256 poolId,string tokenOut,uint256 minAmountOut,uint256 deadline)";
+const STABLECOIN_INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,bool toStablecoin)";
+const AURORA_INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,address auroraRecipient)";
+const MT_INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,string mtTokenId)";
+const DEFERRED_INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,uint256 executeAfter)";
+const DEPENDENT_INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,bytes32 dependsOn)";
+const ACTIONS_INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,IntentAction[] actions)IntentAction(string kind,string receiverId,uint256 amount,string msg,uint256 poolId,string tokenOut,uint256 minAmountOut,uint256 deadline)";
+const QUOTED_INTENT_TYPE: &str = "CrossChainIntent(address sender,string receiver,string token,uint256 amount,uint256 nonce,uint256 expiresAt,bool settleInDefuse,uint256 minOut)";
+const INTENT_ACTION_TYPE: &str =
+    "IntentAction(string kind,string receiverId,uint256 amount,string msg,uint256 poolId,string tokenOut,uint256 minAmountOut,uint256 deadline)";
+const SWAP_ACTION_TYPE: &str =
+    "SwapAction(uint256 poolId,string tokenOut,uint256 minAmountOut,uint256 deadline)";
+/// Last member of a referred intent's type, with the parenthesis it precedes
+const REFERRER_MEMBER: &str = ",string referrer)";
+
+impl CrossChainIntent {
+    /// EIP-712 digest of the intent's terms, failing on an invalid sender
+    pub fn typed_hash(&self) -> Result<Vec<u8>, ContractError> {
+        let sender = parse_hex(&self.sender)
+            .filter(|bytes| bytes.len() == 20)
+            .ok_or(ContractError::InvalidSenderAddress)?;
+        let intent_type = if self.is_basket() {
+            BASKET_INTENT_TYPE
+        } else if self.is_nft() {
+            NFT_INTENT_TYPE
+        } else if self.swap.is_some() {
+            SWAP_INTENT_TYPE
+        } else if self.to_stablecoin {
+            STABLECOIN_INTENT_TYPE
+        } else if self.aurora_recipient.is_some() {
+            AURORA_INTENT_TYPE
+        } else if self.mt_token_id.is_some() {
+            MT_INTENT_TYPE
+        } else if self.execute_after.is_some() {
+            DEFERRED_INTENT_TYPE
+        } else if self.depends_on.is_some() {
+            DEPENDENT_INTENT_TYPE
+        } else if !self.actions.is_empty() {
+            ACTIONS_INTENT_TYPE
+        } else if self.min_out.is_some() {
+            QUOTED_INTENT_TYPE
+        } else {
+            INTENT_TYPE
+        };
+        let intent_type = match &self.referrer {
+            // The referrer closes the intent's own members, ahead of any
+            // struct types it references
+            Some(_) => intent_type.replacen(')', REFERRER_MEMBER, 1),
+            None => intent_type.to_string(),
+        };
+        let mut fields = vec![
+            env::keccak256(intent_type.as_bytes()),
+            left_pad(&sender),
+            env::keccak256(self.receiver.as_bytes()),
+            env::keccak256(normalize_address(&self.token).as_bytes()),
+            uint_word(self.amount.0),
+            uint_word(self.nonce.into()),
+            uint_word(self.expires_at.0.into()),
+            uint_word(self.settle_in_defuse.into()),
+        ];
+        if self.is_basket() {
+            // Arrays of structs are encoded as the hash of their members' hashes
+            let assets: Vec<Vec<u8>> = self
+                .extra_assets
+                .iter()
+                .map(|asset| {
+                    env::keccak256(
+                        &[
+                            env::keccak256(BASKET_ASSET_TYPE.as_bytes()),
+                            env::keccak256(normalize_address(&asset.token).as_bytes()),
+                            uint_word(asset.amount.0),
+                        ]
+                        .concat(),
+                    )
+                })
+                .collect();
+            fields.push(env::keccak256(&assets.concat()));
+        }
+        if let Some(token_id) = self.nft_token_id {
+            fields.push(uint_word(token_id.0));
+        }
+        if let Some(swap) = &self.swap {
+            fields.push(env::keccak256(
+                &[
+                    env::keccak256(SWAP_ACTION_TYPE.as_bytes()),
+                    uint_word(swap.pool_id.into()),
+                    env::keccak256(swap.token_out.as_bytes()),
+                    uint_word(swap.min_amount_out.0),
+                    uint_word(swap.deadline.0.into()),
+                ]
+                .concat(),
+            ));
+        }
+        if self.to_stablecoin {
+            fields.push(uint_word(1));
+        }
+        if let Some(recipient) = &self.aurora_recipient {
+            let recipient = parse_hex(recipient)
+                .filter(|bytes| bytes.len() == 20)
+                .ok_or(ContractError::InvalidAuroraRecipient)?;
+            fields.push(left_pad(&recipient));
+        }
+        if let Some(token_id) = &self.mt_token_id {
+            fields.push(env::keccak256(token_id.as_bytes()));
+        }
+        if let Some(execute_after) = self.execute_after {
+            fields.push(uint_word(execute_after.0.into()));
+        }
+        if let Some(depends_on) = &self.depends_on {
+            let depends_on = parse_hex(depends_on)
+                .filter(|bytes| bytes.len() == 32)
+                .ok_or(ContractError::InvalidDependencyId)?;
+            fields.push(depends_on);
+        }
+        if !self.actions.is_empty() {
+            let actions: Vec<Vec<u8>> = self.actions.iter().map(action_hash).collect();
+            fields.push(env::keccak256(&actions.concat()));
+        }
+        if let Some(min_out) = self.min_out {
+            fields.push(uint_word(min_out.0));
+        }
+        if let Some(referrer) = &self.referrer {
+            fields.push(env::keccak256(referrer.as_bytes()));
+        }
+        let struct_hash = env::keccak256(&fields.concat());
+        Ok(env::keccak256(
+            &[
+                &[0x19, 0x01][..],
+                &domain_separator(self.chain_id),
+                &struct_hash,
+            ]
+            .concat(),
+        ))
+    }
+
+    /// Typed hash of the intent's terms as hex, as its deposit log names it
+    pub fn typed_id(&self) -> Result<String, ContractError> {
+        self.typed_hash()
+            .map(|hash| format!("0x{}", hex::encode(hash)))
+    }
+
+    /// ID the intent must be created under, derived from its deposit. Fixed
+    /// width fields come first, so the trailing token cannot be shifted
+    /// into another field.
+    pub fn derived_id(&self) -> Result<String, ContractError> {
+        let transaction_hash = parse_hex(&normalize_address(&self.proof.transaction_hash))
+            .filter(|bytes| bytes.len() == 32)
+            .ok_or(ContractError::InvalidTransactionHash)?;
+        let sender = parse_hex(&self.sender)
+            .filter(|bytes| bytes.len() == 20)
+            .ok_or(ContractError::InvalidSenderAddress)?;
+        let preimage = [
+            &self.chain_id.to_be_bytes()[..],
+            &transaction_hash,
+            &self.proof.log_index.to_be_bytes(),
+            &sender,
+            normalize_address(&self.token).as_bytes(),
+            &self.amount.0.to_be_bytes(),
+        ]
+        .concat();
+        Ok(format!("0x{}", hex::encode(env::sha256(&preimage))))
+    }
+
+    /// Check a 65-byte `r || s || v` secp256k1 signature over the typed hash
+    /// recovers to the sender
+    pub fn verify_sender_signature(&self, signature: &[u8]) -> Result<(), ContractError> {
+        if signature.len() != 65 {
+            return Err(ContractError::SenderSignatureLength);
+        }
+        // Wallets produce v as 27 or 28, the host function expects 0 or 1
+        let v = match signature[64] {
+            v @ (0 | 1) => v,
+            v @ (27 | 28) => v - 27,
+            _ => return Err(ContractError::InvalidSenderSignature),
+        };
+        let public_key = env::ecrecover(&self.typed_hash()?, &signature[..64], v, true)
+            .ok_or(ContractError::InvalidSenderSignature)?;
+        let address = &env::keccak256(&public_key)[12..];
+        if parse_hex(&self.sender).as_deref() != Some(address) {
+            return Err(ContractError::SenderSignatureMismatch);
+        }
+        Ok(())
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Typed hash of an intent's terms, which its deposit log names and its
+    /// sender signs
+    pub fn compute_intent_hash(&self, intent: CrossChainIntent) -> String {
+        match intent.typed_id() {
+            Ok(hash) => hash,
+            Err(reason) => reason.panic(),
+        }
+    }
+
+    /// ID an intent must be created under, derived from its deposit
+    pub fn compute_intent_id(&self, intent: CrossChainIntent) -> String {
+        match intent.derived_id() {
+            Ok(id) => id,
+            Err(reason) => reason.panic(),
+        }
+    }
+}
+
+fn domain_separator(chain_id: u64) -> Vec<u8> {
+    env::keccak256(
+        &[
+            env::keccak256(DOMAIN_TYPE.as_bytes()),
+            env::keccak256(DOMAIN_NAME.as_bytes()),
+            env::keccak256(DOMAIN_VERSION.as_bytes()),
+            uint_word(chain_id.into()),
+            env::keccak256(env::current_account_id().as_bytes()),
+        ]
+        .concat(),
+    )
+}
+
+fn left_pad(bytes: &[u8]) -> Vec<u8> {
+    let mut word = vec![0u8; 32 - bytes.len()];
+    word.extend_from_slice(bytes);
+    word
+}
+
+/// Struct hash of an action, the fields its kind lacks left empty
+fn action_hash(action: &IntentAction) -> Vec<u8> {
+    let (kind, receiver_id, msg, swap) = match action {
+        IntentAction::Transfer { receiver_id, .. } => ("transfer", receiver_id.as_str(), "", None),
+        IntentAction::Swap { swap, .. } => ("swap", "", "", Some(swap)),
+        IntentAction::Call {
+            receiver_id, msg, ..
+        } => ("call", receiver_id.as_str(), msg.as_str(), None),
+    };
+    env::keccak256(
+        &[
+            env::keccak256(INTENT_ACTION_TYPE.as_bytes()),
+            env::keccak256(kind.as_bytes()),
+            env::keccak256(receiver_id.as_bytes()),
+            uint_word(action.amount().0),
+            env::keccak256(msg.as_bytes()),
+            uint_word(swap.map_or(0, |swap| swap.pool_id.into())),
+            env::keccak256(swap.map_or("", |swap| swap.token_out.as_str()).as_bytes()),
+            uint_word(swap.map_or(0, |swap| swap.min_amount_out.0)),
+            uint_word(swap.map_or(0, |swap| swap.deadline.0.into())),
+        ]
+        .concat(),
+    )
+}
+
+fn uint_word(value: u128) -> Vec<u8> {
+    left_pad(&value.to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::BASE_CHAIN_ID;
+    use crate::tests::{agent, intent, set_caller, setup, BASE_TOKEN};
+    use near_sdk::json_types::{U128, U64};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::ONE_NEAR;
+
+    #[test]
+    #[should_panic(expected = "Intent ID does not match its deposit")]
+    fn create_rejects_intent_id_not_derived_from_its_deposit() {
+        let mut contract = setup();
+        let mut intent = intent(1_000);
+        assert_eq!(contract.compute_intent_id(intent.clone()), intent.id);
+        intent.amount = U128(2_000);
+
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent ID does not match its deposit")]
+    fn create_rejects_intent_ids_chosen_by_the_agent() {
+        let mut contract = setup();
+        let mut intent = intent(1_000);
+        intent.id = "my-intent".to_string();
+
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+    }
+
+    #[test]
+    fn created_intents_are_stored_under_their_derived_id() {
+        let mut contract = setup();
+        let intent = intent(1_000);
+        let derived = contract.compute_intent_id(intent.clone());
+
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+        assert_eq!(contract.get_intent(derived.clone()).unwrap().id, derived);
+    }
+
+    #[test]
+    fn intent_ids_hash_the_deposit() {
+        let contract = setup();
+        let intent = intent(1_000);
+        let preimage = [
+            &BASE_CHAIN_ID.to_be_bytes()[..],
+            &hex::decode(&intent.proof.transaction_hash[2..]).unwrap(),
+            &0u64.to_be_bytes(),
+            &hex::decode(&intent.sender[2..]).unwrap(),
+            BASE_TOKEN.as_bytes(),
+            &1_000u128.to_be_bytes(),
+        ]
+        .concat();
+        assert_eq!(
+            intent.id,
+            format!("0x{}", hex::encode(env::sha256(&preimage)))
+        );
+        assert_ne!(intent.id, contract.compute_intent_hash(intent.clone()));
+
+        // Terms outside the deposit leave the ID as it is
+        let mut renewed = intent.clone();
+        renewed.nonce += 1;
+        renewed.expires_at = U64(1);
+        assert_eq!(contract.compute_intent_id(renewed), intent.id);
+        let mut next_log = intent.clone();
+        next_log.proof.log_index = 1;
+        assert_ne!(contract.compute_intent_id(next_log), intent.id);
+        let mut unhashed = intent;
+        unhashed.proof.transaction_hash = "0xabc".to_string();
+        assert_eq!(
+            unhashed.derived_id(),
+            Err(ContractError::InvalidTransactionHash)
+        );
+    }
+
+    #[test]
+    fn intent_ids_ignore_hex_case_but_not_the_chain() {
+        let intent = intent(1_000);
+        let mut shouted = intent.clone();
+        shouted.token = BASE_TOKEN.to_uppercase();
+        shouted.proof.transaction_hash = intent.proof.transaction_hash.to_uppercase();
+        assert_eq!(shouted.derived_id(), Ok(intent.id.clone()));
+
+        let mut elsewhere = intent.clone();
+        elsewhere.chain_id = 10;
+        assert_ne!(elsewhere.derived_id(), Ok(intent.id));
+    }
+
+    #[test]
+    fn typed_hashes_cover_the_referrer() {
+        let _contract = setup();
+        let unreferred = intent(1_000);
+        let mut referred = unreferred.clone();
+        referred.referrer = Some(accounts(3));
+        let mut rereferred = unreferred.clone();
+        rereferred.referrer = Some(accounts(4));
+
+        let hashes = [&unreferred, &referred, &rereferred].map(|intent| intent.typed_id());
+        assert_ne!(hashes[0], hashes[1]);
+        assert_ne!(hashes[1], hashes[2]);
+        assert_ne!(hashes[0], hashes[2]);
+    }
+
+    #[test]
+    fn changing_the_referrer_invalidates_the_sender_signature() {
+        let _contract = setup();
+        let secret = near_crypto::SecretKey::from_seed(near_crypto::KeyType::SECP256K1, "sender");
+        let public_key = match secret.public_key() {
+            near_crypto::PublicKey::SECP256K1(public_key) => public_key,
+            _ => unreachable!(),
+        };
+        let mut intent = intent(1_000);
+        intent.sender = format!(
+            "0x{}",
+            hex::encode(&env::keccak256(public_key.as_ref())[12..])
+        );
+        intent.referrer = Some(accounts(3));
+        let signature = match secret.sign(&intent.typed_hash().unwrap()) {
+            near_crypto::Signature::SECP256K1(signature) => <[u8; 65]>::from(signature),
+            _ => unreachable!(),
+        };
+        assert_eq!(intent.verify_sender_signature(&signature), Ok(()));
+
+        intent.referrer = Some(accounts(4));
+        assert_eq!(
+            intent.verify_sender_signature(&signature),
+            Err(ContractError::SenderSignatureMismatch)
+        );
+    }
+}
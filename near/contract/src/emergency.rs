@@ -0,0 +1,169 @@
+//! Emergency recovery of stranded funds under DAO control.
+//!
+//! No single key can move funds out of the executor outside the intent
+//! flows. To recover stranded NEAR or NEP-141 tokens, the emergency DAO, a
+//! Sputnik DAO contract, passes a FunctionCall proposal calling
+//! `approve_emergency_withdrawal` with the hash of the withdrawal's token,
+//! amount and receiver. Once the timelock of the network the contract is
+//! built for has elapsed, anyone can execute exactly that withdrawal, once.
+//! Guardians and the owner can cancel an approved withdrawal before then.
+
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde_json::json;
+use near_sdk::{env, near_bindgen, AccountId, Promise, PromiseError};
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::network::EMERGENCY_WITHDRAWAL_DELAY;
+use crate::roles::Role;
+use crate::tokens::{is_native_token, normalize_address};
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the Sputnik DAO approving emergency withdrawals. The owner picks
+    /// it once, after which only the DAO itself can hand the role on.
+    pub fn set_emergency_dao(&mut self, dao: AccountId) {
+        match &self.emergency_dao {
+            Some(current) => require(
+                &env::predecessor_account_id() == current,
+                ContractError::EmergencyDaoReplacement,
+            ),
+            None => {
+                self.assert_role(&[Role::Owner]);
+            }
+        }
+        self.emergency_dao = Some(dao);
+    }
+
+    /// Get the Sputnik DAO approving emergency withdrawals, if any
+    pub fn get_emergency_dao(&self) -> Option<AccountId> {
+        self.emergency_dao.clone()
+    }
+
+    /// Approve the emergency withdrawal with `withdrawal_hash`, starting its
+    /// timelock. Called by the emergency DAO executing a proposal.
+    pub fn approve_emergency_withdrawal(&mut self, withdrawal_hash: String) {
+        require(
+            Some(env::predecessor_account_id()) == self.emergency_dao,
+            ContractError::NotEmergencyDao,
+        );
+        let withdrawal_hash = withdrawal_hash.to_lowercase();
+        require(
+            !self.emergency_approvals.contains_key(&withdrawal_hash),
+            ContractError::WithdrawalApproved,
+        );
+        let executable_at = env::block_timestamp() + EMERGENCY_WITHDRAWAL_DELAY;
+        self.emergency_approvals
+            .insert(withdrawal_hash.clone(), executable_at);
+
+        IntentEvent::EmergencyWithdrawalApproved {
+            withdrawal_hash,
+            executable_at: U64(executable_at),
+        }
+        .emit();
+    }
+
+    /// Drop an approved emergency withdrawal before it executes
+    pub fn cancel_emergency_withdrawal(&mut self, withdrawal_hash: String) {
+        if Some(env::predecessor_account_id()) != self.emergency_dao {
+            self.assert_role(&[Role::Guardian, Role::Owner]);
+        }
+        let withdrawal_hash = withdrawal_hash.to_lowercase();
+        require(
+            self.emergency_approvals.remove(&withdrawal_hash).is_some(),
+            ContractError::WithdrawalNotApproved,
+        );
+
+        IntentEvent::EmergencyWithdrawalCancelled { withdrawal_hash }.emit();
+    }
+
+    /// Send `amount` of native NEAR or a NEP-141 token to `to`, once the
+    /// DAO-approved withdrawal's timelock has elapsed. Callable by anyone.
+    pub fn emergency_withdraw(&mut self, token: String, amount: U128, to: AccountId) -> Promise {
+        require(amount.0 > 0, ContractError::AmountNotPositive);
+        let token = normalize_address(&token);
+        let withdrawal_hash = emergency_withdrawal_hash(&token, amount, &to);
+        let executable_at = self
+            .emergency_approvals
+            .remove(&withdrawal_hash)
+            .unwrap_or_else(|| ContractError::WithdrawalNotApproved.panic());
+        require(
+            env::block_timestamp() >= executable_at,
+            ContractError::WithdrawalTimelockPending,
+        );
+
+        let transfer = if is_native_token(&token) {
+            self.assert_native_liquidity(amount.0);
+            Promise::new(to.clone()).transfer(amount.0)
+        } else {
+            let token_contract: AccountId = token
+                .parse()
+                .unwrap_or_else(|_| ContractError::InvalidTokenContract.panic());
+            Promise::new(token_contract).function_call(
+                "ft_transfer".to_string(),
+                json!({ "receiver_id": to, "amount": amount, "memo": withdrawal_hash })
+                    .to_string()
+                    .into_bytes(),
+                self.config.ft_transfer_deposit.0,
+                self.config.ft_transfer_gas,
+            )
+        };
+
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(self.config.transfer_callback_gas)
+                .on_emergency_withdrawn(token, amount, to, U64(executable_at)),
+        )
+    }
+
+    /// Restore the approval of an emergency withdrawal whose transfer failed
+    #[private]
+    pub fn on_emergency_withdrawn(
+        &mut self,
+        token: String,
+        amount: U128,
+        to: AccountId,
+        executable_at: U64,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            IntentEvent::EmergencyWithdrawn {
+                token,
+                amount,
+                receiver: to,
+            }
+            .emit();
+            true
+        } else {
+            let withdrawal_hash = emergency_withdrawal_hash(&token, amount, &to);
+            self.emergency_approvals
+                .insert(withdrawal_hash, executable_at.0);
+            false
+        }
+    }
+
+    /// Hash the DAO approves to allow `emergency_withdraw(token, amount, to)`
+    pub fn get_emergency_withdrawal_hash(
+        &self,
+        token: String,
+        amount: U128,
+        to: AccountId,
+    ) -> String {
+        emergency_withdrawal_hash(&normalize_address(&token), amount, &to)
+    }
+
+    /// Nanosecond timestamp from which an approved emergency withdrawal can
+    /// execute, or `None` if it is not approved
+    pub fn get_emergency_withdrawal(&self, withdrawal_hash: String) -> Option<U64> {
+        self.emergency_approvals
+            .get(&withdrawal_hash.to_lowercase())
+            .copied()
+            .map(U64)
+    }
+}
+
+fn emergency_withdrawal_hash(token: &str, amount: U128, to: &AccountId) -> String {
+    let payload = format!("emergency_withdraw:{}:{}:{}", token, amount.0, to);
+    format!("0x{}", hex::encode(env::sha256(payload.as_bytes())))
+}
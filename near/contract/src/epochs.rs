@@ -0,0 +1,167 @@
+//! Epoch-based validator sets.
+//!
+//! Epoch 0 is the live validator registry with the configured
+//! `required_signatures`. Each rotation snapshots registered validators and
+//! their keys into the next epoch's set, signed off by a quorum of the set it
+//! replaces. Intents name the epoch their attestations belong to and are
+//! counted against that epoch's set, so intents signed before a rotation stay
+//! executable. Header attestations and outbound approvals use the current
+//! epoch. Members of any set only count while bonded, and chains may narrow
+//! which members attest for them.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, PublicKey};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::{verify_ed25519, CrossChainExecutor, CrossChainExecutorExt, ValidatorSignature};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EpochValidator {
+    pub account_id: AccountId,
+    pub public_key: PublicKey,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidatorSet {
+    pub validators: Vec<EpochValidator>,
+    /// Signatures from the set needed for a quorum
+    pub threshold: u32,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Replace the validator set with the registered `validators` from the
+    /// next epoch on. `signatures` are over [`rotation_hash`] and must reach
+    /// the current set's quorum. Callable by anyone, typically the relayer.
+    pub fn rotate_validator_set(
+        &mut self,
+        epoch: u64,
+        validators: Vec<AccountId>,
+        threshold: u32,
+        signatures: Vec<ValidatorSignature>,
+    ) {
+        require(
+            epoch == self.current_epoch + 1,
+            ContractError::EpochOutOfOrder,
+        );
+        require(
+            threshold > 0 && threshold as usize <= validators.len(),
+            ContractError::InvalidEpochThreshold,
+        );
+
+        let message = rotation_hash(epoch, &validators, threshold);
+        require(
+            self.count_epoch_signatures(self.current_epoch, &message, &signatures, |_| true)
+                >= self.epoch_threshold(self.current_epoch),
+            ContractError::NotEnoughSignatures,
+        );
+
+        let mut members: Vec<EpochValidator> = Vec::new();
+        for account_id in &validators {
+            if members
+                .iter()
+                .any(|member| &member.account_id == account_id)
+            {
+                ContractError::DuplicateValidator
+                    .panic_with(&format!("Duplicate validator {}", account_id));
+            }
+            let public_key = self.attesting_key(account_id).unwrap_or_else(|| {
+                ContractError::NotBondedValidator
+                    .panic_with(&format!("{} is not a bonded validator", account_id))
+            });
+            members.push(EpochValidator {
+                account_id: account_id.clone(),
+                public_key,
+            });
+        }
+        self.validator_sets.insert(
+            epoch,
+            ValidatorSet {
+                validators: members,
+                threshold,
+            },
+        );
+        self.current_epoch = epoch;
+
+        IntentEvent::ValidatorSetRotated {
+            epoch,
+            validators,
+            threshold,
+        }
+        .emit();
+    }
+
+    /// Epoch of the validator set currently attesting
+    pub fn get_current_epoch(&self) -> u64 {
+        self.current_epoch
+    }
+
+    /// Get the validator set of a rotated epoch. Epoch 0 is the validator
+    /// registry itself and has no snapshot.
+    pub fn get_validator_set(&self, epoch: u64) -> Option<ValidatorSet> {
+        self.validator_sets.get(&epoch).cloned()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Key a validator attests with in an epoch, if it counts towards the
+    /// epoch's quorum
+    pub(crate) fn epoch_key(&self, epoch: u64, validator: &AccountId) -> Option<PublicKey> {
+        if epoch == 0 {
+            return self.attesting_key(validator);
+        }
+        self.attesting_key(validator)?;
+        self.validator_sets
+            .get(&epoch)?
+            .validators
+            .iter()
+            .find(|member| &member.account_id == validator)
+            .map(|member| member.public_key.clone())
+    }
+
+    /// Number of signatures or approvals an epoch's quorum needs
+    pub(crate) fn epoch_threshold(&self, epoch: u64) -> u32 {
+        if epoch == 0 {
+            return self.config.required_signatures;
+        }
+        self.validator_sets
+            .get(&epoch)
+            .map_or(u32::MAX, |set| set.threshold)
+    }
+
+    /// Count distinct members of an epoch's set passing `counts` with a
+    /// valid signature on `message`
+    pub(crate) fn count_epoch_signatures(
+        &self,
+        epoch: u64,
+        message: &[u8],
+        signatures: &[ValidatorSignature],
+        counts: impl Fn(&AccountId) -> bool,
+    ) -> u32 {
+        let mut signers = Vec::new();
+        for entry in signatures {
+            if signers.contains(&&entry.validator) || !counts(&entry.validator) {
+                continue;
+            }
+            if let Some(public_key) = self.epoch_key(epoch, &entry.validator) {
+                if verify_ed25519(&public_key, message, &entry.signature.0) {
+                    signers.push(&entry.validator);
+                }
+            }
+        }
+        signers.len() as u32
+    }
+}
+
+/// Hash validators sign to approve a rotation
+pub fn rotation_hash(epoch: u64, validators: &[AccountId], threshold: u32) -> Vec<u8> {
+    let payload = ("rotate_validator_set", epoch, validators, threshold)
+        .try_to_vec()
+        .expect("Failed to serialize rotation");
+    env::sha256(&payload)
+}
@@ -0,0 +1,594 @@
+//! Contract errors.
+//!
+//! Failures the relayer and the MCP server branch on carry a stable error
+//! code next to their message, so clients match on the code rather than on
+//! wording that may change. A call failing with one panics with both as
+//! JSON, `{"code":"INTENT_EXPIRED","message":"Intent expired"}`, and views
+//! and batch results reporting one return the code. Codes are never reused
+//! or renamed once released.
+
+use std::fmt;
+
+use near_sdk::serde::Serialize;
+use near_sdk::serde_json::json;
+use near_sdk::FunctionError;
+use schemars::JsonSchema;
+
+/// Failure with a stable, machine-readable code
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(crate = "near_sdk::serde", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContractError {
+    // Intent terms, signatures and parties
+    IdMismatch,
+    InvalidTransactionHash,
+    InvalidSenderAddress,
+    SenderSignatureRequired,
+    SenderSignatureLength,
+    InvalidSenderSignature,
+    SenderSignatureMismatch,
+    IdsMigrating,
+    AlreadyExecuted,
+    AlreadyExpired,
+    UnknownEpoch,
+    ChainNotSupported,
+    NonceUsed,
+    SenderBlocked,
+    ReceiverBlocked,
+    ReceiverNotAllowed,
+    BatchOutOfGas,
+
+    // Tokens, amounts and baskets
+    TokenNotSupported,
+    TokenDisabled,
+    AmountBelowMinimum,
+    AmountAboveMaximum,
+    AmountOverflow,
+    DefuseNotConfigured,
+    NativeCannotSettleInDefuse,
+    TooManyBasketAssets,
+    BasketAmountNotPositive,
+    BasketTokensNotDistinct,
+    BasketLogsNotDistinct,
+    BasketCannotSettleInDefuse,
+    CollectionNotSupported,
+    CollectionDisabled,
+    NftBasket,
+    NftCannotSettleInDefuse,
+    NftAmount,
+    NotMultiToken,
+    MultiTokenIdMissing,
+    InvalidMultiTokenId,
+    MultiTokenDelivery,
+    TokenNotRegistered,
+    LimitsNotOrdered,
+    TooManyDecimals,
+    MintableMultiToken,
+    NativeNeedsNoRegistration,
+    DepositRefunded,
+    InsufficientNativeBalance,
+    InvalidDefaultToken,
+    CollectionNotRegistered,
+    NativeNotCollection,
+    NftMintFailed,
+
+    // Swaps, Aurora, deferrals, dependencies, actions and quotes
+    RefNotConfigured,
+    SwapDelivery,
+    NativeCannotSwap,
+    SwapAndStablecoin,
+    SwapDeadlinePassed,
+    NoStablecoinRoute,
+    AuroraNotConfigured,
+    AuroraReceiver,
+    InvalidAuroraRecipient,
+    NativeCannotGoToAurora,
+    AuroraDelivery,
+    ExpiresBeforeEligible,
+    DeferredDelivery,
+    SelfDependency,
+    UnknownDependency,
+    InvalidDependencyId,
+    DependencyCannotComplete,
+    DependentDelivery,
+    TooManyActions,
+    ActionDelivery,
+    NativeCannotSplit,
+    ActionAmountNotPositive,
+    ActionAmountsMismatch,
+    ActionsOutOfGas,
+    QuoteFloorNotPositive,
+    QuotedDelivery,
+    SwapOutputBelowMinimum,
+    SwapResolvedLate,
+    TakesNoQuotes,
+    QuoteAboveDeliverable,
+    QuoteExpiryPassed,
+    QuoteBelowMinimum,
+    QuoteBelowAuctionFloor,
+    TooManyQuotes,
+    AlreadyAuctioned,
+    InvalidAuctionDeadline,
+    FloorAboveDeliverable,
+    FloorBelowMinimum,
+    NoFailedCompensation,
+
+    // Deposit proofs
+    ProofTooOld,
+    ProofFromFuture,
+    DepositUsed,
+    ChainNotCheckpointed,
+    ProofNotCheckpointed,
+    InvalidReceiptProof,
+    ReceiptMismatch,
+    InvalidReceiptEncoding,
+    SourceTransactionFailed,
+    LogNotFound,
+    InvalidLogEncoding,
+    LogNotFromBridge,
+    LogNotDeposit,
+    InvalidLogData,
+    LogIntentMismatch,
+    LogSenderMismatch,
+    LogReceiverMismatch,
+    LogTokenMismatch,
+    LogAmountMismatch,
+    BridgeNotConfigured,
+    InvalidBridgeAddress,
+    InvalidReceiptsRoot,
+
+    // Intent lifecycle and access
+    IntentNotFound,
+    NotPending,
+    ApprovalNotPending,
+    AlreadyApproved,
+    NotEnoughApprovals,
+    Expired,
+    NotEligible,
+    DependencyNotCompleted,
+    NotEnoughGas,
+    Executing,
+    NotExpired,
+    NotRefundable,
+    Claimed,
+    Paused,
+    MissingRole,
+    AgentInactive,
+    AlreadyClaimed,
+    BeingAuctioned,
+    NotDisputed,
+    EvidenceTooLarge,
+    NotTerminal,
+    BatchEmpty,
+    BatchTooLarge,
+    BatchMultiAction,
+    BatchNotEnoughGas,
+    NoIntentsToExecute,
+    TooManyIntentIds,
+    IllegalTransition,
+
+    // Validators, bonds, epochs, checkpoints and light clients
+    NotEnoughSignatures,
+    InvalidAggregateSignature,
+    AggregateKeyNotEd25519,
+    AttestationNotPending,
+    ValidatorKeyNotEd25519,
+    BondBelowMinimum,
+    BondUnbonding,
+    InvalidUnbondAmount,
+    NoBond,
+    NothingToWithdraw,
+    ValidatorNotFound,
+    DepositProofValid,
+    NotSignedByValidator,
+    IntentsNotSignedByValidator,
+    IntentsDoNotConflict,
+    EpochOutOfOrder,
+    InvalidEpochThreshold,
+    DuplicateValidator,
+    NotBondedValidator,
+    CheckpointNotAdvancing,
+    InvalidStateRoot,
+    LightClientNotInitialized,
+    HeaderNotFound,
+    BlockNotConfirmed,
+    HeaderKnown,
+    InvalidBlockNumber,
+    InvalidHeaderEncoding,
+    ParentNotFound,
+
+    // Solvers, agents, delegated calls and referrals
+    NotSolver,
+    SolverNeedsPair,
+    SolverPairUnsupported,
+    NotAgent,
+    AgentQuotaExceeded,
+    AgentVolumeOverflow,
+    DelegateExpired,
+    DelegateReceiverMismatch,
+    DelegateNotSingleCall,
+    DelegateKeyNotEd25519,
+    DelegateDeposit,
+    InvalidCallArguments,
+    DelegateKeyNotAllowed,
+    InvalidDelegateSignature,
+    DelegateNonceUsed,
+    MethodNotDelegable,
+    DelegateNotReceiver,
+    TooManyDelegateKeys,
+    NoReferralFees,
+    ReferralShareTooHigh,
+
+    // Outbound transfers, escrows, storage and fees
+    OutboundNotFound,
+    OutboundNotPending,
+    OutboundTokenMismatch,
+    InvalidTransferMsg,
+    BridgeDepositMissing,
+    SignerFeeMissing,
+    SignerNotConfigured,
+    InvalidBaseAddress,
+    EscrowIdTooLong,
+    EscrowCodeEmpty,
+    EscrowCodeMissing,
+    StorageNotRegistered,
+    StorageDepositTooSmall,
+    StorageAccountInUse,
+    InsufficientStorageBalance,
+    StorageWithdrawTooLarge,
+    StorageBelowMinimum,
+    NoFees,
+    FeeTooHigh,
+    GasFundDepositMissing,
+    GasFundExceeded,
+    NoGasReimbursement,
+
+    // Administration, schedules and limits
+    AlreadyInitialized,
+    NotInitialized,
+    InvalidStateVersion,
+    UnknownStateVersion,
+    StateDowngrade,
+    MissingCode,
+    InvalidRequiredSignatures,
+    InvalidGas,
+    DepositNotPositive,
+    ProofAgeNotPositive,
+    AmountNotPositive,
+    InvalidMultiple,
+    AlreadyPaused,
+    NotPaused,
+    NotProposedOwner,
+    RoleNotGrantable,
+    RoleNotHeld,
+    TimelockNotSet,
+    TimelockDelayTooShort,
+    NotTimelocked,
+    ActionNotQueued,
+    ChangeNotQueued,
+    TimelockPending,
+    NotEmergencyDao,
+    EmergencyDaoReplacement,
+    InvalidTokenContract,
+    WithdrawalApproved,
+    WithdrawalNotApproved,
+    WithdrawalTimelockPending,
+    CroncatNotConfigured,
+    NotScheduler,
+    ComplianceNotConfigured,
+    RateLimitOverflow,
+    RateLimitAmount,
+    RateLimitExecutions,
+    InvalidRateWindow,
+    InvalidDeliveryAttempts,
+    IntervalTooShort,
+    TemplateNotFound,
+    NoPeriods,
+    PeriodNotDue,
+}
+
+impl ContractError {
+    /// Human-readable description of the failure
+    pub fn message(&self) -> &'static str {
+        match self {
+            ContractError::IdMismatch => "Intent ID does not match its deposit",
+            ContractError::InvalidTransactionHash => "Invalid transaction hash",
+            ContractError::InvalidSenderAddress => "Invalid sender address",
+            ContractError::SenderSignatureRequired => "Sender signature required",
+            ContractError::SenderSignatureLength => "Sender signature must be 65 bytes",
+            ContractError::InvalidSenderSignature => "Invalid sender signature",
+            ContractError::SenderSignatureMismatch => "Sender signature does not match the sender",
+            ContractError::IdsMigrating => "Intent IDs are being migrated",
+            ContractError::AlreadyExecuted => "Intent already executed",
+            ContractError::AlreadyExpired => "Intent already expired",
+            ContractError::UnknownEpoch => "Unknown validator set epoch",
+            ContractError::ChainNotSupported => "Chain not supported",
+            ContractError::NonceUsed => "Nonce already used",
+            ContractError::SenderBlocked => "Sender is blocked",
+            ContractError::ReceiverBlocked => "Receiver is blocked",
+            ContractError::ReceiverNotAllowed => "Receiver not allowed",
+            ContractError::BatchOutOfGas => "Not enough gas left in batch",
+            ContractError::TokenNotSupported => "Token not supported",
+            ContractError::TokenDisabled => "Token disabled",
+            ContractError::AmountBelowMinimum => "Amount below token minimum",
+            ContractError::AmountAboveMaximum => "Amount above token maximum",
+            ContractError::AmountOverflow => "Amount overflows after rescaling",
+            ContractError::DefuseNotConfigured => "NEAR Intents contract not configured",
+            ContractError::NativeCannotSettleInDefuse => {
+                "Native NEAR cannot settle in NEAR Intents"
+            }
+            ContractError::TooManyBasketAssets => "Too many basket assets",
+            ContractError::BasketAmountNotPositive => "Basket asset amount must be positive",
+            ContractError::BasketTokensNotDistinct => "Basket assets must be distinct tokens",
+            ContractError::BasketLogsNotDistinct => "Basket assets must be locked by distinct logs",
+            ContractError::BasketCannotSettleInDefuse => {
+                "Basket intents cannot settle in NEAR Intents"
+            }
+            ContractError::CollectionNotSupported => "Collection not supported",
+            ContractError::CollectionDisabled => "Collection disabled",
+            ContractError::NftBasket => "NFT intents cannot carry basket assets",
+            ContractError::NftCannotSettleInDefuse => "NFT intents cannot settle in NEAR Intents",
+            ContractError::NftAmount => "NFT intents move exactly one token",
+            ContractError::NotMultiToken => "Token is not a multi-token",
+            ContractError::MultiTokenIdMissing => "Multi-token intents must name a token ID",
+            ContractError::InvalidMultiTokenId => "Invalid multi-token ID",
+            ContractError::MultiTokenDelivery => {
+                "Multi-token intents are delivered to wallets as is"
+            }
+            ContractError::TokenNotRegistered => "Token not registered",
+            ContractError::LimitsNotOrdered => "Minimum amount exceeds maximum",
+            ContractError::TooManyDecimals => "Too many decimals",
+            ContractError::MintableMultiToken => "Multi-token mappings cannot be mintable",
+            ContractError::NativeNeedsNoRegistration => "Native NEAR does not need registration",
+            ContractError::DepositRefunded => "Deposit was refunded",
+            ContractError::InsufficientNativeBalance => "Insufficient NEAR balance for native transfer",
+            ContractError::InvalidDefaultToken => "Invalid default token",
+            ContractError::CollectionNotRegistered => "Collection not registered",
+            ContractError::NativeNotCollection => "Native NEAR is not an NFT collection",
+            ContractError::NftMintFailed => "NFT mint failed",
+            ContractError::RefNotConfigured => "Ref Finance contract not configured",
+            ContractError::SwapDelivery => {
+                "Only single-token intents delivered to wallets can swap"
+            }
+            ContractError::NativeCannotSwap => "Native NEAR cannot be swapped",
+            ContractError::SwapAndStablecoin => "Intents cannot both swap and ask for a stablecoin",
+            ContractError::SwapDeadlinePassed => "Swap deadline already passed",
+            ContractError::NoStablecoinRoute => "Token has no stablecoin route",
+            ContractError::AuroraNotConfigured => "Aurora engine not configured",
+            ContractError::AuroraReceiver => "Aurora intents must be received by the Aurora engine",
+            ContractError::InvalidAuroraRecipient => "Invalid Aurora recipient",
+            ContractError::NativeCannotGoToAurora => "Native NEAR cannot be delivered to Aurora",
+            ContractError::AuroraDelivery => {
+                "Only single-token intents delivered as is can go to Aurora"
+            }
+            ContractError::ExpiresBeforeEligible => "Intent would expire before it may execute",
+            ContractError::DeferredDelivery => {
+                "Only single-token intents delivered as is can be deferred"
+            }
+            ContractError::SelfDependency => "Intent cannot depend on itself",
+            ContractError::UnknownDependency => "Unknown dependency",
+            ContractError::InvalidDependencyId => "Invalid dependency ID",
+            ContractError::DependencyCannotComplete => "Dependency can no longer complete",
+            ContractError::DependentDelivery => {
+                "Only single-token intents delivered as is can have dependencies"
+            }
+            ContractError::TooManyActions => "Too many actions",
+            ContractError::ActionDelivery => {
+                "Only single-token intents delivered to wallets can carry actions"
+            }
+            ContractError::NativeCannotSplit => "Native NEAR cannot be split across actions",
+            ContractError::ActionAmountNotPositive => "Action amounts must be positive",
+            ContractError::ActionAmountsMismatch => {
+                "Action amounts must add up to the intent amount"
+            }
+            ContractError::ActionsOutOfGas => "Actions need more gas than an execution can attach",
+            ContractError::QuoteFloorNotPositive => "Minimum output must be positive",
+            ContractError::QuotedDelivery => {
+                "Only single-token intents delivered as is can take quotes"
+            }
+            ContractError::SwapOutputBelowMinimum => "Swap output below minimum",
+            ContractError::SwapResolvedLate => "Swap resolved after its deadline",
+            ContractError::TakesNoQuotes => "Intent takes no quotes",
+            ContractError::QuoteAboveDeliverable => "Quote exceeds the deliverable amount",
+            ContractError::QuoteExpiryPassed => "Quote expiry must be in the future",
+            ContractError::QuoteBelowMinimum => "Quote is below the intent's minimum output",
+            ContractError::QuoteBelowAuctionFloor => "Quote is below the auction's minimum output",
+            ContractError::TooManyQuotes => "Too many quotes for the intent",
+            ContractError::AlreadyAuctioned => "Intent is already auctioned",
+            ContractError::InvalidAuctionDeadline => "Deadline must be between now and the intent's expiry",
+            ContractError::FloorAboveDeliverable => "Floor exceeds the deliverable amount",
+            ContractError::FloorBelowMinimum => "Floor is below the intent's minimum output",
+            ContractError::NoFailedCompensation => "No failed compensation for the intent",
+            ContractError::ProofTooOld => "Proof is too old",
+            ContractError::ProofFromFuture => "Proof timestamp is in the future",
+            ContractError::DepositUsed => "Deposit already backs an intent",
+            ContractError::ChainNotCheckpointed => "Chain has no checkpoint",
+            ContractError::ProofNotCheckpointed => "Proof block is not checkpointed yet",
+            ContractError::InvalidReceiptProof => "Invalid receipt proof",
+            ContractError::ReceiptMismatch => "Receipt does not match proof",
+            ContractError::InvalidReceiptEncoding => "Invalid receipt encoding",
+            ContractError::SourceTransactionFailed => "Source chain transaction failed",
+            ContractError::LogNotFound => "Log not found in receipt",
+            ContractError::InvalidLogEncoding => "Invalid log encoding",
+            ContractError::LogNotFromBridge => "Log not emitted by the chain's bridge",
+            ContractError::LogNotDeposit => "Log is not a deposit event",
+            ContractError::InvalidLogData => "Invalid log data",
+            ContractError::LogIntentMismatch => "Log intent ID does not match",
+            ContractError::LogSenderMismatch => "Log sender does not match",
+            ContractError::LogReceiverMismatch => "Log receiver does not match",
+            ContractError::LogTokenMismatch => "Log token does not match",
+            ContractError::LogAmountMismatch => "Log amount does not match",
+            ContractError::BridgeNotConfigured => "Bridge address not configured",
+            ContractError::InvalidBridgeAddress => "Invalid bridge address",
+            ContractError::InvalidReceiptsRoot => "Invalid receipts root",
+            ContractError::IntentNotFound => "Intent not found",
+            ContractError::NotPending => "Intent is not pending",
+            ContractError::ApprovalNotPending => "Only pending intents can be approved",
+            ContractError::AlreadyApproved => "Validator already approved this intent",
+            ContractError::NotEnoughApprovals => "Not enough validator approvals",
+            ContractError::Expired => "Intent expired",
+            ContractError::NotEligible => "Intent is deferred until later",
+            ContractError::DependencyNotCompleted => "Dependency not completed",
+            ContractError::NotEnoughGas => "Not enough gas attached for the transfer and callback",
+            ContractError::Executing => "Intent is executing",
+            ContractError::NotExpired => "Intent has not expired yet",
+            ContractError::NotRefundable => "Intent is not refundable",
+            ContractError::Claimed => "Intent claimed by a solver",
+            ContractError::Paused => "Contract is paused",
+            ContractError::MissingRole => "Method requires role",
+            ContractError::AgentInactive => "Agent is inactive",
+            ContractError::AlreadyClaimed => "Intent already claimed",
+            ContractError::BeingAuctioned => "Intent is being auctioned",
+            ContractError::NotDisputed => "Intent is not disputed",
+            ContractError::EvidenceTooLarge => "Evidence is too large",
+            ContractError::NotTerminal => "Intent is not in a terminal state",
+            ContractError::BatchEmpty => "Batch is empty",
+            ContractError::BatchTooLarge => "Batch too large",
+            ContractError::BatchMultiAction => "Multi-action intents execute on their own",
+            ContractError::BatchNotEnoughGas => "Not enough gas attached for the batch",
+            ContractError::NoIntentsToExecute => "No intents to execute",
+            ContractError::TooManyIntentIds => "Too many intent IDs",
+            ContractError::IllegalTransition => "Illegal status transition",
+            ContractError::NotEnoughSignatures => "Not enough validator signatures",
+            ContractError::InvalidAggregateSignature => "Invalid aggregate signature",
+            ContractError::AggregateKeyNotEd25519 => "Aggregate key must be ed25519",
+            ContractError::AttestationNotPending => "Only pending intents can be attested",
+            ContractError::ValidatorKeyNotEd25519 => "Validator key must be ed25519",
+            ContractError::BondBelowMinimum => "Bond is below the minimum",
+            ContractError::BondUnbonding => "Bond is still unbonding",
+            ContractError::InvalidUnbondAmount => "Invalid unbond amount",
+            ContractError::NoBond => "No bond found",
+            ContractError::NothingToWithdraw => "Nothing to withdraw",
+            ContractError::ValidatorNotFound => "Validator not found",
+            ContractError::DepositProofValid => "Deposit proof is valid",
+            ContractError::NotSignedByValidator => "Intent not signed by the validator",
+            ContractError::IntentsNotSignedByValidator => "Intents not signed by the validator",
+            ContractError::IntentsDoNotConflict => "Intents do not conflict",
+            ContractError::EpochOutOfOrder => "Epoch must follow the current epoch",
+            ContractError::InvalidEpochThreshold => "Threshold must be between 1 and the number of validators",
+            ContractError::DuplicateValidator => "Duplicate validator",
+            ContractError::NotBondedValidator => "Account is not a bonded validator",
+            ContractError::CheckpointNotAdvancing => "Checkpoint must advance the latest checkpoint",
+            ContractError::InvalidStateRoot => "Invalid state root",
+            ContractError::LightClientNotInitialized => "Light client not initialized",
+            ContractError::HeaderNotFound => "Block header not found",
+            ContractError::BlockNotConfirmed => "Block not confirmed",
+            ContractError::HeaderKnown => "Header already known",
+            ContractError::InvalidBlockNumber => "Invalid block number",
+            ContractError::InvalidHeaderEncoding => "Invalid header encoding",
+            ContractError::ParentNotFound => "Parent header not found",
+            ContractError::NotSolver => "Not a registered solver",
+            ContractError::SolverNeedsPair => "Solver must support a token pair",
+            ContractError::SolverPairUnsupported => "Solver does not support the token pair",
+            ContractError::NotAgent => "Account is not an agent",
+            ContractError::AgentQuotaExceeded => "Agent daily quota exceeded",
+            ContractError::AgentVolumeOverflow => "Agent volume overflow",
+            ContractError::DelegateExpired => "Delegate action expired",
+            ContractError::DelegateReceiverMismatch => "Delegate action is for another contract",
+            ContractError::DelegateNotSingleCall => "Delegate action must hold exactly one call",
+            ContractError::DelegateKeyNotEd25519 => "Delegate key must be ed25519",
+            ContractError::DelegateDeposit => "Delegated calls cannot attach a deposit",
+            ContractError::InvalidCallArguments => "Invalid call arguments",
+            ContractError::DelegateKeyNotAllowed => "Key cannot sign for the sender",
+            ContractError::InvalidDelegateSignature => "Invalid delegate action signature",
+            ContractError::DelegateNonceUsed => "Delegate action nonce already used",
+            ContractError::MethodNotDelegable => "Method cannot be delegated",
+            ContractError::DelegateNotReceiver => "Only the receiver can delegate calls for an intent",
+            ContractError::TooManyDelegateKeys => "Too many delegate keys",
+            ContractError::NoReferralFees => "No referral fees to claim",
+            ContractError::ReferralShareTooHigh => "Referral share too high",
+            ContractError::OutboundNotFound => "Outbound intent not found",
+            ContractError::OutboundNotPending => "Outbound intent is not pending",
+            ContractError::OutboundTokenMismatch => "Token does not match the registered Base token",
+            ContractError::InvalidTransferMsg => "Invalid transfer msg",
+            ContractError::BridgeDepositMissing => "Attach the NEAR to bridge",
+            ContractError::SignerFeeMissing => "Attach the signer's fee",
+            ContractError::SignerNotConfigured => "Signer contract not configured",
+            ContractError::InvalidBaseAddress => "Invalid Base address",
+            ContractError::EscrowIdTooLong => "Escrow account ID too long",
+            ContractError::EscrowCodeEmpty => "Escrow code is empty",
+            ContractError::EscrowCodeMissing => "Escrow code not uploaded",
+            ContractError::StorageNotRegistered => "Account is not registered for storage",
+            ContractError::StorageDepositTooSmall => "Attached deposit does not cover intent storage",
+            ContractError::StorageAccountInUse => "Can't unregister an account with stored intents without force",
+            ContractError::InsufficientStorageBalance => "Insufficient storage balance, deposit more with storage_deposit",
+            ContractError::StorageWithdrawTooLarge => "The amount is greater than the available storage balance",
+            ContractError::StorageBelowMinimum => "The attached deposit is less than the minimum storage balance",
+            ContractError::NoFees => "No fees to withdraw",
+            ContractError::FeeTooHigh => "Fee too high",
+            ContractError::GasFundDepositMissing => "Attach NEAR to fund gas reimbursements",
+            ContractError::GasFundExceeded => "Amount exceeds accrued native fees",
+            ContractError::NoGasReimbursement => "No gas reimbursement to withdraw",
+            ContractError::AlreadyInitialized => "Already initialized",
+            ContractError::NotInitialized => "Contract is not initialized",
+            ContractError::InvalidStateVersion => "Invalid state version",
+            ContractError::UnknownStateVersion => "Unknown state version",
+            ContractError::StateDowngrade => "Cannot downgrade contract state",
+            ContractError::MissingCode => "Missing contract code",
+            ContractError::InvalidRequiredSignatures => "Required signatures must be > 0",
+            ContractError::InvalidGas => "Gas must be between 0 and 100 TGas",
+            ContractError::DepositNotPositive => "Deposit must be positive",
+            ContractError::ProofAgeNotPositive => "Proof age must be positive",
+            ContractError::AmountNotPositive => "Amount must be positive",
+            ContractError::InvalidMultiple => "Multiple must be greater than 1",
+            ContractError::AlreadyPaused => "Contract already paused",
+            ContractError::NotPaused => "Contract not paused",
+            ContractError::NotProposedOwner => "Only the proposed owner can accept ownership",
+            ContractError::RoleNotGrantable => "Owner and Validator roles are managed through ownership transfer and the validator registry",
+            ContractError::RoleNotHeld => "Account does not have this role",
+            ContractError::TimelockNotSet => "Admin timelock not set",
+            ContractError::TimelockDelayTooShort => "Timelock delay is too short",
+            ContractError::NotTimelocked => "Action is not timelocked",
+            ContractError::ActionNotQueued => "Action not queued",
+            ContractError::ChangeNotQueued => "Change must be queued in the admin timelock",
+            ContractError::TimelockPending => "Admin timelock has not elapsed",
+            ContractError::NotEmergencyDao => "Only the emergency DAO can approve withdrawals",
+            ContractError::EmergencyDaoReplacement => "Only the emergency DAO can replace itself",
+            ContractError::InvalidTokenContract => "Invalid token contract",
+            ContractError::WithdrawalApproved => "Withdrawal already approved",
+            ContractError::WithdrawalNotApproved => "Withdrawal not approved",
+            ContractError::WithdrawalTimelockPending => "Withdrawal timelock has not elapsed",
+            ContractError::CroncatNotConfigured => "Croncat manager not configured",
+            ContractError::NotScheduler => "Only the scheduler may tick",
+            ContractError::ComplianceNotConfigured => "No compliance registry configured",
+            ContractError::RateLimitOverflow => "Rate limit amount overflow",
+            ContractError::RateLimitAmount => "Rate limit exceeded: amount too large",
+            ContractError::RateLimitExecutions => "Rate limit exceeded: too many executions",
+            ContractError::InvalidRateWindow => "Window must span at least one block",
+            ContractError::InvalidDeliveryAttempts => "Delivery attempts out of range",
+            ContractError::IntervalTooShort => "Interval must be at least an hour",
+            ContractError::TemplateNotFound => "Template not found",
+            ContractError::NoPeriods => "Template has no periods",
+            ContractError::PeriodNotDue => "Next period is not due yet",
+        }
+    }
+
+    /// Panic with the error's code and message
+    pub(crate) fn panic(&self) -> ! {
+        self.panic_with(self.message())
+    }
+
+    /// Panic with the error's code and `message` in place of its own, for
+    /// failures whose message names details
+    pub(crate) fn panic_with(&self, message: &str) -> ! {
+        panic!("{}", json!({ "code": self, "message": message }))
+    }
+}
+
+impl fmt::Display for ContractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl FunctionError for ContractError {
+    fn panic(&self) -> ! {
+        ContractError::panic(self)
+    }
+}
+
+/// Panic with `error` unless `condition` holds
+pub(crate) fn require(condition: bool, error: ContractError) {
+    if !condition {
+        error.panic();
+    }
+}
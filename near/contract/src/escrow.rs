@@ -0,0 +1,174 @@
+//! Per-intent escrow subaccounts.
+//!
+//! Executions delivering at least the escrow threshold move their funds out
+//! of the shared pool into a subaccount of their own, named after a hash of
+//! the intent ID and running the escrow contract the owner uploaded. The
+//! escrow pays the receiver, so a fault in one intent's delivery cannot
+//! reach the pool, and is deleted once the intent settles, returning the
+//! funds to the pool if the payout failed. Intents settling into NEAR
+//! Intents are never escrowed.
+
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::serde_json::json;
+use near_sdk::{env, near_bindgen, AccountId, Balance, Gas, Promise, ONE_NEAR};
+
+use crate::errors::{require, ContractError};
+use crate::roles::Role;
+use crate::tokens::is_native_token;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// NEAR funding an escrow's storage, returned when it is deleted
+const ESCROW_DEPOSIT: Balance = 3 * ONE_NEAR;
+
+/// Gas attached to each call into an escrow
+const ESCROW_CALL_GAS: Gas = Gas(20_000_000_000_000);
+
+/// NEP-145 deposit registering an escrow with a token contract
+const FT_STORAGE_DEPOSIT: Balance = 1_250_000_000_000_000_000_000;
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Upload the escrow contract deployed on each escrow subaccount
+    pub fn set_escrow_code(&mut self, code: Base64VecU8) {
+        self.assert_role(&[Role::Owner]);
+        require(!code.0.is_empty(), ContractError::EscrowCodeEmpty);
+        self.escrow_code.set(Some(code.0));
+    }
+
+    /// Set the delivered amount from which executions are escrowed, or stop
+    /// escrowing
+    pub fn set_escrow_threshold(&mut self, threshold: Option<U128>) {
+        self.assert_role(&[Role::Owner]);
+        require(
+            threshold.is_none() || self.escrow_code.get().is_some(),
+            ContractError::EscrowCodeMissing,
+        );
+        self.escrow_threshold = threshold.map(|threshold| threshold.0);
+    }
+
+    /// Get the delivered amount from which executions are escrowed
+    pub fn get_escrow_threshold(&self) -> Option<U128> {
+        self.escrow_threshold.map(U128)
+    }
+
+    /// Get the escrow subaccount of an executing intent
+    pub fn get_escrow_account(&self, intent_id: String) -> Option<AccountId> {
+        self.escrows
+            .get(&intent_id)
+            .map(|(escrow, _)| escrow)
+            .cloned()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Whether delivering `amount` for an intent goes through an escrow
+    pub(crate) fn should_escrow(&self, intent: &CrossChainIntent, amount: Balance) -> bool {
+        !intent.settle_in_defuse
+            && self
+                .escrow_threshold
+                .is_some_and(|threshold| amount >= threshold)
+    }
+
+    /// Create an intent's escrow, fund it with `amount` and have it pay the
+    /// receiver. Resolves to the outcome of the payout.
+    pub(crate) fn deliver_via_escrow(
+        &mut self,
+        intent: &CrossChainIntent,
+        amount: U128,
+    ) -> Promise {
+        let escrow = escrow_account_id(&intent.id);
+        let native = is_native_token(&intent.token);
+        let funding = if native { amount.0 } else { 0 };
+        self.assert_native_liquidity(ESCROW_DEPOSIT + funding);
+        self.escrows
+            .insert(intent.id.clone(), (escrow.clone(), amount.0));
+
+        let create = Promise::new(escrow.clone())
+            .create_account()
+            .transfer(ESCROW_DEPOSIT + funding)
+            .deploy_contract(
+                self.escrow_code
+                    .get()
+                    .clone()
+                    .unwrap_or_else(|| ContractError::EscrowCodeMissing.panic()),
+            )
+            .function_call(
+                "new".to_string(),
+                json!({ "executor": env::current_account_id() })
+                    .to_string()
+                    .into_bytes(),
+                0,
+                ESCROW_CALL_GAS,
+            );
+        let (funded, token) = if native {
+            (create, None)
+        } else {
+            let token = self.resolve_token(intent.chain_id, &intent.token);
+            let register = Promise::new(token.clone()).function_call(
+                "storage_deposit".to_string(),
+                json!({ "account_id": escrow, "registration_only": true })
+                    .to_string()
+                    .into_bytes(),
+                FT_STORAGE_DEPOSIT,
+                self.config.ft_transfer_gas,
+            );
+            let funded = create.then(register).then(self.deliver_token(
+                intent.chain_id,
+                &intent.token,
+                &escrow,
+                amount,
+            ));
+            (funded, Some(token))
+        };
+        funded.then(
+            Promise::new(escrow).function_call(
+                "release".to_string(),
+                json!({ "token": token, "receiver": intent.receiver, "amount": amount })
+                    .to_string()
+                    .into_bytes(),
+                0,
+                ESCROW_CALL_GAS,
+            ),
+        )
+    }
+
+    /// Delete the escrow of a settled intent, first returning its tokens to
+    /// the pool if the payout failed. Native NEAR returns with the deletion.
+    pub(crate) fn close_escrow(&mut self, intent: &CrossChainIntent, succeeded: bool) {
+        let (escrow, amount) = match self.escrows.remove(&intent.id) {
+            Some(escrowed) => escrowed,
+            None => return,
+        };
+        let close = |escrow: AccountId| {
+            Promise::new(escrow).function_call("close".to_string(), vec![], 0, ESCROW_CALL_GAS)
+        };
+        if succeeded || is_native_token(&intent.token) {
+            close(escrow);
+            return;
+        }
+        let token = self.resolve_token(intent.chain_id, &intent.token);
+        Promise::new(escrow.clone())
+            .function_call(
+                "release".to_string(),
+                json!({
+                    "token": token,
+                    "receiver": env::current_account_id(),
+                    "amount": U128(amount),
+                })
+                .to_string()
+                .into_bytes(),
+                0,
+                ESCROW_CALL_GAS,
+            )
+            .then(close(escrow));
+    }
+}
+
+/// Escrow subaccount of an intent, named after a hash of its ID so any
+/// intent ID fits in an account ID
+fn escrow_account_id(intent_id: &str) -> AccountId {
+    let hash = hex::encode(&env::sha256(intent_id.as_bytes())[..16]);
+    format!("{}.{}", hash, env::current_account_id())
+        .parse()
+        .unwrap_or_else(|_| ContractError::EscrowIdTooLong.panic())
+}
@@ -0,0 +1,442 @@
+//! NEP-297 events emitted by the executor.
+//!
+//! Every event is logged as
+//! `EVENT_JSON:{"standard":"near-intents","version":"1.3.0","event":"<name>","data":{..}}`
+//! so indexers, wallets and the relayer can consume them without custom parsing.
+//! Bump [`EVENT_STANDARD_VERSION`] whenever an event's data fields change.
+
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::Serialize;
+use near_sdk::{env, serde_json, AccountId};
+
+use crate::basket::BasketAsset;
+use crate::disputes::DisputeRuling;
+use crate::governance::GovernanceAction;
+use crate::roles::Role;
+
+pub const EVENT_STANDARD: &str = "near-intents";
+pub const EVENT_STANDARD_VERSION: &str = "1.3.0";
+
+#[derive(Serialize, Debug)]
+#[cfg_attr(any(test, feature = "schema"), derive(schemars::JsonSchema))]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data", rename_all = "snake_case")]
+#[must_use = "don't forget to `.emit()` this event"]
+pub enum IntentEvent {
+    IntentCreated {
+        intent_id: String,
+        chain_id: u64,
+        sender: String,
+        receiver: AccountId,
+        token: String,
+        amount: U128,
+        extra_assets: Vec<BasketAsset>,
+        nft_token_id: Option<U128>,
+    },
+    IntentApproved {
+        intent_id: String,
+        validator: AccountId,
+    },
+    IntentAttested {
+        intent_id: String,
+        epoch: u64,
+    },
+    IntentExecuted {
+        intent_id: String,
+        receiver: AccountId,
+        amount: U128,
+    },
+    IntentCompleted {
+        intent_id: String,
+    },
+    IntentFailed {
+        intent_id: String,
+        reason: String,
+    },
+    DeliveryRetryQueued {
+        intent_id: String,
+        /// Failed deliveries of the intent so far
+        attempts: u32,
+        next_attempt_at: U64,
+    },
+    DeliveryRetried {
+        intent_id: String,
+        executor: AccountId,
+        attempt: u32,
+    },
+    IntentCancelled {
+        intent_id: String,
+    },
+    IntentDisputed {
+        intent_id: String,
+        disputer: AccountId,
+        evidence: String,
+    },
+    DisputeResolved {
+        intent_id: String,
+        arbiter: AccountId,
+        ruling: DisputeRuling,
+    },
+    IntentRefunded {
+        intent_id: String,
+        chain_id: u64,
+        sender: String,
+        token: String,
+        amount: U128,
+        extra_assets: Vec<BasketAsset>,
+        nft_token_id: Option<U128>,
+    },
+    OutboundIntentCreated {
+        intent_id: String,
+        sender: AccountId,
+        recipient: String,
+        base_token: String,
+        amount: U128,
+    },
+    OutboundTokensBurned {
+        intent_id: String,
+        near_token: AccountId,
+        amount: U128,
+    },
+    OutboundIntentRefunded {
+        intent_id: String,
+        amount: U128,
+    },
+    OutboundIntentCompleted {
+        intent_id: String,
+        release_tx_hash: String,
+    },
+    FeeCollected {
+        intent_id: String,
+        token: String,
+        amount: U128,
+    },
+    FeesWithdrawn {
+        token: String,
+        treasury: AccountId,
+        amount: U128,
+    },
+    ReferralFeeAccrued {
+        intent_id: String,
+        referrer: AccountId,
+        token: String,
+        amount: U128,
+    },
+    ReferralFeesClaimed {
+        referrer: AccountId,
+        token: String,
+        amount: U128,
+    },
+    GasFunded {
+        account_id: AccountId,
+        amount: U128,
+    },
+    GasReimbursed {
+        intent_id: String,
+        executor: AccountId,
+        amount: U128,
+    },
+    CapReached {
+        intent_id: String,
+        token: String,
+        cap: U128,
+        volume: U128,
+    },
+    AnomalyDetected {
+        intent_id: String,
+        token: String,
+        volume: U128,
+        average: U128,
+    },
+    BaseHeaderSubmitted {
+        chain_id: u64,
+        block_number: u64,
+        block_hash: String,
+    },
+    CheckpointSubmitted {
+        chain_id: u64,
+        block_number: u64,
+        state_root: String,
+    },
+    TokenRegistered {
+        chain_id: u64,
+        base_token: String,
+        near_contract: AccountId,
+    },
+    TokenRemoved {
+        chain_id: u64,
+        base_token: String,
+    },
+    NftCollectionRegistered {
+        chain_id: u64,
+        base_address: String,
+        near_contract: AccountId,
+    },
+    NftCollectionRemoved {
+        chain_id: u64,
+        base_address: String,
+    },
+    RoleGranted {
+        role: Role,
+        account_id: AccountId,
+    },
+    RoleRevoked {
+        role: Role,
+        account_id: AccountId,
+    },
+    AgentConfigured {
+        agent: AccountId,
+        active: bool,
+        daily_quota: Option<U128>,
+    },
+    ReceiverAllowed {
+        account_id: AccountId,
+        /// Timestamp (ns) the receiver stays allowed until, `None` while
+        /// the owner's registration stands
+        until: Option<U64>,
+    },
+    ReceiverDisallowed {
+        account_id: AccountId,
+    },
+    SenderBlocked {
+        sender: String,
+        by: AccountId,
+    },
+    SenderUnblocked {
+        sender: String,
+        by: AccountId,
+    },
+    ReceiverBlocked {
+        receiver: AccountId,
+        by: AccountId,
+    },
+    ReceiverUnblocked {
+        receiver: AccountId,
+        by: AccountId,
+    },
+    IntentBlocked {
+        intent_id: String,
+        /// `create` or `execute`
+        stage: String,
+        sender: String,
+        receiver: AccountId,
+        reason: String,
+    },
+    RecurringTemplateCreated {
+        template_id: u64,
+        sender: String,
+        receiver: AccountId,
+        token: String,
+        amount: U128,
+        interval: U64,
+    },
+    RecurringTemplateCancelled {
+        template_id: u64,
+    },
+    RecurringIntentInstantiated {
+        template_id: u64,
+        intent_id: String,
+    },
+    ActionsRolledBack {
+        intent_id: String,
+        failed_action: u32,
+        compensated: U128,
+    },
+    CompensationFailed {
+        intent_id: String,
+        failed_action: u32,
+        /// Amount still owed to the receiver
+        compensated: U128,
+    },
+    ConfigChanged {
+        field: String,
+        old_value: String,
+        new_value: String,
+    },
+    OwnershipProposed {
+        owner: AccountId,
+        proposed_owner: AccountId,
+    },
+    OwnershipTransferred {
+        previous_owner: AccountId,
+        new_owner: AccountId,
+    },
+    Paused {
+        by: AccountId,
+    },
+    Unpaused {
+        by: AccountId,
+    },
+    ValidatorAdded {
+        validator: AccountId,
+    },
+    ValidatorRemoved {
+        validator: AccountId,
+    },
+    ValidatorBonded {
+        validator: AccountId,
+        amount: U128,
+    },
+    ValidatorUnbonding {
+        validator: AccountId,
+        amount: U128,
+        unlocks_at: U64,
+    },
+    BondWithdrawn {
+        validator: AccountId,
+        amount: U128,
+    },
+    ValidatorSetRotated {
+        epoch: u64,
+        validators: Vec<AccountId>,
+        threshold: u32,
+    },
+    ValidatorSlashed {
+        validator: AccountId,
+        amount: U128,
+        reason: String,
+    },
+    ChainConfigured {
+        chain_id: u64,
+        name: String,
+    },
+    ChainRemoved {
+        chain_id: u64,
+    },
+    SolverRegistered {
+        solver: AccountId,
+        bond: U128,
+    },
+    SolverUnregistered {
+        solver: AccountId,
+    },
+    IntentClaimed {
+        intent_id: String,
+        solver: AccountId,
+        expires_at: U64,
+    },
+    QuoteSubmitted {
+        intent_id: String,
+        solver: AccountId,
+        out_amount: U128,
+        expires_at: U64,
+    },
+    QuoteSelected {
+        intent_id: String,
+        solver: AccountId,
+        out_amount: U128,
+    },
+    AuctionStarted {
+        intent_id: String,
+        start_out: U128,
+        floor_out: U128,
+        deadline: U64,
+    },
+    DelegateActionRelayed {
+        sender_id: AccountId,
+        method_name: String,
+        nonce: U64,
+    },
+    ReleaseSigned {
+        intent_id: String,
+        transaction: String,
+        big_r: String,
+        s: String,
+        recovery_id: u8,
+    },
+    EmergencyWithdrawalApproved {
+        withdrawal_hash: String,
+        executable_at: U64,
+    },
+    EmergencyWithdrawalCancelled {
+        withdrawal_hash: String,
+    },
+    EmergencyWithdrawn {
+        token: String,
+        amount: U128,
+        receiver: AccountId,
+    },
+    AdminActionQueued {
+        id: u64,
+        action: GovernanceAction,
+        executable_at: U64,
+    },
+    AdminActionApplied {
+        id: u64,
+    },
+    AdminActionCancelled {
+        id: u64,
+        by: AccountId,
+    },
+}
+
+#[derive(Serialize)]
+#[cfg_attr(any(test, feature = "schema"), derive(schemars::JsonSchema))]
+#[serde(crate = "near_sdk::serde")]
+pub(crate) struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a IntentEvent,
+}
+
+impl IntentEvent {
+    /// Logs the event to the host in the NEP-297 format
+    pub fn emit(self) {
+        let log = EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_STANDARD_VERSION,
+            event: &self,
+        };
+        // Events cannot fail to serialize so fine to abort on error
+        let json = serde_json::to_string(&log).unwrap_or_else(|_| env::abort());
+        env::log_str(&format!("EVENT_JSON:{}", json));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_logs};
+
+    #[test]
+    fn events_are_logged_in_the_nep297_format() {
+        IntentEvent::IntentApproved {
+            intent_id: "0xabc".to_string(),
+            validator: accounts(1),
+        }
+        .emit();
+        let data = r#"{"intent_id":"0xabc","validator":"bob"}"#;
+        assert_eq!(
+            get_logs(),
+            [format!(
+                concat!(
+                    r#"EVENT_JSON:{{"standard":"{}","version":"{}","#,
+                    r#""event":"intent_approved","data":{}}}"#
+                ),
+                EVENT_STANDARD, EVENT_STANDARD_VERSION, data
+            )]
+        );
+    }
+
+    #[test]
+    fn event_amounts_are_logged_as_strings() {
+        IntentEvent::IntentCreated {
+            intent_id: "0xabc".to_string(),
+            chain_id: 8453,
+            sender: "0x71c7656ec7ab88b098defb751b7401b5f6d8976f".to_string(),
+            receiver: accounts(2),
+            token: "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913".to_string(),
+            amount: U128(u128::MAX),
+            extra_assets: Vec::new(),
+            nft_token_id: None,
+        }
+        .emit();
+        let log = get_logs().remove(0);
+        let event: serde_json::Value =
+            serde_json::from_str(log.strip_prefix("EVENT_JSON:").unwrap()).unwrap();
+        assert_eq!(event["event"], "intent_created");
+        assert_eq!(event["data"]["amount"], u128::MAX.to_string());
+        assert_eq!(event["data"]["receiver"], "charlie");
+    }
+}
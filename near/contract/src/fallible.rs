@@ -0,0 +1,47 @@
+//! Non-panicking entry points.
+//!
+//! `create_intent` and `execute_intent` panic when an intent is rejected,
+//! which callers can only tell apart by the panic message. Their `try_`
+//! variants return the `ContractError` instead. The call still fails and its
+//! changes are reverted, but the error reaches the caller as the failed
+//! receipt's code, so a contract creating or executing intents in separate
+//! promises handles each failure in its callback while the others go
+//! through. Blocked parties are reported as an error rather than recorded,
+//! and calls by accounts without the required role still panic.
+
+use near_sdk::{near_bindgen, PromiseOrValue};
+
+use crate::errors::ContractError;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Create a new cross-chain intent, attaching NEAR for its storage, or
+    /// return why it was rejected
+    #[payable]
+    #[handle_result]
+    pub fn try_create_intent(&mut self, intent: CrossChainIntent) -> Result<(), ContractError> {
+        if self.paused {
+            return Err(ContractError::Paused);
+        }
+        let agent = self.assert_agent();
+        self.check_parties_unblocked(&intent)?;
+        self.validate_new_intent(&intent)?;
+        self.accept_intent(&agent, intent);
+        Ok(())
+    }
+
+    /// Execute a cross-chain intent, as an agent or as the solver holding
+    /// a claim on it, or return why it cannot execute
+    #[handle_result]
+    pub fn try_execute_intent(
+        &mut self,
+        intent_id: String,
+    ) -> Result<PromiseOrValue<bool>, ContractError> {
+        if self.paused {
+            return Err(ContractError::Paused);
+        }
+        let executor = self.assert_executor(&intent_id);
+        self.try_execute(&executor, intent_id)
+    }
+}
@@ -0,0 +1,155 @@
+//! Protocol fees.
+//!
+//! A basis-point fee is withheld from every executed intent, with optional
+//! per-token overrides. Withheld amounts stay in the executor and accrue per
+//! intent token, in the decimals of the NEAR token delivered for it, until
+//! they are withdrawn to the treasury account.
+
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, AccountId, Balance, Promise, PromiseError};
+
+use crate::chains::chain_or_base;
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::tokens::{is_native_token, normalize_address, NATIVE_TOKEN};
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+/// Highest fee the owner can configure (10%)
+const MAX_FEE_BPS: u16 = 1_000;
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the default fee withheld from executed intents
+    pub fn set_fee(&mut self, fee_bps: u16) {
+        self.assert_untimelocked_owner();
+        self.set_default_fee(fee_bps);
+    }
+
+    /// Override the fee for one intent token, or clear the override
+    pub fn set_token_fee(&mut self, token: String, fee_bps: Option<u16>) {
+        self.assert_untimelocked_owner();
+        self.set_token_fee_override(token, fee_bps);
+    }
+
+    /// Set the account fees are withdrawn to
+    pub fn set_treasury(&mut self, treasury: AccountId) {
+        self.assert_role(&[Role::Owner]);
+        self.treasury = treasury;
+    }
+
+    /// Send all accrued fees of a token to the treasury, resolving its FT
+    /// contract on the chain it is registered for, Base by default
+    pub fn withdraw_fees(&mut self, token: String, chain_id: Option<u64>) -> Promise {
+        self.assert_role(&[Role::Owner, Role::FeeCollector]);
+        let token = normalize_address(&token);
+        let amount = self.accrued_fees.remove(&token).unwrap_or(0);
+        require(amount > 0, ContractError::NoFees);
+
+        let transfer = if is_native_token(&token) {
+            self.assert_native_liquidity(amount);
+            Promise::new(self.treasury.clone()).transfer(amount)
+        } else {
+            self.deliver_token(
+                chain_or_base(chain_id),
+                &token,
+                &self.treasury,
+                U128(amount),
+            )
+        };
+
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(self.config.transfer_callback_gas)
+                .on_fees_withdrawn(token, U128(amount)),
+        )
+    }
+
+    /// Re-credit fees whose withdrawal transfer failed
+    #[private]
+    pub fn on_fees_withdrawn(
+        &mut self,
+        token: String,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            IntentEvent::FeesWithdrawn {
+                token,
+                treasury: self.treasury.clone(),
+                amount,
+            }
+            .emit();
+            true
+        } else {
+            self.accrue_fee(&token, amount.0);
+            false
+        }
+    }
+
+    /// Fee in basis points applied to intents of a token
+    pub fn get_fee_bps(&self, token: String) -> u16 {
+        self.token_fee_bps
+            .get(&normalize_address(&token))
+            .copied()
+            .unwrap_or(self.fee_bps)
+    }
+
+    /// Fees of a token accrued and not yet withdrawn
+    pub fn get_accrued_fees(&self, token: String) -> U128 {
+        U128(
+            self.accrued_fees
+                .get(&normalize_address(&token))
+                .copied()
+                .unwrap_or(0),
+        )
+    }
+
+    /// Get the account fees are withdrawn to
+    pub fn get_treasury(&self) -> AccountId {
+        self.treasury.clone()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Set the default fee, capped at [`MAX_FEE_BPS`]
+    pub(crate) fn set_default_fee(&mut self, fee_bps: u16) {
+        require(fee_bps <= MAX_FEE_BPS, ContractError::FeeTooHigh);
+        self.fee_bps = fee_bps;
+    }
+
+    /// Set or clear the fee override of a token
+    pub(crate) fn set_token_fee_override(&mut self, token: String, fee_bps: Option<u16>) {
+        let token = normalize_address(&token);
+        match fee_bps {
+            Some(fee_bps) => {
+                require(fee_bps <= MAX_FEE_BPS, ContractError::FeeTooHigh);
+                self.token_fee_bps.insert(token.clone(), fee_bps);
+            }
+            None => {
+                self.token_fee_bps.remove(&token);
+            }
+        }
+    }
+
+    /// Fee withheld from an intent of `amount` in `token`
+    pub(crate) fn compute_fee(&self, token: &str, amount: Balance) -> Balance {
+        let fee_bps = Balance::from(self.get_fee_bps(token.to_string()));
+        let denominator = Balance::from(BPS_DENOMINATOR);
+        // Split the multiplication so amounts near u128::MAX cannot overflow
+        amount / denominator * fee_bps + amount % denominator * fee_bps / denominator
+    }
+
+    /// Credit a withheld fee to the accrued balance of its token
+    pub(crate) fn accrue_fee(&mut self, token: &str, amount: Balance) {
+        let token = normalize_address(token);
+        *self.accrued_fees.entry(token).or_insert(0) += amount;
+    }
+
+    /// Native NEAR held as fees, which intents must not spend
+    pub(crate) fn accrued_native_fees(&self) -> Balance {
+        self.accrued_fees.get(NATIVE_TOKEN).copied().unwrap_or(0)
+    }
+}
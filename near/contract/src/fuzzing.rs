@@ -0,0 +1,346 @@
+//! Entry point of the cargo-fuzz targets in `fuzz/`.
+//!
+//! Feeds arbitrary bytes through the same JSON argument deserialization the
+//! `#[near_bindgen]` wrappers perform on chain, then calls the method on a
+//! freshly deployed executor within a mocked blockchain. Methods may panic
+//! to reject input, but only with the contract's own messages: a Rust
+//! runtime panic (overflow, out-of-bounds indexing, a failed unwrap) or a
+//! host error other than a guest panic means the input reached code that
+//! does not guard against it. After a call succeeds the state must still
+//! round-trip and serve the core views.
+
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_contract_standards::storage_management::StorageManagement;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde::Deserialize;
+use near_sdk::test_utils::VMContextBuilder;
+use near_sdk::{
+    env, serde_json, AccountId, Balance, MockedBlockchain, PublicKey, RuntimeFeesConfig, VMConfig,
+    ONE_NEAR,
+};
+
+use crate::aggregate::AggregateSignature;
+use crate::config::ConfigUpdate;
+use crate::rate_limit::RateLimit;
+use crate::roles::Role;
+use crate::tokens::{TokenDecimals, TokenMapping};
+use crate::{CrossChainExecutor, CrossChainIntent, IntentStatus};
+
+const BASE_TOKEN: &str = "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+
+/// Panic messages of the Rust runtime rather than of the contract
+const RUNTIME_PANICS: &[&str] = &[
+    "attempt to ",
+    "index out of bounds",
+    "is not a char boundary",
+    "range end index",
+    "range start index",
+    "slice index",
+    "called `Option::unwrap()`",
+    "called `Result::unwrap()`",
+    "capacity overflow",
+];
+
+/// A public method with its JSON arguments, as the bindgen wrappers take them
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "method", content = "args", rename_all = "snake_case")]
+enum MethodCall {
+    CreateIntent {
+        intent: CrossChainIntent,
+    },
+    CreateIntents {
+        intents: Vec<CrossChainIntent>,
+    },
+    ComputeIntentHash {
+        intent: CrossChainIntent,
+    },
+    ApproveIntent {
+        intent_id: String,
+    },
+    ExecuteIntent {
+        intent_id: String,
+    },
+    CancelIntent {
+        intent_id: String,
+    },
+    ExpireIntent {
+        intent_id: String,
+    },
+    RefundIntent {
+        intent_id: String,
+    },
+    AttestIntent {
+        intent_id: String,
+        attestation: AggregateSignature,
+    },
+    SubmitQuote {
+        intent_id: String,
+        out_amount: U128,
+        expiry: U64,
+    },
+    GetIntent {
+        intent_id: String,
+    },
+    GetIntentsBulk {
+        intent_ids: Vec<String>,
+    },
+    GetIntents {
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    },
+    GetIntentsByStatus {
+        status: IntentStatus,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    },
+    UpdateConfig {
+        update: ConfigUpdate,
+    },
+    RegisterToken {
+        mapping: TokenMapping,
+        chain_id: Option<u64>,
+    },
+    SetRateLimit {
+        token: String,
+        limit: Option<RateLimit>,
+    },
+    SetTrustedHeader {
+        header_rlp: Base64VecU8,
+        confirmations: Option<u64>,
+        chain_id: Option<u64>,
+    },
+    GrantRole {
+        role: Role,
+        account_id: AccountId,
+    },
+    RegisterValidator {
+        public_key: PublicKey,
+    },
+    FtOnTransfer {
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    },
+}
+
+fn executor() -> AccountId {
+    "executor.near".parse().unwrap()
+}
+
+fn token() -> AccountId {
+    "token.near".parse().unwrap()
+}
+
+fn agent() -> AccountId {
+    "agent.near".parse().unwrap()
+}
+
+fn validator() -> AccountId {
+    "validator.near".parse().unwrap()
+}
+
+fn set_caller(account_id: &AccountId, deposit: Balance) {
+    let storage = near_sdk::mock::with_mocked_blockchain(|b| b.take_storage());
+    set_blockchain(account_id, deposit, storage);
+}
+
+fn set_blockchain(account_id: &AccountId, deposit: Balance, storage: HashMap<Vec<u8>, Vec<u8>>) {
+    env::set_blockchain_interface(MockedBlockchain::new(
+        VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(account_id.clone())
+            .attached_deposit(deposit)
+            .build(),
+        VMConfig::test(),
+        RuntimeFeesConfig::test(),
+        vec![],
+        storage,
+        Default::default(),
+        None,
+    ));
+}
+
+/// Deploy an executor with a token mapping, a validator and a registered agent
+fn deploy() -> CrossChainExecutor {
+    set_blockchain(&executor(), 0, HashMap::new());
+    let mut contract = CrossChainExecutor::new(1, agent(), None);
+    contract.register_token(
+        TokenMapping {
+            base_address: BASE_TOKEN.to_string(),
+            near_contract: token(),
+            decimals: TokenDecimals { source: 6, near: 6 },
+            symbol: "USDC".to_string(),
+            enabled: true,
+            mintable: false,
+            min_amount: None,
+            max_amount: None,
+            stablecoin_route: None,
+            multi_token: false,
+            transfer_gas: None,
+        },
+        None,
+    );
+    set_caller(&validator(), 100 * ONE_NEAR);
+    contract.register_validator(
+        "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+            .parse()
+            .unwrap(),
+    );
+    set_caller(&agent(), ONE_NEAR);
+    contract.storage_deposit(None, None);
+    contract
+}
+
+/// Call a method as the account most likely to get past its access checks,
+/// so the fuzzer reaches the code handling the arguments
+fn call(contract: &mut CrossChainExecutor, method: MethodCall) {
+    match method {
+        MethodCall::CreateIntent { intent } => {
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(intent);
+        }
+        MethodCall::CreateIntents { intents } => {
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intents(intents);
+        }
+        MethodCall::ComputeIntentHash { intent } => {
+            contract.compute_intent_hash(intent);
+        }
+        MethodCall::ApproveIntent { intent_id } => {
+            set_caller(&validator(), 0);
+            contract.approve_intent(intent_id);
+        }
+        MethodCall::ExecuteIntent { intent_id } => {
+            set_caller(&agent(), 0);
+            contract.execute_intent(intent_id);
+        }
+        MethodCall::CancelIntent { intent_id } => {
+            set_caller(&agent(), 0);
+            contract.cancel_intent(intent_id);
+        }
+        MethodCall::ExpireIntent { intent_id } => contract.expire_intent(intent_id),
+        MethodCall::RefundIntent { intent_id } => {
+            set_caller(&agent(), 0);
+            contract.refund_intent(intent_id);
+        }
+        MethodCall::AttestIntent {
+            intent_id,
+            attestation,
+        } => contract.attest_intent(intent_id, attestation),
+        MethodCall::SubmitQuote {
+            intent_id,
+            out_amount,
+            expiry,
+        } => contract.submit_quote(intent_id, out_amount, expiry),
+        MethodCall::GetIntent { intent_id } => {
+            contract.get_intent(intent_id);
+        }
+        MethodCall::GetIntentsBulk { intent_ids } => {
+            contract.get_intents_bulk(intent_ids);
+        }
+        MethodCall::GetIntents { from_index, limit } => {
+            contract.get_intents(from_index, limit);
+        }
+        MethodCall::GetIntentsByStatus {
+            status,
+            from_index,
+            limit,
+        } => {
+            contract.get_intents_by_status(status, from_index, limit);
+        }
+        MethodCall::UpdateConfig { update } => {
+            set_caller(&executor(), 0);
+            contract.update_config(update);
+        }
+        MethodCall::RegisterToken { mapping, chain_id } => {
+            set_caller(&executor(), 0);
+            contract.register_token(mapping, chain_id);
+        }
+        MethodCall::SetRateLimit { token, limit } => {
+            set_caller(&executor(), 0);
+            contract.set_rate_limit(token, limit);
+        }
+        MethodCall::SetTrustedHeader {
+            header_rlp,
+            confirmations,
+            chain_id,
+        } => {
+            set_caller(&executor(), 0);
+            contract.set_trusted_header(header_rlp, confirmations, chain_id);
+        }
+        MethodCall::GrantRole { role, account_id } => {
+            set_caller(&executor(), 0);
+            contract.grant_role(role, account_id);
+        }
+        MethodCall::RegisterValidator { public_key } => {
+            set_caller(&validator(), 100 * ONE_NEAR);
+            contract.register_validator(public_key);
+        }
+        MethodCall::FtOnTransfer {
+            sender_id,
+            amount,
+            msg,
+        } => {
+            set_caller(&token(), 0);
+            contract.ft_on_transfer(sender_id, amount, msg);
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| {
+            payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a panic is the contract rejecting input, rather than the Rust
+/// runtime or the host failing underneath it
+fn is_clean_panic(message: &str) -> bool {
+    if message.contains("HostError(") {
+        return message.contains("GuestPanic") || message.contains("GasExceeded");
+    }
+    !RUNTIME_PANICS
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+/// Run one fuzz input, panicking if it reveals unguarded code
+pub fn fuzz_method_args(data: &[u8]) {
+    // Malformed arguments are rejected by the wrapper before any contract code runs
+    let method: MethodCall = match serde_json::from_slice(data) {
+        Ok(method) => method,
+        Err(_) => return,
+    };
+    let mut contract = deploy();
+    let result = catch_unwind(AssertUnwindSafe(|| call(&mut contract, method)));
+    match result {
+        Ok(()) => {
+            env::state_write(&contract);
+            drop(contract);
+            let contract: CrossChainExecutor =
+                env::state_read().expect("State does not round-trip");
+            contract.get_config();
+            contract.get_intents(None, None);
+            contract.get_intents_by_status(IntentStatus::Pending, None, None);
+        }
+        Err(payload) => {
+            let message = panic_message(&*payload);
+            assert!(
+                is_clean_panic(&message),
+                "Unguarded panic on {}: {}",
+                String::from_utf8_lossy(data),
+                message
+            );
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Gas reimbursement for executing accounts.
+//!
+//! Agents and solvers pay for the gas of every intent they execute. To give
+//! third parties a reason to run relayers, execution records an estimate of
+//! the gas it burns, including the gas attached to its transfers and
+//! callback, and the cost of that gas is credited to the executing account
+//! once the intent completes. Credits are paid from a gas fund of native
+//! NEAR that anyone can top up and the owner can move accrued native fees
+//! into, and stop once the fund runs dry. Executions take no deposit and
+//! creation deposits are refunded when intents are pruned, so only gas is
+//! reimbursed. Gas is priced at the protocol's minimum gas price, so
+//! credits never exceed what was actually paid.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, AccountId, Balance, Gas, Promise};
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::tokens::NATIVE_TOKEN;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// Minimum gas price of the NEAR protocol in yoctoNEAR per gas unit
+const MIN_GAS_PRICE: Balance = 100_000_000;
+
+/// Gas an execution is estimated to burn, recorded until its intent settles
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ExecutionGas {
+    pub executor: AccountId,
+    pub gas: Gas,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Add the attached NEAR to the gas fund
+    #[payable]
+    pub fn fund_gas(&mut self) {
+        let amount = env::attached_deposit();
+        require(amount > 0, ContractError::GasFundDepositMissing);
+        self.gas_fund += amount;
+
+        IntentEvent::GasFunded {
+            account_id: env::predecessor_account_id(),
+            amount: U128(amount),
+        }
+        .emit();
+    }
+
+    /// Move accrued native NEAR fees into the gas fund
+    pub fn fund_gas_from_fees(&mut self, amount: U128) {
+        let account_id = self.assert_role(&[Role::Owner, Role::FeeCollector]);
+        let accrued = self.accrued_native_fees();
+        require(
+            amount.0 > 0 && amount.0 <= accrued,
+            ContractError::GasFundExceeded,
+        );
+        self.accrued_fees
+            .insert(NATIVE_TOKEN.to_string(), accrued - amount.0);
+        self.gas_fund += amount.0;
+
+        IntentEvent::GasFunded { account_id, amount }.emit();
+    }
+
+    /// Send the caller the gas reimbursements credited to it
+    pub fn withdraw_gas_reimbursement(&mut self) -> Promise {
+        let account_id = env::predecessor_account_id();
+        let amount = self
+            .gas_credits
+            .remove(&account_id)
+            .unwrap_or_else(|| ContractError::NoGasReimbursement.panic());
+        self.gas_credited -= amount;
+        Promise::new(account_id).transfer(amount)
+    }
+
+    /// NEAR left in the gas fund to reimburse future executions
+    pub fn get_gas_fund(&self) -> U128 {
+        U128(self.gas_fund)
+    }
+
+    /// Gas reimbursements credited to an account and not yet withdrawn
+    pub fn get_gas_reimbursement(&self, account_id: AccountId) -> U128 {
+        U128(self.gas_credits.get(&account_id).copied().unwrap_or(0))
+    }
+}
+
+impl CrossChainExecutor {
+    /// Record the gas an execution burns, from the gas used since it
+    /// started at `gas_at_start` and the gas attached to its promises
+    pub(crate) fn record_execution_gas(
+        &mut self,
+        executor: &AccountId,
+        intent: &CrossChainIntent,
+        gas_at_start: Gas,
+    ) {
+        let gas = env::used_gas().0 - gas_at_start.0
+            + self.delivery_gas(intent).0
+            + self.config.transfer_callback_gas.0;
+        self.execution_gas.insert(
+            intent.id.clone(),
+            ExecutionGas {
+                executor: executor.clone(),
+                gas: Gas(gas),
+            },
+        );
+    }
+
+    /// Credit the executor of a settled intent with the cost of its gas if
+    /// the intent completed, as far as the gas fund allows
+    pub(crate) fn settle_execution_gas(&mut self, intent_id: &String, completed: bool) {
+        let Some(ExecutionGas { executor, gas }) = self.execution_gas.remove(intent_id) else {
+            return;
+        };
+        let amount = (Balance::from(gas.0) * MIN_GAS_PRICE).min(self.gas_fund);
+        if !completed || amount == 0 {
+            return;
+        }
+        self.gas_fund -= amount;
+        self.gas_credited += amount;
+        *self.gas_credits.entry(executor.clone()).or_insert(0) += amount;
+
+        IntentEvent::GasReimbursed {
+            intent_id: intent_id.clone(),
+            executor,
+            amount: U128(amount),
+        }
+        .emit();
+    }
+
+    /// Native NEAR held for gas reimbursements, which intents must not spend
+    pub(crate) fn reserved_gas_funds(&self) -> Balance {
+        self.gas_fund + self.gas_credited
+    }
+}
@@ -0,0 +1,188 @@
+//! Sputnik DAO governance of the admin methods.
+//!
+//! The owner may be a Sputnik DAO contract, which takes over by accepting
+//! ownership through a FunctionCall proposal calling `accept_ownership`.
+//! From then on, validator management and config updates are proposed to
+//! the DAO as a [`GovernanceAction`] and applied when `act_proposal`
+//! approves the proposal and the DAO calls `execute_governance_action`.
+//! Upgrades go through an UpgradeRemote proposal of a code blob stored in
+//! the DAO, which calls `upgrade` with the code as its raw input. The
+//! proposal views build the `add_proposal` arguments for each. Actions the
+//! admin timelock covers must be queued instead once it is set.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::{self, json};
+use near_sdk::{env, near_bindgen, AccountId, Gas, GasWeight, Promise};
+use schemars::JsonSchema;
+
+use crate::config::ConfigUpdate;
+use crate::errors::{require, ContractError};
+use crate::nft::NftCollection;
+use crate::roles::Role;
+use crate::tokens::TokenMapping;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+/// Gas the DAO attaches when executing an approved governance action
+const GOVERNANCE_ACTION_GAS: Gas = Gas(50_000_000_000_000);
+
+/// Admin change a Sputnik DAO owner applies through a proposal
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum GovernanceAction {
+    /// Remove a bridge validator, starting to unbond its whole bond
+    RemoveValidator { validator: AccountId },
+    /// Map a source chain token, replacing any existing mapping
+    RegisterToken {
+        mapping: TokenMapping,
+        chain_id: Option<u64>,
+    },
+    /// Remove a token from a source chain's registry
+    RemoveToken {
+        base_token: String,
+        chain_id: Option<u64>,
+    },
+    /// Map a source chain NFT collection, replacing any existing mapping
+    RegisterNftCollection {
+        collection: NftCollection,
+        chain_id: Option<u64>,
+    },
+    /// Remove an NFT collection from a source chain's registry
+    RemoveNftCollection {
+        base_address: String,
+        chain_id: Option<u64>,
+    },
+    /// Set the default fee
+    SetFee { fee_bps: u16 },
+    /// Override the fee of a token, or clear the override
+    SetTokenFee { token: String, fee_bps: Option<u16> },
+    /// Set the share of the protocol fee credited to referrers
+    SetReferralShare { share_bps: u16 },
+    /// Change the delay of the admin timelock
+    SetAdminTimelock { delay: U64 },
+    /// Grant a grantable role to an account
+    GrantRole { role: Role, account_id: AccountId },
+    /// Revoke a grantable role from an account
+    RevokeRole { role: Role, account_id: AccountId },
+    /// Change configuration fields
+    UpdateConfig { update: ConfigUpdate },
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Apply a governance action, called by the DAO owner when its proposal
+    /// is approved
+    pub fn execute_governance_action(&mut self, action: GovernanceAction) {
+        if action.is_timelocked() {
+            self.assert_untimelocked_owner();
+        } else {
+            self.assert_role(&[Role::Owner]);
+        }
+        self.apply_governance_action(action);
+    }
+
+    /// Deploy the contract code passed as the raw call input and migrate the
+    /// state, called by the DAO owner executing an UpgradeRemote proposal
+    pub fn upgrade(&self) -> Promise {
+        self.assert_role(&[Role::Owner]);
+        let code = env::input().unwrap_or_else(|| ContractError::MissingCode.panic());
+        require(!code.is_empty(), ContractError::MissingCode);
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call_weight("migrate".to_string(), vec![], 0, Gas(0), GasWeight(1))
+    }
+
+    /// Arguments of the DAO's `add_proposal` call proposing a governance action
+    pub fn get_governance_proposal(
+        &self,
+        action: GovernanceAction,
+        description: String,
+    ) -> serde_json::Value {
+        let args = json!({ "action": action }).to_string().into_bytes();
+        json!({
+            "proposal": {
+                "description": description,
+                "kind": {
+                    "FunctionCall": {
+                        "receiver_id": env::current_account_id(),
+                        "actions": [{
+                            "method_name": "execute_governance_action",
+                            "args": Base64VecU8(args),
+                            "deposit": "0",
+                            "gas": GOVERNANCE_ACTION_GAS,
+                        }],
+                    }
+                }
+            }
+        })
+    }
+
+    /// Arguments of the DAO's `add_proposal` call proposing an upgrade to
+    /// the code blob with base58 hash `code_hash` stored in the DAO
+    pub fn get_upgrade_proposal(
+        &self,
+        code_hash: String,
+        description: String,
+    ) -> serde_json::Value {
+        json!({
+            "proposal": {
+                "description": description,
+                "kind": {
+                    "UpgradeRemote": {
+                        "receiver_id": env::current_account_id(),
+                        "method_name": "upgrade",
+                        "hash": code_hash,
+                    }
+                }
+            }
+        })
+    }
+}
+
+impl GovernanceAction {
+    /// Whether the action must wait out the admin timelock once it is set
+    pub fn is_timelocked(&self) -> bool {
+        !matches!(
+            self,
+            GovernanceAction::GrantRole { .. }
+                | GovernanceAction::RevokeRole { .. }
+                | GovernanceAction::UpdateConfig { .. }
+        )
+    }
+}
+
+impl CrossChainExecutor {
+    /// Apply an action whose caller the entry point already checked
+    pub(crate) fn apply_governance_action(&mut self, action: GovernanceAction) {
+        match action {
+            GovernanceAction::RemoveValidator { validator } => self.drop_validator(&validator),
+            GovernanceAction::RegisterToken { mapping, chain_id } => {
+                self.insert_token_mapping(mapping, chain_id)
+            }
+            GovernanceAction::RemoveToken {
+                base_token,
+                chain_id,
+            } => self.remove_token_mapping(base_token, chain_id),
+            GovernanceAction::RegisterNftCollection {
+                collection,
+                chain_id,
+            } => self.insert_nft_collection(collection, chain_id),
+            GovernanceAction::RemoveNftCollection {
+                base_address,
+                chain_id,
+            } => self.remove_nft_collection_mapping(base_address, chain_id),
+            GovernanceAction::SetFee { fee_bps } => self.set_default_fee(fee_bps),
+            GovernanceAction::SetTokenFee { token, fee_bps } => {
+                self.set_token_fee_override(token, fee_bps)
+            }
+            GovernanceAction::SetReferralShare { share_bps } => {
+                self.set_referral_share_bps(share_bps)
+            }
+            GovernanceAction::SetAdminTimelock { delay } => self.set_timelock_delay(delay.0),
+            GovernanceAction::GrantRole { role, account_id } => self.grant(role, &account_id),
+            GovernanceAction::RevokeRole { role, account_id } => self.revoke(role, &account_id),
+            GovernanceAction::UpdateConfig { update } => self.update_config(update),
+        }
+    }
+}
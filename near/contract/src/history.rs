@@ -0,0 +1,62 @@
+//! Intent lifecycle history.
+//!
+//! Every step of an inbound intent's lifecycle is recorded with the account
+//! that took it and the block it happened in: creation, each validator
+//! approval or aggregate attestation, and every status transition. Steps
+//! taken in a callback, such as completing an execution, are recorded with
+//! the executor itself as the actor. The history is deleted along with the
+//! intent when it is pruned; the events emitted for each step remain.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::{CrossChainExecutor, CrossChainExecutorExt, IntentStatus};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum IntentStep {
+    Created,
+    Approved,
+    /// Attested with an aggregate signature of the given epoch
+    Attested(u64),
+    /// Moved to a new status
+    Transitioned(IntentStatus),
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HistoryEntry {
+    pub step: IntentStep,
+    pub actor: AccountId,
+    pub timestamp: U64,
+    pub block_height: U64,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Get the lifecycle history of an intent, oldest step first
+    pub fn get_intent_history(&self, intent_id: String) -> Vec<HistoryEntry> {
+        self.intent_history
+            .get(&intent_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Record a lifecycle step of an intent, taken by the caller
+    pub(crate) fn record_step(&mut self, intent_id: &str, step: IntentStep) {
+        self.intent_history
+            .entry(intent_id.to_string())
+            .or_default()
+            .push(HistoryEntry {
+                step,
+                actor: env::predecessor_account_id(),
+                timestamp: U64(env::block_timestamp()),
+                block_height: U64(env::block_height()),
+            });
+    }
+}
@@ -1,164 +1,5498 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LookupMap, UnorderedSet};
-use near_sdk::json_types::U128;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::store::{LazyOption, LookupMap, LookupSet, TreeMap, UnorderedMap, UnorderedSet};
 use near_sdk::{
     env, near_bindgen, AccountId, Balance, BorshStorageKey, PanicOnDefault, Promise,
+    PromiseError, PromiseOrValue, PublicKey,
     serde::{Deserialize, Serialize},
 };
+use schemars::JsonSchema;
+
+mod actions;
+mod agents;
+mod aggregate;
+mod anomaly;
+mod aurora;
+mod auction;
+mod basket;
+mod batch;
+mod blacklist;
+#[cfg(any(test, feature = "bench"))]
+pub mod bench;
+mod chain_signatures;
+mod chains;
+mod checkpoints;
+mod compliance;
+mod config;
+mod costs;
+mod croncat;
+mod defuse;
+mod deferred;
+mod delegate;
+mod dependencies;
+mod disputes;
+mod eip712;
+mod emergency;
+mod escrow;
+mod epochs;
+mod errors;
+mod events;
+mod fallible;
+mod fees;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
+mod gas_fund;
+mod governance;
+mod history;
+mod light_client;
+mod migration;
+mod multi_token;
+mod network;
+mod nft;
+mod outbound;
+mod ownership;
+mod pause;
+mod preflight;
+mod proof;
+mod quotes;
+mod rate_limit;
+mod recurring;
+mod referrals;
+mod retry;
+mod rlp;
+mod roles;
+#[cfg(any(test, feature = "schema"))]
+pub mod schema;
+mod slashing;
+mod solvers;
+mod staking;
+mod stats;
+mod storage;
+mod swap;
+mod timelock;
+mod tokens;
+mod views;
+mod volume_caps;
+
+use actions::IntentAction;
+use agents::AgentConfig;
+use auction::Auction;
+use basket::BasketAsset;
+use chain_signatures::ReleaseSignature;
+use chains::{ChainConfig, BASE_CHAIN_ID};
+use checkpoints::Checkpoint;
+use compliance::ComplianceMode;
+use config::Config;
+use disputes::Dispute;
+use epochs::ValidatorSet;
+use errors::{require, ContractError};
+use events::IntentEvent;
+use gas_fund::ExecutionGas;
+use history::{HistoryEntry, IntentStep};
+use nft::NftCollection;
+use outbound::OutboundIntent;
+use quotes::Quote;
+use rate_limit::{RateLimit, RateWindow};
+use recurring::RecurringTemplate;
+use retry::DeliveryRetry;
+use migration::{LegacyIntentIds, VersionedIntent};
+use roles::Role;
+use solvers::{IntentClaim, Solver};
+use staking::ValidatorBond;
+use storage::{IntentDeposit, StorageAccount};
+use swap::SwapAction;
+use timelock::QueuedAction;
+use tokens::{is_native_token, normalize_address, TokenMapping};
+use volume_caps::VolumeBucket;
 
 #[derive(BorshSerialize, BorshStorageKey)]
-enum StorageKey {
+pub(crate) enum StorageKey {
+    #[allow(dead_code)] // Layout 0 executed IDs, read in place by migration
     ExecutedIntents,
     Proofs,
+    #[allow(dead_code)] // Layout 0 validators, registered without keys
     BridgeValidators,
+    Intents,
+    Approvals,
+    StorageAccounts,
+    OutboundIntents,
+    OutboundApprovals,
+    BaseHeaders,
+    CanonicalHeaders,
+    Roles,
+    Agents,
+    TokenFees,
+    AccruedFees,
+    RateLimits,
+    RateWindows,
+    VolumeCaps,
+    VolumeBuckets,
+    IntentsBySender,
+    IntentsByReceiver,
+    IntentDeposits,
+    AggregateKeys,
+    AggregateAttestations,
+    ValidatorBonds,
+    ValidatorSets,
+    Disputes,
+    SenderNonces,
+    ChainHeads,
+    ChainHeaders { chain_id: u64 },
+    ChainCanonicalHeaders { chain_id: u64 },
+    TokenMappingsByChain,
+    IntentClaims,
+    Quotes,
+    BoundQuotes,
+    Auctions,
+    EscrowCode,
+    Escrows,
+    DelegateKeys,
+    DelegateNonces,
+    ReleaseSignatures,
+    IntentHistory,
+    IntentIds,
+    RefundableIntents,
+    RoleMemberSets { role: Role },
+    ChainConfigs,
+    ChainTokenMappings { chain_id: u64 },
+    SolverAccounts,
+    EmergencyApprovals,
+    AdminQueue,
+    NftCollections,
+    GasCredits,
+    ExecutionGas,
+    ReferralFees,
+    Checkpoints,
+    StatusCounts,
+    FailureCounts,
+    TokenVolumes,
+    AllowedReceivers,
+    BlockedSenders,
+    BlockedReceivers,
+    RetryQueue,
+    DeliveryAttempts,
+    ScheduledIntents,
+    RecurringTemplates,
+    Validators,
+    FailedCompensations,
+    UsedNonces,
+    IntentsByStatus,
+    IntentPositions,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct CrossChainIntent {
-    pub id: String,
-    pub sender: String,      // Base chain address
+    pub id: String,          // 0x-prefixed typed hash of the intent terms
+    pub sender: String,      // Source chain address
     pub receiver: AccountId, // NEAR account
-    pub token: String,       // Token address on Base
+    pub token: String,       // Token address on the source chain
     pub amount: U128,
     pub proof: BridgeProof,
     pub status: IntentStatus,
+    pub signatures: Vec<ValidatorSignature>,
+    pub expires_at: U64, // Block timestamp (ns) after which the intent can no longer execute
+    /// Validator set epoch whose signatures and approvals count for the intent
+    #[serde(default)]
+    pub epoch: u64,
+    /// Position in the sender's intent sequence, above any nonce it used before
+    pub nonce: u64,
+    /// Sender's signature over the typed hash, checked on creation only
+    #[borsh_skip]
+    #[serde(default)]
+    pub sender_signature: Option<Base64VecU8>,
+    /// EIP-155 ID of the chain the deposit was made on
+    #[serde(default = "default_chain_id")]
+    pub chain_id: u64,
+    /// Whether the tokens are credited to the receiver inside NEAR Intents
+    /// rather than sent to its wallet
+    #[serde(default)]
+    pub settle_in_defuse: bool,
+    /// Assets delivered alongside the primary token, each locked by its own
+    /// log in the deposit receipt
+    #[serde(default)]
+    pub extra_assets: Vec<BasketAsset>,
+    /// Source chain ID of the NFT the intent moves from the `token`
+    /// collection instead of fungible tokens
+    #[serde(default)]
+    pub nft_token_id: Option<U128>,
+    /// Wallet or dApp that routed the intent, credited a share of its fee
+    #[serde(default)]
+    pub referrer: Option<AccountId>,
+    /// Ref Finance swap of the delivered token into the one the receiver
+    /// gets
+    #[serde(default)]
+    pub swap: Option<SwapAction>,
+    /// Whether the delivered token is swapped into the stablecoin its
+    /// registry mapping routes it to
+    #[serde(default)]
+    pub to_stablecoin: bool,
+    /// Aurora EVM address, 0x-prefixed hex, the Aurora engine receiving
+    /// the intent credits the tokens to
+    #[serde(default)]
+    pub aurora_recipient: Option<String>,
+    /// NEP-245 token ID delivered from the intent's multi-token contract
+    #[serde(default)]
+    pub mt_token_id: Option<String>,
+    /// Block timestamp (ns) before which the intent may not execute
+    #[serde(default)]
+    pub execute_after: Option<U64>,
+    /// Intent that must complete before this one may execute
+    #[serde(default)]
+    pub depends_on: Option<String>,
+    /// Transfers, swaps and calls the delivered tokens are split across,
+    /// run one after another instead of a single delivery
+    #[serde(default)]
+    pub actions: Vec<IntentAction>,
+    /// Least the receiver takes from a solver quote, in the NEAR token's
+    /// decimals. Only intents setting it are quoted or auctioned.
+    #[serde(default)]
+    pub min_out: Option<U128>,
+}
+
+fn default_chain_id() -> u64 {
+    BASE_CHAIN_ID
+}
+
+impl CrossChainIntent {
+    /// Hash validators sign over, binding the intent terms to their proof
+    pub fn signing_hash(&self) -> Vec<u8> {
+        let typed_hash = self.typed_hash().unwrap_or_else(|reason| reason.panic());
+        let payload = (typed_hash, &self.proof)
+            .try_to_vec()
+            .expect("Failed to serialize intent");
+        env::sha256(&payload)
+    }
+
+    /// How the intent hands its tokens over to the receiver
+    pub(crate) fn delivery_kind(&self) -> DeliveryKind {
+        let kinds = [
+            (self.settle_in_defuse, DeliveryKind::Defuse),
+            (self.is_basket(), DeliveryKind::Basket),
+            (self.is_nft(), DeliveryKind::Nft),
+            (self.mt_token_id.is_some(), DeliveryKind::MultiToken),
+            (self.swap.is_some(), DeliveryKind::Swap),
+            (self.to_stablecoin, DeliveryKind::Stablecoin),
+            (self.aurora_recipient.is_some(), DeliveryKind::Aurora),
+            (!self.actions.is_empty(), DeliveryKind::Actions),
+        ];
+        let mut used = kinds
+            .into_iter()
+            .filter_map(|(used, kind)| used.then_some(kind));
+        match (used.next(), used.next()) {
+            (None, _) => DeliveryKind::Transfer,
+            (Some(kind), None) => kind,
+            (Some(_), Some(_)) => DeliveryKind::Mixed,
+        }
+    }
+}
+
+/// Way an intent delivers its tokens. Intents use one at most, on top of
+/// which they may be deferred, depend on another intent or be quoted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DeliveryKind {
+    /// Transfer of the primary token to the receiver or its escrow
+    Transfer,
+    /// Deposit credited to the receiver inside NEAR Intents
+    Defuse,
+    /// Transfers of the primary token and the extra assets
+    Basket,
+    /// NFT minted or released to the receiver
+    Nft,
+    /// NEP-245 transfer of the intent's multi-token
+    MultiToken,
+    /// Ref Finance swap the intent names
+    Swap,
+    /// Swap along the stablecoin route of the token's mapping
+    Stablecoin,
+    /// Deposit credited to an Aurora EVM address
+    Aurora,
+    /// Transfers, swaps and calls the intent's actions split it across
+    Actions,
+    /// Several of the above, which no intent may combine
+    Mixed,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidatorSignature {
+    pub validator: AccountId,
+    pub signature: Base64VecU8, // 64-byte ed25519 signature over the intent hash
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub enum IntentStatus {
     Pending,
     Executing,
     Completed,
     Failed(String),
+    Cancelled,
+    Refunded,
+    /// Frozen until an arbiter rules on a dispute
+    Disputed,
+}
+
+impl IntentStatus {
+    /// Whether the intent lifecycle allows moving from this status to `next`
+    pub fn can_transition_to(&self, next: &IntentStatus) -> bool {
+        use IntentStatus::*;
+        matches!(
+            (self, next),
+            (Pending, Executing)
+                | (Pending, Failed(_))
+                | (Pending, Cancelled)
+                | (Pending, Disputed)
+                | (Disputed, Pending)
+                | (Disputed, Cancelled)
+                | (Executing, Completed)
+                | (Executing, Failed(_))
+                | (Failed(_), Refunded)
+        )
+    }
+
+    /// Whether no further transitions are possible
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            IntentStatus::Completed | IntentStatus::Cancelled | IntentStatus::Refunded
+        )
+    }
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "near_sdk::serde")]
 pub struct BridgeProof {
     pub block_number: u64,
     pub timestamp: u64,
     pub transaction_hash: String,
+    pub receipt: Base64VecU8,          // RLP-encoded receipt of the deposit transaction
+    pub receipt_index: u64,            // Index of the receipt in the block's receipts trie
+    pub proof_nodes: Vec<Base64VecU8>, // Merkle-Patricia nodes from the receipts root
+    pub log_index: u64,                // Index of the TokensLocked log within the receipt
 }
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct CrossChainExecutor {
+    /// Account allowed to administer the contract
+    owner: AccountId,
+    /// Proposed owner awaiting acceptance
+    pending_owner: Option<AccountId>,
     /// Set of executed intent IDs
     executed_intents: UnorderedSet<String>,
-    /// Mapping of validator addresses
-    bridge_validators: LookupMap<AccountId, bool>,
-    /// Required number of validator signatures
-    required_signatures: u32,
-    /// Token contract address
-    token_contract: AccountId,
-    /// CDP Agent account
-    agent_account: AccountId,
+    /// Full intent payloads keyed by intent ID
+    intents: LookupMap<String, VersionedIntent>,
+    /// Validators that approved each intent on-chain
+    approvals: LookupMap<String, Vec<AccountId>>,
+    /// Failed intents awaiting a refund on their source chain
+    refund_queue: UnorderedSet<String>,
+    /// NEP-145 storage balances of intent creators
+    storage_accounts: LookupMap<AccountId, StorageAccount>,
+    /// Mapping of validator addresses to their ed25519 public keys
+    bridge_validators: UnorderedMap<AccountId, PublicKey>,
+    /// Tunable operational parameters
+    config: Config,
+    /// NEAR→Base intents for tokens locked in the executor
+    outbound_intents: LookupMap<String, OutboundIntent>,
+    /// Validators that attested each outbound intent
+    outbound_approvals: LookupMap<String, Vec<AccountId>>,
+    /// Counter used to derive outbound intent IDs
+    outbound_nonce: u64,
+    /// Whether intent creation and execution are halted
+    paused: bool,
+    /// Members of each grantable role
+    role_members: LookupMap<Role, UnorderedSet<AccountId>>,
+    /// Activation flags and daily quotas of agent accounts
+    agents: LookupMap<AccountId, AgentConfig>,
+    /// Default protocol fee in basis points
+    fee_bps: u16,
+    /// Fee overrides keyed by intent token
+    token_fee_bps: LookupMap<String, u16>,
+    /// Withheld fees not yet withdrawn, keyed by intent token
+    accrued_fees: LookupMap<String, Balance>,
+    /// Account fees are withdrawn to
+    treasury: AccountId,
+    /// Execution rate limits keyed by intent token
+    rate_limits: LookupMap<String, RateLimit>,
+    /// Current rate limit window usage keyed by intent token
+    rate_windows: LookupMap<String, RateWindow>,
+    /// Rolling 24h volume caps keyed by destination token
+    volume_caps: LookupMap<String, Balance>,
+    /// Hourly executed volume keyed by destination token
+    volume_buckets: LookupMap<String, Vec<VolumeBucket>>,
+    /// Intent IDs keyed by Base sender address
+    intents_by_sender: LookupMap<String, Vec<String>>,
+    /// Intent IDs keyed by NEAR receiver
+    intents_by_receiver: LookupMap<AccountId, Vec<String>>,
+    /// Intent IDs keyed by status name and creation position
+    intents_by_status: TreeMap<(String, u64), String>,
+    /// Creation position of each intent, locating it in `intents_by_status`
+    intent_positions: LookupMap<String, u64>,
+    /// Storage deposits held for inbound intents until they are pruned
+    intent_deposits: LookupMap<String, IntentDeposit>,
+    /// Aggregate validator public keys keyed by chain ID and epoch
+    aggregate_keys: LookupMap<(u64, u64), PublicKey>,
+    /// Epoch of the aggregate attestation recorded for each intent
+    aggregate_attestations: LookupMap<String, u64>,
+    /// Bonded and unbonding NEAR of each validator
+    validator_bonds: LookupMap<AccountId, ValidatorBond>,
+    /// NEAR held in validator bonds, bonded or unbonding, and solver bonds
+    total_bonded: Balance,
+    /// Validator sets of rotated epochs
+    validator_sets: LookupMap<u64, ValidatorSet>,
+    /// Epoch of the validator set currently attesting
+    current_epoch: u64,
+    /// Open disputes keyed by intent ID
+    disputes: LookupMap<String, Dispute>,
+    /// Highest intent nonce used, keyed by sender address
+    sender_nonces: LookupMap<String, u64>,
+    /// Source chains intents are accepted from, keyed by EIP-155 chain ID
+    chains: UnorderedMap<u64, ChainConfig>,
+    /// Block number of each source chain's canonical head
+    chain_heads: LookupMap<u64, u64>,
+    /// Token mappings of each source chain
+    token_mappings: LookupMap<u64, UnorderedMap<String, TokenMapping>>,
+    /// NEAR Intents verifier contract intents can settle into
+    defuse_contract: Option<AccountId>,
+    /// Bonded solvers keyed by account
+    solvers: UnorderedMap<AccountId, Solver>,
+    /// Solver claims reserving the execution of intents
+    intent_claims: LookupMap<String, IntentClaim>,
+    /// Solver quotes for pending intents
+    quotes: LookupMap<String, Vec<Quote>>,
+    /// Solver and spread of the quote each executing intent is bound to
+    bound_quotes: LookupMap<String, (AccountId, Balance)>,
+    /// Dutch auctions of intents no solver claimed
+    auctions: LookupMap<String, Auction>,
+    /// Contract deployed on escrow subaccounts
+    escrow_code: LazyOption<Vec<u8>>,
+    /// Delivered amount from which executions are escrowed, if any
+    escrow_threshold: Option<Balance>,
+    /// Escrow subaccount and escrowed amount of each executing intent
+    escrows: LookupMap<String, (AccountId, Balance)>,
+    /// Keys accounts registered for signing delegate actions
+    delegate_keys: LookupMap<AccountId, Vec<PublicKey>>,
+    /// Last delegate action nonce used by each account
+    delegate_nonces: LookupMap<AccountId, u64>,
+    /// MPC signer contract signing Base releases, if any
+    signer_contract: Option<AccountId>,
+    /// Latest signed release transaction of each outbound intent
+    release_signatures: LookupMap<String, ReleaseSignature>,
+    /// Lifecycle steps of each inbound intent
+    intent_history: LookupMap<String, Vec<HistoryEntry>>,
+    /// IDs of intents created before the move to lazy collections that are
+    /// still being migrated into `executed_intents`
+    legacy_intent_ids: Option<LegacyIntentIds>,
+    /// Sputnik DAO approving emergency withdrawals
+    emergency_dao: Option<AccountId>,
+    /// Timestamp from which each approved emergency withdrawal can execute,
+    /// keyed by withdrawal hash
+    emergency_approvals: LookupMap<String, u64>,
+    /// Delay of the admin timelock in nanoseconds, once it is set
+    admin_timelock: Option<u64>,
+    /// Privileged changes waiting out the admin timelock, keyed by ID
+    admin_queue: UnorderedMap<u64, QueuedAction>,
+    /// Counter used to derive queued admin action IDs
+    admin_action_nonce: u64,
+    /// NEP-171 contract of each source chain NFT collection, keyed by chain
+    /// ID and collection address
+    nft_collections: UnorderedMap<(u64, String), NftCollection>,
+    /// Native NEAR available to reimburse the gas of executions
+    gas_fund: Balance,
+    /// Native NEAR credited to executing accounts and not yet withdrawn
+    gas_credited: Balance,
+    /// Gas reimbursements each executing account can withdraw
+    gas_credits: LookupMap<AccountId, Balance>,
+    /// Gas each executing intent's execution burnt and who paid for it
+    execution_gas: LookupMap<String, ExecutionGas>,
+    /// Share of the protocol fee, in basis points, credited to referrers
+    referral_share_bps: u16,
+    /// Unclaimed referral fees, keyed by referrer and intent token
+    referral_fees: LookupMap<(AccountId, String), Balance>,
+    /// Native NEAR held as unclaimed referral fees
+    native_referral_fees: Balance,
+    /// Intent each source chain deposit backs, keyed by chain ID, block
+    /// number, receipt index and log index
+    proofs: LookupMap<String, String>,
+    /// Latest validator-signed checkpoint of each source chain
+    checkpoints: LookupMap<u64, Checkpoint>,
+    /// Intents in each status, keyed by status name
+    status_counts: UnorderedMap<String, u64>,
+    /// Intents failed for each reason
+    failure_counts: UnorderedMap<String, u64>,
+    /// Amount delivered per source chain token
+    token_volumes: UnorderedMap<String, Balance>,
+    /// Distinct NEAR accounts intents were created for
+    unique_receivers: u64,
+    /// Multiple of a token's trailing average hourly volume at which
+    /// executions pause the contract, if the anomaly guard is on
+    anomaly_multiple: Option<u32>,
+    /// How receivers are vetted before their intents execute
+    compliance_mode: ComplianceMode,
+    /// Timestamp (ns) each allowed receiver stays allowed until, `u64::MAX`
+    /// for receivers the owner registered
+    allowed_receivers: LookupMap<AccountId, u64>,
+    /// Source chain sender addresses intents may not be created or
+    /// executed for, lowercased
+    blocked_senders: LookupSet<String>,
+    /// NEAR accounts intents may not be created or executed for
+    blocked_receivers: LookupSet<AccountId>,
+    /// Ref Finance exchange intents swap their delivered token on
+    ref_contract: Option<AccountId>,
+    /// Aurora engine intents with an Aurora recipient deliver into
+    aurora_engine: Option<AccountId>,
+    /// Deliveries an intent gets before a failed one routes it to refund
+    max_delivery_attempts: u32,
+    /// Intents whose delivery failed, waiting out their backoff
+    retry_queue: UnorderedMap<String, DeliveryRetry>,
+    /// Failed deliveries of each intent queued for, or undergoing, a retry
+    delivery_attempts: LookupMap<String, u32>,
+    /// Croncat manager whose task ticks scheduled executions
+    croncat_manager: Option<AccountId>,
+    /// Intents created while a scheduler was set, until they leave pending
+    scheduled_intents: UnorderedSet<String>,
+    /// Position in `scheduled_intents` the next tick resumes from
+    tick_cursor: u64,
+    /// Recurring transfer templates by ID
+    recurring_templates: UnorderedMap<u64, RecurringTemplate>,
+    /// ID the next recurring template is stored under
+    next_template_id: u64,
+    /// Multi-action intents whose compensating transfer failed, with the
+    /// failed action and the amount still owed to the receiver
+    failed_compensations: UnorderedMap<String, (u32, U128)>,
+    /// Intent nonces each sender address has used
+    used_nonces: LookupSet<(String, u64)>,
+}
+
+impl CrossChainExecutor {
+    /// Fresh state with the default chains and tokens, `agent_account`
+    /// holding the agent role
+    pub(crate) fn initial_state(
+        required_signatures: u32,
+        agent_account: &AccountId,
+        owner: AccountId,
+    ) -> Self {
+        require(required_signatures > 0, ContractError::InvalidRequiredSignatures);
+
+        let mut this = Self {
+            owner: owner.clone(),
+            pending_owner: None,
+            executed_intents: UnorderedSet::new(StorageKey::IntentIds),
+            intents: LookupMap::new(StorageKey::Intents),
+            approvals: LookupMap::new(StorageKey::Approvals),
+            refund_queue: UnorderedSet::new(StorageKey::RefundableIntents),
+            storage_accounts: LookupMap::new(StorageKey::StorageAccounts),
+            bridge_validators: UnorderedMap::new(StorageKey::Validators),
+            config: Config::new(required_signatures),
+            outbound_intents: LookupMap::new(StorageKey::OutboundIntents),
+            outbound_approvals: LookupMap::new(StorageKey::OutboundApprovals),
+            outbound_nonce: 0,
+            paused: false,
+            role_members: LookupMap::new(StorageKey::Roles),
+            agents: LookupMap::new(StorageKey::Agents),
+            fee_bps: 0,
+            token_fee_bps: LookupMap::new(StorageKey::TokenFees),
+            accrued_fees: LookupMap::new(StorageKey::AccruedFees),
+            treasury: owner,
+            rate_limits: LookupMap::new(StorageKey::RateLimits),
+            rate_windows: LookupMap::new(StorageKey::RateWindows),
+            volume_caps: LookupMap::new(StorageKey::VolumeCaps),
+            volume_buckets: LookupMap::new(StorageKey::VolumeBuckets),
+            intents_by_sender: LookupMap::new(StorageKey::IntentsBySender),
+            intents_by_receiver: LookupMap::new(StorageKey::IntentsByReceiver),
+            intents_by_status: TreeMap::new(StorageKey::IntentsByStatus),
+            intent_positions: LookupMap::new(StorageKey::IntentPositions),
+            intent_deposits: LookupMap::new(StorageKey::IntentDeposits),
+            aggregate_keys: LookupMap::new(StorageKey::AggregateKeys),
+            aggregate_attestations: LookupMap::new(StorageKey::AggregateAttestations),
+            validator_bonds: LookupMap::new(StorageKey::ValidatorBonds),
+            total_bonded: 0,
+            validator_sets: LookupMap::new(StorageKey::ValidatorSets),
+            current_epoch: 0,
+            disputes: LookupMap::new(StorageKey::Disputes),
+            sender_nonces: LookupMap::new(StorageKey::SenderNonces),
+            chains: UnorderedMap::new(StorageKey::ChainConfigs),
+            chain_heads: LookupMap::new(StorageKey::ChainHeads),
+            token_mappings: LookupMap::new(StorageKey::TokenMappingsByChain),
+            defuse_contract: None,
+            solvers: UnorderedMap::new(StorageKey::SolverAccounts),
+            intent_claims: LookupMap::new(StorageKey::IntentClaims),
+            quotes: LookupMap::new(StorageKey::Quotes),
+            bound_quotes: LookupMap::new(StorageKey::BoundQuotes),
+            auctions: LookupMap::new(StorageKey::Auctions),
+            escrow_code: LazyOption::new(StorageKey::EscrowCode, None),
+            escrow_threshold: None,
+            escrows: LookupMap::new(StorageKey::Escrows),
+            delegate_keys: LookupMap::new(StorageKey::DelegateKeys),
+            delegate_nonces: LookupMap::new(StorageKey::DelegateNonces),
+            signer_contract: None,
+            release_signatures: LookupMap::new(StorageKey::ReleaseSignatures),
+            intent_history: LookupMap::new(StorageKey::IntentHistory),
+            legacy_intent_ids: None,
+            emergency_dao: None,
+            emergency_approvals: LookupMap::new(StorageKey::EmergencyApprovals),
+            admin_timelock: None,
+            admin_queue: UnorderedMap::new(StorageKey::AdminQueue),
+            admin_action_nonce: 0,
+            nft_collections: UnorderedMap::new(StorageKey::NftCollections),
+            gas_fund: 0,
+            gas_credited: 0,
+            gas_credits: LookupMap::new(StorageKey::GasCredits),
+            execution_gas: LookupMap::new(StorageKey::ExecutionGas),
+            referral_share_bps: 0,
+            referral_fees: LookupMap::new(StorageKey::ReferralFees),
+            native_referral_fees: 0,
+            proofs: LookupMap::new(StorageKey::Proofs),
+            checkpoints: LookupMap::new(StorageKey::Checkpoints),
+            status_counts: UnorderedMap::new(StorageKey::StatusCounts),
+            failure_counts: UnorderedMap::new(StorageKey::FailureCounts),
+            token_volumes: UnorderedMap::new(StorageKey::TokenVolumes),
+            unique_receivers: 0,
+            anomaly_multiple: None,
+            compliance_mode: ComplianceMode::Open,
+            allowed_receivers: LookupMap::new(StorageKey::AllowedReceivers),
+            blocked_senders: LookupSet::new(StorageKey::BlockedSenders),
+            blocked_receivers: LookupSet::new(StorageKey::BlockedReceivers),
+            ref_contract: None,
+            aurora_engine: None,
+            max_delivery_attempts: 1,
+            retry_queue: UnorderedMap::new(StorageKey::RetryQueue),
+            delivery_attempts: LookupMap::new(StorageKey::DeliveryAttempts),
+            croncat_manager: None,
+            scheduled_intents: UnorderedSet::new(StorageKey::ScheduledIntents),
+            tick_cursor: 0,
+            recurring_templates: UnorderedMap::new(StorageKey::RecurringTemplates),
+            next_template_id: 0,
+            failed_compensations: UnorderedMap::new(StorageKey::FailedCompensations),
+            used_nonces: LookupSet::new(StorageKey::UsedNonces),
+        };
+        for (chain_id, name, confirmations) in network::DEFAULT_CHAINS {
+            let chain = ChainConfig {
+                confirmations,
+                ..ChainConfig::new(name)
+            };
+            this.chains.insert(chain_id, chain);
+        }
+        for token in network::DEFAULT_TOKENS {
+            this.chain_token_mappings_mut(token.chain_id)
+                .insert(token.base_address.to_string(), token.mapping());
+        }
+        this.grant(Role::Agent, agent_account);
+        this
+    }
 }
 
 #[near_bindgen]
 impl CrossChainExecutor {
     #[init]
     pub fn new(
-        token_contract: AccountId,
         required_signatures: u32,
-        agent_account: AccountId
+        agent_account: AccountId,
+        owner_id: Option<AccountId>, // Defaults to the contract account
     ) -> Self {
-        assert!(!env::state_exists(), "Already initialized");
-        assert!(required_signatures > 0, "Required signatures must be > 0");
+        require(!env::state_exists(), ContractError::AlreadyInitialized);
+        let owner = owner_id.unwrap_or_else(env::current_account_id);
+        let this = Self::initial_state(required_signatures, &agent_account, owner);
+        migration::write_state_version();
+        this
+    }
 
-        Self {
-            executed_intents: UnorderedSet::new(StorageKey::ExecutedIntents),
-            bridge_validators: LookupMap::new(StorageKey::BridgeValidators),
-            required_signatures,
-            token_contract,
-            agent_account,
+    /// Create a new cross-chain intent, attaching NEAR for its storage
+    #[payable]
+    pub fn create_intent(&mut self, intent: CrossChainIntent) {
+        self.assert_not_paused();
+
+        let agent = self.assert_agent();
+        // Record blocked attempts instead of panicking them away
+        if self.screen_intent(&intent, "create").is_err() {
+            self.settle_attached_deposit(0);
+            return;
         }
+        if let Err(reason) = self.validate_new_intent(&intent) {
+            reason.panic();
+        }
+        self.accept_intent(&agent, intent);
     }
 
-    /// Add a bridge validator
-    pub fn add_validator(&mut self, validator: AccountId) {
-        self.assert_owner();
-        self.bridge_validators.insert(&validator, &true);
+    /// Approve a pending intent as a registered validator
+    pub fn approve_intent(&mut self, intent_id: String) {
+        let validator = self.assert_role(&[Role::Validator]);
+        let intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        require(matches!(intent.status, IntentStatus::Pending), ContractError::ApprovalNotPending);
+
+        let approvals = self.approvals.entry(intent_id.clone()).or_default();
+        require(!approvals.contains(&validator), ContractError::AlreadyApproved);
+        approvals.push(validator.clone());
+        self.record_step(&intent_id, IntentStep::Approved);
+
+        IntentEvent::IntentApproved {
+            intent_id,
+            validator,
+        }
+        .emit();
     }
 
-    /// Remove a bridge validator
-    pub fn remove_validator(&mut self, validator: &AccountId) {
-        self.assert_owner();
-        self.bridge_validators.remove(validator);
+    /// Execute a cross-chain intent, as an agent or as the solver holding
+    /// a claim on it
+    pub fn execute_intent(&mut self, intent_id: String) -> PromiseOrValue<bool> {
+        self.assert_not_paused();
+
+        let executor = self.assert_executor(&intent_id);
+        self.execute(&executor, intent_id)
     }
 
-    /// Create a new cross-chain intent
-    pub fn create_intent(&mut self, intent: CrossChainIntent) {
-        // Only allow the CDP agent to create intents
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.agent_account,
-            "Only CDP agent can create intents"
-        );
+    /// Finalize an intent once its transfer, and the transfers of any
+    /// extra basket assets joined to it, have resolved
+    #[private]
+    pub fn on_transfer_complete(
+        &mut self,
+        intent_id: String,
+        fee: U128,
+        extra_fees: Option<Vec<U128>>,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        let extra_fees = extra_fees.unwrap_or_default();
+        let mut delivered = vec![result.is_ok()];
+        delivered.extend(basket::transfer_results(1, extra_fees.len()));
+        self.finish_execution(intent_id, fee, extra_fees, delivered)
+    }
+
+    /// Mark an expired pending intent as failed and free its approval data.
+    /// Callable by anyone once the intent is past its expiry. Executing
+    /// intents are left to their transfer callback, even once past expiry.
+    pub fn expire_intent(&mut self, intent_id: String) {
+        let mut intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        require(!matches!(intent.status, IntentStatus::Executing), ContractError::Executing);
+        require(env::block_timestamp() >= intent.expires_at.0, ContractError::NotExpired);
+
+        intent.signatures.clear();
+        self.fail_intent(&mut intent, "expired");
+    }
+
+    /// Refund a failed intent by instructing the relayer to release the
+    /// locked funds back to the sender on the source chain
+    pub fn refund_intent(&mut self, intent_id: String) {
+        self.assert_agent();
+        require(self.refund_queue.contains(&intent_id), ContractError::NotRefundable);
+
+        let mut intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        self.transition(&mut intent, IntentStatus::Refunded);
+        self.refund_queue.remove(&intent_id);
+
+        // Emit refund instruction for the source chain bridge
+        IntentEvent::IntentRefunded {
+            intent_id,
+            chain_id: intent.chain_id,
+            sender: intent.sender,
+            token: intent.token,
+            amount: intent.amount,
+            extra_assets: intent.extra_assets,
+            nft_token_id: intent.nft_token_id,
+        }
+        .emit();
+    }
+
+    /// List failed intents awaiting a refund
+    pub fn get_refundable_intents(&self) -> Vec<String> {
+        self.refund_queue.iter().cloned().collect()
+    }
+
+    /// Cancel a pending intent created by mistake
+    pub fn cancel_intent(&mut self, intent_id: String) {
+        self.assert_agent();
+        self.cancel(intent_id);
+    }
+
+    /// Get intent status
+    pub fn get_intent_status(&self, intent_id: String) -> Option<IntentStatus> {
+        self.load_intent(&intent_id).map(|intent| intent.status)
+    }
+
+    /// Start an intent's transfer on behalf of `executor`, settling it in a
+    /// callback
+    fn execute(&mut self, executor: &AccountId, intent_id: String) -> PromiseOrValue<bool> {
+        self.try_execute(executor, intent_id).unwrap_or_else(|reason| reason.panic())
+    }
+
+    /// Start an intent's transfer on behalf of `executor`, settling it in a
+    /// callback, or return why it cannot execute
+    fn try_execute(
+        &mut self,
+        executor: &AccountId,
+        intent_id: String,
+    ) -> Result<PromiseOrValue<bool>, ContractError> {
+        let mut has_actions = false;
+        if let Some(intent) = self.load_intent(&intent_id) {
+            // A transfer left short of its callback's gas would never settle
+            let needed = self.delivery_gas(&intent).0 + self.config.transfer_callback_gas.0;
+            if env::prepaid_gas().0.saturating_sub(env::used_gas().0) < needed {
+                return Err(ContractError::NotEnoughGas);
+            }
+            has_actions = !intent.actions.is_empty();
+        }
+        Ok(match self.start_execution(executor, &intent_id)? {
+            Some(delivery) => {
+                PromiseOrValue::Promise(self.settle_delivery(intent_id, has_actions, delivery))
+            }
+            None => PromiseOrValue::Value(false),
+        })
+    }
 
+    /// Chain the callback settling a delivery started by `dispatch_delivery`
+    fn settle_delivery(
+        &self,
+        intent_id: String,
+        has_actions: bool,
+        delivery: (Promise, U128, Vec<U128>),
+    ) -> Promise {
+        let (transfer, fee, extra_fees) = delivery;
+        // Multi-action intents are settled by the callbacks of their actions
+        if has_actions {
+            return transfer;
+        }
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(self.config.transfer_callback_gas)
+                .on_transfer_complete(intent_id, fee, Some(extra_fees)),
+        )
+    }
+
+    /// Cancel a pending intent and free its approval data
+    fn cancel(&mut self, intent_id: String) {
+        let mut intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        self.transition(&mut intent, IntentStatus::Cancelled);
+        self.approvals.remove(&intent_id);
+        self.aggregate_attestations.remove(&intent_id);
+
+        // Emit event for the relayer to release the source chain deposit
+        IntentEvent::IntentCancelled { intent_id }.emit();
+    }
+
+    /// Check that a new intent can be accepted
+    fn validate_new_intent(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        if intent.id != intent.derived_id()? {
+            return Err(ContractError::IdMismatch);
+        }
+        match &intent.sender_signature {
+            Some(signature) => intent.verify_sender_signature(&signature.0)?,
+            None if self.config.require_sender_signature => {
+                return Err(ContractError::SenderSignatureRequired)
+            }
+            None => {}
+        }
+        if self.migrating_intent_ids() {
+            return Err(ContractError::IdsMigrating);
+        }
         // Verify intent hasn't been executed
-        assert!(
-            !self.executed_intents.contains(&intent.id),
-            "Intent already executed"
-        );
+        if self.executed_intents.contains(&intent.id) {
+            return Err(ContractError::AlreadyExecuted);
+        }
+        if intent.expires_at.0 <= env::block_timestamp() {
+            return Err(ContractError::AlreadyExpired);
+        }
+        if intent.epoch > self.current_epoch {
+            return Err(ContractError::UnknownEpoch);
+        }
+        if self.chains.get(&intent.chain_id).is_none() {
+            return Err(ContractError::ChainNotSupported);
+        }
+        self.check_proof_fresh(intent)?;
+        self.check_deposits_unused(intent)?;
+        self.check_swap(intent)?;
+        self.check_aurora(intent)?;
+        self.check_multi_token(intent)?;
+        self.check_execute_after(intent)?;
+        self.check_dependency(intent)?;
+        self.check_actions(intent)?;
+        self.check_quote_floor(intent)?;
+        if intent.is_nft() {
+            self.check_nft_intent(intent)?;
+        } else {
+            self.check_fungible_intent(intent)?;
+        }
+        // Nonces may arrive out of order, as deposits are relayed, but each
+        // is used once
+        if self
+            .used_nonces
+            .contains(&(normalize_address(&intent.sender), intent.nonce))
+        {
+            return Err(ContractError::NonceUsed);
+        }
+        Ok(())
+    }
+
+    /// Check the tokens of a new fungible intent can be delivered
+    fn check_fungible_intent(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        // Reject tokens that cannot be delivered on NEAR
+        self.check_token_enabled(intent.chain_id, &intent.token)?;
+        self.check_basket(intent)?;
+        intent.assets().try_for_each(|(token, amount)| {
+            self.check_amount_limits(intent.chain_id, token, amount.0)
+        })?;
+        if intent.settle_in_defuse {
+            if self.defuse_contract.is_none() {
+                return Err(ContractError::DefuseNotConfigured);
+            }
+            if is_native_token(&intent.token) {
+                return Err(ContractError::NativeCannotSettleInDefuse);
+            }
+        }
+        if self
+            .rescale_amount(intent.chain_id, &intent.token, intent.amount.0)
+            .is_none()
+        {
+            return Err(ContractError::AmountOverflow);
+        }
+        Ok(())
+    }
 
-        // Store intent as pending
-        self.executed_intents.insert(&intent.id);
+    /// Record a validated intent as pending
+    fn store_new_intent(&mut self, intent: CrossChainIntent) {
+        let mut intent = intent;
+        intent.status = IntentStatus::Pending;
+        self.count_status(None, &intent.status);
+        self.index_status(&intent.id, None, &intent.status);
+        self.executed_intents.insert(intent.id.clone());
+        let sender = normalize_address(&intent.sender);
+        self.used_nonces.insert((sender.clone(), intent.nonce));
+        let highest = self.get_sender_nonce(sender.clone()).max(intent.nonce);
+        self.sender_nonces.insert(sender, highest);
+        self.record_deposits(&intent);
+        self.save_intent(&intent);
+        self.index_intent(&intent);
+        self.schedule_intent(&intent.id);
+        self.record_step(&intent.id, IntentStep::Created);
 
         // Emit event for tracking
-        env::log_str(&format!("Intent created: {}", intent.id));
+        IntentEvent::IntentCreated {
+            intent_id: intent.id,
+            chain_id: intent.chain_id,
+            sender: intent.sender,
+            receiver: intent.receiver,
+            token: intent.token,
+            amount: intent.amount,
+            extra_assets: intent.extra_assets,
+            nft_token_id: intent.nft_token_id,
+        }
+        .emit();
     }
 
-    /// Execute a cross-chain intent
-    pub fn execute_intent(&mut self, intent_id: String) -> Promise {
-        // Only allow the CDP agent to execute intents
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.agent_account,
-            "Only CDP agent can execute intents"
+    /// Store a validated intent as pending, paying for its state from the
+    /// attached deposit
+    fn accept_intent(&mut self, agent: &AccountId, intent: CrossChainIntent) {
+        let initial_storage = self.storage_usage();
+        let intent_id = intent.id.clone();
+        self.store_new_intent(intent);
+        let cost = self.record_intent_deposit(&intent_id, agent, initial_storage);
+        self.settle_attached_deposit(cost);
+    }
+
+    /// Check an intent can execute and start its transfers, returning them
+    /// joined with the fee withheld from the primary transfer and from each
+    /// extra basket asset, or return why it cannot execute before anything
+    /// changed. Returns `None` and leaves the intent pending when
+    /// one of its parties is blocked or executing it would break a token's
+    /// daily cap or trip the anomaly guard, and fails it once its swap
+    /// deadline passed. The intent is moved to `Executing` before any
+    /// transfer is created, which locks it against further executions and
+    /// expiry until its callback settles it.
+    fn start_execution(
+        &mut self,
+        executor: &AccountId,
+        intent_id: &String,
+    ) -> Result<Option<(Promise, U128, Vec<U128>)>, ContractError> {
+        let gas_at_start = env::used_gas();
+        let mut intent = self.load_intent(intent_id).ok_or(ContractError::IntentNotFound)?;
+        self.check_claim_allows(executor, intent_id)?;
+        if !matches!(intent.status, IntentStatus::Pending) {
+            return Err(ContractError::NotPending);
+        }
+        if env::block_timestamp() >= intent.expires_at.0 {
+            return Err(ContractError::Expired);
+        }
+        if !intent.is_eligible() {
+            return Err(ContractError::NotEligible);
+        }
+        self.check_dependency_completed(&intent)?;
+        // Leave intents of parties blocked since their creation pending
+        if self.screen_intent(&intent, "execute").is_err() {
+            return Ok(None);
+        }
+
+        // Require a quorum of registered validators to have signed or approved
+        // the intent, or an aggregate signature standing in for one
+        if !self.has_aggregate_attestation(&intent)
+            && self.count_approvals(&intent)
+                < self.chain_threshold(&self.chain(intent.chain_id), intent.epoch)
+        {
+            return Err(ContractError::NotEnoughApprovals);
+        }
+
+        self.check_delivery_allowed(&intent)?;
+
+        // Require proof that the deposit was included in a known source chain
+        // block, no later than the chain's latest checkpoint
+        self.check_checkpointed(&intent)?;
+        let header = self.confirmed_header(intent.chain_id, intent.proof.block_number);
+        // An intent its proof does not back fails without a refund, freeing
+        // the deposit it claimed for the intent that deposit really backs
+        if let Err(reason) = self.check_receipt_proof(&intent, &header) {
+            self.mark_failed(&mut intent, reason.message());
+            self.release_deposits(&intent);
+            return Ok(None);
+        }
+
+        // Leave the intent pending and pause if it would make a token's volume
+        // anomalous, holding back the rest of its batch too
+        if self.paused || self.trip_anomaly_guard(&intent) {
+            return Ok(None);
+        }
+
+        // Fail rather than swap past the deadline the sender set
+        if intent
+            .swap_deadline()
+            .is_some_and(|deadline| env::block_timestamp() > deadline)
+        {
+            self.fail_intent(&mut intent, "swap deadline passed");
+            return Ok(None);
+        }
+
+        // Leave the intent pending rather than break a token's daily cap
+        for (token, amount) in intent.assets() {
+            if let Err((cap, volume)) = self.check_volume_cap(token, amount.0) {
+                IntentEvent::CapReached {
+                    intent_id: intent.id.clone(),
+                    token: token.clone(),
+                    cap: U128(cap),
+                    volume: U128(volume),
+                }
+                .emit();
+                return Ok(None);
+            }
+        }
+
+        for (token, amount) in intent.assets() {
+            self.consume_agent_quota(executor, amount.0);
+            self.consume_rate_limit(token, amount.0);
+            self.record_volume(token, amount.0);
+        }
+        self.transition(&mut intent, IntentStatus::Executing);
+        self.clear_claim(intent_id);
+
+        IntentEvent::IntentExecuted {
+            intent_id: intent.id.clone(),
+            receiver: intent.receiver.clone(),
+            amount: intent.amount,
+        }
+        .emit();
+
+        let delivery = self.dispatch_delivery(&intent);
+        self.record_execution_gas(executor, &intent, gas_at_start);
+        Ok(Some(delivery))
+    }
+
+    /// Check the intent's tokens are enabled and its receiver is allowed
+    fn check_delivery_allowed(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        if intent.is_nft() {
+            self.check_nft_collection_enabled(intent.chain_id, &intent.token)?;
+        } else {
+            intent
+                .assets()
+                .try_for_each(|(token, _)| self.check_token_enabled(intent.chain_id, token))?;
+        }
+        self.check_receiver_allowed(&intent.receiver)
+    }
+
+    /// Start the transfers delivering an executing intent, returning them
+    /// with the fees withheld from each
+    fn dispatch_delivery(&mut self, intent: &CrossChainIntent) -> (Promise, U128, Vec<U128>) {
+        if let Some(token_id) = intent.nft_token_id {
+            return (self.deliver_nft(intent, token_id), U128(0), Vec::new());
+        }
+        if let Some(token_id) = &intent.mt_token_id {
+            let amount = self
+                .rescale_amount(intent.chain_id, &intent.token, intent.amount.0)
+                .unwrap_or_else(|| ContractError::AmountOverflow.panic());
+            let transfer = self.deliver_multi_token(intent, token_id, U128(amount));
+            return (transfer, U128(0), Vec::new());
+        }
+        // Each action withholds its own fee
+        if !intent.actions.is_empty() {
+            return (self.start_action(intent, 0), U128(0), Vec::new());
+        }
+
+        // Transfer tokens to recipient in the NEAR token's decimals,
+        // withholding the protocol fee and the spread of the best quote
+        let amount = self
+            .rescale_amount(intent.chain_id, &intent.token, intent.amount.0)
+            .unwrap_or_else(|| ContractError::AmountOverflow.panic());
+        let fee = self.compute_fee(&intent.token, amount);
+        let delivered = U128(self.bind_best_quote(intent, amount - fee));
+        let transfer = if intent.settle_in_defuse {
+            self.deposit_to_defuse(intent.chain_id, &intent.token, &intent.receiver, delivered)
+        } else if let Some(swap) = self.intent_swap(intent, delivered) {
+            self.swap_on_ref(intent, &swap, delivered)
+        } else if let Some(recipient) = &intent.aurora_recipient {
+            self.deposit_to_aurora(intent, recipient, delivered)
+        } else if self.should_escrow(intent, delivered.0) {
+            self.deliver_via_escrow(intent, delivered)
+        } else if is_native_token(&intent.token) {
+            self.assert_native_liquidity(delivered.0);
+            Promise::new(intent.receiver.clone()).transfer(delivered.0)
+        } else {
+            self.deliver_token(intent.chain_id, &intent.token, &intent.receiver, delivered)
+        };
+        let (transfers, extra_fees) = self.join_basket_transfers(intent, transfer);
+        (transfers, U128(fee), extra_fees)
+    }
+
+    /// Complete an executing intent all of whose transfers succeeded, or
+    /// fail it. `delivered` holds the result of the primary transfer
+    /// followed by those of the extra basket assets.
+    fn finish_execution(
+        &mut self,
+        intent_id: String,
+        fee: U128,
+        extra_fees: Vec<U128>,
+        delivered: Vec<bool>,
+    ) -> bool {
+        let mut intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        // Quotes and escrows only ever cover the primary transfer
+        self.settle_bound_quote(&intent, delivered[0]);
+        self.close_escrow(&intent, delivered[0]);
+        let completed = delivered.iter().all(|succeeded| *succeeded);
+        self.settle_execution_gas(&intent_id, completed);
+
+        // Keep the fees of every transfer that went through
+        let fees = std::iter::once(fee).chain(extra_fees);
+        let assets: Vec<(String, U128)> = intent
+            .assets()
+            .map(|(token, amount)| (token.clone(), amount))
+            .collect();
+        for (((token, amount), fee), succeeded) in assets.iter().zip(fees).zip(&delivered) {
+            if *succeeded {
+                self.count_volume(&intent, token, amount.0);
+            }
+            if *succeeded && fee.0 > 0 {
+                let protocol_fee = self.split_referral_fee(&intent, token, fee.0);
+                self.accrue_fee(token, protocol_fee);
+                IntentEvent::FeeCollected {
+                    intent_id: intent_id.clone(),
+                    token: token.clone(),
+                    amount: fee,
+                }
+                .emit();
+            }
+        }
+
+        if completed {
+            self.delivery_attempts.remove(&intent_id);
+            self.transition(&mut intent, IntentStatus::Completed);
+            IntentEvent::IntentCompleted { intent_id }.emit();
+            true
+        } else if delivered.contains(&true) {
+            // Delivered assets cannot be recalled, so nothing is refunded
+            self.delivery_attempts.remove(&intent_id);
+            self.mark_failed(&mut intent, "basket partially delivered");
+            false
+        } else {
+            self.retry_or_fail(&mut intent);
+            false
+        }
+    }
+
+    /// Move an intent to a new status, panicking on illegal transitions
+    fn transition(&mut self, intent: &mut CrossChainIntent, next: IntentStatus) {
+        if !intent.status.can_transition_to(&next) {
+            ContractError::IllegalTransition.panic_with(&format!(
+                "Illegal status transition from {:?} to {:?}",
+                intent.status, next
+            ));
+        }
+        self.record_step(&intent.id, IntentStep::Transitioned(next.clone()));
+        self.count_status(Some(&intent.status), &next);
+        self.index_status(&intent.id, Some(&intent.status), &next);
+        intent.status = next;
+        self.save_intent(intent);
+    }
+
+    /// Read an intent, upgrading it from the layout it was stored with
+    fn load_intent(&self, intent_id: &String) -> Option<CrossChainIntent> {
+        self.intents.get(intent_id).cloned().map(CrossChainIntent::from)
+    }
+
+    /// Store an intent in the current layout
+    fn save_intent(&mut self, intent: &CrossChainIntent) {
+        self.intents.insert(intent.id.clone(), intent.clone().into());
+    }
+
+    /// Mark an intent as failed and queue it for refund
+    fn fail_intent(&mut self, intent: &mut CrossChainIntent, reason: &str) {
+        self.mark_failed(intent, reason);
+        self.refund_queue.insert(intent.id.clone());
+    }
+
+    /// Mark an intent as failed and free its approval data
+    fn mark_failed(&mut self, intent: &mut CrossChainIntent, reason: &str) {
+        self.transition(intent, IntentStatus::Failed(reason.to_string()));
+        self.count_failure(reason);
+        self.approvals.remove(&intent.id);
+        self.aggregate_attestations.remove(&intent.id);
+        self.clear_claim(&intent.id);
+        self.clear_quotes(&intent.id);
+
+        IntentEvent::IntentFailed {
+            intent_id: intent.id.clone(),
+            reason: reason.to_string(),
+        }
+        .emit();
+    }
+
+    /// Get the number of validators that approved an intent on-chain
+    pub fn get_approval_count(&self, intent_id: String) -> u32 {
+        self.approvals
+            .get(&intent_id)
+            .map(|approvals| approvals.len() as u32)
+            .unwrap_or(0)
+    }
+
+    /// Count distinct validators of the intent's epoch attesting for its
+    /// chain that either signed the intent payload or approved it on-chain
+    fn count_approvals(&self, intent: &CrossChainIntent) -> u32 {
+        let message = intent.signing_hash();
+        let chain = self.chain(intent.chain_id);
+        let mut signers: Vec<AccountId> = Vec::new();
+
+        for entry in &intent.signatures {
+            if signers.contains(&entry.validator)
+                || !self.attests_for_chain(&chain, &entry.validator)
+            {
+                continue;
+            }
+            let public_key = match self.epoch_key(intent.epoch, &entry.validator) {
+                Some(key) => key,
+                None => continue,
+            };
+            if verify_ed25519(&public_key, &message, &entry.signature.0) {
+                signers.push(entry.validator.clone());
+            }
+        }
+
+        // Validators removed after approving no longer count
+        for validator in self.approvals.get(&intent.id).into_iter().flatten() {
+            if !signers.contains(validator)
+                && self.attests_for_chain(&chain, validator)
+                && self.epoch_key(intent.epoch, validator).is_some()
+            {
+                signers.push(validator.clone());
+            }
+        }
+
+        signers.len() as u32
+    }
+}
+
+/// Verify an ed25519 signature against a NEAR-format public key
+fn verify_ed25519(public_key: &PublicKey, message: &[u8], signature: &[u8]) -> bool {
+    // Skip the leading curve type byte
+    let key = match ed25519_dalek::PublicKey::from_bytes(&public_key.as_bytes()[1..]) {
+        Ok(key) => key,
+        Err(_) => return false,
+    };
+    let signature = match ed25519_dalek::Signature::from_bytes(signature) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    key.verify_strict(message, &signature).is_ok()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::rlp::encode;
+    use near_contract_standards::storage_management::StorageManagement;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use ed25519_dalek::Signer;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use near_sdk::serde_json::json;
+    use near_sdk::{serde_json, testing_env, Gas, PromiseResult, ONE_NEAR};
+
+    pub(crate) fn executor() -> AccountId {
+        "executor.near".parse().unwrap()
+    }
+
+    pub(crate) fn token() -> AccountId {
+        "token.near".parse().unwrap()
+    }
+
+    pub(crate) fn agent() -> AccountId {
+        accounts(0)
+    }
+
+    pub(crate) fn validator() -> AccountId {
+        accounts(1)
+    }
+
+    pub(crate) fn set_caller(account_id: &AccountId, deposit: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(account_id.clone())
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    pub(crate) fn setup() -> CrossChainExecutor {
+        set_caller(&executor(), 0);
+        let mut contract = CrossChainExecutor::new(1, agent(), None);
+        contract.register_token(
+            mapping("0x833589FCD6EDB6E08F4C7C32D4F71B54BDA02913", 6, 6),
+            None,
+        );
+        contract.set_base_bridge(BASE_BRIDGE.to_string(), None);
+        set_caller(&validator(), VALIDATOR_BOND);
+        contract.register_validator(
+            "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+                .parse()
+                .unwrap(),
         );
 
-        assert!(
-            self.executed_intents.contains(&intent_id),
-            "Intent not found"
+        set_caller(&agent(), ONE_NEAR);
+        contract.storage_deposit(None, None);
+        checkpoint(&mut contract, BASE_CHAIN_ID, 1);
+        contract
+    }
+
+    /// Record a checkpoint of `block_number` of a chain, standing in for
+    /// one the validators signed
+    pub(crate) fn checkpoint(contract: &mut CrossChainExecutor, chain_id: u64, block_number: u64) {
+        contract.checkpoints.insert(
+            chain_id,
+            checkpoints::Checkpoint {
+                block_number,
+                state_root: format!("0x{}", "00".repeat(32)),
+                epoch: contract.current_epoch,
+                submitted_at: U64(env::block_timestamp()),
+            },
         );
+    }
 
-        // Transfer tokens to recipient
-        Promise::new(self.token_contract.clone()).function_call(
-            "ft_transfer".to_string(),
-            format!(
-                r#"{{"receiver_id": "{}", "amount": "{}"}}"#,
-                env::predecessor_account_id(),
-                "1" // Amount would come from intent data in production
-            )
-            .into_bytes(),
-            1, // 1 yoctoNEAR deposit for storage
-            near_sdk::Gas(5_000_000_000_000), // 5 TGas
-        )
+    /// Enabled mapping of a source chain token to `token()`
+    pub(crate) fn mapping(base_address: &str, source: u8, near: u8) -> tokens::TokenMapping {
+        tokens::TokenMapping {
+            base_address: base_address.to_string(),
+            near_contract: token(),
+            decimals: tokens::TokenDecimals { source, near },
+            symbol: "USDC".to_string(),
+            enabled: true,
+            mintable: false,
+            min_amount: None,
+            max_amount: None,
+            stablecoin_route: None,
+            multi_token: false,
+            transfer_gas: None,
+        }
     }
 
-    /// Get intent status
-    pub fn get_intent_status(&self, intent_id: String) -> Option<IntentStatus> {
-        if self.executed_intents.contains(&intent_id) {
-            Some(IntentStatus::Completed)
+    pub(crate) const VALIDATOR_BOND: Balance = 100 * ONE_NEAR;
+    static NEXT_NONCE: AtomicU64 = AtomicU64::new(1);
+    pub(crate) const BASE_BRIDGE: &str = "0x1111111111111111111111111111111111111111";
+    pub(crate) const BASE_TOKEN: &str = "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+    pub(crate) const BASE_WNEAR: &str = "0x85f17cf997934a597031b2e18a9ab6ebd4b9f6a4";
+
+    /// Register Base's wrapped NEAR as the token natively locked NEAR is
+    /// released as
+    pub(crate) fn register_wnear(contract: &mut CrossChainExecutor) {
+        let mut wnear = mapping(BASE_WNEAR, 24, 24);
+        wnear.near_contract = tokens::NATIVE_TOKEN.parse().unwrap();
+        wnear.symbol = "wNEAR".to_string();
+        set_caller(&executor(), 0);
+        contract.register_token(wnear, None);
+    }
+
+    pub(crate) fn intent(amount: u128) -> CrossChainIntent {
+        let nonce = NEXT_NONCE.fetch_add(1, Ordering::Relaxed);
+        let mut intent = CrossChainIntent {
+            id: String::new(),
+            sender: "0x71c7656ec7ab88b098defb751b7401b5f6d8976f".to_string(),
+            receiver: accounts(2),
+            token: BASE_TOKEN.to_string(),
+            amount: U128(amount),
+            proof: BridgeProof {
+                block_number: 1,
+                timestamp: 0,
+                transaction_hash: format!("0xabc{:061x}", nonce),
+                receipt: Base64VecU8(vec![]),
+                receipt_index: nonce,
+                proof_nodes: vec![],
+                log_index: 0,
+            },
+            status: IntentStatus::Pending,
+            signatures: vec![],
+            expires_at: U64(u64::MAX),
+            epoch: 0,
+            nonce,
+            sender_signature: None,
+            chain_id: BASE_CHAIN_ID,
+            settle_in_defuse: false,
+            extra_assets: Vec::new(),
+            nft_token_id: None,
+            referrer: None,
+            swap: None,
+            to_stablecoin: false,
+            aurora_recipient: None,
+            mt_token_id: None,
+            execute_after: None,
+            depends_on: None,
+            actions: Vec::new(),
+            min_out: None,
+        };
+        rehash(&mut intent);
+        intent
+    }
+
+    /// Intent open to solver quotes of at least `min_out`
+    fn quoted_intent(amount: u128, min_out: u128) -> CrossChainIntent {
+        let mut intent = intent(amount);
+        intent.min_out = Some(U128(min_out));
+        rehash(&mut intent);
+        intent
+    }
+
+    /// Derive the intent's ID from its terms and rebuild its deposit receipt
+    pub(crate) fn rehash(intent: &mut CrossChainIntent) {
+        intent.id = intent.derived_id().unwrap();
+        let locked = intent.nft_token_id.map_or(intent.amount.0, |token_id| token_id.0);
+        intent.proof.receipt = Base64VecU8(deposit_receipt(intent, locked));
+    }
+
+    fn address_topic(address: &str) -> Vec<u8> {
+        let mut topic = vec![0u8; 12];
+        topic.extend(hex::decode(&address[2..]).unwrap());
+        topic
+    }
+
+    fn abi_word(value: u128) -> Vec<u8> {
+        let mut word = vec![0u8; 16];
+        word.extend(value.to_be_bytes());
+        word
+    }
+
+    /// Base bridge's TokensLocked log of `locked_amount` of `token` for
+    /// `intent`, or NftLocked log of the NFT with that ID for an NFT intent
+    pub(crate) fn deposit_log(
+        intent: &CrossChainIntent,
+        token: &str,
+        locked_amount: u128,
+    ) -> Vec<u8> {
+        let receiver = intent.receiver.as_str().as_bytes();
+        let mut data = [abi_word(96), abi_word(locked_amount), abi_word(1)].concat();
+        data.extend(abi_word(receiver.len() as u128));
+        data.extend(receiver);
+        data.resize(data.len() + (32 - receiver.len() % 32) % 32, 0);
+
+        let event = match intent.nft_token_id {
+            Some(_) => "NftLocked(string,address,address,string,uint256,uint256)",
+            None => "TokensLocked(string,address,address,string,uint256,uint256)",
+        };
+        // Native NEAR is locked as the bridge's zero token address
+        let token_topic = if is_native_token(token) {
+            vec![0u8; 32]
         } else {
-            None
+            address_topic(token)
+        };
+        let topics = [
+            env::keccak256(event.as_bytes()),
+            env::keccak256(intent.typed_id().unwrap().as_bytes()),
+            token_topic,
+            address_topic(&intent.sender),
+        ];
+        encode::list(&[
+            encode::bytes(&hex::decode(&BASE_BRIDGE[2..]).unwrap()),
+            encode::list(&topics.iter().map(|topic| encode::bytes(topic)).collect::<Vec<_>>()),
+            encode::bytes(&data),
+        ])
+    }
+
+    /// RLP receipt holding the TokensLocked log for `intent`, followed by
+    /// those of its extra basket assets
+    pub(crate) fn deposit_receipt(intent: &CrossChainIntent, locked_amount: u128) -> Vec<u8> {
+        let mut logs = vec![deposit_log(intent, &intent.token, locked_amount)];
+        for asset in &intent.extra_assets {
+            logs.push(deposit_log(intent, &asset.token, asset.amount.0));
         }
+        receipt_holding(&logs)
     }
 
-    /// Assert caller is contract owner
-    fn assert_owner(&self) {
+    /// RLP receipt of a successful transaction emitting `logs`
+    pub(crate) fn receipt_holding(logs: &[Vec<u8>]) -> Vec<u8> {
+        encode::list(&[
+            encode::bytes(&[1]),
+            encode::bytes(&[0x52, 0x08]),
+            encode::bytes(&[0u8; 256]),
+            encode::list(logs),
+        ])
+    }
+
+    /// Trust a header of the intent's chain whose receipts trie holds only this intent's receipt
+    pub(crate) fn submit_proof(contract: &mut CrossChainExecutor, intent: &mut CrossChainIntent) {
+        // Single leaf keyed by rlp(receipt_index), hex-prefix encoded as an
+        // even-length leaf path
+        let path = [&[0x20][..], &crate::rlp::encode_u64(intent.proof.receipt_index)].concat();
+        let leaf = encode::list(&[encode::bytes(&path), encode::bytes(&intent.proof.receipt.0)]);
+        let receipts_root = env::keccak256(&leaf);
+        intent.proof.proof_nodes = vec![Base64VecU8(leaf)];
+
+        let mut fields = vec![encode::bytes(&[]); 15];
+        fields[0] = encode::bytes(&[0u8; 32]);
+        fields[5] = encode::bytes(&receipts_root);
+        fields[8] = encode::bytes(&intent.proof.block_number.to_be_bytes()[7..]);
+
+        set_caller(&executor(), 0);
+        contract.set_trusted_header(
+            Base64VecU8(encode::list(&fields)),
+            Some(0),
+            Some(intent.chain_id),
+        );
+        let checkpointed = contract.checkpoints.get(&intent.chain_id).map(|c| c.block_number);
+        if checkpointed < Some(intent.proof.block_number) {
+            checkpoint(contract, intent.chain_id, intent.proof.block_number);
+        }
+    }
+
+    /// Assert the JSON schema of `T` describes exactly the fields `value`
+    /// serializes
+    pub(crate) fn assert_schema_describes<T: schemars::JsonSchema + Serialize>(value: &T) {
+        let schema = serde_json::to_value(schemars::schema_for!(T)).unwrap();
+        let keys = |value: &serde_json::Value| {
+            value.as_object().unwrap().keys().cloned().collect::<Vec<_>>()
+        };
+        let mut properties = keys(&schema["properties"]);
+        let mut fields = keys(&serde_json::to_value(value).unwrap());
+        properties.sort();
+        fields.sort();
+        assert_eq!(fields, properties);
+    }
+
+    /// Create an intent with a valid proof and a validator approval
+    pub(crate) fn create_approved(
+        contract: &mut CrossChainExecutor,
+        mut intent: CrossChainIntent,
+    ) -> String {
+        submit_proof(contract, &mut intent);
+        let intent_id = intent.id.clone();
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+        set_caller(&validator(), 0);
+        contract.approve_intent(intent_id.clone());
+        intent_id
+    }
+
+    /// Deterministic ed25519 keypair with its NEAR-format public key
+    pub(crate) fn keypair(seed: u8) -> (ed25519_dalek::Keypair, PublicKey) {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[seed; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        let public_key = PublicKey::try_from([&[0u8][..], public.as_bytes()].concat()).unwrap();
+        (ed25519_dalek::Keypair { secret, public }, public_key)
+    }
+
+    /// Create, approve and execute an intent, returning the ft_transfer args
+    fn execute(contract: &mut CrossChainExecutor, intent: CrossChainIntent) -> serde_json::Value {
+        let intent_id = create_approved(contract, intent);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .expect("No ft_transfer receipt");
+        match &receipt.actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                args,
+                ..
+            } => {
+                assert_eq!(function_name, "ft_transfer");
+                serde_json::from_slice(args).unwrap()
+            }
+            action => panic!("Unexpected action {:?}", action),
+        }
+    }
+
+    /// NEAR transferred to `account_id` by receipts created since the last context change
+    pub(crate) fn transfers_to(account_id: &AccountId) -> Vec<Balance> {
+        get_created_receipts()
+            .into_iter()
+            .filter(|receipt| &receipt.receiver_id == account_id)
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                VmAction::Transfer { deposit } => Some(deposit),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Create, approve and execute an intent as the agent, resolving its
+    /// transfer as `succeeded`
+    fn execute_and_resolve(contract: &mut CrossChainExecutor, succeeded: bool) -> String {
+        let intent_id = create_approved(contract, intent(1_000));
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        let result = if succeeded {
+            Ok(())
+        } else {
+            Err(PromiseError::Failed)
+        };
+        set_caller(&executor(), 0);
+        contract.on_transfer_complete(intent_id.clone(), U128(0), None, result);
+        intent_id
+    }
+
+    #[test]
+    fn execute_transfers_to_intent_receiver() {
+        let mut contract = setup();
+        let args = execute(&mut contract, intent(1_000));
+
+        assert_eq!(args["receiver_id"], accounts(2).to_string());
+        assert_eq!(args["amount"], "1000");
+    }
+
+    #[test]
+    fn execute_preserves_max_u128_amount() {
+        let mut contract = setup();
+        let args = execute(&mut contract, intent(u128::MAX));
+
+        assert_eq!(args["amount"], u128::MAX.to_string());
+    }
+
+    #[test]
+    fn execute_preserves_amount_above_u64() {
+        let mut contract = setup();
+        let amount = u64::MAX as u128 * 1_000 + 7;
+        let args = execute(&mut contract, intent(amount));
+
+        assert_eq!(args["amount"], amount.to_string());
+    }
+
+    #[test]
+    fn execute_fails_intents_proving_a_different_amount() {
+        let mut contract = setup();
+        let mut intent = intent(1_000_000);
+        intent.proof.receipt = Base64VecU8(deposit_receipt(&intent, 1_000));
+        let intent_id = create_approved(&mut contract, intent);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+
+        assert_eq!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Failed("Log amount does not match".to_string()))
+        );
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn execute_waits_for_the_required_approvals() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.update_config(config::ConfigUpdate {
+            required_signatures: Some(2),
+            ..Default::default()
+        });
+        set_caller(&accounts(4), VALIDATOR_BOND);
+        contract.register_validator(keypair(9).1);
+        let intent_id = create_approved(&mut contract, intent(1_000));
+
+        set_caller(&agent(), 0);
+        assert_eq!(
+            contract.try_execute_intent(intent_id.clone()).err(),
+            Some(ContractError::NotEnoughApprovals)
+        );
+        set_caller(&accounts(4), 0);
+        contract.approve_intent(intent_id.clone());
+        assert_eq!(contract.get_approval_count(intent_id.clone()), 2);
+        set_caller(&agent(), 0);
+        assert!(contract.try_execute_intent(intent_id).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only pending intents can be approved")]
+    fn cancelled_intents_cannot_be_approved() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&agent(), 0);
+        contract.cancel_intent(intent_id.clone());
+
+        set_caller(&validator(), 0);
+        contract.approve_intent(intent_id);
+    }
+
+    /// Call as `account_id` at block timestamp `timestamp`
+    fn set_caller_at(account_id: &AccountId, timestamp: u64) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(account_id.clone())
+            .block_timestamp(timestamp)
+            .build());
+    }
+
+    /// Create an approved intent expiring at block timestamp 100
+    fn create_expiring(contract: &mut CrossChainExecutor) -> String {
+        let mut expiring = intent(1_000);
+        expiring.expires_at = U64(100);
+        rehash(&mut expiring);
+        create_approved(contract, expiring)
+    }
+
+    #[test]
+    fn expired_intents_fail_and_free_their_approvals() {
+        let mut contract = setup();
+        let intent_id = create_expiring(&mut contract);
+
+        set_caller_at(&accounts(3), 100);
+        contract.expire_intent(intent_id.clone());
         assert_eq!(
-            env::predecessor_account_id(),
-            env::current_account_id(),
-            "Only owner can call this method"
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Failed("expired".to_string()))
+        );
+        assert_eq!(contract.get_approval_count(intent_id.clone()), 0);
+        assert_eq!(contract.get_refundable_intents(), vec![intent_id]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent expired")]
+    fn execute_rejects_expired_intents() {
+        let mut contract = setup();
+        let intent_id = create_expiring(&mut contract);
+        set_caller_at(&agent(), 100);
+        contract.execute_intent(intent_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent has not expired yet")]
+    fn intents_cannot_be_expired_early() {
+        let mut contract = setup();
+        let intent_id = create_expiring(&mut contract);
+        set_caller_at(&accounts(3), 99);
+        contract.expire_intent(intent_id);
+    }
+
+    #[test]
+    fn cancelled_intents_free_their_approvals_for_the_relayer() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&agent(), 0);
+        contract.cancel_intent(intent_id.clone());
+
+        assert!(matches!(
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Cancelled)
+        ));
+        assert_eq!(contract.get_approval_count(intent_id.clone()), 0);
+        let cancelled = format!(
+            r#""event":"intent_cancelled","data":{{"intent_id":"{}"}}"#,
+            intent_id
         );
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains(&cancelled)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent is not pending")]
+    fn cancelled_intents_cannot_execute() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&agent(), 0);
+        contract.cancel_intent(intent_id.clone());
+        contract.execute_intent(intent_id);
     }
 
-    /// View method to check if an account is a validator
-    pub fn is_validator(&self, account_id: AccountId) -> bool {
-        self.bridge_validators.contains_key(&account_id)
+    #[test]
+    fn failed_transfers_are_refunded_from_the_queue() {
+        let mut contract = setup();
+        let intent_id = execute_and_resolve(&mut contract, false);
+        assert_eq!(contract.get_refundable_intents(), vec![intent_id.clone()]);
+
+        set_caller(&agent(), 0);
+        contract.refund_intent(intent_id.clone());
+        assert!(matches!(
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Refunded)
+        ));
+        assert!(contract.get_refundable_intents().is_empty());
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains(r#""event":"intent_refunded""#)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent is not refundable")]
+    fn pending_intents_cannot_be_refunded() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&agent(), 0);
+        contract.refund_intent(intent_id);
+    }
+
+    #[test]
+    fn statuses_move_only_along_the_lifecycle() {
+        use IntentStatus::*;
+        let failed = Failed("expired".to_string());
+        assert!(Pending.can_transition_to(&Executing));
+        assert!(Executing.can_transition_to(&Completed));
+        assert!(Executing.can_transition_to(&failed));
+        assert!(failed.can_transition_to(&Refunded));
+        assert!(!Pending.can_transition_to(&Completed));
+        assert!(!Executing.can_transition_to(&Pending));
+        assert!(!Completed.can_transition_to(&failed));
+        assert!(!Refunded.can_transition_to(&Pending));
+        assert!([Completed, Cancelled, Refunded].iter().all(IntentStatus::is_terminal));
+        assert!(!failed.is_terminal());
+    }
+
+    #[test]
+    #[should_panic(expected = "Illegal status transition from Completed to Cancelled")]
+    fn completed_intents_cannot_be_cancelled() {
+        let mut contract = setup();
+        let intent_id = execute_and_resolve(&mut contract, true);
+        set_caller(&agent(), 0);
+        contract.cancel_intent(intent_id);
+    }
+
+    #[test]
+    fn executions_settle_in_a_transfer_callback() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+
+        let callback = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == executor())
+            .expect("No callback receipt");
+        assert!(matches!(
+            &callback.actions[0],
+            VmAction::FunctionCall { function_name, .. } if function_name == "on_transfer_complete"
+        ));
+        // Left executing until the callback settles it
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Executing)
+        ));
+    }
+
+    #[test]
+    fn transfer_callbacks_complete_delivered_intents() {
+        let mut contract = setup();
+        let intent_id = execute_and_resolve(&mut contract, true);
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Completed)
+        ));
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains(r#""event":"intent_completed""#)));
+    }
+
+    #[test]
+    fn transfer_callbacks_fail_undelivered_intents() {
+        let mut contract = setup();
+        let intent_id = execute_and_resolve(&mut contract, false);
+        assert_eq!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Failed("transfer failed".to_string()))
+        );
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains(r#""event":"intent_failed""#)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Method requires role Agent")]
+    fn revoked_agent_cannot_create_intents() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.revoke_role(Role::Agent, agent());
+
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent(1_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Account does not have this role")]
+    fn revoking_a_role_requires_holding_it() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.revoke_role(Role::Guardian, accounts(3));
+    }
+
+    #[test]
+    fn granted_guardian_can_pause() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.grant_role(Role::Guardian, accounts(3));
+        assert_eq!(contract.get_role_members(Role::Guardian), vec![accounts(3)]);
+
+        set_caller(&accounts(3), 0);
+        contract.pause();
+        assert!(contract.is_paused());
+    }
+
+    #[test]
+    #[should_panic(expected = "Agent daily quota exceeded")]
+    fn execute_rejects_volume_above_agent_quota() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_agent_quota(agent(), Some(U128(999)));
+        execute(&mut contract, intent(1_000));
+    }
+
+    #[test]
+    fn execute_withholds_protocol_fee() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_fee(30);
+        contract.set_token_fee(BASE_TOKEN.to_string(), Some(50));
+        let args = execute(&mut contract, intent(10_000));
+
+        assert_eq!(args["amount"], "9950");
+    }
+
+    #[test]
+    fn completed_execution_reimburses_executor_gas() {
+        let mut contract = setup();
+        set_caller(&accounts(3), 10 * ONE_NEAR);
+        contract.fund_gas();
+
+        let intent_id = execute_and_resolve(&mut contract, true);
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Completed)
+        ));
+        let credited = contract.get_gas_reimbursement(agent()).0;
+        assert!(credited > 0);
+        assert_eq!(contract.get_gas_fund().0, 10 * ONE_NEAR - credited);
+
+        set_caller(&agent(), 0);
+        contract.withdraw_gas_reimbursement();
+        assert_eq!(transfers_to(&agent()), [credited]);
+        assert_eq!(contract.get_gas_reimbursement(agent()), U128(0));
+
+        // Failed executions are not reimbursed
+        execute_and_resolve(&mut contract, false);
+        assert_eq!(contract.get_gas_reimbursement(agent()), U128(0));
+    }
+
+    #[test]
+    fn stats_track_statuses_volume_and_failures() {
+        let mut contract = setup();
+        execute_and_resolve(&mut contract, true);
+        execute_and_resolve(&mut contract, false);
+        create_approved(&mut contract, intent(500));
+
+        let stats = contract.get_stats();
+        assert_eq!(stats.total_intents, 3);
+        assert_eq!(
+            stats.intents_by_status,
+            [("Completed", 1), ("Failed", 1), ("Pending", 1)]
+                .map(|(status, count)| (status.to_string(), count))
+                .into()
+        );
+        assert_eq!(stats.volume_by_token[BASE_TOKEN], U128(1_000));
+        assert_eq!(stats.unique_receivers, 1);
+        assert_eq!(stats.failures_by_reason["transfer failed"], 1);
+    }
+
+    #[test]
+    fn referrer_claims_its_share_of_the_fee() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_fee(100);
+        contract.set_referral_share(2_000);
+
+        let mut referred = intent(10_000);
+        referred.referrer = Some(accounts(4));
+        rehash(&mut referred);
+        let intent_id = create_approved(&mut contract, referred);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        set_caller(&executor(), 0);
+        contract.on_transfer_complete(intent_id, U128(100), None, Ok(()));
+        assert_eq!(contract.get_accrued_fees(BASE_TOKEN.to_string()), U128(80));
+
+        set_caller(&accounts(4), 0);
+        contract.claim_referral_fees(BASE_TOKEN.to_string(), None);
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .expect("No ft_transfer receipt");
+        match &receipt.actions[0] {
+            VmAction::FunctionCall { args, .. } => {
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["receiver_id"], accounts(4).to_string());
+                assert_eq!(args["amount"], "20");
+            }
+            action => panic!("Unexpected action {:?}", action),
+        }
+        assert_eq!(
+            contract.get_referral_fees(accounts(4), BASE_TOKEN.to_string()),
+            U128(0)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Rate limit exceeded: too many executions")]
+    fn execute_rejects_executions_above_rate_limit() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_rate_limit(
+            BASE_TOKEN.to_string(),
+            Some(RateLimit {
+                window_blocks: 1,
+                max_executions: 1,
+                max_amount: None,
+            }),
+        );
+        execute(&mut contract, intent(1_000));
+        execute(&mut contract, intent(1_000));
+    }
+
+    #[test]
+    fn execute_leaves_intent_pending_at_volume_cap() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_volume_cap(BASE_TOKEN.to_string(), Some(U128(1_500)));
+        execute(&mut contract, intent(1_000));
+
+        let over_cap = create_approved(&mut contract, intent(1_000));
+        set_caller(&agent(), 0);
+        contract.execute_intent(over_cap.clone());
+
+        assert!(matches!(
+            contract.get_intent_status(over_cap),
+            Some(IntentStatus::Pending)
+        ));
+        assert_eq!(contract.get_daily_volume(BASE_TOKEN.to_string()).0, 1_000);
+    }
+
+    #[test]
+    fn anomalous_hourly_volume_pauses_executions() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_anomaly_multiple(Some(10));
+        // Establish an hourly average of 100 over the trailing window
+        execute(&mut contract, intent(2_300));
+
+        let spike = create_approved(&mut contract, intent(1_001));
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(agent())
+            .block_timestamp(3_600 * 1_000_000_000)
+            .build());
+        contract.execute_intent(spike.clone());
+
+        assert!(contract.is_paused());
+        assert!(matches!(
+            contract.get_intent_status(spike),
+            Some(IntentStatus::Pending)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Receiver not allowed")]
+    fn allowlist_mode_rejects_unregistered_receivers() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_compliance_mode(ComplianceMode::Allowlist);
+        contract.register_receivers(vec![accounts(3)]);
+        execute(&mut contract, intent(100));
+    }
+
+    #[test]
+    fn registry_verdicts_allow_receivers_until_they_lapse() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_compliance_mode(ComplianceMode::Registry {
+            contract: "registry.near".parse().unwrap(),
+        });
+        assert!(!contract.is_receiver_allowed(accounts(2)));
+
+        assert!(contract.on_receiver_verified(accounts(2), Ok(true)));
+        assert!(contract.is_receiver_allowed(accounts(2)));
+        execute(&mut contract, intent(100));
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .block_timestamp(24 * 3_600 * 1_000_000_000)
+            .build());
+        assert!(!contract.is_receiver_allowed(accounts(2)));
+    }
+
+    #[test]
+    fn blocked_parties_are_screened_at_creation_and_execution() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.grant_role(Role::Guardian, accounts(3));
+        set_caller(&accounts(3), 0);
+        contract.block_senders(vec!["0x71C7656EC7AB88B098DEFB751B7401B5F6D8976F".to_string()]);
+
+        let mut blocked = intent(100);
+        submit_proof(&mut contract, &mut blocked);
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(blocked.clone());
+        assert!(contract.get_intent_status(blocked.id).is_none());
+
+        set_caller(&accounts(3), 0);
+        contract.unblock_senders(vec![intent(0).sender]);
+        let intent_id = create_approved(&mut contract, intent(100));
+        set_caller(&accounts(3), 0);
+        contract.block_receivers(vec![accounts(2)]);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Pending)
+        ));
+    }
+
+    #[test]
+    fn intents_must_move_amounts_within_token_limits() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_token_limits(
+            BASE_TOKEN.to_string(),
+            Some(U128(10)),
+            Some(U128(1_000)),
+            None,
+        );
+
+        assert_eq!(
+            contract.validate_new_intent(&intent(9)),
+            Err(ContractError::AmountBelowMinimum)
+        );
+        assert_eq!(
+            contract.validate_new_intent(&intent(1_001)),
+            Err(ContractError::AmountAboveMaximum)
+        );
+        execute(&mut contract, intent(1_000));
+    }
+
+    #[test]
+    fn deliveries_attach_the_gas_registered_for_their_token() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_token_gas(
+            "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913".to_string(),
+            Some(Gas(40_000_000_000_000)),
+            None,
+        );
+        execute(&mut contract, intent(1_000));
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .unwrap();
+        match &receipt.actions[0] {
+            VmAction::FunctionCall { gas, .. } => assert_eq!(*gas, Gas(40_000_000_000_000)),
+            action => panic!("Unexpected action {:?}", action),
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough gas attached for the transfer and callback")]
+    fn execute_rejects_gas_short_of_the_transfer_and_callback() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_token_gas(
+            "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913".to_string(),
+            Some(Gas(60_000_000_000_000)),
+            None,
+        );
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(agent())
+            .prepaid_gas(Gas(50_000_000_000_000))
+            .build());
+        contract.execute_intent(intent_id);
+    }
+
+    #[test]
+    fn failed_deliveries_are_retried_until_their_attempts_run_out() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_max_delivery_attempts(2);
+        let intent_id = execute_and_resolve(&mut contract, false);
+        assert!(matches!(
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Executing)
+        ));
+        let queued = contract.get_retry_queue();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].1.attempts, 1);
+
+        // Nothing is retried until the backoff elapsed
+        set_caller(&accounts(3), 0);
+        assert!(contract.retry_failed_intents(10).is_empty());
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(accounts(3))
+            .block_timestamp(queued[0].1.next_attempt_at.0)
+            .build());
+        assert_eq!(contract.retry_failed_intents(10), vec![intent_id.clone()]);
+        assert!(get_created_receipts()
+            .iter()
+            .any(|receipt| receipt.receiver_id == token()));
+        assert!(contract.get_retry_queue().is_empty());
+
+        // The last attempt failing routes the intent to refund
+        set_caller(&executor(), 0);
+        contract.on_transfer_complete(
+            intent_id.clone(),
+            U128(0),
+            Some(vec![]),
+            Err(PromiseError::Failed),
+        );
+        assert!(matches!(
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Failed(_))
+        ));
+        assert_eq!(contract.get_refundable_intents(), [intent_id]);
+        assert!(contract.get_retry_queue().is_empty());
+    }
+
+    #[test]
+    fn retries_wait_while_the_receiver_is_blocked() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_max_delivery_attempts(2);
+        let intent_id = execute_and_resolve(&mut contract, false);
+        let next_attempt_at = contract.get_retry_queue()[0].1.next_attempt_at;
+        set_caller(&executor(), 0);
+        contract.block_receivers(vec![accounts(2)]);
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(accounts(3))
+            .block_timestamp(next_attempt_at.0)
+            .build());
+        assert!(contract.retry_failed_intents(10).is_empty());
+        assert!(get_created_receipts().is_empty());
+        assert_eq!(contract.get_retry_queue()[0].0, intent_id);
+    }
+
+    /// Queue an executing intent for a retry due at once
+    fn queue_retry(contract: &mut CrossChainExecutor, intent_id: &str) {
+        contract.retry_queue.insert(
+            intent_id.to_string(),
+            retry::DeliveryRetry {
+                attempts: 1,
+                next_attempt_at: U64(0),
+            },
+        );
+    }
+
+    #[test]
+    fn retried_actions_are_settled_by_their_own_callbacks() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, action_intent());
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        queue_retry(&mut contract, &intent_id);
+
+        set_caller(&accounts(3), 0);
+        assert_eq!(contract.retry_failed_intents(10), vec![intent_id]);
+        let callbacks: Vec<String> = get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id == executor())
+            .flat_map(|receipt| receipt.actions)
+            .filter_map(|action| match action {
+                VmAction::FunctionCall { function_name, .. } => Some(function_name),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(callbacks, ["on_action_complete"]);
+    }
+
+    #[test]
+    fn retries_fail_action_swaps_past_their_deadline() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_ref_contract(Some("v2.ref-finance.near".parse().unwrap()));
+        let mut intent = action_intent();
+        intent.actions[1] = IntentAction::Swap {
+            amount: U128(600),
+            swap: swap_intent(1_000).swap.unwrap(),
+        };
+        rehash(&mut intent);
+        let intent_id = create_approved(&mut contract, intent);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        queue_retry(&mut contract, &intent_id);
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(accounts(3))
+            .block_timestamp(2_000)
+            .build());
+        assert!(contract.retry_failed_intents(10).is_empty());
+        assert_eq!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Failed("swap deadline passed".to_string()))
+        );
+    }
+
+    #[test]
+    fn croncat_ticks_execute_attested_intents() {
+        let mut contract = setup();
+        let manager: AccountId = "manager.croncat.near".parse().unwrap();
+        set_caller(&executor(), 0);
+        contract.set_croncat_manager(Some(manager.clone()));
+        let intent_id = create_approved(&mut contract, intent(1_000));
+
+        set_caller(&manager, 0);
+        assert_eq!(contract.tick(None), vec![intent_id.clone()]);
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == executor())
+            .expect("No scheduled execution");
+        match &receipt.actions[0] {
+            VmAction::FunctionCall { function_name, .. } => {
+                assert_eq!(function_name, "execute_scheduled")
+            }
+            action => panic!("Unexpected action {:?}", action),
+        }
+
+        set_caller(&executor(), 0);
+        contract.execute_scheduled(intent_id.clone());
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Executing)
+        ));
+        assert!(get_created_receipts()
+            .iter()
+            .any(|receipt| receipt.receiver_id == token()));
+
+        // Intents no longer pending drop out of the schedule
+        set_caller(&manager, 0);
+        assert!(contract.tick(None).is_empty());
+        assert!(contract.scheduled_intents.is_empty());
+    }
+
+    #[test]
+    fn croncat_ticks_page_through_the_schedule() {
+        let mut contract = setup();
+        let manager: AccountId = "manager.croncat.near".parse().unwrap();
+        set_caller(&executor(), 0);
+        contract.set_croncat_manager(Some(manager.clone()));
+        let ids: Vec<String> = (0..3)
+            .map(|_| create_approved(&mut contract, intent(1_000)))
+            .collect();
+
+        set_caller(&manager, 0);
+        assert_eq!(contract.tick(Some(2)), ids[..2]);
+        assert_eq!(contract.tick(Some(2)), ids[2..]);
+        // Past the last scheduled intent the next tick starts over
+        assert_eq!(contract.tick(Some(2)), ids[..2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the scheduler may tick")]
+    fn tick_rejects_callers_other_than_the_scheduler() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_croncat_manager(Some("manager.croncat.near".parse().unwrap()));
+        set_caller(&agent(), 0);
+        contract.tick(None);
+    }
+
+    #[test]
+    fn deferred_intents_execute_once_eligible() {
+        let mut contract = setup();
+        let mut deferred = intent(1_000);
+        deferred.execute_after = Some(U64(5_000));
+        rehash(&mut deferred);
+        let intent_id = create_approved(&mut contract, deferred);
+
+        let pending = contract.get_pending_intents(None, None);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].eligible_at.map(|at| at.0), Some(5_000));
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(agent())
+            .block_timestamp(5_000)
+            .build());
+        contract.execute_intent(intent_id.clone());
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Executing)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent is deferred until later")]
+    fn execute_rejects_deferred_intents_before_their_time() {
+        let mut contract = setup();
+        let mut deferred = intent(1_000);
+        deferred.execute_after = Some(U64(5_000));
+        rehash(&mut deferred);
+        let intent_id = create_approved(&mut contract, deferred);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+    }
+
+    /// Template paying the `template_intent`'s receiver every day, twice
+    fn recurring_template(template_intent: &CrossChainIntent) -> recurring::RecurringTemplate {
+        recurring::RecurringTemplate {
+            sender: template_intent.sender.clone(),
+            receiver: template_intent.receiver.clone(),
+            token: template_intent.token.clone(),
+            amount: template_intent.amount,
+            chain_id: template_intent.chain_id,
+            interval: U64(24 * 3_600 * 1_000_000_000),
+            next_due_at: U64(0),
+            remaining: Some(2),
+            keeper: Some(accounts(3)),
+        }
+    }
+
+    #[test]
+    fn keepers_instantiate_recurring_templates_each_period() {
+        let mut contract = setup();
+        let mut period = intent(1_000);
+        submit_proof(&mut contract, &mut period);
+        set_caller(&agent(), 0);
+        let template_id = contract.create_recurring_template(recurring_template(&period));
+
+        set_caller(&accounts(3), ONE_NEAR);
+        let intent_id = contract.instantiate_recurring_intent(
+            template_id,
+            period.nonce,
+            period.expires_at,
+            period.proof.clone(),
+            None,
+        );
+        assert_eq!(intent_id, Some(period.id.clone()));
+        assert!(matches!(
+            contract.get_intent_status(period.id),
+            Some(IntentStatus::Pending)
+        ));
+        let template = contract.get_recurring_template(template_id).unwrap();
+        assert_eq!(template.next_due_at.0, 24 * 3_600 * 1_000_000_000);
+        assert_eq!(template.remaining, Some(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Next period is not due yet")]
+    fn recurring_periods_wait_for_their_interval() {
+        let mut contract = setup();
+        let mut period = intent(1_000);
+        submit_proof(&mut contract, &mut period);
+        set_caller(&agent(), 0);
+        let template_id = contract.create_recurring_template(recurring_template(&period));
+        set_caller(&agent(), ONE_NEAR);
+        contract.instantiate_recurring_intent(
+            template_id,
+            period.nonce,
+            period.expires_at,
+            period.proof.clone(),
+            None,
+        );
+
+        let mut next = intent(1_000);
+        submit_proof(&mut contract, &mut next);
+        set_caller(&agent(), ONE_NEAR);
+        contract.instantiate_recurring_intent(
+            template_id,
+            next.nonce,
+            next.expires_at,
+            next.proof,
+            None,
+        );
+    }
+
+    #[test]
+    fn dependent_intents_execute_after_their_dependency_completes() {
+        let mut contract = setup();
+        let parent_id = execute_and_resolve(&mut contract, true);
+        let mut child = intent(500);
+        child.depends_on = Some(parent_id);
+        rehash(&mut child);
+        let child_id = create_approved(&mut contract, child);
+
+        set_caller(&agent(), 0);
+        contract.execute_intent(child_id.clone());
+        assert!(matches!(
+            contract.get_intent_status(child_id),
+            Some(IntentStatus::Executing)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Dependency not completed")]
+    fn execute_rejects_intents_whose_dependency_is_pending() {
+        let mut contract = setup();
+        let parent_id = create_approved(&mut contract, intent(1_000));
+        let mut child = intent(500);
+        child.depends_on = Some(parent_id);
+        rehash(&mut child);
+        let child_id = create_approved(&mut contract, child);
+
+        set_caller(&agent(), 0);
+        contract.execute_intent(child_id);
+    }
+
+    /// Intent of 1000 sending 400 to `accounts(3)` and depositing 600 into
+    /// `accounts(4)`
+    fn action_intent() -> CrossChainIntent {
+        let mut intent = intent(1_000);
+        intent.actions = vec![
+            IntentAction::Transfer {
+                receiver_id: accounts(3),
+                amount: U128(400),
+            },
+            IntentAction::Call {
+                receiver_id: accounts(4),
+                amount: U128(600),
+                msg: "deposit".to_string(),
+            },
+        ];
+        rehash(&mut intent);
+        intent
+    }
+
+    /// Resolve the transfer an action callback waits on as `result`
+    pub(crate) fn resolve_callback(result: PromiseResult) {
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(executor())
+                .predecessor_account_id(executor())
+                .build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![result],
+        );
+    }
+
+    /// Method and arguments of the token call created since the last
+    /// context change
+    fn token_call() -> (String, serde_json::Value) {
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .expect("No token receipt");
+        match &receipt.actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                args,
+                ..
+            } => (function_name.clone(), serde_json::from_slice(args).unwrap()),
+            action => panic!("Unexpected action {:?}", action),
+        }
+    }
+
+    #[test]
+    fn failed_actions_compensate_the_receiver_for_the_rest() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, action_intent());
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        let (method, args) = token_call();
+        assert_eq!(method, "ft_transfer");
+        assert_eq!(args["receiver_id"], accounts(3).to_string());
+        let sent: U128 = serde_json::from_value(args["amount"].clone()).unwrap();
+
+        resolve_callback(PromiseResult::Successful(vec![]));
+        contract.on_action_complete(intent_id.clone(), 0, U128(0), sent);
+        let (method, args) = token_call();
+        assert_eq!(method, "ft_transfer_call");
+        assert_eq!(args["receiver_id"], accounts(4).to_string());
+        let deposited: U128 = serde_json::from_value(args["amount"].clone()).unwrap();
+
+        // The contract keeps a third and refunds the rest
+        let used = deposited.0 / 3;
+        resolve_callback(PromiseResult::Successful(
+            serde_json::to_vec(&U128(used)).unwrap(),
+        ));
+        contract.on_action_complete(intent_id.clone(), 1, U128(0), deposited);
+        let (method, args) = token_call();
+        assert_eq!(method, "ft_transfer");
+        assert_eq!(args["receiver_id"], accounts(2).to_string());
+        let compensated = U128(deposited.0 - used);
+        assert_eq!(args["amount"], compensated.0.to_string());
+
+        resolve_callback(PromiseResult::Successful(vec![]));
+        assert!(contract.on_actions_compensated(intent_id.clone(), 1, compensated));
+        assert_eq!(
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Failed("actions partially executed".to_string()))
+        );
+        assert!(!contract.get_refundable_intents().contains(&intent_id));
+    }
+
+    #[test]
+    fn failing_the_first_action_queues_the_intent_for_refund() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, action_intent());
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        let (_, args) = token_call();
+        let sent: U128 = serde_json::from_value(args["amount"].clone()).unwrap();
+
+        resolve_callback(PromiseResult::Failed);
+        contract.on_action_complete(intent_id.clone(), 0, U128(0), sent);
+        assert_eq!(
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Failed("action failed".to_string()))
+        );
+        assert!(contract.get_refundable_intents().contains(&intent_id));
+    }
+
+    #[test]
+    fn blocked_action_receivers_leave_the_intent_pending() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, action_intent());
+        set_caller(&executor(), 0);
+        contract.block_receivers(vec![accounts(4)]);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Pending)
+        ));
+        assert!(get_created_receipts().is_empty());
+    }
+
+    #[test]
+    fn failed_compensations_are_queued_for_retry() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, action_intent());
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        resolve_callback(PromiseResult::Failed);
+        contract.on_action_complete(intent_id.clone(), 1, U128(0), U128(600));
+        resolve_callback(PromiseResult::Failed);
+        assert!(!contract.on_actions_compensated(intent_id.clone(), 1, U128(600)));
+        assert!(matches!(
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Executing)
+        ));
+        assert_eq!(
+            contract.get_failed_compensations(),
+            vec![(intent_id.clone(), U128(600))]
+        );
+
+        set_caller(&accounts(5), 0);
+        contract.retry_compensation(intent_id.clone());
+        let (method, args) = token_call();
+        assert_eq!(method, "ft_transfer");
+        assert_eq!(args["receiver_id"], accounts(2).to_string());
+        assert_eq!(args["amount"], "600");
+        assert!(contract.get_failed_compensations().is_empty());
+
+        resolve_callback(PromiseResult::Successful(vec![]));
+        assert!(contract.on_actions_compensated(intent_id.clone(), 1, U128(600)));
+        assert_eq!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Failed("actions partially executed".to_string()))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Action amounts must add up to the intent amount")]
+    fn create_rejects_actions_not_splitting_the_amount() {
+        let mut contract = setup();
+        let mut intent = action_intent();
+        intent.amount = U128(1_200);
+        rehash(&mut intent);
+        create_approved(&mut contract, intent);
+    }
+
+    #[test]
+    fn intents_deliver_one_way_at_most() {
+        let plain = intent(1_000);
+        assert_eq!(plain.delivery_kind(), DeliveryKind::Transfer);
+        let mut evm = plain.clone();
+        evm.aurora_recipient = Some("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".to_string());
+        assert_eq!(evm.delivery_kind(), DeliveryKind::Aurora);
+        let mut mixed = evm.clone();
+        mixed.to_stablecoin = true;
+        assert_eq!(mixed.delivery_kind(), DeliveryKind::Mixed);
+        // Deferring an intent leaves how it is delivered alone
+        let mut deferred = plain;
+        deferred.execute_after = Some(U64(1));
+        assert_eq!(deferred.delivery_kind(), DeliveryKind::Transfer);
+    }
+
+    #[test]
+    fn estimates_cover_the_storage_and_fee_of_an_intent() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_fee(100);
+        let mut intent = intent(10_000);
+        submit_proof(&mut contract, &mut intent);
+        let estimate = contract.estimate_execution(intent.clone());
+        assert_eq!(estimate.fee, U128(100));
+        assert!(estimate.extra_fees.is_empty());
+        assert!(estimate.execution_gas.0 > contract.config.transfer_callback_gas.0);
+
+        let intent_id = intent.id.clone();
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+        let deposit = contract.get_intent_deposit(intent_id).unwrap();
+        assert!(estimate.storage_deposit.0 >= deposit.0);
+        assert!(estimate.storage_deposit.0 < 2 * deposit.0);
+    }
+
+    #[test]
+    fn validate_intent_dry_runs_creation_in_a_view() {
+        let mut contract = setup();
+        let mut valid = intent(1_000);
+        submit_proof(&mut contract, &mut valid);
+        let mut mismatched = valid.clone();
+        mismatched.amount = U128(2_000);
+        set_caller(&executor(), 0);
+        contract.block_receivers(vec![accounts(3)]);
+        let mut blocked = valid.clone();
+        blocked.receiver = accounts(3);
+        rehash(&mut blocked);
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .is_view(true)
+            .build());
+        let result = contract.validate_intent(valid);
+        assert!(result.valid);
+        assert_eq!(result.error, None);
+        let result = contract.validate_intent(mismatched);
+        assert!(!result.valid);
+        assert_eq!(result.code, Some(ContractError::IdMismatch));
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Intent ID does not match its deposit")
+        );
+        assert_eq!(
+            contract.validate_intent(blocked).error.as_deref(),
+            Some("Receiver is blocked")
+        );
+        // Leave the view so dropping the contract can write out its collections
+        set_caller(&executor(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = r#"{"code":"ALREADY_EXECUTED","message":"Intent already executed"}"#)]
+    fn rejections_panic_with_a_stable_error_code() {
+        let mut contract = setup();
+        let intent = intent(1_000);
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent.clone());
+        contract.create_intent(intent);
+    }
+
+    #[test]
+    fn try_variants_return_errors_instead_of_panicking() {
+        let mut contract = setup();
+        let mut intent = intent(1_000);
+        submit_proof(&mut contract, &mut intent);
+        let mut unsupported = intent.clone();
+        unsupported.token = "0x2222222222222222222222222222222222222222".to_string();
+        rehash(&mut unsupported);
+
+        set_caller(&agent(), ONE_NEAR);
+        assert_eq!(
+            contract.try_create_intent(unsupported),
+            Err(ContractError::TokenNotSupported)
+        );
+        assert_eq!(contract.get_intent_count(), 0);
+        assert_eq!(contract.try_create_intent(intent.clone()), Ok(()));
+
+        set_caller(&agent(), 0);
+        assert!(matches!(
+            contract.try_execute_intent(intent.id.clone()),
+            Err(ContractError::NotEnoughApprovals)
+        ));
+        assert!(matches!(
+            contract.get_intent_status(intent.id.clone()),
+            Some(IntentStatus::Pending)
+        ));
+        set_caller(&validator(), 0);
+        contract.approve_intent(intent.id.clone());
+        set_caller(&agent(), 0);
+        assert!(matches!(
+            contract.try_execute_intent(intent.id.clone()),
+            Ok(PromiseOrValue::Promise(_))
+        ));
+        assert!(matches!(
+            contract.try_execute_intent(intent.id),
+            Err(ContractError::NotPending)
+        ));
+    }
+
+    #[test]
+    fn validators_are_listed_in_pages() {
+        let mut contract = setup();
+        let (_, public_key) = keypair(9);
+        set_caller(&accounts(4), VALIDATOR_BOND);
+        contract.register_validator(public_key.clone());
+        set_caller(&accounts(4), 0);
+        contract.unbond(U128(VALIDATOR_BOND));
+
+        assert_eq!(contract.get_validator_count(), 2);
+        let validators = contract.get_validators(None, None);
+        let listed: Vec<_> = validators
+            .iter()
+            .map(|validator| (validator.account_id.clone(), validator.attesting))
+            .collect();
+        assert_eq!(listed, [(validator(), true), (accounts(4), false)]);
+        assert_eq!(validators[1].public_key, public_key);
+        assert_eq!(validators[1].bond.unbonding.0, VALIDATOR_BOND);
+
+        let page = contract.get_validators(Some(U128(1)), Some(1));
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].account_id, accounts(4));
+        assert!(contract.get_validators(Some(U128(2)), None).is_empty());
+    }
+
+    #[test]
+    fn migrate_preserves_current_state() {
+        let contract = setup();
+        env::state_write(&contract);
+        // Dropping the contract writes out its collections
+        drop(contract);
+
+        set_caller(&executor(), 0);
+        let migrated = CrossChainExecutor::migrate();
+        assert_eq!(migrated.get_state_version(), migration::STATE_VERSION);
+        assert_eq!(migrated.get_owner(), executor());
+        assert!(migrated.has_role(Role::Agent, agent()));
+    }
+
+    #[test]
+    fn migrate_upgrades_first_deployment() {
+        use near_sdk::collections;
+
+        set_caller(&executor(), 0);
+        let ids: Vec<String> = (0..3).map(|i| format!("0x{:064x}", i)).collect();
+        let mut executed_intents = collections::UnorderedSet::new(StorageKey::ExecutedIntents);
+        executed_intents.extend(ids.iter().cloned());
+        let mut bridge_validators = collections::LookupMap::new(StorageKey::BridgeValidators);
+        bridge_validators.insert(&validator(), &true);
+        let old = migration::CrossChainExecutorV0 {
+            executed_intents,
+            bridge_validators,
+            required_signatures: 2,
+            token_contract: token(),
+            agent_account: agent(),
+        };
+        // The first deployment wrote no version key
+        env::state_write(&old);
+
+        let mut migrated = CrossChainExecutor::migrate();
+        assert_eq!(migrated.get_state_version(), migration::STATE_VERSION);
+        assert_eq!(migrated.get_owner(), executor());
+        assert_eq!(migrated.get_config().required_signatures, 2);
+        assert!(migrated.has_role(Role::Agent, agent()));
+        assert!(migrated.get_chain(BASE_CHAIN_ID).is_some());
+        // Validators without keys register again
+        assert!(!migrated.is_validator(validator()));
+
+        assert_eq!(migrated.get_intent_count(), 3);
+
+        // Creation waits for every executed ID to reach the new set
+        checkpoint(&mut migrated, BASE_CHAIN_ID, 1);
+        let fresh = intent(1_000);
+        assert_eq!(
+            migrated.validate_new_intent(&fresh),
+            Err(ContractError::IdsMigrating)
+        );
+        assert!(!migrated.migrate_intent_ids(2));
+        assert_eq!(migrated.intent_ids().collect::<Vec<_>>(), ids);
+        assert!(!migrated.migrate_intent_ids(2));
+        assert_eq!(migrated.validate_new_intent(&fresh), Ok(()));
+        // Each ID is copied, then deleted from the old set
+        assert!(migrated.migrate_intent_ids(2));
+        assert!(migrated.legacy_intent_ids.is_none());
+        assert_eq!(migrated.intent_ids().collect::<Vec<_>>(), ids);
+    }
+
+    #[test]
+    fn get_intents_pages_by_status() {
+        let mut contract = setup();
+        let ids: Vec<String> = (0..3)
+            .map(|_| {
+                let intent = intent(1_000);
+                let intent_id = intent.id.clone();
+                set_caller(&agent(), ONE_NEAR);
+                contract.create_intent(intent);
+                intent_id
+            })
+            .collect();
+        set_caller(&agent(), 0);
+        contract.cancel_intent(ids[1].clone());
+
+        let page = contract.get_intents(Some(U128(1)), Some(1));
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].id, ids[1]);
+
+        let pending = contract.get_intents_by_status(IntentStatus::Pending, None, None);
+        let pending: Vec<_> = pending.into_iter().map(|intent| intent.id).collect();
+        assert_eq!(pending, [ids[0].clone(), ids[2].clone()]);
+    }
+
+    #[test]
+    fn status_listings_follow_intents_until_pruned() {
+        let mut contract = setup();
+        let ids: Vec<String> = (0..3)
+            .map(|_| {
+                let intent = intent(1_000);
+                let intent_id = intent.id.clone();
+                set_caller(&agent(), ONE_NEAR);
+                contract.create_intent(intent);
+                intent_id
+            })
+            .collect();
+        set_caller(&agent(), 0);
+        contract.cancel_intent(ids[2].clone());
+        contract.cancel_intent(ids[0].clone());
+        let listed = |contract: &CrossChainExecutor, status, from_index| {
+            contract
+                .get_intents_by_status(status, from_index, None)
+                .into_iter()
+                .map(|intent| intent.id)
+                .collect::<Vec<_>>()
+        };
+
+        // Listed in creation order, not in the order they were cancelled
+        assert_eq!(
+            listed(&contract, IntentStatus::Cancelled, None),
+            [ids[0].clone(), ids[2].clone()]
+        );
+        assert_eq!(listed(&contract, IntentStatus::Cancelled, Some(U128(1))), [ids[2].clone()]);
+        let pending = contract.get_pending_intents(None, None);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].intent.id, ids[1]);
+
+        contract.prune_intent(ids[0].clone());
+        assert_eq!(listed(&contract, IntentStatus::Cancelled, None), [ids[2].clone()]);
+    }
+
+    #[test]
+    fn get_intents_pages_by_receiver() {
+        let mut contract = setup();
+        let ids: Vec<String> = (0..3)
+            .map(|_| {
+                let intent = intent(1_000);
+                let intent_id = intent.id.clone();
+                set_caller(&agent(), ONE_NEAR);
+                contract.create_intent(intent);
+                intent_id
+            })
+            .collect();
+
+        let by_receiver = contract.get_intents_by_receiver(accounts(2), Some(U128(2)), None);
+        assert_eq!(by_receiver.len(), 1);
+        assert_eq!(by_receiver[0].id, ids[2]);
+        let first_page = contract.get_intents_by_receiver(accounts(2), None, Some(2));
+        let first_page: Vec<_> = first_page.into_iter().map(|intent| intent.id).collect();
+        assert_eq!(first_page, ids[..2]);
+        assert!(contract
+            .get_intents_by_receiver(accounts(3), None, None)
+            .is_empty());
+    }
+
+    #[test]
+    fn get_intents_pages_by_sender_in_any_case() {
+        let mut contract = setup();
+        let other_sender = format!("0x{}", "11".repeat(20));
+        let ids: Vec<String> = (0..3)
+            .map(|index| {
+                let mut intent = intent(1_000);
+                if index == 1 {
+                    intent.sender = other_sender.clone();
+                    rehash(&mut intent);
+                }
+                let intent_id = intent.id.clone();
+                set_caller(&agent(), ONE_NEAR);
+                contract.create_intent(intent);
+                intent_id
+            })
+            .collect();
+
+        let sender = "0x71C7656EC7ab88b098defB751B7401B5f6d8976F".to_string();
+        let by_sender = contract.get_intents_by_sender(sender.clone(), None, None);
+        let by_sender: Vec<_> = by_sender.into_iter().map(|intent| intent.id).collect();
+        assert_eq!(by_sender, [ids[0].clone(), ids[2].clone()]);
+        let second_page = contract.get_intents_by_sender(sender, Some(U128(1)), Some(1));
+        assert_eq!(second_page.len(), 1);
+        assert_eq!(second_page[0].id, ids[2]);
+        let other = contract.get_intents_by_sender(other_sender, None, None);
+        assert_eq!(other.len(), 1);
+        assert_eq!(other[0].id, ids[1]);
+    }
+
+    #[test]
+    fn get_intent_returns_full_records() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, intent(1_000));
+
+        let stored = contract.get_intent(intent_id.clone()).unwrap();
+        assert_eq!(stored.receiver, accounts(2));
+        assert_eq!(stored.amount, U128(1_000));
+        assert!(matches!(stored.status, IntentStatus::Pending));
+
+        let bulk = contract.get_intents_bulk(vec![intent_id.clone(), "missing".to_string()]);
+        assert_eq!(bulk.len(), 2);
+        assert_eq!(bulk[0].as_ref().unwrap().id, intent_id);
+        assert!(bulk[1].is_none());
+    }
+
+    #[test]
+    fn intent_schemas_list_their_fields() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        let stored = contract.get_intent(intent_id).unwrap();
+
+        assert_schema_describes(&stored);
+        assert_schema_describes(&stored.proof);
+        assert_schema_describes(&ValidatorSignature {
+            validator: validator(),
+            signature: Base64VecU8(vec![0; 64]),
+        });
+        assert_schema_describes(&contract.get_config());
+        assert_schema_describes(&config::ConfigUpdate::default());
+    }
+
+    #[test]
+    fn created_intents_are_read_back_from_contract_state() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        env::state_write(&contract);
+        drop(contract);
+
+        let contract: CrossChainExecutor = env::state_read().unwrap();
+        let stored = contract.get_intent(intent_id.clone()).unwrap();
+        assert_eq!(stored.id, intent_id);
+        assert_eq!(stored.sender, "0x71c7656ec7ab88b098defb751b7401b5f6d8976f");
+        assert_eq!(stored.receiver, accounts(2));
+        assert_eq!(stored.token, BASE_TOKEN);
+        assert_eq!(stored.amount, U128(1_000));
+        assert!(!stored.proof.receipt.0.is_empty());
+        assert!(contract.get_intent("missing".to_string()).is_none());
+    }
+
+    #[test]
+    fn intent_history_records_each_step_and_actor() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(executor())
+                .predecessor_account_id(executor())
+                .block_timestamp(42)
+                .build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])],
+        );
+        contract.on_transfer_complete(intent_id.clone(), U128(0), None, Ok(()));
+
+        let history = contract.get_intent_history(intent_id);
+        let steps: Vec<_> = history
+            .iter()
+            .map(|entry| (format!("{:?}", entry.step), entry.actor.clone()))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                ("Created".to_string(), agent()),
+                ("Approved".to_string(), validator()),
+                ("Transitioned(Executing)".to_string(), agent()),
+                ("Transitioned(Completed)".to_string(), executor()),
+            ]
+        );
+        assert_eq!(history[3].timestamp, U64(42));
+    }
+
+    #[test]
+    fn create_intents_reports_each_item() {
+        let mut contract = setup();
+        let ok = intent(1_000);
+        let mut expired = intent(1_000);
+        expired.expires_at = U64(0);
+        rehash(&mut expired);
+        let mut unsupported = intent(1_000);
+        unsupported.token = "0x2222222222222222222222222222222222222222".to_string();
+        rehash(&mut unsupported);
+
+        set_caller(&agent(), ONE_NEAR);
+        let results = contract.create_intents(vec![ok.clone(), expired, unsupported, ok]);
+
+        let errors: Vec<_> = results.iter().map(|result| result.error.as_deref()).collect();
+        assert_eq!(
+            errors,
+            [
+                None,
+                Some("Intent already expired"),
+                Some("Token not supported"),
+                Some("Intent already executed"),
+            ]
+        );
+        assert_eq!(results[3].code, Some(ContractError::AlreadyExecuted));
+        assert_eq!(contract.get_intent_count(), 1);
+    }
+
+    #[test]
+    fn execute_intents_settles_each_transfer() {
+        let mut contract = setup();
+        let first = create_approved(&mut contract, intent(1_000));
+        let mut second = intent(2_000);
+        second.proof.block_number = 2;
+        let second = create_approved(&mut contract, second);
+        set_caller(&agent(), 0);
+        contract.execute_intents(vec![first.clone(), second.clone()]);
+        assert_eq!(
+            get_created_receipts()
+                .iter()
+                .filter(|receipt| receipt.receiver_id == token())
+                .count(),
+            2
+        );
+
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(executor())
+                .predecessor_account_id(executor())
+                .build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![]), PromiseResult::Failed],
+        );
+        let results = contract.on_batch_transfers_complete(
+            vec![first.clone(), second.clone()],
+            vec![U128(0), U128(0)],
+            None,
+        );
+
+        assert_eq!(results, [true, false]);
+        assert!(matches!(
+            contract.get_intent_status(first),
+            Some(IntentStatus::Completed)
+        ));
+        assert!(matches!(
+            contract.get_intent_status(second),
+            Some(IntentStatus::Failed(_))
+        ));
+    }
+
+    /// Create, approve and execute an intent delivering 1000 of the primary
+    /// token plus 500 of a second one, resolving its transfers with `results`
+    fn execute_basket(contract: &mut CrossChainExecutor, results: Vec<PromiseResult>) -> String {
+        let top_up: AccountId = "gas.near".parse().unwrap();
+        set_caller(&executor(), 0);
+        contract.set_fee(100);
+        contract.register_token(
+            tokens::TokenMapping {
+                near_contract: top_up.clone(),
+                ..mapping("0x2222222222222222222222222222222222222222", 6, 6)
+            },
+            None,
+        );
+
+        let mut basket = intent(1_000);
+        basket.extra_assets = vec![BasketAsset {
+            token: "0x2222222222222222222222222222222222222222".to_string(),
+            amount: U128(500),
+            log_index: 1,
+        }];
+        rehash(&mut basket);
+        let intent_id = create_approved(contract, basket);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        let receivers: Vec<AccountId> = get_created_receipts()
+            .into_iter()
+            .map(|receipt| receipt.receiver_id)
+            .collect();
+        assert!(receivers.contains(&token()) && receivers.contains(&top_up));
+
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(executor())
+                .predecessor_account_id(executor())
+                .build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            results,
+        );
+        contract.on_transfer_complete(intent_id.clone(), U128(10), Some(vec![U128(5)]), Ok(()));
+        intent_id
+    }
+
+    #[test]
+    fn basket_intent_delivers_every_asset() {
+        let mut contract = setup();
+        let results = vec![PromiseResult::Successful(vec![]), PromiseResult::Successful(vec![])];
+        let intent_id = execute_basket(&mut contract, results);
+
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Completed)
+        ));
+        assert_eq!(contract.get_accrued_fees(BASE_TOKEN.to_string()), U128(10));
+        assert_eq!(
+            contract.get_accrued_fees("0x2222222222222222222222222222222222222222".to_string()),
+            U128(5)
+        );
+    }
+
+    #[test]
+    fn partially_delivered_basket_fails_without_refund() {
+        let mut contract = setup();
+        let results = vec![PromiseResult::Successful(vec![]), PromiseResult::Failed];
+        let intent_id = execute_basket(&mut contract, results);
+
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Failed(reason)) if reason == "basket partially delivered"
+        ));
+        assert!(contract.get_refundable_intents().is_empty());
+        // The fee of the delivered transfer is kept
+        assert_eq!(contract.get_accrued_fees(BASE_TOKEN.to_string()), U128(10));
+    }
+
+    #[test]
+    fn update_config_applies_only_given_fields() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.update_config(config::ConfigUpdate {
+            required_signatures: Some(2),
+            ..Default::default()
+        });
+
+        let config = contract.get_config();
+        assert_eq!(config.required_signatures, 2);
+        assert_eq!(config.ft_transfer_deposit.0, 1);
+    }
+
+    /// Hand ownership to the Sputnik DAO `accounts(5)`
+    fn transfer_to_dao(contract: &mut CrossChainExecutor) -> AccountId {
+        let dao = accounts(5);
+        set_caller(&executor(), 0);
+        contract.propose_owner(dao.clone());
+        set_caller(&dao, 0);
+        contract.accept_ownership();
+        dao
+    }
+
+    #[test]
+    fn dao_owner_applies_proposed_governance_action() {
+        let mut contract = setup();
+        let dao = transfer_to_dao(&mut contract);
+        let proposal = contract.get_governance_proposal(
+            governance::GovernanceAction::UpdateConfig {
+                update: config::ConfigUpdate {
+                    required_signatures: Some(2),
+                    ..Default::default()
+                },
+            },
+            "Require two signatures".to_string(),
+        );
+        let call = &proposal["proposal"]["kind"]["FunctionCall"]["actions"][0];
+        assert_eq!(call["method_name"], "execute_governance_action");
+
+        // The DAO makes the proposed call once the proposal is approved
+        let args: Base64VecU8 = serde_json::from_value(call["args"].clone()).unwrap();
+        let args: serde_json::Value = serde_json::from_slice(&args.0).unwrap();
+        set_caller(&dao, 0);
+        let action = serde_json::from_value(args["action"].clone()).unwrap();
+        contract.execute_governance_action(action);
+        assert_eq!(contract.get_config().required_signatures, 2);
+    }
+
+    #[test]
+    fn dao_owner_upgrade_deploys_input_code_and_migrates() {
+        let contract = setup();
+        let mut context = VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(executor())
+            .build();
+        context.input = vec![0, 97, 115, 109];
+        testing_env!(context);
+        contract.upgrade();
+
+        let actions: Vec<VmAction> = get_created_receipts()
+            .into_iter()
+            .filter(|receipt| receipt.receiver_id == executor())
+            .flat_map(|receipt| receipt.actions)
+            .collect();
+        assert!(matches!(
+            &actions[0],
+            VmAction::DeployContract { code } if code == &[0, 97, 115, 109]
+        ));
+        assert!(matches!(
+            &actions[1],
+            VmAction::FunctionCall { function_name, .. } if function_name == "migrate"
+        ));
+    }
+
+    /// Start the admin timelock at its minimum delay and queue a fee change
+    fn queue_fee_change(contract: &mut CrossChainExecutor) -> u64 {
+        set_caller(&executor(), 0);
+        contract.set_admin_timelock(U64(network::MIN_ADMIN_TIMELOCK));
+        contract.queue_admin_action(governance::GovernanceAction::SetFee { fee_bps: 100 })
+    }
+
+    #[test]
+    fn queued_admin_action_applies_after_timelock() {
+        let mut contract = setup();
+        let id = queue_fee_change(&mut contract);
+        let queued = contract.get_queued_admin_actions();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].executable_at.0, network::MIN_ADMIN_TIMELOCK);
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(executor())
+            .block_timestamp(network::MIN_ADMIN_TIMELOCK)
+            .build());
+        contract.apply_admin_action(id);
+        assert_eq!(contract.get_fee_bps(BASE_TOKEN.to_string()), 100);
+        assert!(contract.get_queued_admin_actions().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Admin timelock has not elapsed")]
+    fn queued_admin_action_waits_for_timelock() {
+        let mut contract = setup();
+        let id = queue_fee_change(&mut contract);
+        contract.apply_admin_action(id);
+    }
+
+    #[test]
+    fn prune_intent_refunds_storage_deposit() {
+        let mut contract = setup();
+        let intent = intent(1_000);
+        let intent_id = intent.id.clone();
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+        let deposit = contract
+            .get_intent_deposit(intent_id.clone())
+            .expect("No deposit recorded");
+        assert_eq!(transfers_to(&agent()), [ONE_NEAR - deposit.0]);
+
+        set_caller(&agent(), 0);
+        contract.cancel_intent(intent_id.clone());
+        contract.prune_intent(intent_id.clone());
+
+        assert!(contract.get_intent_status(intent_id).is_none());
+        assert_eq!(transfers_to(&agent()), [deposit.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent already executed")]
+    fn pruned_intent_cannot_be_recreated() {
+        let mut contract = setup();
+        let intent = intent(1_000);
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent.clone());
+        set_caller(&agent(), 0);
+        contract.cancel_intent(intent.id.clone());
+        contract.prune_intent(intent.id.clone());
+
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+    }
+
+    /// Register `aggregate_key` for Base, signed by the validator `accounts(4)`
+    /// bonded with the key of `keypair(9)`
+    fn set_aggregate_key(contract: &mut CrossChainExecutor, aggregate_key: PublicKey) {
+        let (validator_keypair, public_key) = keypair(9);
+        set_caller(&accounts(4), VALIDATOR_BOND);
+        contract.register_validator(public_key);
+        let message = aggregate::aggregate_key_hash(BASE_CHAIN_ID, 0, &aggregate_key);
+        contract.set_aggregate_key(
+            aggregate_key,
+            vec![ValidatorSignature {
+                validator: accounts(4),
+                signature: Base64VecU8(validator_keypair.sign(&message).to_bytes().to_vec()),
+            }],
+            None,
+        );
+    }
+
+    #[test]
+    fn execute_accepts_aggregate_attestation_instead_of_approvals() {
+        let mut contract = setup();
+        let (keypair, aggregate_key) = keypair(7);
+        set_aggregate_key(&mut contract, aggregate_key.clone());
+        assert_eq!(contract.get_aggregate_key(0, None), Some(aggregate_key));
+
+        let mut intent = intent(1_000);
+        submit_proof(&mut contract, &mut intent);
+        let signature = keypair.sign(&intent.signing_hash()).to_bytes().to_vec();
+        let intent_id = intent.id.clone();
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+        contract.attest_intent(
+            intent_id.clone(),
+            aggregate::AggregateSignature {
+                epoch: 0,
+                signature: Base64VecU8(signature),
+            },
+        );
+
+        set_caller(&agent(), 0);
+        assert!(matches!(
+            contract.execute_intent(intent_id),
+            PromiseOrValue::Promise(_)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough validator signatures")]
+    fn aggregate_keys_need_the_chain_quorum() {
+        let mut contract = setup();
+        let mut base = contract.get_chain(BASE_CHAIN_ID).unwrap();
+        base.validators = vec![validator()];
+        set_caller(&executor(), 0);
+        contract.set_chain(BASE_CHAIN_ID, Some(base));
+
+        // accounts(4) does not attest for Base
+        set_aggregate_key(&mut contract, keypair(7).1);
+    }
+
+    #[test]
+    fn unbonded_validator_stops_counting_until_withdrawn() {
+        let mut contract = setup();
+        set_caller(&validator(), 0);
+        contract.unbond(U128(ONE_NEAR));
+        assert!(!contract.is_validator(validator()));
+
+        let unlocks_at = contract
+            .get_validator_bond(validator())
+            .expect("No bond recorded")
+            .unlocks_at;
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(validator())
+            .block_timestamp(unlocks_at.0)
+            .build());
+        contract.withdraw_bond();
+
+        assert_eq!(transfers_to(&validator()), [ONE_NEAR]);
+        let bond = contract.get_validator_bond(validator()).unwrap();
+        assert_eq!(bond.bonded.0, VALIDATOR_BOND - ONE_NEAR);
+        assert_eq!(bond.unbonding.0, 0);
+    }
+
+    #[test]
+    fn conflicting_intents_slash_the_signing_validator() {
+        let mut contract = setup();
+        let (keypair, public_key) = keypair(9);
+        set_caller(&accounts(4), VALIDATOR_BOND);
+        contract.register_validator(public_key);
+
+        // Both intents claim the same deposit
+        let signed = |amount| {
+            let mut intent = intent(amount);
+            intent.proof.transaction_hash = "0xabc".to_string();
+            let signature = keypair.sign(&intent.signing_hash()).to_bytes().to_vec();
+            intent.signatures.push(ValidatorSignature {
+                validator: accounts(4),
+                signature: Base64VecU8(signature),
+            });
+            Box::new(intent)
+        };
+        let slashed = contract.submit_misbehavior_proof(
+            accounts(4),
+            slashing::Misbehavior::ConflictingIntents {
+                first: signed(1_000),
+                second: signed(2_000),
+            },
+        );
+
+        assert_eq!(slashed.0, VALIDATOR_BOND);
+        assert!(!contract.is_validator(accounts(4)));
+        assert!(contract.get_validator_bond(accounts(4)).is_none());
+        assert_eq!(contract.get_accrued_fees("near".to_string()).0, VALIDATOR_BOND);
+    }
+
+    #[test]
+    #[should_panic(expected = "Proof block is not checkpointed yet")]
+    fn intents_wait_for_a_checkpoint_of_the_proof_block() {
+        let mut contract = setup();
+        contract.checkpoints.remove(&BASE_CHAIN_ID);
+        let (keypair, public_key) = keypair(9);
+        set_caller(&accounts(4), VALIDATOR_BOND);
+        contract.register_validator(public_key);
+
+        let state_root = format!("0x{}", "ab".repeat(32));
+        let message = checkpoints::checkpoint_hash(BASE_CHAIN_ID, 1, &[0xab; 32]);
+        contract.submit_checkpoint(
+            1,
+            state_root.clone(),
+            vec![ValidatorSignature {
+                validator: accounts(4),
+                signature: Base64VecU8(keypair.sign(&message).to_bytes().to_vec()),
+            }],
+            None,
+        );
+        let checkpoint = contract.get_checkpoint(None).unwrap();
+        assert_eq!((checkpoint.block_number, checkpoint.state_root), (1, state_root));
+
+        let mut intent = intent(1_000);
+        intent.proof.block_number = 2;
+        rehash(&mut intent);
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+    }
+
+    /// Approve withdrawing `amount` of `token()` to `accounts(4)` through
+    /// the emergency DAO `accounts(5)`, returning the withdrawal hash
+    fn approve_emergency_withdrawal(contract: &mut CrossChainExecutor, amount: U128) -> String {
+        set_caller(&executor(), 0);
+        contract.set_emergency_dao(accounts(5));
+        let withdrawal_hash =
+            contract.get_emergency_withdrawal_hash(token().to_string(), amount, accounts(4));
+        set_caller(&accounts(5), 0);
+        contract.approve_emergency_withdrawal(withdrawal_hash.clone());
+        withdrawal_hash
+    }
+
+    #[test]
+    fn approved_emergency_withdrawal_executes_after_timelock() {
+        let mut contract = setup();
+        let withdrawal_hash = approve_emergency_withdrawal(&mut contract, U128(500));
+        let executable_at = contract
+            .get_emergency_withdrawal(withdrawal_hash.clone())
+            .expect("Withdrawal not approved");
+        assert_eq!(executable_at.0, network::EMERGENCY_WITHDRAWAL_DELAY);
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(accounts(3))
+            .block_timestamp(executable_at.0)
+            .build());
+        contract.emergency_withdraw(token().to_string(), U128(500), accounts(4));
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .expect("No ft_transfer receipt");
+        match &receipt.actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                args,
+                ..
+            } => {
+                assert_eq!(function_name, "ft_transfer");
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["receiver_id"], accounts(4).to_string());
+                assert_eq!(args["amount"], "500");
+            }
+            action => panic!("Unexpected action {:?}", action),
+        }
+        // Each approval releases a single withdrawal
+        assert!(contract.get_emergency_withdrawal(withdrawal_hash).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Withdrawal timelock has not elapsed")]
+    fn emergency_withdrawal_waits_for_timelock() {
+        let mut contract = setup();
+        approve_emergency_withdrawal(&mut contract, U128(500));
+        contract.emergency_withdraw(token().to_string(), U128(500), accounts(4));
+    }
+
+    #[test]
+    fn rotated_set_attests_intents_of_its_epoch() {
+        let mut contract = setup();
+        let (keypair, public_key) = keypair(9);
+        set_caller(&accounts(4), VALIDATOR_BOND);
+        contract.register_validator(public_key);
+
+        let validators = vec![accounts(4)];
+        let rotation = epochs::rotation_hash(1, &validators, 1);
+        contract.rotate_validator_set(
+            1,
+            validators,
+            1,
+            vec![ValidatorSignature {
+                validator: accounts(4),
+                signature: Base64VecU8(keypair.sign(&rotation).to_bytes().to_vec()),
+            }],
+        );
+        assert_eq!(contract.get_current_epoch(), 1);
+
+        let mut intent = intent(1_000);
+        intent.epoch = 1;
+        submit_proof(&mut contract, &mut intent);
+        let signature = keypair.sign(&intent.signing_hash()).to_bytes().to_vec();
+        intent.signatures.push(ValidatorSignature {
+            validator: accounts(4),
+            signature: Base64VecU8(signature),
+        });
+        let intent_id = intent.id.clone();
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+
+        set_caller(&agent(), 0);
+        assert!(matches!(
+            contract.execute_intent(intent_id),
+            PromiseOrValue::Promise(_)
+        ));
+    }
+
+    #[test]
+    fn disputed_intent_is_frozen_until_arbiter_rules() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.grant_role(Role::Arbiter, accounts(5));
+        let intent_id = create_approved(&mut contract, intent(1_000));
+
+        set_caller(&validator(), 0);
+        contract.dispute_intent(intent_id.clone(), "0xdeadbeef".to_string());
+        assert!(matches!(
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Disputed)
+        ));
+
+        set_caller(&accounts(5), 0);
+        contract.resolve_dispute(intent_id.clone(), disputes::DisputeRuling::Cancel);
+        assert!(matches!(
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Cancelled)
+        ));
+        assert!(contract.get_dispute(intent_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Nonce already used")]
+    fn create_rejects_reused_sender_nonce() {
+        let mut contract = setup();
+        let first = intent(1_000);
+        let mut replay = intent(2_000);
+        replay.nonce = first.nonce;
+        rehash(&mut replay);
+
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(first);
+        contract.create_intent(replay);
+    }
+
+    #[test]
+    fn create_accepts_sender_nonces_relayed_out_of_order() {
+        let mut contract = setup();
+        let earlier = intent(1_000);
+        let later = intent(2_000);
+        let sender = later.sender.clone();
+        let highest = later.nonce;
+
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(later);
+        contract.create_intent(earlier.clone());
+
+        assert!(contract.get_intent_status(earlier.id).is_some());
+        assert_eq!(contract.get_sender_nonce(sender), highest);
+    }
+
+    #[test]
+    fn create_accepts_intent_signed_by_base_sender() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.update_config(config::ConfigUpdate {
+            require_sender_signature: Some(true),
+            ..Default::default()
+        });
+
+        let secret = near_crypto::SecretKey::from_seed(near_crypto::KeyType::SECP256K1, "sender");
+        let public_key = match secret.public_key() {
+            near_crypto::PublicKey::SECP256K1(public_key) => public_key,
+            _ => unreachable!(),
+        };
+        let mut intent = intent(1_000);
+        intent.sender = format!("0x{}", hex::encode(&env::keccak256(public_key.as_ref())[12..]));
+        rehash(&mut intent);
+        let signature = match secret.sign(&intent.typed_hash().unwrap()) {
+            near_crypto::Signature::SECP256K1(signature) => <[u8; 65]>::from(signature),
+            _ => unreachable!(),
+        };
+        intent.sender_signature = Some(Base64VecU8(signature.to_vec()));
+        let intent_id = intent.id.clone();
+
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Pending)
+        ));
+    }
+
+    #[test]
+    fn initialization_applies_network_defaults() {
+        set_caller(&executor(), 0);
+        let contract = CrossChainExecutor::new(1, agent(), None);
+        for (chain_id, name, confirmations) in network::DEFAULT_CHAINS {
+            let chain = contract.get_chain(chain_id).unwrap();
+            assert_eq!(chain.name, name);
+            assert_eq!(chain.confirmations, confirmations);
+        }
+        for token in network::DEFAULT_TOKENS {
+            assert_eq!(
+                contract.get_token_contract(token.base_address.to_string(), Some(token.chain_id)),
+                Some(token.near_contract.parse().unwrap())
+            );
+        }
+        assert_eq!(
+            contract.get_config().min_validator_bond.0,
+            network::MIN_VALIDATOR_BOND
+        );
+    }
+
+    #[test]
+    fn executes_intent_from_another_configured_chain() {
+        let mut contract = setup();
+        let usdc = "0x0b2c639c533813f4aa9d7837caf62653d097ff85";
+        set_caller(&executor(), 0);
+        let mut optimism = contract.get_chain(network::OPTIMISM_CHAIN_ID).unwrap();
+        optimism.bridge = Some(BASE_BRIDGE.to_string());
+        contract.set_chain(network::OPTIMISM_CHAIN_ID, Some(optimism));
+        contract.register_token(mapping(usdc, 6, 6), Some(network::OPTIMISM_CHAIN_ID));
+
+        let mut intent = intent(1_000);
+        intent.chain_id = network::OPTIMISM_CHAIN_ID;
+        intent.token = usdc.to_string();
+        rehash(&mut intent);
+        let args = execute(&mut contract, intent);
+        assert_eq!(args["amount"], "1000");
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough validator approvals")]
+    fn execute_ignores_validators_not_attesting_for_the_chain() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        let mut arbitrum = contract.get_chain(network::ARBITRUM_CHAIN_ID).unwrap();
+        arbitrum.bridge = Some(BASE_BRIDGE.to_string());
+        arbitrum.validators = vec![accounts(3)];
+        contract.set_chain(network::ARBITRUM_CHAIN_ID, Some(arbitrum));
+        contract.register_token(mapping(BASE_TOKEN, 6, 6), Some(network::ARBITRUM_CHAIN_ID));
+
+        let mut intent = intent(1_000);
+        intent.chain_id = network::ARBITRUM_CHAIN_ID;
+        rehash(&mut intent);
+        let intent_id = create_approved(&mut contract, intent);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+    }
+
+    /// Create an intent carrying a signature of its payload by accounts(4),
+    /// registered as a validator with the key of `registered`, made with the
+    /// key of `signing`
+    fn create_signed(
+        contract: &mut CrossChainExecutor,
+        mut intent: CrossChainIntent,
+        registered: u8,
+        signing: u8,
+    ) -> String {
+        set_caller(&accounts(4), VALIDATOR_BOND);
+        contract.register_validator(keypair(registered).1);
+        submit_proof(contract, &mut intent);
+        let signature = keypair(signing).0.sign(&intent.signing_hash());
+        intent.signatures.push(ValidatorSignature {
+            validator: accounts(4),
+            signature: Base64VecU8(signature.to_bytes().to_vec()),
+        });
+        let intent_id = intent.id.clone();
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+        intent_id
+    }
+
+    #[test]
+    fn execute_accepts_payloads_signed_by_a_validator() {
+        let mut contract = setup();
+        let intent_id = create_signed(&mut contract, intent(1_000), 9, 9);
+        assert_eq!(contract.get_approval_count(intent_id.clone()), 0);
+
+        set_caller(&agent(), 0);
+        assert!(matches!(
+            contract.execute_intent(intent_id.clone()),
+            PromiseOrValue::Promise(_)
+        ));
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Executing)
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough validator approvals")]
+    fn execute_ignores_signatures_of_other_keys() {
+        let mut contract = setup();
+        let intent_id = create_signed(&mut contract, intent(1_000), 9, 8);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+    }
+
+    #[test]
+    fn repeated_signatures_of_a_validator_count_once() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.update_config(config::ConfigUpdate {
+            required_signatures: Some(2),
+            ..Default::default()
+        });
+        set_caller(&accounts(4), VALIDATOR_BOND);
+        contract.register_validator(keypair(9).1);
+
+        let mut signed = intent(1_000);
+        submit_proof(&mut contract, &mut signed);
+        let signature = keypair(9).0.sign(&signed.signing_hash()).to_bytes().to_vec();
+        let signature = ValidatorSignature {
+            validator: accounts(4),
+            signature: Base64VecU8(signature),
+        };
+        signed.signatures = vec![signature.clone(), signature];
+        let intent_id = signed.id.clone();
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(signed);
+
+        set_caller(&agent(), 0);
+        assert!(contract.try_execute_intent(intent_id.clone()).is_err());
+        set_caller(&validator(), 0);
+        contract.approve_intent(intent_id.clone());
+        set_caller(&agent(), 0);
+        assert!(contract.try_execute_intent(intent_id).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Chain not supported")]
+    fn create_rejects_intent_from_unregistered_chain() {
+        let mut contract = setup();
+        let mut intent = intent(1_000);
+        intent.chain_id = 137;
+        rehash(&mut intent);
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+    }
+
+    #[test]
+    fn execute_rescales_amounts_to_near_token_decimals() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_fee(100);
+        contract.register_token(mapping(BASE_TOKEN, 6, 18), None);
+
+        let args = execute(&mut contract, intent(1_000));
+        assert_eq!(args["amount"], "990000000000000");
+
+        // Scaling down rounds towards zero
+        set_caller(&executor(), 0);
+        contract.register_token(mapping(BASE_TOKEN, 18, 6), None);
+        let args = execute(&mut contract, intent(1_999_999_999_999));
+        assert_eq!(args["amount"], "1");
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount overflows after rescaling")]
+    fn create_rejects_amount_overflowing_near_decimals() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.register_token(mapping(BASE_TOKEN, 6, 24), None);
+
+        let mut intent = intent(u128::MAX / 1_000);
+        submit_proof(&mut contract, &mut intent);
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent);
+    }
+
+    #[test]
+    fn disabled_tokens_are_hidden_and_rejected() {
+        let mut contract = setup();
+        assert_eq!(contract.get_supported_tokens(None).len(), 1);
+
+        set_caller(&executor(), 0);
+        contract.set_token_enabled(BASE_TOKEN.to_string(), false, None);
+        assert!(contract.get_supported_tokens(None).is_empty());
+        assert_eq!(
+            contract.get_token_mapping(BASE_TOKEN.to_string(), None).unwrap().symbol,
+            "USDC"
+        );
+
+        set_caller(&agent(), ONE_NEAR);
+        let report = contract.create_intents(vec![intent(1_000)]);
+        assert_eq!(report[0].error.as_deref(), Some("Token disabled"));
+    }
+
+    #[test]
+    fn execute_mints_mintable_tokens() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        let mut wrapped = mapping(BASE_TOKEN, 6, 6);
+        wrapped.mintable = true;
+        contract.register_token(wrapped, None);
+
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .expect("No mint receipt");
+        match &receipt.actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                args,
+                deposit,
+                ..
+            } => {
+                assert_eq!(function_name, "mint");
+                assert_eq!(*deposit, 0);
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["account_id"], accounts(2).to_string());
+                assert_eq!(args["amount"], "1000");
+            }
+            _ => panic!("Expected a function call"),
+        }
+    }
+
+    #[test]
+    fn execute_settles_into_near_intents() {
+        let mut contract = setup();
+        let defuse: AccountId = "intents.near".parse().unwrap();
+        set_caller(&executor(), 0);
+        contract.set_defuse_contract(Some(defuse.clone()));
+
+        let mut settled = intent(1_000);
+        settled.settle_in_defuse = true;
+        rehash(&mut settled);
+        let intent_id = create_approved(&mut contract, settled);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .expect("No deposit receipt");
+        match &receipt.actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                args,
+                ..
+            } => {
+                assert_eq!(function_name, "ft_transfer_call");
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["receiver_id"], defuse.to_string());
+                assert_eq!(args["msg"], accounts(2).to_string());
+            }
+            _ => panic!("Expected a function call"),
+        }
+    }
+
+    /// Start executing a 1000 token intent settling into NEAR Intents
+    fn execute_into_near_intents(contract: &mut CrossChainExecutor) -> String {
+        set_caller(&executor(), 0);
+        contract.set_defuse_contract(Some("intents.near".parse().unwrap()));
+        let mut settled = intent(1_000);
+        settled.settle_in_defuse = true;
+        rehash(&mut settled);
+        let intent_id = create_approved(contract, settled);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        intent_id
+    }
+
+    #[test]
+    fn near_intents_deposits_kept_in_full_complete() {
+        let mut contract = setup();
+        let intent_id = execute_into_near_intents(&mut contract);
+        assert!(get_created_receipts().iter().any(|receipt| {
+            receipt.receiver_id == executor()
+                && matches!(
+                    &receipt.actions[0],
+                    VmAction::FunctionCall { function_name, .. }
+                        if function_name == "on_token_deposited"
+                )
+        }));
+
+        resolve_callback(PromiseResult::Successful(b"\"1000\"".to_vec()));
+        contract.on_token_deposited(U128(1_000));
+        contract.on_transfer_complete(intent_id.clone(), U128(0), None, Ok(()));
+        assert_eq!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Completed)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit was refunded")]
+    fn partly_refunded_near_intents_deposits_fail() {
+        let mut contract = setup();
+        execute_into_near_intents(&mut contract);
+
+        resolve_callback(PromiseResult::Successful(b"\"400\"".to_vec()));
+        contract.on_token_deposited(U128(1_000));
+    }
+
+    /// Intent delivering 1000 tokens swapped to wNEAR with a deadline of `deadline`
+    fn swap_intent(deadline: u64) -> CrossChainIntent {
+        let mut swapped = intent(1_000);
+        swapped.swap = Some(SwapAction {
+            pool_id: 3,
+            token_out: "wrap.near".parse().unwrap(),
+            min_amount_out: U128(990),
+            deadline: U64(deadline),
+        });
+        rehash(&mut swapped);
+        swapped
+    }
+
+    #[test]
+    fn execute_swaps_through_ref_finance() {
+        let mut contract = setup();
+        let exchange: AccountId = "v2.ref-finance.near".parse().unwrap();
+        set_caller(&executor(), 0);
+        contract.set_ref_contract(Some(exchange.clone()));
+
+        let intent_id = create_approved(&mut contract, swap_intent(1_000));
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .expect("No swap receipt");
+        match &receipt.actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                args,
+                ..
+            } => {
+                assert_eq!(function_name, "ft_transfer_call");
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["receiver_id"], exchange.to_string());
+                let msg: serde_json::Value =
+                    serde_json::from_str(args["msg"].as_str().unwrap()).unwrap();
+                assert_eq!(msg["swap_out_recipient"], accounts(2).to_string());
+                assert_eq!(msg["actions"][0]["pool_id"], 3);
+                assert_eq!(msg["actions"][0]["token_out"], "wrap.near");
+                assert_eq!(msg["actions"][0]["min_amount_out"], "990");
+            }
+            _ => panic!("Expected a function call"),
+        }
+    }
+
+    #[test]
+    fn stablecoin_intents_swap_along_their_token_route() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_ref_contract(Some("v2.ref-finance.near".parse().unwrap()));
+        let mut stable = intent(1_000);
+        stable.to_stablecoin = true;
+        rehash(&mut stable);
+        assert_eq!(
+            contract.validate_new_intent(&stable),
+            Err(ContractError::NoStablecoinRoute)
+        );
+
+        contract.set_stablecoin_route(
+            BASE_TOKEN.to_string(),
+            Some(swap::StablecoinRoute {
+                pool_id: 7,
+                stablecoin: "usdt.near".parse().unwrap(),
+                min_price: U128(2_000_000),
+            }),
+            None,
+        );
+        let intent_id = create_approved(&mut contract, stable);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .expect("No swap receipt");
+        let VmAction::FunctionCall { args, .. } = &receipt.actions[0] else {
+            panic!("Expected a function call");
+        };
+        let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+        let msg: serde_json::Value = serde_json::from_str(args["msg"].as_str().unwrap()).unwrap();
+        assert_eq!(msg["actions"][0]["pool_id"], 7);
+        assert_eq!(msg["actions"][0]["token_out"], "usdt.near");
+        assert_eq!(msg["actions"][0]["min_amount_out"], "2000");
+    }
+
+    #[test]
+    fn execute_deposits_into_aurora_for_evm_recipients() {
+        let mut contract = setup();
+        let engine: AccountId = "aurora".parse().unwrap();
+        set_caller(&executor(), 0);
+        contract.set_aurora_engine(Some(engine.clone()));
+
+        let mut evm = intent(1_000);
+        evm.receiver = engine.clone();
+        evm.aurora_recipient = Some("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED".to_string());
+        rehash(&mut evm);
+        let intent_id = create_approved(&mut contract, evm);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .expect("No deposit receipt");
+        let VmAction::FunctionCall {
+            function_name,
+            args,
+            ..
+        } = &receipt.actions[0]
+        else {
+            panic!("Expected a function call");
+        };
+        assert_eq!(function_name, "ft_transfer_call");
+        let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+        assert_eq!(args["receiver_id"], engine.to_string());
+        assert_eq!(args["msg"], "5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+    }
+
+    /// Start executing a 1000 token intent delivered to an Aurora address
+    fn execute_into_aurora(contract: &mut CrossChainExecutor) -> String {
+        let engine: AccountId = "aurora".parse().unwrap();
+        set_caller(&executor(), 0);
+        contract.set_aurora_engine(Some(engine.clone()));
+        let mut evm = intent(1_000);
+        evm.receiver = engine;
+        evm.aurora_recipient = Some("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED".to_string());
+        rehash(&mut evm);
+        let intent_id = create_approved(contract, evm);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        intent_id
+    }
+
+    #[test]
+    fn aurora_deposits_kept_in_full_complete() {
+        let mut contract = setup();
+        let intent_id = execute_into_aurora(&mut contract);
+
+        resolve_callback(PromiseResult::Successful(b"\"1000\"".to_vec()));
+        contract.on_token_deposited(U128(1_000));
+        contract.on_transfer_complete(intent_id.clone(), U128(0), None, Ok(()));
+        assert_eq!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Completed)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Deposit was refunded")]
+    fn aurora_deposits_the_engine_refunded_fail() {
+        let mut contract = setup();
+        execute_into_aurora(&mut contract);
+
+        resolve_callback(PromiseResult::Successful(b"\"0\"".to_vec()));
+        contract.on_token_deposited(U128(1_000));
+    }
+
+    #[test]
+    fn execute_transfers_multi_tokens_by_id() {
+        let mut contract = setup();
+        let multi_token: AccountId = "mt.near".parse().unwrap();
+        set_caller(&executor(), 0);
+        contract.register_token(
+            tokens::TokenMapping {
+                near_contract: multi_token.clone(),
+                multi_token: true,
+                ..mapping("0x3333333333333333333333333333333333333333", 0, 0)
+            },
+            None,
+        );
+        let mut mt = intent(5);
+        mt.token = "0x3333333333333333333333333333333333333333".to_string();
+        rehash(&mut mt);
+        assert_eq!(
+            contract.validate_new_intent(&mt),
+            Err(ContractError::MultiTokenIdMissing)
+        );
+
+        mt.mt_token_id = Some("gold".to_string());
+        rehash(&mut mt);
+        let intent_id = create_approved(&mut contract, mt);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == multi_token)
+            .expect("No mt_transfer receipt");
+        let VmAction::FunctionCall {
+            function_name,
+            args,
+            ..
+        } = &receipt.actions[0]
+        else {
+            panic!("Expected a function call");
+        };
+        assert_eq!(function_name, "mt_transfer");
+        let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+        assert_eq!(args["receiver_id"], accounts(2).to_string());
+        assert_eq!(args["token_id"], "gold");
+        assert_eq!(args["amount"], "5");
+    }
+
+    #[test]
+    #[should_panic(expected = "Swap output below minimum")]
+    fn refunded_swaps_fail_their_transfer() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.on_ref_swap(U128(1_000), U64(1_000), Ok(U128(0)));
+    }
+
+    #[test]
+    fn swaps_consuming_their_input_by_the_deadline_succeed() {
+        let mut contract = setup();
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(executor())
+            .block_timestamp(1_000)
+            .build());
+        contract.on_ref_swap(U128(1_000), U64(1_000), Ok(U128(1_000)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Swap resolved after its deadline")]
+    fn swaps_resolving_after_their_deadline_fail_their_transfer() {
+        let mut contract = setup();
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(executor())
+            .block_timestamp(1_001)
+            .build());
+        contract.on_ref_swap(U128(1_000), U64(1_000), Ok(U128(1_000)));
+    }
+
+    #[test]
+    fn swaps_past_their_deadline_fail_for_refund() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_ref_contract(Some("v2.ref-finance.near".parse().unwrap()));
+
+        let intent_id = create_approved(&mut contract, swap_intent(1_000));
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(agent())
+            .block_timestamp(1_001)
+            .build());
+        contract.execute_intent(intent_id.clone());
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Failed(_))
+        ));
+    }
+
+    #[test]
+    fn create_rejects_settlement_without_near_intents_contract() {
+        let mut contract = setup();
+        let mut settled = intent(1_000);
+        settled.settle_in_defuse = true;
+        rehash(&mut settled);
+        set_caller(&agent(), ONE_NEAR);
+        let report = contract.create_intents(vec![settled]);
+        assert_eq!(
+            report[0].error.as_deref(),
+            Some("NEAR Intents contract not configured")
+        );
+    }
+
+    fn register_solver(contract: &mut CrossChainExecutor, solver: &str) -> AccountId {
+        let solver: AccountId = solver.parse().unwrap();
+        set_caller(&solver, 10 * ONE_NEAR);
+        contract.register_solver(vec![solvers::TokenPair {
+            chain_id: BASE_CHAIN_ID,
+            source_token: BASE_TOKEN.to_string(),
+            near_token: token().to_string(),
+        }]);
+        solver
+    }
+
+    #[test]
+    fn solver_claims_and_executes_intent() {
+        let mut contract = setup();
+        let solver = register_solver(&mut contract, "solver.near");
+        assert_eq!(contract.get_solvers().len(), 1);
+
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&solver, 0);
+        contract.claim_intent(intent_id.clone());
+        assert_eq!(
+            contract.get_intent_claim(intent_id.clone()).unwrap().solver,
+            solver
+        );
+
+        contract.execute_intent(intent_id.clone());
+        assert!(matches!(
+            contract.get_intent_status(intent_id.clone()),
+            Some(IntentStatus::Executing)
+        ));
+        assert!(contract.get_intent_claim(intent_id).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent claimed by a solver")]
+    fn agent_cannot_execute_claimed_intent() {
+        let mut contract = setup();
+        let solver = register_solver(&mut contract, "solver.near");
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&solver, 0);
+        contract.claim_intent(intent_id.clone());
+
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+    }
+
+    #[test]
+    fn execution_binds_best_quote_and_pays_its_spread() {
+        let mut contract = setup();
+        let low = register_solver(&mut contract, "low.near");
+        let high = register_solver(&mut contract, "high.near");
+        let intent_id = create_approved(&mut contract, quoted_intent(1_000, 900));
+        let expiry = U64(env::block_timestamp() + 1_000);
+        set_caller(&low, 0);
+        contract.submit_quote(intent_id.clone(), U128(900), expiry);
+        set_caller(&high, 0);
+        contract.submit_quote(intent_id.clone(), U128(950), expiry);
+        assert_eq!(
+            contract.get_best_quote(intent_id.clone()).unwrap().solver,
+            high
+        );
+
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        let amounts = |receipts: Vec<near_sdk::mock::Receipt>| -> Vec<serde_json::Value> {
+            receipts
+                .into_iter()
+                .filter(|receipt| receipt.receiver_id == token())
+                .map(|receipt| match &receipt.actions[0] {
+                    VmAction::FunctionCall { args, .. } => serde_json::from_slice(args).unwrap(),
+                    _ => panic!("Expected a function call"),
+                })
+                .collect()
+        };
+        let transfers = amounts(get_created_receipts());
+        assert_eq!(transfers[0]["receiver_id"], accounts(2).to_string());
+        assert_eq!(transfers[0]["amount"], "950");
+        assert!(contract.get_quotes(intent_id.clone()).is_empty());
+
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(executor())
+                .predecessor_account_id(executor())
+                .build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])],
+        );
+        contract.on_transfer_complete(intent_id, U128(0), None, Ok(()));
+        let spreads = amounts(get_created_receipts());
+        assert_eq!(spreads[0]["receiver_id"], high.to_string());
+        assert_eq!(spreads[0]["amount"], "50");
+    }
+
+    #[test]
+    #[should_panic(expected = "Quote is below the intent's minimum output")]
+    fn quotes_below_the_signed_minimum_output_are_rejected() {
+        let mut contract = setup();
+        let solver = register_solver(&mut contract, "solver.near");
+        let intent_id = create_approved(&mut contract, quoted_intent(1_000, 900));
+
+        set_caller(&solver, 0);
+        contract.submit_quote(intent_id, U128(1), U64(env::block_timestamp() + 1_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Intent takes no quotes")]
+    fn intents_without_a_minimum_output_take_no_quotes() {
+        let mut contract = setup();
+        let solver = register_solver(&mut contract, "solver.near");
+        let intent_id = create_approved(&mut contract, intent(1_000));
+
+        set_caller(&solver, 0);
+        contract.submit_quote(intent_id, U128(900), U64(env::block_timestamp() + 1_000));
+    }
+
+    #[test]
+    fn auction_minimum_output_decays_to_its_floor() {
+        let mut contract = setup();
+        let solver = register_solver(&mut contract, "solver.near");
+        let intent_id = create_approved(&mut contract, quoted_intent(1_000, 600));
+        set_caller(&agent(), 0);
+        contract.start_auction(intent_id.clone(), U128(600), U64(1_000));
+        assert_eq!(contract.get_min_out(intent_id.clone()), Some(U128(1_000)));
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(solver)
+            .block_timestamp(250)
+            .build());
+        assert_eq!(contract.get_min_out(intent_id.clone()), Some(U128(900)));
+        contract.submit_quote(intent_id.clone(), U128(900), U64(2_000));
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .block_timestamp(5_000)
+            .build());
+        assert_eq!(contract.get_min_out(intent_id), Some(U128(600)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Quote is below the auction's minimum output")]
+    fn auction_rejects_quotes_below_minimum_output() {
+        let mut contract = setup();
+        let solver = register_solver(&mut contract, "solver.near");
+        let intent_id = create_approved(&mut contract, quoted_intent(1_000, 600));
+        set_caller(&agent(), 0);
+        contract.start_auction(intent_id.clone(), U128(600), U64(1_000));
+
+        set_caller(&solver, 0);
+        contract.submit_quote(intent_id, U128(800), U64(2_000));
+    }
+
+    #[test]
+    fn high_value_executions_go_through_an_escrow_subaccount() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_escrow_code(Base64VecU8(vec![0; 8]));
+        contract.set_escrow_threshold(Some(U128(500)));
+
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        // Fund the escrow's storage deposit
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(agent())
+            .account_balance(1_000 * ONE_NEAR)
+            .build());
+        contract.execute_intent(intent_id.clone());
+        let escrow = contract
+            .get_escrow_account(intent_id.clone())
+            .expect("No escrow account");
+        assert!(escrow.as_str().ends_with(".executor.near"));
+        let receipts = get_created_receipts();
+        let creation = receipts
+            .iter()
+            .find(|receipt| receipt.receiver_id == escrow)
+            .expect("No escrow receipt");
+        assert!(matches!(creation.actions[0], VmAction::CreateAccount));
+
+        testing_env!(
+            VMContextBuilder::new()
+                .current_account_id(executor())
+                .predecessor_account_id(executor())
+                .build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])],
+        );
+        contract.on_transfer_complete(intent_id.clone(), U128(0), None, Ok(()));
+        assert!(contract.get_escrow_account(intent_id).is_none());
+        assert!(get_created_receipts().iter().any(|receipt| {
+            receipt.receiver_id == escrow
+                && matches!(
+                    &receipt.actions[0],
+                    VmAction::FunctionCall { function_name, .. } if function_name == "close"
+                )
+        }));
+    }
+
+    /// Delegate action of the receiver calling `method_name` for an intent,
+    /// signed with `keypair`
+    fn signed_delegate(
+        keypair: &ed25519_dalek::Keypair,
+        public_key: PublicKey,
+        method_name: &str,
+        intent_id: &str,
+        nonce: u64,
+    ) -> delegate::SignedDelegateAction {
+        use ed25519_dalek::Signer;
+        let delegate_action = delegate::DelegateAction {
+            sender_id: accounts(2),
+            receiver_id: executor(),
+            actions: vec![delegate::DelegateFunctionCall {
+                method_name: method_name.to_string(),
+                args: Base64VecU8(json!({ "intent_id": intent_id }).to_string().into_bytes()),
+                gas: U64(30_000_000_000_000),
+                deposit: U128(0),
+            }],
+            nonce: U64(nonce),
+            max_block_height: U64(100),
+            public_key,
+        };
+        let signature = keypair.sign(&delegate_action.signing_hash());
+        delegate::SignedDelegateAction {
+            delegate_action,
+            signature: Base64VecU8(signature.to_bytes().to_vec()),
+        }
+    }
+
+    #[test]
+    fn receiver_cancels_intent_through_relayed_delegate_action() {
+        let mut contract = setup();
+        let (keypair, public_key) = keypair(7);
+        set_caller(&accounts(2), 0);
+        contract.add_delegate_key(public_key.clone());
+        let intent_id = create_approved(&mut contract, intent(1_000));
+
+        set_caller(&agent(), 0);
+        contract.execute_delegate_action(signed_delegate(
+            &keypair,
+            public_key,
+            "cancel_intent",
+            &intent_id,
+            1,
+        ));
+        assert!(matches!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Cancelled)
+        ));
+        assert_eq!(contract.get_delegate_nonce(accounts(2)), U64(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "Key cannot sign for the sender")]
+    fn delegate_action_rejects_unregistered_keys() {
+        let mut contract = setup();
+        let (keypair, public_key) = keypair(7);
+        let intent_id = create_approved(&mut contract, intent(1_000));
+
+        set_caller(&agent(), 0);
+        contract.execute_delegate_action(signed_delegate(
+            &keypair,
+            public_key,
+            "execute_intent",
+            &intent_id,
+            1,
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid delegate action signature")]
+    fn delegate_action_rejects_altered_actions() {
+        let mut contract = setup();
+        let (keypair, public_key) = keypair(7);
+        set_caller(&accounts(2), 0);
+        contract.add_delegate_key(public_key.clone());
+        let intent_id = create_approved(&mut contract, intent(1_000));
+
+        let mut signed = signed_delegate(&keypair, public_key, "cancel_intent", &intent_id, 1);
+        signed.delegate_action.nonce = U64(2);
+        set_caller(&agent(), 0);
+        contract.execute_delegate_action(signed);
+    }
+
+    #[test]
+    #[should_panic(expected = "Delegate action nonce already used")]
+    fn delegate_action_rejects_replayed_nonces() {
+        let mut contract = setup();
+        let (keypair, public_key) = keypair(7);
+        set_caller(&accounts(2), 0);
+        contract.add_delegate_key(public_key.clone());
+        let intent_id = create_approved(&mut contract, intent(1_000));
+
+        let signed = signed_delegate(&keypair, public_key, "cancel_intent", &intent_id, 1);
+        set_caller(&agent(), 0);
+        contract.execute_delegate_action(signed.clone());
+        contract.execute_delegate_action(signed);
+    }
+
+    fn release_params() -> chain_signatures::ReleaseTxParams {
+        chain_signatures::ReleaseTxParams {
+            nonce: U64(7),
+            max_priority_fee_per_gas: U128(1_000_000),
+            max_fee_per_gas: U128(2_000_000_000),
+            gas_limit: U64(120_000),
+        }
+    }
+
+    #[test]
+    fn attested_outbound_intents_are_signed_by_the_mpc_signer() {
+        let mut contract = setup();
+        let signer: AccountId = "v1.signer".parse().unwrap();
+        set_caller(&executor(), 0);
+        contract.set_signer_contract(Some(signer.clone()));
+
+        register_wnear(&mut contract);
+
+        set_caller(&agent(), 5 * ONE_NEAR);
+        let intent_id = contract.create_outbound_intent(
+            "0x71c7656ec7ab88b098defb751b7401b5f6d8976f".to_string(),
+            BASE_WNEAR.to_string(),
+        );
+        set_caller(&validator(), 0);
+        contract.approve_outbound_intent(intent_id.clone());
+
+        set_caller(&agent(), 1);
+        contract.request_release_signature(intent_id.clone(), release_params());
+
+        let sign = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == signer)
+            .expect("No sign receipt");
+        match &sign.actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                args,
+                deposit,
+                ..
+            } => {
+                assert_eq!(function_name, "sign");
+                assert_eq!(*deposit, 1);
+                let args: serde_json::Value = serde_json::from_slice(args).unwrap();
+                assert_eq!(args["request"]["path"], chain_signatures::RELEASE_KEY_PATH);
+                assert_eq!(args["request"]["payload"].as_array().unwrap().len(), 32);
+            }
+            _ => panic!("Expected a function call"),
+        }
+
+        let response = json!({
+            "big_r": { "affine_point": "02ab" },
+            "s": { "scalar": "cd" },
+            "recovery_id": 1,
+        });
+        assert!(contract.on_release_signed(
+            intent_id.clone(),
+            "0x02".to_string(),
+            Ok(serde_json::from_value(response).unwrap()),
+        ));
+        let signature = contract.get_release_signature(intent_id).unwrap();
+        assert_eq!(signature.big_r, "02ab");
+        assert_eq!(signature.recovery_id, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough validator approvals")]
+    fn release_signatures_require_attestation() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_signer_contract(Some("v1.signer".parse().unwrap()));
+
+        register_wnear(&mut contract);
+
+        set_caller(&agent(), 5 * ONE_NEAR);
+        let intent_id = contract.create_outbound_intent(
+            "0x71c7656ec7ab88b098defb751b7401b5f6d8976f".to_string(),
+            BASE_WNEAR.to_string(),
+        );
+
+        set_caller(&agent(), 1);
+        contract.request_release_signature(intent_id, release_params());
+    }
+
+    #[test]
+    fn fuzz_seeds_panic_cleanly() {
+        let call = |method: &str, args: serde_json::Value| {
+            json!({ "method": method, "args": args }).to_string().into_bytes()
+        };
+        let mut valid = serde_json::to_value(intent(1_000)).unwrap();
+        valid["status"] = json!("Pending");
+        let create_with = |field: &str, value: serde_json::Value| {
+            let mut intent = valid.clone();
+            intent[field] = value;
+            call("create_intent", json!({ "intent": intent }))
+        };
+        let max_u128 = u128::MAX.to_string();
+        let malformed: [&[u8]; 4] = [
+            b"",
+            b"{",
+            b"\xff\xfe",
+            br#"{"method":"create_intent","args":{"intent":{}}}"#,
+        ];
+
+        let calls = [
+            create_with("amount", json!(max_u128)),
+            create_with("amount", json!("340282366920938463463374607431768211456")),
+            create_with("amount", json!("0")),
+            create_with("id", json!("")),
+            create_with("sender", json!("")),
+            create_with("sender", json!("0x")),
+            create_with("sender", json!("0xé1c7656ec7ab88b098defb751b7401b5f6d8976")),
+            create_with("token", json!("")),
+            create_with("token", json!("ü")),
+            create_with("receiver", json!("álice.near")),
+            create_with("expires_at", json!("0")),
+            create_with("nonce", json!(u64::MAX)),
+            create_with("chain_id", json!(0)),
+            call("compute_intent_hash", json!({ "intent": valid })),
+            call("approve_intent", json!({ "intent_id": "" })),
+            call("execute_intent", json!({ "intent_id": "\u{0}" })),
+            call(
+                "get_intents",
+                json!({ "from_index": max_u128, "limit": u64::MAX }),
+            ),
+            call(
+                "get_intents_by_status",
+                json!({ "status": { "Failed": "" }, "limit": 0 }),
+            ),
+            call("get_intents_bulk", json!({ "intent_ids": vec![""; 1_000] })),
+            call(
+                "update_config",
+                json!({ "update": { "required_signatures": 0 } }),
+            ),
+            call(
+                "update_config",
+                json!({ "update": { "unbonding_period": u64::MAX.to_string() } }),
+            ),
+            call(
+                "register_token",
+                json!({ "mapping": {
+                    "base_address": "",
+                    "near_contract": "token.near",
+                    "decimals": { "source": 255, "near": 0 },
+                    "symbol": "",
+                    "enabled": true,
+                    "mintable": false,
+                } }),
+            ),
+            call(
+                "set_rate_limit",
+                json!({
+                    "token": "",
+                    "limit": { "window_blocks": u64::MAX, "max_executions": 0 },
+                }),
+            ),
+            call(
+                "set_trusted_header",
+                json!({ "header_rlp": "", "confirmations": u64::MAX }),
+            ),
+            call(
+                "set_trusted_header",
+                json!({ "header_rlp": "/w==", "confirmations": 0 }),
+            ),
+            call(
+                "attest_intent",
+                json!({
+                    "intent_id": "",
+                    "attestation": { "epoch": u64::MAX, "signature": "AAA=" },
+                }),
+            ),
+            call(
+                "submit_quote",
+                json!({ "intent_id": "", "out_amount": "0", "expiry": "0" }),
+            ),
+            call(
+                "grant_role",
+                json!({ "role": "Agent", "account_id": "álice.near" }),
+            ),
+            call("register_validator", json!({ "public_key": "ed25519:" })),
+            call(
+                "ft_on_transfer",
+                json!({ "sender_id": "bob.near", "amount": max_u128, "msg": "ü" }),
+            ),
+            call(
+                "ft_on_transfer",
+                json!({ "sender_id": "bob.near", "amount": "1", "msg": "{\"recipient\":\"\"}" }),
+            ),
+        ];
+        for input in malformed.iter().copied().chain(calls.iter().map(Vec::as_slice)) {
+            fuzzing::fuzz_method_args(input);
+        }
+    }
+
+    #[test]
+    fn schemas_describe_serialized_payloads() {
+        let schemas = schema::schemas();
+        let schema = |name: &str| {
+            let (_, schema) = schemas.iter().find(|(stem, _)| *stem == name).unwrap();
+            serde_json::to_value(schema).unwrap()
+        };
+
+        // Every field an intent serializes is a property of its schema
+        let intent = serde_json::to_value(intent(1_000)).unwrap();
+        let fields: std::collections::BTreeSet<_> = intent.as_object().unwrap().keys().collect();
+        let intent_schema = schema("cross_chain_intent");
+        let properties: std::collections::BTreeSet<_> =
+            intent_schema["properties"].as_object().unwrap().keys().collect();
+        assert_eq!(fields, properties);
+
+        // Event logs carry the standard next to the tagged event
+        let event_schema = schema("intent_event").to_string();
+        for field in ["\"standard\"", "\"version\"", "\"intent_created\"", "\"data\""] {
+            assert!(event_schema.contains(field), "{} missing", field);
+        }
+    }
+
+    #[test]
+    fn bench_state_loads_as_the_contract() {
+        let params = bench::SeedParams {
+            executor: executor(),
+            agent: agent(),
+            validator: validator(),
+            validator_key: "ed25519:6E8sCci9badyRkXb3JoRpBj5p8C6Tw41ELDZoiihKEtp"
+                .parse()
+                .unwrap(),
+            token: token(),
+            base_token: BASE_TOKEN.to_string(),
+            base_bridge: BASE_BRIDGE.to_string(),
+            receiver: accounts(2),
+            intents: 120,
+        };
+        let storage = bench::seeded_state(&params).into_iter().collect();
+        env::set_blockchain_interface(near_sdk::MockedBlockchain::new(
+            VMContextBuilder::new().current_account_id(executor()).build(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            vec![],
+            storage,
+            Default::default(),
+            None,
+        ));
+
+        // A fresh intent executes against the seeded state like any other
+        let mut contract: CrossChainExecutor = env::state_read().unwrap();
+        assert_eq!(contract.get_intent_count(), 120);
+        assert_eq!(contract.get_intents_by_receiver(accounts(2), None, None).len(), 2);
+        let args = execute(&mut contract, intent(1_000));
+        assert_eq!(args["amount"], "1000");
+        assert_eq!(contract.get_intent_count(), 121);
+    }
+
+    /// Every role and state check guarding a state-changing method, each
+    /// exercised by a caller that must be turned away
+    mod access_control {
+        use super::*;
+
+        fn stranger() -> AccountId {
+            accounts(3)
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Agent")]
+        fn non_agents_cannot_create_intents() {
+            let mut contract = setup();
+            set_caller(&stranger(), ONE_NEAR);
+            contract.create_intent(intent(1_000));
+        }
+
+        #[test]
+        #[should_panic(expected = "Agent is inactive")]
+        fn inactive_agents_cannot_create_intents() {
+            let mut contract = setup();
+            set_caller(&executor(), 0);
+            contract.set_agent_active(agent(), false);
+
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(intent(1_000));
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Validator")]
+        fn non_validators_cannot_approve_intents() {
+            let mut contract = setup();
+            let intent = intent(1_000);
+            let intent_id = intent.id.clone();
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(intent);
+
+            set_caller(&stranger(), 0);
+            contract.approve_intent(intent_id);
+        }
+
+        #[test]
+        #[should_panic(expected = "Validator already approved this intent")]
+        fn validators_cannot_approve_twice() {
+            let mut contract = setup();
+            let intent_id = create_approved(&mut contract, intent(1_000));
+
+            set_caller(&validator(), 0);
+            contract.approve_intent(intent_id);
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Agent")]
+        fn non_agents_cannot_execute_intents() {
+            let mut contract = setup();
+            let intent_id = create_approved(&mut contract, intent(1_000));
+
+            set_caller(&stranger(), 0);
+            contract.execute_intent(intent_id);
+        }
+
+        #[test]
+        #[should_panic(expected = "Intent is not pending")]
+        fn executing_intents_cannot_execute_again() {
+            let mut contract = setup();
+            let intent_id = create_approved(&mut contract, intent(1_000));
+            set_caller(&agent(), 0);
+            contract.execute_intent(intent_id.clone());
+
+            contract.execute_intent(intent_id);
+        }
+
+        #[test]
+        #[should_panic(expected = "Intent is executing")]
+        fn executing_intents_cannot_expire() {
+            let mut contract = setup();
+            let mut intent = intent(1_000);
+            intent.expires_at = U64(1_000);
+            rehash(&mut intent);
+            let intent_id = create_approved(&mut contract, intent);
+            set_caller(&agent(), 0);
+            contract.execute_intent(intent_id.clone());
+
+            testing_env!(VMContextBuilder::new()
+                .current_account_id(executor())
+                .predecessor_account_id(stranger())
+                .block_timestamp(2_000)
+                .build());
+            contract.expire_intent(intent_id);
+        }
+
+        #[test]
+        #[should_panic(expected = "Intent is not pending")]
+        fn completed_intents_cannot_execute_again() {
+            let mut contract = setup();
+            let intent_id = create_approved(&mut contract, intent(1_000));
+            set_caller(&agent(), 0);
+            contract.execute_intent(intent_id.clone());
+            set_caller(&executor(), 0);
+            assert!(contract.on_transfer_complete(intent_id.clone(), U128(0), None, Ok(())));
+
+            set_caller(&agent(), 0);
+            contract.execute_intent(intent_id);
+        }
+
+        #[test]
+        #[should_panic(expected = "Intent already executed")]
+        fn intents_cannot_be_created_twice() {
+            let mut contract = setup();
+            let intent = intent(1_000);
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(intent.clone());
+
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(intent);
+        }
+
+        #[test]
+        #[should_panic(expected = "Intent already executed")]
+        fn intents_cannot_share_a_deposit() {
+            let mut contract = setup();
+            let first = intent(1_000);
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(first.clone());
+            assert_eq!(
+                contract.get_deposit_intent(1, first.proof.receipt_index, 0, None),
+                Some(first.id.clone())
+            );
+
+            let mut second = intent(1_000);
+            second.proof.receipt_index = first.proof.receipt_index;
+            second.proof.transaction_hash = first.proof.transaction_hash.to_uppercase();
+            rehash(&mut second);
+            // The same deposit derives the same ID, whatever else differs
+            assert_eq!(second.id, first.id);
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(second);
+        }
+
+        #[test]
+        #[should_panic(expected = "Deposit already backs an intent")]
+        fn intents_cannot_claim_a_used_deposit_under_another_hash() {
+            let mut contract = setup();
+            let first = intent(1_000);
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(first.clone());
+
+            let mut second = intent(1_000);
+            second.proof.receipt_index = first.proof.receipt_index;
+            second.proof.transaction_hash = format!("0x{}", "ab".repeat(32));
+            rehash(&mut second);
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(second);
+        }
+
+        #[test]
+        #[should_panic(expected = "Proof is too old")]
+        fn stale_proofs_cannot_back_intents() {
+            let mut contract = setup();
+            testing_env!(VMContextBuilder::new()
+                .current_account_id(executor())
+                .predecessor_account_id(agent())
+                .attached_deposit(ONE_NEAR)
+                .block_timestamp(network::MAX_PROOF_AGE + 1)
+                .build());
+            contract.create_intent(intent(1_000));
+        }
+
+        #[test]
+        #[should_panic(expected = "Proof block is not checkpointed yet")]
+        fn proofs_past_the_checkpoint_cannot_back_intents() {
+            let mut contract = setup();
+            let mut intent = intent(1_000);
+            submit_proof(&mut contract, &mut intent);
+            intent.proof.block_number += 1;
+
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(intent);
+        }
+
+        #[test]
+        #[should_panic(expected = "Chain has no checkpoint")]
+        fn proofs_of_chains_without_a_checkpoint_cannot_back_intents() {
+            let mut contract = setup();
+            contract.checkpoints.remove(&BASE_CHAIN_ID);
+
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(intent(1_000));
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Agent")]
+        fn non_agents_cannot_cancel_intents() {
+            let mut contract = setup();
+            let intent = intent(1_000);
+            let intent_id = intent.id.clone();
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(intent);
+
+            set_caller(&stranger(), 0);
+            contract.cancel_intent(intent_id);
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Agent")]
+        fn non_agents_cannot_refund_intents() {
+            let mut contract = setup();
+            let intent_id = create_approved(&mut contract, intent(1_000));
+            set_caller(&agent(), 0);
+            contract.execute_intent(intent_id.clone());
+            set_caller(&executor(), 0);
+            let failed = Err(PromiseError::Failed);
+            contract.on_transfer_complete(intent_id.clone(), U128(0), None, failed);
+
+            set_caller(&stranger(), 0);
+            contract.refund_intent(intent_id);
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Owner")]
+        fn non_owners_cannot_remove_validators() {
+            let mut contract = setup();
+            set_caller(&agent(), 0);
+            contract.remove_validator(&validator());
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Owner")]
+        fn validators_cannot_remove_other_validators() {
+            let mut contract = setup();
+            set_caller(&validator(), 0);
+            contract.remove_validator(&stranger());
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Owner")]
+        fn non_owners_cannot_grant_roles() {
+            let mut contract = setup();
+            set_caller(&agent(), 0);
+            contract.grant_role(Role::Validator, stranger());
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Owner")]
+        fn non_owners_cannot_update_config() {
+            let mut contract = setup();
+            set_caller(&agent(), 0);
+            contract.update_config(config::ConfigUpdate {
+                required_signatures: Some(0),
+                ..Default::default()
+            });
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Owner")]
+        fn non_owners_cannot_register_tokens() {
+            let mut contract = setup();
+            set_caller(&agent(), 0);
+            contract.register_token(
+                mapping("0x2222222222222222222222222222222222222222", 6, 6),
+                None,
+            );
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Owner")]
+        fn previous_owner_cannot_execute_governance_actions() {
+            let mut contract = setup();
+            transfer_to_dao(&mut contract);
+            set_caller(&executor(), 0);
+            contract.execute_governance_action(governance::GovernanceAction::RemoveValidator {
+                validator: validator(),
+            });
+        }
+
+        #[test]
+        #[should_panic(expected = "Change must be queued in the admin timelock")]
+        fn timelocked_changes_cannot_be_made_directly() {
+            let mut contract = setup();
+            let id = queue_fee_change(&mut contract);
+            set_caller(&executor(), 0);
+            contract.cancel_admin_action(id);
+            contract.register_token(mapping(BASE_TOKEN, 6, 6), None);
+        }
+
+        #[test]
+        #[should_panic(expected = "Only the emergency DAO can approve withdrawals")]
+        fn owner_cannot_approve_emergency_withdrawals() {
+            let mut contract = setup();
+            set_caller(&executor(), 0);
+            contract.set_emergency_dao(accounts(5));
+            contract.approve_emergency_withdrawal("0x00".to_string());
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Owner")]
+        fn non_owners_cannot_trust_headers() {
+            let mut contract = setup();
+            // The role check runs before the header is decoded
+            set_caller(&agent(), 0);
+            contract.set_trusted_header(Base64VecU8(vec![]), Some(0), None);
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Guardian or Owner")]
+        fn agents_cannot_pause() {
+            let mut contract = setup();
+            set_caller(&agent(), 0);
+            contract.pause();
+        }
+
+        #[test]
+        #[should_panic(expected = "Method requires role Owner")]
+        fn guardians_cannot_unpause() {
+            let mut contract = setup();
+            set_caller(&executor(), 0);
+            contract.grant_role(Role::Guardian, stranger());
+            contract.pause();
+
+            set_caller(&stranger(), 0);
+            contract.unpause();
+        }
+
+        #[test]
+        #[should_panic(expected = "Contract is paused")]
+        fn paused_contracts_reject_new_intents() {
+            let mut contract = setup();
+            set_caller(&executor(), 0);
+            contract.pause();
+
+            set_caller(&agent(), ONE_NEAR);
+            contract.create_intent(intent(1_000));
+        }
+
+        #[test]
+        #[should_panic(expected = "Contract is paused")]
+        fn paused_contracts_reject_executions() {
+            let mut contract = setup();
+            let intent_id = create_approved(&mut contract, intent(1_000));
+            set_caller(&executor(), 0);
+            contract.pause();
+
+            set_caller(&agent(), 0);
+            contract.execute_intent(intent_id);
+        }
+    }
+
+    /// Property tests driving random sequences of lifecycle calls over a few
+    /// intents. Each call runs as a transaction would, a panic rolling back
+    /// its state changes, and the lifecycle invariants are checked after
+    /// every call. Failures shrink to the shortest sequence of calls that
+    /// still breaks an invariant.
+    mod state_machine {
+        use super::*;
+        use near_sdk::{MockedBlockchain, RuntimeFeesConfig, VMConfig, VMContext};
+        use proptest::collection::vec;
+        use proptest::prelude::*;
+        use proptest::strategy::ValueTree;
+        use proptest::test_runner::TestRunner;
+        use std::collections::HashMap;
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        const CASES: u32 = 64;
+        const STEPS: usize = 40;
+        const INTENTS: usize = 3;
+        const AMOUNT: u128 = 1_000;
+
+        #[derive(Debug, Clone, Copy)]
+        enum Call {
+            Create(usize),
+            Approve(usize),
+            Cancel(usize),
+            Execute(usize),
+            /// Deliver the callback of an execution, its transfer having
+            /// succeeded or failed
+            Resolve(usize, bool),
+            Expire(usize),
+            Refund(usize),
+            Advance(u64),
+        }
+
+        fn call() -> impl Strategy<Value = Call> {
+            let intent = 0..INTENTS;
+            prop_oneof![
+                intent.clone().prop_map(Call::Create),
+                intent.clone().prop_map(Call::Approve),
+                intent.clone().prop_map(Call::Cancel),
+                intent.clone().prop_map(Call::Execute),
+                (intent.clone(), any::<bool>())
+                    .prop_map(|(intent, succeeded)| Call::Resolve(intent, succeeded)),
+                intent.clone().prop_map(Call::Expire),
+                intent.prop_map(Call::Refund),
+                (1..=1_000u64).prop_map(Call::Advance),
+            ]
+        }
+
+        fn set_blockchain(context: VMContext, storage: HashMap<Vec<u8>, Vec<u8>>) {
+            env::set_blockchain_interface(MockedBlockchain::new(
+                context,
+                VMConfig::test(),
+                RuntimeFeesConfig::test(),
+                vec![],
+                storage,
+                Default::default(),
+                None,
+            ));
+        }
+
+        struct Runner {
+            contract: CrossChainExecutor,
+            intents: Vec<CrossChainIntent>,
+            timestamp: u64,
+            /// Executions whose callback has not arrived yet
+            awaiting_callback: Vec<bool>,
+            executions: Vec<u32>,
+            delivered: Vec<u128>,
+        }
+
+        impl Runner {
+            fn new() -> Self {
+                // Start from empty storage, each case deploying afresh
+                set_blockchain(VMContextBuilder::new().build(), HashMap::new());
+                let mut contract = setup();
+                let intents = (0..INTENTS)
+                    .map(|i| {
+                        let mut intent = intent(AMOUNT);
+                        intent.expires_at = U64(2_000 + 1_000 * i as u64);
+                        intent.proof.block_number = 1 + i as u64;
+                        rehash(&mut intent);
+                        submit_proof(&mut contract, &mut intent);
+                        intent
+                    })
+                    .collect();
+                // Write out the collections so every transaction starts
+                // from the full state
+                env::state_write(&contract);
+                drop(contract);
+                Self {
+                    contract: env::state_read().unwrap(),
+                    intents,
+                    timestamp: 0,
+                    awaiting_callback: vec![false; INTENTS],
+                    executions: vec![0; INTENTS],
+                    delivered: vec![0; INTENTS],
+                }
+            }
+
+            /// Run `call` as `caller`, restoring the state it started from
+            /// if it panics. Returns `None` when it panicked.
+            fn transact<R>(
+                &mut self,
+                caller: &AccountId,
+                deposit: Balance,
+                call: impl FnOnce(&mut CrossChainExecutor) -> R,
+            ) -> Option<R> {
+                env::state_write(&self.contract);
+                let storage = near_sdk::mock::with_mocked_blockchain(|b| b.take_storage());
+                let context = VMContextBuilder::new()
+                    .current_account_id(executor())
+                    .predecessor_account_id(caller.clone())
+                    .attached_deposit(deposit)
+                    .block_timestamp(self.timestamp)
+                    .build();
+                set_blockchain(context.clone(), storage.clone());
+
+                let result = catch_unwind(AssertUnwindSafe(|| {
+                    let mut contract: CrossChainExecutor = env::state_read().unwrap();
+                    let result = call(&mut contract);
+                    env::state_write(&contract);
+                    result
+                }));
+                if result.is_err() {
+                    set_blockchain(context, storage);
+                }
+                self.contract = env::state_read().unwrap();
+                result.ok()
+            }
+
+            fn run(&mut self, call: Call) {
+                match call {
+                    Call::Create(i) => {
+                        let intent = self.intents[i].clone();
+                        self.transact(&agent(), ONE_NEAR, |c| c.create_intent(intent));
+                    }
+                    Call::Approve(i) => {
+                        let intent_id = self.intents[i].id.clone();
+                        self.transact(&validator(), 0, |c| c.approve_intent(intent_id));
+                    }
+                    Call::Cancel(i) => {
+                        let intent_id = self.intents[i].id.clone();
+                        self.transact(&agent(), 0, |c| c.cancel_intent(intent_id));
+                    }
+                    Call::Execute(i) => {
+                        let intent_id = self.intents[i].id.clone();
+                        let started = self.transact(&agent(), 0, |c| c.execute_intent(intent_id));
+                        if let Some(PromiseOrValue::Promise(_)) = started {
+                            self.executions[i] += 1;
+                            self.awaiting_callback[i] = true;
+                            self.delivered[i] += transferred_amount();
+                        }
+                    }
+                    Call::Resolve(i, succeeded) => {
+                        // The runtime delivers exactly one callback per execution
+                        if !std::mem::take(&mut self.awaiting_callback[i]) {
+                            return;
+                        }
+                        let intent_id = self.intents[i].id.clone();
+                        let result = if succeeded {
+                            Ok(())
+                        } else {
+                            Err(PromiseError::Failed)
+                        };
+                        self.transact(&executor(), 0, |c| {
+                            c.on_transfer_complete(intent_id, U128(0), None, result)
+                        })
+                        .expect("Transfer callback panicked");
+                    }
+                    Call::Expire(i) => {
+                        let intent_id = self.intents[i].id.clone();
+                        self.transact(&accounts(3), 0, |c| c.expire_intent(intent_id));
+                    }
+                    Call::Refund(i) => {
+                        let intent_id = self.intents[i].id.clone();
+                        self.transact(&agent(), 0, |c| c.refund_intent(intent_id));
+                    }
+                    Call::Advance(nanos) => self.timestamp += nanos,
+                }
+            }
+
+            fn statuses(&self) -> Vec<Option<IntentStatus>> {
+                self.intents
+                    .iter()
+                    .map(|intent| self.contract.get_intent_status(intent.id.clone()))
+                    .collect()
+            }
+
+            /// Check the invariants against the statuses seen before the last call
+            fn check(&self, before: &[Option<IntentStatus>]) -> Result<(), String> {
+                for (i, (before, after)) in before.iter().zip(self.statuses()).enumerate() {
+                    match (before, &after) {
+                        (Some(before), Some(after)) if before.is_terminal() && before != after => {
+                            return Err(format!("intent {} left terminal {:?}", i, before));
+                        }
+                        (Some(before), Some(after))
+                            if before != after && !before.can_transition_to(after) =>
+                        {
+                            return Err(format!("intent {} moved {:?} to {:?}", i, before, after));
+                        }
+                        (Some(_), None) => return Err(format!("intent {} disappeared", i)),
+                        _ => {}
+                    }
+                    if self.executions[i] > 1 {
+                        return Err(format!("intent {} executed twice", i));
+                    }
+                    if self.delivered[i] > AMOUNT {
+                        return Err(format!("intent {} delivered {}", i, self.delivered[i]));
+                    }
+                    if after == Some(IntentStatus::Completed) && self.executions[i] != 1 {
+                        return Err(format!("intent {} completed without executing", i));
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        /// Amount of the ft_transfer the last call created
+        fn transferred_amount() -> u128 {
+            get_created_receipts()
+                .into_iter()
+                .filter(|receipt| receipt.receiver_id == token())
+                .flat_map(|receipt| receipt.actions)
+                .filter_map(|action| match action {
+                    VmAction::FunctionCall { args, .. } => {
+                        let args: serde_json::Value = serde_json::from_slice(&args).ok()?;
+                        args["amount"].as_str()?.parse::<u128>().ok()
+                    }
+                    _ => None,
+                })
+                .sum()
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+            #[test]
+            fn random_call_sequences_keep_lifecycle_invariants(calls in vec(call(), STEPS)) {
+                let mut runner = Runner::new();
+                for (step, call) in calls.iter().enumerate() {
+                    let before = runner.statuses();
+                    runner.run(*call);
+                    if let Err(violation) = runner.check(&before) {
+                        prop_assert!(false, "{} after {:?}", violation, &calls[..=step]);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn random_call_sequences_reach_every_outcome() {
+            let mut seen = HashMap::new();
+            // Sample the same sequences on every run so the check is stable
+            let mut sampler = TestRunner::deterministic();
+            let sequences = vec(call(), STEPS);
+            for _ in 0..CASES {
+                let calls = sequences.new_tree(&mut sampler).unwrap().current();
+                let mut runner = Runner::new();
+                for call in calls {
+                    runner.run(call);
+                }
+                for status in runner.statuses().into_iter().flatten() {
+                    *seen.entry(format!("{:?}", status)).or_insert(0) += 1;
+                }
+            }
+            // Guard against the generator no longer exercising the lifecycle
+            for status in ["Completed", "Cancelled", "Refunded"] {
+                assert!(seen.contains_key(status), "No intent ended {}", status);
+            }
+        }
     }
 }
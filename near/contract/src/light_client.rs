@@ -0,0 +1,399 @@
+//! Source chain light clients.
+//!
+//! Validators attest RLP-encoded block headers of each registered chain by
+//! signing their block hash. Each accepted header must extend a known parent,
+//! and the contract follows the highest chain it has seen, rewriting the
+//! canonical index on reorgs. A single aggregated validator signature may
+//! stand in for the individual ones. Receipt proofs are only checked against
+//! canonical headers buried under at least the chain's `confirmations`
+//! blocks. Methods take an optional chain ID defaulting to Base.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+use schemars::JsonSchema;
+
+use crate::aggregate::AggregateSignature;
+use crate::chains::chain_or_base;
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::rlp;
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, ValidatorSignature};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BaseHeader {
+    pub block_number: u64,
+    pub block_hash: String,    // 0x-prefixed hex
+    pub parent_hash: String,   // 0x-prefixed hex
+    pub receipts_root: String, // 0x-prefixed hex
+}
+
+impl BaseHeader {
+    /// Decode the fields the light client tracks from an RLP-encoded header
+    pub fn from_rlp(header_rlp: &[u8]) -> Self {
+        let fields = rlp::decode_exact(header_rlp)
+            .and_then(|item| item.items())
+            .unwrap_or_else(|| ContractError::InvalidHeaderEncoding.panic());
+        require(fields.len() >= 15, ContractError::InvalidHeaderEncoding);
+
+        let number = fields[8].payload;
+        require(number.len() <= 8, ContractError::InvalidBlockNumber);
+        let block_number = number
+            .iter()
+            .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte));
+
+        Self {
+            block_number,
+            block_hash: to_hex(&env::keccak256(header_rlp)),
+            parent_hash: to_hex(fields[0].payload),
+            receipts_root: to_hex(fields[5].payload),
+        }
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Seed a chain's light client with a trusted header and optionally set
+    /// the confirmation depth proofs must reach, keeping the chain's current
+    /// one, the network default until changed, otherwise
+    pub fn set_trusted_header(
+        &mut self,
+        header_rlp: Base64VecU8,
+        confirmations: Option<u64>,
+        chain_id: Option<u64>,
+    ) {
+        self.assert_role(&[Role::Owner]);
+        let chain_id = chain_or_base(chain_id);
+        self.chain(chain_id);
+        let header = BaseHeader::from_rlp(&header_rlp.0);
+
+        self.chain_heads.insert(chain_id, header.block_number);
+        self.chain_canonical_headers(chain_id)
+            .insert(header.block_number, header.block_hash.clone());
+        self.chain_headers(chain_id)
+            .insert(header.block_hash.clone(), header);
+        if let Some(confirmations) = confirmations {
+            self.chains
+                .get_mut(&chain_id)
+                .unwrap_or_else(|| ContractError::ChainNotSupported.panic())
+                .confirmations = confirmations;
+        }
+    }
+
+    /// Submit a header attested by a quorum of the validators attesting for
+    /// its chain. Callable by anyone, typically the relayer.
+    pub fn submit_base_header(
+        &mut self,
+        header_rlp: Base64VecU8,
+        signatures: Vec<ValidatorSignature>,
+        chain_id: Option<u64>,
+    ) {
+        let chain_id = chain_or_base(chain_id);
+        let chain = self.chain(chain_id);
+        let header = BaseHeader::from_rlp(&header_rlp.0);
+        let block_hash = env::keccak256(&header_rlp.0);
+        let signers = self.count_epoch_signatures(
+            self.current_epoch,
+            &block_hash,
+            &signatures,
+            |validator| self.attests_for_chain(&chain, validator),
+        );
+        require(
+            signers >= self.chain_threshold(&chain, self.current_epoch),
+            ContractError::NotEnoughSignatures,
+        );
+        self.accept_header(chain_id, header);
+    }
+
+    /// Submit a header attested by an aggregated validator signature over
+    /// its block hash. Callable by anyone, typically the relayer.
+    pub fn submit_base_header_aggregate(
+        &mut self,
+        header_rlp: Base64VecU8,
+        attestation: AggregateSignature,
+        chain_id: Option<u64>,
+    ) {
+        let chain_id = chain_or_base(chain_id);
+        require(
+            self.chains.get(&chain_id).is_some(),
+            ContractError::ChainNotSupported,
+        );
+        let header = BaseHeader::from_rlp(&header_rlp.0);
+        let block_hash = env::keccak256(&header_rlp.0);
+        require(
+            self.verify_aggregate(chain_id, &block_hash, &attestation),
+            ContractError::InvalidAggregateSignature,
+        );
+        self.accept_header(chain_id, header);
+    }
+
+    /// Get a chain's canonical header at a block number
+    pub fn get_base_header(&self, block_number: u64, chain_id: Option<u64>) -> Option<BaseHeader> {
+        let chain_id = chain_or_base(chain_id);
+        self.chain_canonical_headers(chain_id)
+            .get(&block_number)
+            .and_then(|hash| self.chain_headers(chain_id).get(hash).cloned())
+    }
+
+    /// Get the block number of a chain's canonical head
+    pub fn get_base_head(&self, chain_id: Option<u64>) -> Option<u64> {
+        self.chain_heads.get(&chain_or_base(chain_id)).copied()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Store an attested header, following it if it extends the highest chain
+    fn accept_header(&mut self, chain_id: u64, header: BaseHeader) {
+        let mut headers = self.chain_headers(chain_id);
+        require(
+            !headers.contains_key(&header.block_hash),
+            ContractError::HeaderKnown,
+        );
+        require(
+            headers.contains_key(&header.parent_hash),
+            ContractError::ParentNotFound,
+        );
+
+        headers.insert(header.block_hash.clone(), header.clone());
+        let head = *self
+            .chain_heads
+            .get(&chain_id)
+            .unwrap_or_else(|| ContractError::LightClientNotInitialized.panic());
+        if header.block_number > head {
+            self.update_canonical_chain(chain_id, &header);
+        }
+
+        IntentEvent::BaseHeaderSubmitted {
+            chain_id,
+            block_number: header.block_number,
+            block_hash: header.block_hash,
+        }
+        .emit();
+    }
+
+    /// Canonical header of a chain at `block_number` with enough
+    /// confirmations for proofs
+    pub(crate) fn confirmed_header(&self, chain_id: u64, block_number: u64) -> BaseHeader {
+        let header = self
+            .get_base_header(block_number, Some(chain_id))
+            .unwrap_or_else(|| ContractError::HeaderNotFound.panic());
+        let head = *self
+            .chain_heads
+            .get(&chain_id)
+            .unwrap_or_else(|| ContractError::LightClientNotInitialized.panic());
+        require(
+            head - block_number >= self.chain(chain_id).confirmations,
+            ContractError::BlockNotConfirmed,
+        );
+        header
+    }
+
+    /// Point a chain's canonical index at `head` and its ancestors, stopping
+    /// once the branch rejoins the existing canonical chain
+    fn update_canonical_chain(&mut self, chain_id: u64, head: &BaseHeader) {
+        self.chain_heads.insert(chain_id, head.block_number);
+
+        let headers = self.chain_headers(chain_id);
+        let mut canonical = self.chain_canonical_headers(chain_id);
+        let mut current = head.clone();
+        loop {
+            if canonical.get(&current.block_number) == Some(&current.block_hash) {
+                break;
+            }
+            canonical.insert(current.block_number, current.block_hash.clone());
+            match headers.get(&current.parent_hash) {
+                Some(parent) => current = parent.clone(),
+                None => break,
+            }
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chains::BASE_CHAIN_ID;
+    use crate::rlp::encode;
+    use crate::tests::{
+        assert_schema_describes, executor, keypair, set_caller, setup, VALIDATOR_BOND,
+    };
+    use ed25519_dalek::{Keypair, Signer};
+    use near_sdk::test_utils::accounts;
+
+    const TRUSTED_BLOCK: u64 = 10;
+
+    /// RLP header of `block_number` extending `parent`, told apart from its
+    /// siblings by `extra`
+    fn header(parent: &[u8], block_number: u64, extra: u8) -> Vec<u8> {
+        let mut fields = vec![encode::bytes(&[]); 15];
+        fields[0] = encode::bytes(parent);
+        fields[5] = encode::bytes(&[0u8; 32]);
+        fields[8] = encode::bytes(&block_number.to_be_bytes());
+        fields[12] = encode::bytes(&[extra]);
+        encode::list(&fields)
+    }
+
+    /// Contract trusting a Base header at `TRUSTED_BLOCK` with 2
+    /// confirmations, plus the key of a second validator attesting headers
+    fn setup_light_client() -> (CrossChainExecutor, Vec<u8>, Keypair) {
+        let mut contract = setup();
+        let (signer, public_key) = keypair(3);
+        set_caller(&accounts(4), VALIDATOR_BOND);
+        contract.register_validator(public_key);
+
+        let trusted = header(&[0u8; 32], TRUSTED_BLOCK, 0);
+        set_caller(&executor(), 0);
+        contract.set_trusted_header(Base64VecU8(trusted.clone()), Some(2), None);
+        (contract, trusted, signer)
+    }
+
+    fn submit(contract: &mut CrossChainExecutor, signer: &Keypair, header_rlp: &[u8]) {
+        let signature = signer.sign(&env::keccak256(header_rlp));
+        contract.submit_base_header(
+            Base64VecU8(header_rlp.to_vec()),
+            vec![ValidatorSignature {
+                validator: accounts(4),
+                signature: Base64VecU8(signature.to_bytes().to_vec()),
+            }],
+            None,
+        );
+    }
+
+    #[test]
+    fn attested_headers_extend_the_canonical_chain() {
+        let (mut contract, trusted, signer) = setup_light_client();
+        let child = header(&env::keccak256(&trusted), TRUSTED_BLOCK + 1, 0);
+        submit(&mut contract, &signer, &child);
+
+        assert_eq!(contract.get_base_head(None), Some(TRUSTED_BLOCK + 1));
+        let stored = contract.get_base_header(TRUSTED_BLOCK + 1, None).unwrap();
+        assert_eq!(stored.block_hash, to_hex(&env::keccak256(&child)));
+        assert_eq!(stored.parent_hash, to_hex(&env::keccak256(&trusted)));
+    }
+
+    #[test]
+    fn longer_branches_take_over_the_canonical_chain() {
+        let (mut contract, trusted, signer) = setup_light_client();
+        let parent = env::keccak256(&trusted);
+        let first = header(&parent, TRUSTED_BLOCK + 1, 1);
+        let second = header(&parent, TRUSTED_BLOCK + 1, 2);
+        submit(&mut contract, &signer, &first);
+        submit(&mut contract, &signer, &second);
+        // A sibling of the same height leaves the followed chain as it is
+        let canonical = |contract: &CrossChainExecutor, block_number| {
+            contract
+                .get_base_header(block_number, None)
+                .unwrap()
+                .block_hash
+        };
+        assert_eq!(
+            canonical(&contract, TRUSTED_BLOCK + 1),
+            to_hex(&env::keccak256(&first))
+        );
+
+        let grandchild = header(&env::keccak256(&second), TRUSTED_BLOCK + 2, 0);
+        submit(&mut contract, &signer, &grandchild);
+        assert_eq!(contract.get_base_head(None), Some(TRUSTED_BLOCK + 2));
+        assert_eq!(
+            canonical(&contract, TRUSTED_BLOCK + 1),
+            to_hex(&env::keccak256(&second))
+        );
+    }
+
+    #[test]
+    fn headers_back_proofs_once_confirmed() {
+        let (mut contract, trusted, signer) = setup_light_client();
+        let mut parent = env::keccak256(&trusted);
+        for block_number in TRUSTED_BLOCK + 1..=TRUSTED_BLOCK + 2 {
+            let child = header(&parent, block_number, 0);
+            submit(&mut contract, &signer, &child);
+            parent = env::keccak256(&child);
+        }
+
+        let confirmed = contract.confirmed_header(BASE_CHAIN_ID, TRUSTED_BLOCK);
+        assert_eq!(confirmed.block_hash, to_hex(&env::keccak256(&trusted)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Block not confirmed")]
+    fn headers_under_the_confirmation_depth_back_no_proofs() {
+        let (mut contract, trusted, signer) = setup_light_client();
+        let child = header(&env::keccak256(&trusted), TRUSTED_BLOCK + 1, 0);
+        submit(&mut contract, &signer, &child);
+
+        contract.confirmed_header(BASE_CHAIN_ID, TRUSTED_BLOCK);
+    }
+
+    #[test]
+    #[should_panic(expected = "Parent header not found")]
+    fn headers_must_extend_a_known_parent() {
+        let (mut contract, _, signer) = setup_light_client();
+        submit(
+            &mut contract,
+            &signer,
+            &header(&[1u8; 32], TRUSTED_BLOCK + 1, 0),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough validator signatures")]
+    fn headers_need_validator_attestation() {
+        let (mut contract, trusted, _) = setup_light_client();
+        let child = header(&env::keccak256(&trusted), TRUSTED_BLOCK + 1, 0);
+        let (outsider, _) = keypair(4);
+        submit(&mut contract, &outsider, &child);
+    }
+
+    #[test]
+    #[should_panic(expected = "Header already known")]
+    fn headers_are_accepted_once() {
+        let (mut contract, trusted, signer) = setup_light_client();
+        let child = header(&env::keccak256(&trusted), TRUSTED_BLOCK + 1, 0);
+        submit(&mut contract, &signer, &child);
+        submit(&mut contract, &signer, &child);
+    }
+
+    #[test]
+    fn accepted_headers_are_announced() {
+        let (mut contract, trusted, signer) = setup_light_client();
+        let child = header(&env::keccak256(&trusted), TRUSTED_BLOCK + 1, 0);
+        submit(&mut contract, &signer, &child);
+
+        let logs = near_sdk::test_utils::get_logs();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].contains("\"event\":\"base_header_submitted\""));
+        assert!(logs[0].contains(&to_hex(&env::keccak256(&child))));
+    }
+
+    #[test]
+    #[should_panic(expected = "Block header not found")]
+    fn unknown_blocks_back_no_proofs() {
+        let (contract, _, _) = setup_light_client();
+        contract.confirmed_header(BASE_CHAIN_ID, TRUSTED_BLOCK - 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Method requires role Owner")]
+    fn only_the_owner_sets_a_trusted_header() {
+        let mut contract = setup();
+        set_caller(&accounts(4), 0);
+        contract.set_trusted_header(
+            Base64VecU8(header(&[0u8; 32], TRUSTED_BLOCK, 0)),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn base_header_schema_lists_its_fields() {
+        let (contract, _, _) = setup_light_client();
+        assert_schema_describes(&contract.get_base_header(TRUSTED_BLOCK, None).unwrap());
+    }
+}
@@ -0,0 +1,220 @@
+//! State versioning and upgrades.
+//!
+//! The contract root records the layout version it was written with under
+//! its own storage key, and every stored intent carries a version tag. A
+//! layout is frozen once a release is deployed with it; changes made before
+//! then edit the current layout in place. To change a released layout:
+//!
+//! 1. Copy the current struct into this module as `...V<n>`, fields verbatim.
+//! 2. Add a variant holding it to [`VersionedState`] or [`VersionedIntent`]
+//!    and convert it to the new layout in the matching `From` impl.
+//! 3. For the root, bump [`STATE_VERSION`] and decode the old variant in
+//!    [`VersionedState::read`]; collections that change must be rewritten
+//!    under a fresh `StorageKey` variant inside the conversion.
+//!
+//! The first deployment is root layout 0, which wrote no version key and
+//! stored no intents. Intents are upgraded lazily on read, so `migrate` never
+//! walks the map. The executed intent IDs, which grow with every intent, are
+//! likewise moved in batches by [`CrossChainExecutor::migrate_intent_ids`]
+//! after `migrate`.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections;
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::errors::{require, ContractError};
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// Layout version written by this release
+pub const STATE_VERSION: u32 = 1;
+
+const STATE_VERSION_KEY: &[u8] = b"STATE_VERSION";
+
+/// Layouts the contract root has been stored with
+#[allow(clippy::large_enum_variant)] // Decoded once per upgrade
+pub enum VersionedState {
+    V0(CrossChainExecutorV0),
+    V1(CrossChainExecutor),
+}
+
+impl VersionedState {
+    /// Decode the stored root as the layout of `version`
+    fn read(version: u32) -> Self {
+        match version {
+            0 => VersionedState::V0(
+                env::state_read().unwrap_or_else(|| ContractError::NotInitialized.panic()),
+            ),
+            1 => VersionedState::V1(
+                env::state_read().unwrap_or_else(|| ContractError::NotInitialized.panic()),
+            ),
+            _ => ContractError::UnknownStateVersion
+                .panic_with(&format!("Unknown state version {}", version)),
+        }
+    }
+}
+
+impl From<VersionedState> for CrossChainExecutor {
+    fn from(state: VersionedState) -> Self {
+        match state {
+            VersionedState::V0(state) => state.into(),
+            VersionedState::V1(state) => state,
+        }
+    }
+}
+
+/// Root layout of the first deployment, which kept no version key
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct CrossChainExecutorV0 {
+    pub executed_intents: collections::UnorderedSet<String>,
+    pub bridge_validators: collections::LookupMap<AccountId, bool>,
+    pub required_signatures: u32,
+    pub token_contract: AccountId,
+    pub agent_account: AccountId,
+}
+
+impl From<CrossChainExecutorV0> for CrossChainExecutor {
+    fn from(old: CrossChainExecutorV0) -> Self {
+        // The contract account owned the first deployment. Its validators
+        // were registered without keys and its token contract was not tied
+        // to a Base token, so both are set up again by the owner.
+        let mut state = CrossChainExecutor::initial_state(
+            old.required_signatures,
+            &old.agent_account,
+            env::current_account_id(),
+        );
+        state.legacy_intent_ids = (!old.executed_intents.is_empty()).then_some(LegacyIntentIds {
+            ids: old.executed_intents,
+            copied: 0,
+        });
+        state
+    }
+}
+
+/// Executed intent IDs still stored in the layout 0 set
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct LegacyIntentIds {
+    ids: collections::UnorderedSet<String>,
+    /// Number of IDs, in creation order, already copied to the new set
+    copied: u64,
+}
+
+/// Layouts stored intents have been written with
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub enum VersionedIntent {
+    V0(CrossChainIntent),
+}
+
+impl From<VersionedIntent> for CrossChainIntent {
+    fn from(intent: VersionedIntent) -> Self {
+        match intent {
+            VersionedIntent::V0(intent) => intent,
+        }
+    }
+}
+
+impl From<CrossChainIntent> for VersionedIntent {
+    fn from(intent: CrossChainIntent) -> Self {
+        VersionedIntent::V0(intent)
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Upgrade the stored state to this release's layout.
+    /// Call in the same transaction as the code deployment.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let version = stored_state_version();
+        require(version <= STATE_VERSION, ContractError::StateDowngrade);
+        let state = VersionedState::read(version).into();
+        write_state_version();
+        state
+    }
+
+    /// Layout version of the stored contract state
+    pub fn get_state_version(&self) -> u32 {
+        stored_state_version()
+    }
+
+    /// Move up to `limit` executed intent IDs out of the layout 0 set,
+    /// copying all of them in creation order before deleting the old
+    /// entries. Returns whether the move is complete. Intent creation is
+    /// rejected until every ID has been copied.
+    pub fn migrate_intent_ids(&mut self, limit: u32) -> bool {
+        self.assert_role(&[Role::Owner]);
+        let Some(legacy) = self.legacy_intent_ids.as_mut() else {
+            return true;
+        };
+
+        let mut remaining = limit;
+        while remaining > 0 && legacy.copied < legacy.ids.len() {
+            let id = legacy.ids.as_vector().get(legacy.copied).unwrap();
+            self.executed_intents.insert(id);
+            legacy.copied += 1;
+            remaining -= 1;
+        }
+        // Removing the last element never moves another one
+        while remaining > 0 && !legacy.ids.is_empty() {
+            let last = legacy.ids.as_vector().get(legacy.ids.len() - 1).unwrap();
+            legacy.ids.remove(&last);
+            remaining -= 1;
+        }
+
+        let done = legacy.ids.is_empty();
+        if done {
+            self.legacy_intent_ids = None;
+        }
+        done
+    }
+}
+
+impl CrossChainExecutor {
+    /// Whether executed intent IDs are still being copied out of the
+    /// layout 0 set, leaving the new set incomplete
+    pub(crate) fn migrating_intent_ids(&self) -> bool {
+        self.legacy_intent_ids
+            .as_ref()
+            .is_some_and(|legacy| legacy.copied < legacy.ids.len())
+    }
+
+    /// IDs of all intents ever created, in creation order
+    pub(crate) fn intent_ids(&self) -> impl Iterator<Item = String> + '_ {
+        let legacy = self.legacy_intent_ids.iter().flat_map(|legacy| {
+            legacy
+                .ids
+                .as_vector()
+                .iter()
+                .skip(usize::try_from(legacy.copied).unwrap_or(usize::MAX))
+        });
+        self.executed_intents.iter().cloned().chain(legacy)
+    }
+
+    /// Number of intents ever created
+    pub(crate) fn intent_count(&self) -> u64 {
+        let legacy = self
+            .legacy_intent_ids
+            .as_ref()
+            .map_or(0, |legacy| legacy.ids.len().saturating_sub(legacy.copied));
+        u64::from(self.executed_intents.len()) + legacy
+    }
+}
+
+/// Record that the state is stored in this release's layout
+pub(crate) fn write_state_version() {
+    env::storage_write(STATE_VERSION_KEY, &STATE_VERSION.to_le_bytes());
+}
+
+/// The first deployment has no version key and uses layout 0
+fn stored_state_version() -> u32 {
+    env::storage_read(STATE_VERSION_KEY)
+        .map(|bytes| {
+            u32::from_le_bytes(
+                bytes
+                    .try_into()
+                    .unwrap_or_else(|_| ContractError::InvalidStateVersion.panic()),
+            )
+        })
+        .unwrap_or(0)
+}
@@ -0,0 +1,59 @@
+//! NEP-245 multi-token delivery.
+//!
+//! A token mapping may point at a NEP-245 contract instead of a NEP-141
+//! one. Intents of such tokens name the multi-token ID they deliver, which
+//! the typed hash covers, and execute by calling `mt_transfer` on the
+//! mapped contract. Like NFT intents they are delivered whole to the
+//! receiver's wallet, without a protocol fee, quotes or an escrow.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_sdk::Promise;
+
+use crate::errors::ContractError;
+use crate::tokens::is_native_token;
+use crate::{CrossChainExecutor, CrossChainIntent, DeliveryKind};
+
+impl CrossChainExecutor {
+    /// Check a new intent names a multi-token ID exactly when its token is
+    /// a multi-token
+    pub(crate) fn check_multi_token(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        let multi_token = !is_native_token(&intent.token)
+            && self
+                .token_mapping(intent.chain_id, &intent.token)
+                .is_some_and(|mapping| mapping.multi_token);
+        match &intent.mt_token_id {
+            None if multi_token => Err(ContractError::MultiTokenIdMissing),
+            None => Ok(()),
+            Some(_) if !multi_token => Err(ContractError::NotMultiToken),
+            Some(token_id) if token_id.is_empty() => Err(ContractError::InvalidMultiTokenId),
+            Some(_) => {
+                if intent.delivery_kind() != DeliveryKind::MultiToken {
+                    return Err(ContractError::MultiTokenDelivery);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Transfer `amount` of a multi-token to an intent's receiver
+    pub(crate) fn deliver_multi_token(
+        &self,
+        intent: &CrossChainIntent,
+        token_id: &str,
+        amount: U128,
+    ) -> Promise {
+        Promise::new(self.resolve_token(intent.chain_id, &intent.token)).function_call(
+            "mt_transfer".to_string(),
+            json!({
+                "receiver_id": intent.receiver,
+                "token_id": token_id,
+                "amount": amount,
+            })
+            .to_string()
+            .into_bytes(),
+            self.config.ft_transfer_deposit.0,
+            self.token_transfer_gas(intent.chain_id, &intent.token),
+        )
+    }
+}
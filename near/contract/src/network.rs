@@ -0,0 +1,126 @@
+//! Defaults compiled in for the network the contract is built for.
+//!
+//! Building with the `testnet` feature targets NEAR testnet and the Sepolia
+//! testnets of the source chains, with shallow confirmation depths, small
+//! bonds and short periods so a deployment can be exercised end to end in
+//! minutes. Any other build, including one with the `mainnet` feature,
+//! targets mainnet. The defaults only seed `new`, so everything they set
+//! can still be changed by the owner afterwards.
+
+use near_sdk::{Balance, ONE_NEAR};
+
+use crate::errors::ContractError;
+use crate::tokens::{TokenDecimals, TokenMapping};
+
+#[cfg(all(feature = "testnet", feature = "mainnet"))]
+compile_error!("The `testnet` and `mainnet` features are mutually exclusive");
+
+/// Token mapping registered at initialization
+pub struct DefaultToken {
+    pub chain_id: u64,
+    pub base_address: &'static str,
+    pub near_contract: &'static str,
+    pub symbol: &'static str,
+    pub source_decimals: u8,
+    pub near_decimals: u8,
+}
+
+impl DefaultToken {
+    pub fn mapping(&self) -> TokenMapping {
+        TokenMapping {
+            base_address: self.base_address.to_string(),
+            near_contract: self
+                .near_contract
+                .parse()
+                .unwrap_or_else(|_| ContractError::InvalidDefaultToken.panic()),
+            decimals: TokenDecimals {
+                source: self.source_decimals,
+                near: self.near_decimals,
+            },
+            symbol: self.symbol.to_string(),
+            enabled: true,
+            mintable: false,
+            min_amount: None,
+            max_amount: None,
+            stablecoin_route: None,
+            multi_token: false,
+            transfer_gas: None,
+        }
+    }
+}
+
+#[cfg(not(feature = "testnet"))]
+pub use mainnet::*;
+#[cfg(feature = "testnet")]
+pub use testnet::*;
+
+#[cfg(not(feature = "testnet"))]
+mod mainnet {
+    use super::*;
+
+    pub const BASE_CHAIN_ID: u64 = 8453;
+    pub const OPTIMISM_CHAIN_ID: u64 = 10;
+    pub const ARBITRUM_CHAIN_ID: u64 = 42161;
+    pub const ETHEREUM_CHAIN_ID: u64 = 1;
+
+    /// Chains registered at initialization with their confirmation depths
+    pub const DEFAULT_CHAINS: [(u64, &str, u64); 4] = [
+        (BASE_CHAIN_ID, "Base", 30),
+        (OPTIMISM_CHAIN_ID, "Optimism", 30),
+        (ARBITRUM_CHAIN_ID, "Arbitrum", 240),
+        // Two epochs, after which blocks are finalized
+        (ETHEREUM_CHAIN_ID, "Ethereum", 64),
+    ];
+
+    pub const DEFAULT_TOKENS: [DefaultToken; 1] = [DefaultToken {
+        chain_id: BASE_CHAIN_ID,
+        base_address: "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913",
+        near_contract: "17208628f84f5d6ad33f0da3bbbeb27ffcb398eac501a31bd6ad2011e36133a1",
+        symbol: "USDC",
+        source_decimals: 6,
+        near_decimals: 6,
+    }];
+
+    pub const MIN_VALIDATOR_BOND: Balance = 100 * ONE_NEAR;
+    pub const UNBONDING_PERIOD: u64 = 7 * 24 * 3_600 * 1_000_000_000;
+    pub const MIN_SOLVER_BOND: Balance = 10 * ONE_NEAR;
+    pub const SOLVER_CLAIM_PERIOD: u64 = 5 * 60 * 1_000_000_000;
+    pub const EMERGENCY_WITHDRAWAL_DELAY: u64 = 3 * 24 * 3_600 * 1_000_000_000;
+    pub const MIN_ADMIN_TIMELOCK: u64 = 24 * 3_600 * 1_000_000_000;
+    pub const MAX_PROOF_AGE: u64 = 24 * 3_600 * 1_000_000_000;
+}
+
+#[cfg(feature = "testnet")]
+mod testnet {
+    use super::*;
+
+    pub const BASE_CHAIN_ID: u64 = 84532; // Base Sepolia
+    pub const OPTIMISM_CHAIN_ID: u64 = 11155420; // OP Sepolia
+    pub const ARBITRUM_CHAIN_ID: u64 = 421614; // Arbitrum Sepolia
+    pub const ETHEREUM_CHAIN_ID: u64 = 11155111; // Sepolia
+
+    /// Chains registered at initialization with their confirmation depths
+    pub const DEFAULT_CHAINS: [(u64, &str, u64); 4] = [
+        (BASE_CHAIN_ID, "Base Sepolia", 2),
+        (OPTIMISM_CHAIN_ID, "OP Sepolia", 2),
+        (ARBITRUM_CHAIN_ID, "Arbitrum Sepolia", 8),
+        (ETHEREUM_CHAIN_ID, "Sepolia", 4),
+    ];
+
+    pub const DEFAULT_TOKENS: [DefaultToken; 1] = [DefaultToken {
+        chain_id: BASE_CHAIN_ID,
+        base_address: "0x036cbd53842c5426634e7929541ec2318f3dcf7e",
+        near_contract: "3e2210e1184b45b64c8a434c0a7e7b23cc04ea7eb7a6c3c32520d03d4afcb8af",
+        symbol: "USDC",
+        source_decimals: 6,
+        near_decimals: 6,
+    }];
+
+    pub const MIN_VALIDATOR_BOND: Balance = ONE_NEAR;
+    pub const UNBONDING_PERIOD: u64 = 3_600 * 1_000_000_000;
+    pub const MIN_SOLVER_BOND: Balance = ONE_NEAR / 10;
+    pub const SOLVER_CLAIM_PERIOD: u64 = 60 * 1_000_000_000;
+    pub const EMERGENCY_WITHDRAWAL_DELAY: u64 = 10 * 60 * 1_000_000_000;
+    pub const MIN_ADMIN_TIMELOCK: u64 = 60 * 1_000_000_000;
+    pub const MAX_PROOF_AGE: u64 = 3_600 * 1_000_000_000;
+}
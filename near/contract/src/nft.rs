@@ -0,0 +1,378 @@
+//! NEP-171 NFT intents.
+//!
+//! An intent whose `nft_token_id` is set moves one ERC-721 token from its
+//! source chain instead of fungible tokens. Its `token` is the collection
+//! address, its amount is always one, and its deposit is backed by an
+//! `NftLocked` log carrying the token ID where `TokensLocked` carries the
+//! amount. Each collection maps to a NEP-171 contract on NEAR, which
+//! execution either calls `nft_transfer` on, handing over a token the
+//! executor holds, or mints into when it is a wrapped collection the
+//! executor is the minter of. The token keeps its decimal ID on NEAR, and
+//! minted tokens carry metadata naming the collection they bridged from.
+//! A mint returns the minted token, which a callback drops so the intent
+//! settles as it does after a transfer.
+//! Fees, quotes, escrows, basket assets and NEAR Intents settlement only
+//! apply to fungible intents.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{env, near_bindgen, AccountId, Balance, Gas, Promise, PromiseResult};
+use schemars::JsonSchema;
+
+use crate::chains::chain_or_base;
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::tokens::{is_native_token, normalize_address};
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// Deposit covering the storage of a minted token, any excess is refunded
+const NFT_MINT_DEPOSIT: Balance = 10_000_000_000_000_000_000_000;
+
+/// Gas reserved for settling a mint
+const ON_NFT_MINTED_GAS: Gas = Gas(5_000_000_000_000);
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NftCollection {
+    /// Collection address on the source chain, 0x-prefixed hex
+    pub base_address: String,
+    /// NEP-171 contract delivered for the collection
+    pub near_contract: AccountId,
+    /// Whether new intents may use the collection
+    pub enabled: bool,
+    /// Whether the NEAR contract is a wrapped collection the executor mints
+    /// into on delivery rather than transferring tokens it holds
+    #[serde(default)]
+    pub mintable: bool,
+}
+
+impl CrossChainIntent {
+    /// Whether the intent moves an NFT rather than fungible tokens
+    pub fn is_nft(&self) -> bool {
+        self.nft_token_id.is_some()
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Map an NFT collection on a source chain, Base by default, to the
+    /// NEP-171 contract delivered for it, replacing any existing mapping
+    pub fn register_nft_collection(&mut self, collection: NftCollection, chain_id: Option<u64>) {
+        self.assert_untimelocked_owner();
+        self.insert_nft_collection(collection, chain_id);
+    }
+
+    /// Allow or stop new intents and transfers of a registered collection
+    pub fn set_nft_collection_enabled(
+        &mut self,
+        base_address: String,
+        enabled: bool,
+        chain_id: Option<u64>,
+    ) {
+        self.assert_role(&[Role::Owner]);
+        let key = (chain_or_base(chain_id), normalize_address(&base_address));
+        let mut collection = self
+            .nft_collections
+            .get(&key)
+            .cloned()
+            .unwrap_or_else(|| ContractError::CollectionNotRegistered.panic());
+        collection.enabled = enabled;
+        self.nft_collections.insert(key, collection);
+    }
+
+    /// Remove an NFT collection from a source chain's registry, Base by default
+    pub fn remove_nft_collection(&mut self, base_address: String, chain_id: Option<u64>) {
+        self.assert_untimelocked_owner();
+        self.remove_nft_collection_mapping(base_address, chain_id);
+    }
+
+    /// Get the mapping registered for a source chain NFT collection, Base
+    /// by default
+    pub fn get_nft_collection(
+        &self,
+        base_address: String,
+        chain_id: Option<u64>,
+    ) -> Option<NftCollection> {
+        self.nft_collection(chain_or_base(chain_id), &base_address)
+            .cloned()
+    }
+
+    /// List the enabled NFT collections intents from a chain, Base by
+    /// default, can bridge
+    pub fn get_supported_nft_collections(&self, chain_id: Option<u64>) -> Vec<NftCollection> {
+        let chain_id = chain_or_base(chain_id);
+        self.nft_collections
+            .iter()
+            .filter(|((collection_chain, _), collection)| {
+                *collection_chain == chain_id && collection.enabled
+            })
+            .map(|(_, collection)| collection.clone())
+            .collect()
+    }
+
+    /// Fail the delivery unless the mint succeeded, dropping the minted
+    /// token NEP-171 mints return
+    #[private]
+    pub fn on_nft_minted(&mut self) {
+        require(
+            matches!(env::promise_result(0), PromiseResult::Successful(_)),
+            ContractError::NftMintFailed,
+        );
+    }
+}
+
+impl CrossChainExecutor {
+    /// Store an NFT collection mapping, replacing any existing one
+    pub(crate) fn insert_nft_collection(
+        &mut self,
+        collection: NftCollection,
+        chain_id: Option<u64>,
+    ) {
+        let chain_id = chain_or_base(chain_id);
+        require(
+            self.chains.get(&chain_id).is_some(),
+            ContractError::ChainNotSupported,
+        );
+        let mut collection = collection;
+        collection.base_address = normalize_address(&collection.base_address);
+        require(
+            !is_native_token(&collection.base_address),
+            ContractError::NativeNotCollection,
+        );
+        IntentEvent::NftCollectionRegistered {
+            chain_id,
+            base_address: collection.base_address.clone(),
+            near_contract: collection.near_contract.clone(),
+        }
+        .emit();
+        self.nft_collections
+            .insert((chain_id, collection.base_address.clone()), collection);
+    }
+
+    /// Remove an NFT collection mapping, panicking if it is not registered
+    pub(crate) fn remove_nft_collection_mapping(
+        &mut self,
+        base_address: String,
+        chain_id: Option<u64>,
+    ) {
+        let chain_id = chain_or_base(chain_id);
+        let base_address = normalize_address(&base_address);
+        require(
+            self.nft_collections
+                .remove(&(chain_id, base_address.clone()))
+                .is_some(),
+            ContractError::CollectionNotRegistered,
+        );
+
+        IntentEvent::NftCollectionRemoved {
+            chain_id,
+            base_address,
+        }
+        .emit();
+    }
+
+    /// Mapping of a source chain NFT collection, if registered
+    pub(crate) fn nft_collection(
+        &self,
+        chain_id: u64,
+        base_address: &str,
+    ) -> Option<&NftCollection> {
+        self.nft_collections
+            .get(&(chain_id, normalize_address(base_address)))
+    }
+
+    /// Check a source chain NFT collection can back new intents and transfers
+    pub(crate) fn check_nft_collection_enabled(
+        &self,
+        chain_id: u64,
+        base_address: &str,
+    ) -> Result<(), ContractError> {
+        match self.nft_collection(chain_id, base_address) {
+            Some(collection) if collection.enabled => Ok(()),
+            Some(_) => Err(ContractError::CollectionDisabled),
+            None => Err(ContractError::CollectionNotSupported),
+        }
+    }
+
+    /// Check a new NFT intent moves exactly one token of a deliverable collection
+    pub(crate) fn check_nft_intent(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        if intent.amount.0 != 1 {
+            return Err(ContractError::NftAmount);
+        }
+        if intent.settle_in_defuse {
+            return Err(ContractError::NftCannotSettleInDefuse);
+        }
+        if intent.is_basket() {
+            return Err(ContractError::NftBasket);
+        }
+        self.check_nft_collection_enabled(intent.chain_id, &intent.token)
+    }
+
+    /// Deliver the NFT of an intent to its receiver, minting it if the
+    /// collection is mintable
+    pub(crate) fn deliver_nft(&self, intent: &CrossChainIntent, token_id: U128) -> Promise {
+        let collection = self
+            .nft_collection(intent.chain_id, &intent.token)
+            .unwrap_or_else(|| ContractError::CollectionNotSupported.panic());
+        let token_id = token_id.0.to_string();
+        let contract = Promise::new(collection.near_contract.clone());
+        if !collection.mintable {
+            return contract.function_call(
+                "nft_transfer".to_string(),
+                json!({ "receiver_id": intent.receiver, "token_id": token_id, "memo": intent.id })
+                    .to_string()
+                    .into_bytes(),
+                // NEP-171 requires exactly one yoctoNEAR
+                1,
+                self.config.ft_transfer_gas,
+            );
+        }
+        self.assert_native_liquidity(NFT_MINT_DEPOSIT);
+        let token_metadata = json!({
+            "title": format!("#{}", token_id),
+            "description": format!(
+                "Bridged from collection {} on chain {}",
+                collection.base_address, intent.chain_id
+            ),
+            "copies": 1,
+        });
+        contract
+            .function_call(
+                "nft_mint".to_string(),
+                json!({
+                    "token_id": token_id,
+                    "receiver_id": intent.receiver,
+                    "token_metadata": token_metadata,
+                })
+                .to_string()
+                .into_bytes(),
+                NFT_MINT_DEPOSIT,
+                self.config.ft_transfer_gas,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(ON_NFT_MINTED_GAS)
+                    .on_nft_minted(),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{
+        agent, create_approved, executor, intent, rehash, resolve_callback, set_caller, setup,
+    };
+    use crate::IntentStatus;
+    use near_sdk::mock::VmAction;
+    use near_sdk::serde_json;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{testing_env, ONE_NEAR};
+
+    const COLLECTION: &str = "0x3333333333333333333333333333333333333333";
+
+    /// Execute an intent moving NFT 7 of a collection registered with
+    /// `mintable`, returning the contract, the intent's ID and the method
+    /// called on the NEP-171 contract with its args
+    fn execute_nft(mintable: bool) -> (CrossChainExecutor, String, String, serde_json::Value) {
+        let mut contract = setup();
+        let collection: AccountId = "nft.near".parse().unwrap();
+        set_caller(&executor(), 0);
+        contract.register_nft_collection(
+            NftCollection {
+                base_address: COLLECTION.to_string(),
+                near_contract: collection.clone(),
+                enabled: true,
+                mintable,
+            },
+            None,
+        );
+
+        let mut nft_intent = intent(1);
+        nft_intent.token = COLLECTION.to_string();
+        nft_intent.nft_token_id = Some(U128(7));
+        rehash(&mut nft_intent);
+        let intent_id = create_approved(&mut contract, nft_intent);
+        // Fund the minting storage deposit
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(agent())
+            .account_balance(1_000 * ONE_NEAR)
+            .build());
+        contract.execute_intent(intent_id.clone());
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == collection)
+            .expect("No NFT receipt");
+        match &receipt.actions[0] {
+            VmAction::FunctionCall {
+                function_name,
+                args,
+                ..
+            } => (
+                contract,
+                intent_id,
+                function_name.clone(),
+                serde_json::from_slice(args).unwrap(),
+            ),
+            action => panic!("Unexpected action {:?}", action),
+        }
+    }
+
+    /// Whether a callback named `method` was created since the last context change
+    fn calls_back(method: &str) -> bool {
+        get_created_receipts().iter().any(|receipt| {
+            receipt.receiver_id == executor()
+                && matches!(
+                    &receipt.actions[0],
+                    VmAction::FunctionCall { function_name, .. } if function_name == method
+                )
+        })
+    }
+
+    #[test]
+    fn execute_transfers_nft_of_mapped_collection() {
+        let (_, _, method, args) = execute_nft(false);
+        assert_eq!(method, "nft_transfer");
+        assert_eq!(args["receiver_id"], accounts(2).to_string());
+        assert_eq!(args["token_id"], "7");
+        assert!(!calls_back("on_nft_minted"));
+    }
+
+    #[test]
+    fn execute_mints_nft_into_wrapped_collection() {
+        let (_, _, method, args) = execute_nft(true);
+        assert_eq!(method, "nft_mint");
+        assert_eq!(args["receiver_id"], accounts(2).to_string());
+        assert_eq!(args["token_id"], "7");
+        assert_eq!(args["token_metadata"]["title"], "#7");
+        assert_eq!(args["token_metadata"]["copies"], 1);
+        assert!(calls_back("on_nft_minted"));
+    }
+
+    #[test]
+    fn minted_nfts_settle_whatever_token_the_mint_returns() {
+        let (mut contract, intent_id, ..) = execute_nft(true);
+        let token = serde_json::json!({ "token_id": "7", "owner_id": accounts(2) });
+        resolve_callback(PromiseResult::Successful(token.to_string().into_bytes()));
+        contract.on_nft_minted();
+        contract.on_transfer_complete(intent_id.clone(), U128(0), None, Ok(()));
+
+        assert_eq!(
+            contract.get_intent_status(intent_id),
+            Some(IntentStatus::Completed)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "NFT mint failed")]
+    fn failed_mints_fail_the_delivery() {
+        let (mut contract, ..) = execute_nft(true);
+        resolve_callback(PromiseResult::Failed);
+        contract.on_nft_minted();
+    }
+}
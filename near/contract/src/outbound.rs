@@ -0,0 +1,560 @@
+//! NEAR→Base direction: tokens locked on NEAR and released on Base.
+//!
+//! Funds are locked either with `ft_transfer_call` (NEP-141 tokens) or by
+//! attaching NEAR to `create_outbound_intent`, and released as the enabled
+//! Base token registered for them; native NEAR as one whose mapping names
+//! the `near` sentinel. Wrapped tokens of mintable mappings are burned
+//! instead of locked, as the original asset is what the Base bridge holds.
+//! Such an intent waits for its burn, and becomes attestable only once the
+//! burn succeeded; should it fail the tokens are handed back to the sender.
+//! Validators attest each outbound intent, the relayer releases the funds
+//! on Base once a quorum has attested, and the agent records the Base
+//! release transaction to complete it.
+
+use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{
+    env, near_bindgen, serde_json, AccountId, Gas, Promise, PromiseOrValue, PromiseResult,
+};
+use schemars::JsonSchema;
+
+use crate::chains::BASE_CHAIN_ID;
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::tokens::{normalize_address, NATIVE_TOKEN};
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+/// Gas reserved for settling the burn of wrapped tokens sent back
+const ON_BURNED_GAS: Gas = Gas(5_000_000_000_000);
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OutboundIntent {
+    pub id: String,
+    pub sender: AccountId,  // NEAR account that locked the tokens
+    pub recipient: String,  // Base chain address
+    pub base_token: String, // Token address on Base
+    pub near_token: String, // NEP-141 contract, or the native NEAR sentinel
+    pub amount: U128,
+    pub status: OutboundStatus,
+    pub created_at: U64,
+    pub release_tx_hash: Option<String>, // Base transaction that released the funds
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum OutboundStatus {
+    Pending,
+    Completed,
+    /// Waiting for its wrapped tokens to be burned before it can be attested
+    Burning,
+    /// Handed back to the sender after burning its wrapped tokens failed
+    Refunded,
+}
+
+/// `msg` payload of an `ft_transfer_call` bridging tokens to Base
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OutboundTransferMsg {
+    pub recipient: String,
+    pub base_token: String,
+}
+
+#[near_bindgen]
+impl FungibleTokenReceiver for CrossChainExecutor {
+    /// Lock tokens sent via `ft_transfer_call`, or burn them if they are
+    /// wrapped, and record an outbound intent. Panicking here makes the FT
+    /// contract refund the sender in full.
+    fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        self.assert_not_paused();
+        let near_token = env::predecessor_account_id();
+        let transfer: OutboundTransferMsg = serde_json::from_str(&msg)
+            .unwrap_or_else(|_| ContractError::InvalidTransferMsg.panic());
+
+        require(amount.0 > 0, ContractError::AmountNotPositive);
+
+        let mintable = self
+            .get_token_mapping(transfer.base_token.clone(), Some(BASE_CHAIN_ID))
+            .is_some_and(|mapping| mapping.mintable);
+        let status = if mintable {
+            OutboundStatus::Burning
+        } else {
+            OutboundStatus::Pending
+        };
+        let intent_id = self.record_outbound_intent(
+            sender_id,
+            transfer.recipient,
+            transfer.base_token,
+            near_token.to_string(),
+            amount,
+            status,
+        );
+        if !mintable {
+            // Keep all of the tokens locked
+            return PromiseOrValue::Value(U128(0));
+        }
+
+        // Burn the received wrapped tokens out of the executor's balance,
+        // reporting back to the FT contract what it should refund
+        Promise::new(near_token.clone())
+            .function_call(
+                "burn".to_string(),
+                json!({ "amount": amount, "memo": intent_id })
+                    .to_string()
+                    .into_bytes(),
+                self.config.ft_transfer_deposit.0,
+                self.config.ft_transfer_gas,
+            )
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(ON_BURNED_GAS)
+                    .on_outbound_burned(intent_id, near_token, amount),
+            )
+            .into()
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Lock the attached NEAR and record an outbound intent releasing it on Base
+    #[payable]
+    pub fn create_outbound_intent(&mut self, recipient: String, base_token: String) -> String {
+        self.assert_not_paused();
+        let amount = env::attached_deposit();
+        require(amount > 0, ContractError::BridgeDepositMissing);
+
+        self.record_outbound_intent(
+            env::predecessor_account_id(),
+            recipient,
+            base_token,
+            NATIVE_TOKEN.to_string(),
+            U128(amount),
+            OutboundStatus::Pending,
+        )
+    }
+
+    /// Make an outbound intent attestable once its wrapped tokens are
+    /// burned, or mark it refunded, returning the amount the FT contract
+    /// hands back to the sender
+    #[private]
+    pub fn on_outbound_burned(
+        &mut self,
+        intent_id: String,
+        near_token: AccountId,
+        amount: U128,
+    ) -> U128 {
+        let burned = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        let intent = self
+            .outbound_intents
+            .get_mut(&intent_id)
+            .unwrap_or_else(|| ContractError::OutboundNotFound.panic());
+        if !burned {
+            intent.status = OutboundStatus::Refunded;
+            IntentEvent::OutboundIntentRefunded { intent_id, amount }.emit();
+            return amount;
+        }
+
+        intent.status = OutboundStatus::Pending;
+        let intent = intent.clone();
+        IntentEvent::OutboundTokensBurned {
+            intent_id,
+            near_token,
+            amount,
+        }
+        .emit();
+        emit_outbound_created(intent);
+        U128(0)
+    }
+
+    /// Attest a pending outbound intent as a registered validator
+    pub fn approve_outbound_intent(&mut self, intent_id: String) {
+        let validator = self.assert_role(&[Role::Validator]);
+        let intent = self
+            .outbound_intents
+            .get(&intent_id)
+            .unwrap_or_else(|| ContractError::OutboundNotFound.panic());
+        require(
+            matches!(intent.status, OutboundStatus::Pending),
+            ContractError::ApprovalNotPending,
+        );
+
+        let approvals = self
+            .outbound_approvals
+            .entry(intent_id.clone())
+            .or_default();
+        require(
+            !approvals.contains(&validator),
+            ContractError::AlreadyApproved,
+        );
+        approvals.push(validator.clone());
+
+        IntentEvent::IntentApproved {
+            intent_id,
+            validator,
+        }
+        .emit();
+    }
+
+    /// Record the Base transaction that released an attested outbound intent
+    pub fn complete_outbound_intent(&mut self, intent_id: String, release_tx_hash: String) {
+        self.assert_agent();
+
+        let intent = self
+            .outbound_intents
+            .get(&intent_id)
+            .unwrap_or_else(|| ContractError::OutboundNotFound.panic());
+        require(
+            matches!(intent.status, OutboundStatus::Pending),
+            ContractError::OutboundNotPending,
+        );
+
+        self.assert_outbound_attested(&intent_id);
+
+        let intent = self
+            .outbound_intents
+            .get_mut(&intent_id)
+            .unwrap_or_else(|| ContractError::OutboundNotFound.panic());
+        intent.status = OutboundStatus::Completed;
+        intent.release_tx_hash = Some(release_tx_hash.clone());
+        self.outbound_approvals.remove(&intent_id);
+
+        IntentEvent::OutboundIntentCompleted {
+            intent_id,
+            release_tx_hash,
+        }
+        .emit();
+    }
+
+    /// Get an outbound intent by ID
+    pub fn get_outbound_intent(&self, intent_id: String) -> Option<OutboundIntent> {
+        self.outbound_intents.get(&intent_id).cloned()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Assert a quorum of the current validator set attested an outbound intent
+    pub(crate) fn assert_outbound_attested(&self, intent_id: &String) {
+        // Validators removed after approving no longer count
+        let approvals = self
+            .outbound_approvals
+            .get(intent_id)
+            .map_or(0, |approvals| {
+                approvals
+                    .iter()
+                    .filter(|validator| self.epoch_key(self.current_epoch, validator).is_some())
+                    .count() as u32
+            });
+        require(
+            approvals >= self.epoch_threshold(self.current_epoch),
+            ContractError::NotEnoughApprovals,
+        );
+    }
+
+    /// Store a new outbound intent, charging the sender for its storage.
+    /// The Base token must be enabled and registered for the locked token.
+    fn record_outbound_intent(
+        &mut self,
+        sender: AccountId,
+        recipient: String,
+        base_token: String,
+        near_token: String,
+        amount: U128,
+        status: OutboundStatus,
+    ) -> String {
+        if let Err(reason) = self.check_token_enabled(BASE_CHAIN_ID, &base_token) {
+            reason.panic();
+        }
+        require(
+            self.resolve_token(BASE_CHAIN_ID, &base_token).as_str() == near_token,
+            ContractError::OutboundTokenMismatch,
+        );
+
+        let initial_storage = self.storage_usage();
+        let intent = OutboundIntent {
+            id: self.next_outbound_id(),
+            sender: sender.clone(),
+            recipient,
+            base_token: normalize_address(&base_token),
+            near_token,
+            amount,
+            status,
+            created_at: U64(env::block_timestamp()),
+            release_tx_hash: None,
+        };
+        self.outbound_intents
+            .insert(intent.id.clone(), intent.clone());
+        self.charge_storage(&sender, initial_storage);
+
+        let intent_id = intent.id.clone();
+        // Intents waiting for a burn are announced once it succeeded
+        if matches!(intent.status, OutboundStatus::Pending) {
+            emit_outbound_created(intent);
+        }
+        intent_id
+    }
+
+    fn next_outbound_id(&mut self) -> String {
+        self.outbound_nonce += 1;
+        format!("out-{}", self.outbound_nonce)
+    }
+}
+
+/// Announce an attestable outbound intent for the relayer to release on Base
+fn emit_outbound_created(intent: OutboundIntent) {
+    IntentEvent::OutboundIntentCreated {
+        intent_id: intent.id,
+        sender: intent.sender,
+        recipient: intent.recipient,
+        base_token: intent.base_token,
+        amount: intent.amount,
+    }
+    .emit();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{
+        agent, assert_schema_describes, executor, mapping, register_wnear, resolve_callback,
+        set_caller, setup, token, validator, BASE_TOKEN, BASE_WNEAR,
+    };
+    use near_contract_standards::storage_management::StorageManagement;
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, get_logs};
+    use near_sdk::ONE_NEAR;
+
+    const RECIPIENT: &str = "0x71c7656ec7ab88b098defb751b7401b5f6d8976f";
+
+    fn transfer_msg() -> String {
+        json!({ "recipient": RECIPIENT, "base_token": BASE_TOKEN.to_uppercase() }).to_string()
+    }
+
+    fn create_native(contract: &mut CrossChainExecutor) -> String {
+        register_wnear(contract);
+        set_caller(&agent(), 5 * ONE_NEAR);
+        contract.create_outbound_intent(RECIPIENT.to_string(), BASE_WNEAR.to_string())
+    }
+
+    #[test]
+    fn ft_transfers_lock_tokens_as_outbound_intents() {
+        let mut contract = setup();
+        set_caller(&token(), 0);
+        let unused = contract.ft_on_transfer(agent(), U128(500), transfer_msg());
+        assert!(matches!(unused, PromiseOrValue::Value(U128(0))));
+
+        let intent = contract.get_outbound_intent("out-1".to_string()).unwrap();
+        assert_eq!(intent.sender, agent());
+        assert_eq!(intent.recipient, RECIPIENT);
+        assert_eq!(intent.base_token, BASE_TOKEN);
+        assert_eq!(intent.near_token, token().to_string());
+        assert_eq!(intent.amount, U128(500));
+        assert!(matches!(intent.status, OutboundStatus::Pending));
+    }
+
+    #[test]
+    #[should_panic(expected = "Token does not match the registered Base token")]
+    fn ft_transfers_of_other_contracts_are_refunded() {
+        let mut contract = setup();
+        set_caller(&accounts(5), 0);
+        contract.ft_on_transfer(agent(), U128(500), transfer_msg());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid transfer msg")]
+    fn ft_transfers_need_a_base_destination() {
+        let mut contract = setup();
+        set_caller(&token(), 0);
+        contract.ft_on_transfer(agent(), U128(500), "{}".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Amount must be positive")]
+    fn ft_transfers_of_nothing_are_rejected() {
+        let mut contract = setup();
+        set_caller(&token(), 0);
+        contract.ft_on_transfer(agent(), U128(0), transfer_msg());
+    }
+
+    #[test]
+    fn locked_outbound_intents_are_announced_for_the_relayer() {
+        let mut contract = setup();
+        set_caller(&token(), 0);
+        let _ = contract.ft_on_transfer(agent(), U128(500), transfer_msg());
+
+        let logs = get_logs();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].starts_with("EVENT_JSON:"));
+        assert!(logs[0].contains("\"event\":\"outbound_intent_created\""));
+        assert!(logs[0].contains("\"intent_id\":\"out-1\""));
+        assert!(logs[0].contains(RECIPIENT));
+        assert!(logs[0].contains("\"amount\":\"500\""));
+    }
+
+    #[test]
+    fn outbound_intents_are_charged_to_the_sender() {
+        let mut contract = setup();
+        let before = contract.storage_balance_of(agent()).unwrap();
+        set_caller(&token(), 0);
+        let _ = contract.ft_on_transfer(agent(), U128(500), transfer_msg());
+
+        let after = contract.storage_balance_of(agent()).unwrap();
+        assert_eq!(after.total, before.total);
+        assert!(after.available.0 < before.available.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Account is not registered for storage")]
+    fn ft_transfers_from_unregistered_senders_are_refunded() {
+        let mut contract = setup();
+        set_caller(&token(), 0);
+        contract.ft_on_transfer(accounts(3), U128(500), transfer_msg());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token does not match the registered Base token")]
+    fn native_near_is_released_only_as_its_registered_token() {
+        let mut contract = setup();
+        register_wnear(&mut contract);
+        set_caller(&agent(), 5 * ONE_NEAR);
+        contract.create_outbound_intent(RECIPIENT.to_string(), BASE_TOKEN.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Token disabled")]
+    fn native_near_is_not_released_as_a_disabled_token() {
+        let mut contract = setup();
+        register_wnear(&mut contract);
+        set_caller(&executor(), 0);
+        contract.set_token_enabled(BASE_WNEAR.to_string(), false, None);
+
+        set_caller(&agent(), 5 * ONE_NEAR);
+        contract.create_outbound_intent(RECIPIENT.to_string(), BASE_WNEAR.to_string());
+    }
+
+    /// Send wrapped tokens of a mintable mapping back, returning the burn promise
+    fn send_back_wrapped(contract: &mut CrossChainExecutor) -> PromiseOrValue<U128> {
+        set_caller(&executor(), 0);
+        let mut wrapped = mapping(BASE_TOKEN, 6, 6);
+        wrapped.mintable = true;
+        contract.register_token(wrapped, None);
+
+        set_caller(&token(), 0);
+        contract.ft_on_transfer(agent(), U128(500), transfer_msg())
+    }
+
+    #[test]
+    fn wrapped_tokens_sent_back_are_burned_for_release() {
+        let mut contract = setup();
+        let unused = send_back_wrapped(&mut contract);
+        assert!(matches!(unused, PromiseOrValue::Promise(_)));
+        // Scheduled once dropped, as when returned from the call
+        drop(unused);
+
+        let burn = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == token())
+            .expect("No burn receipt");
+        assert!(matches!(
+            &burn.actions[0],
+            VmAction::FunctionCall { function_name, .. } if function_name == "burn"
+        ));
+        let intent = contract.get_outbound_intent("out-1".to_string()).unwrap();
+        assert_eq!(intent.amount, U128(500));
+        assert!(matches!(intent.status, OutboundStatus::Burning));
+        // Not announced to the relayer before the burn succeeded
+        assert!(!get_logs().iter().any(|log| log.contains("outbound_intent_created")));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only pending intents can be approved")]
+    fn burning_outbound_intents_cannot_be_attested() {
+        let mut contract = setup();
+        let _ = send_back_wrapped(&mut contract);
+        set_caller(&validator(), 0);
+        contract.approve_outbound_intent("out-1".to_string());
+    }
+
+    #[test]
+    fn burned_outbound_intents_become_attestable() {
+        let mut contract = setup();
+        let _ = send_back_wrapped(&mut contract);
+
+        resolve_callback(PromiseResult::Successful(vec![]));
+        let refunded = contract.on_outbound_burned("out-1".to_string(), token(), U128(500));
+        assert_eq!(refunded, U128(0));
+        let logs = get_logs();
+        assert!(logs.iter().any(|log| log.contains("outbound_tokens_burned")));
+        assert!(logs.iter().any(|log| log.contains("outbound_intent_created")));
+
+        set_caller(&validator(), 0);
+        contract.approve_outbound_intent("out-1".to_string());
+        let intent = contract.get_outbound_intent("out-1".to_string()).unwrap();
+        assert!(matches!(intent.status, OutboundStatus::Pending));
+    }
+
+    #[test]
+    fn failed_burns_refund_the_sender() {
+        let mut contract = setup();
+        let _ = send_back_wrapped(&mut contract);
+
+        resolve_callback(PromiseResult::Failed);
+        let refunded = contract.on_outbound_burned("out-1".to_string(), token(), U128(500));
+        assert_eq!(refunded, U128(500));
+        assert!(get_logs().iter().any(|log| log.contains("outbound_intent_refunded")));
+        let intent = contract.get_outbound_intent("out-1".to_string()).unwrap();
+        assert!(matches!(intent.status, OutboundStatus::Refunded));
+    }
+
+    #[test]
+    fn attested_outbound_intents_complete_with_their_release() {
+        let mut contract = setup();
+        let intent_id = create_native(&mut contract);
+        let intent = contract.get_outbound_intent(intent_id.clone()).unwrap();
+        assert_eq!(intent.near_token, NATIVE_TOKEN);
+        assert_eq!(intent.amount, U128(5 * ONE_NEAR));
+
+        set_caller(&validator(), 0);
+        contract.approve_outbound_intent(intent_id.clone());
+        set_caller(&agent(), 0);
+        contract.complete_outbound_intent(intent_id.clone(), "0xfeed".to_string());
+
+        let intent = contract.get_outbound_intent(intent_id).unwrap();
+        assert!(matches!(intent.status, OutboundStatus::Completed));
+        assert_eq!(intent.release_tx_hash.as_deref(), Some("0xfeed"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough validator approvals")]
+    fn outbound_intents_complete_only_once_attested() {
+        let mut contract = setup();
+        let intent_id = create_native(&mut contract);
+        set_caller(&agent(), 0);
+        contract.complete_outbound_intent(intent_id, "0xfeed".to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "Validator already approved this intent")]
+    fn validators_attest_an_outbound_intent_once() {
+        let mut contract = setup();
+        let intent_id = create_native(&mut contract);
+        set_caller(&validator(), 0);
+        contract.approve_outbound_intent(intent_id.clone());
+        contract.approve_outbound_intent(intent_id);
+    }
+
+    #[test]
+    fn outbound_intent_schema_lists_its_fields() {
+        let mut contract = setup();
+        let intent_id = create_native(&mut contract);
+        assert_schema_describes(&contract.get_outbound_intent(intent_id).unwrap());
+    }
+}
@@ -0,0 +1,151 @@
+//! Two-step ownership transfer.
+//!
+//! The current owner proposes a successor, and ownership only moves once the
+//! proposed account accepts, so a typo can never strand the contract.
+
+use near_sdk::{env, near_bindgen, AccountId};
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Propose a new owner, replacing any earlier proposal
+    pub fn propose_owner(&mut self, new_owner: AccountId) {
+        self.assert_role(&[Role::Owner]);
+        self.pending_owner = Some(new_owner.clone());
+
+        IntentEvent::OwnershipProposed {
+            owner: self.owner.clone(),
+            proposed_owner: new_owner,
+        }
+        .emit();
+    }
+
+    /// Accept a pending ownership proposal
+    pub fn accept_ownership(&mut self) {
+        let caller = env::predecessor_account_id();
+        require(
+            self.pending_owner.as_ref() == Some(&caller),
+            ContractError::NotProposedOwner,
+        );
+
+        let previous_owner = std::mem::replace(&mut self.owner, caller.clone());
+        self.pending_owner = None;
+
+        IntentEvent::OwnershipTransferred {
+            previous_owner,
+            new_owner: caller,
+        }
+        .emit();
+    }
+
+    /// Get the contract owner
+    pub fn get_owner(&self) -> AccountId {
+        self.owner.clone()
+    }
+
+    /// Get the proposed owner awaiting acceptance
+    pub fn get_pending_owner(&self) -> Option<AccountId> {
+        self.pending_owner.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{executor, set_caller, setup};
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn proposed_owner_takes_over_once_it_accepts() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.propose_owner(accounts(3));
+        assert_eq!(contract.get_owner(), executor());
+        assert_eq!(contract.get_pending_owner(), Some(accounts(3)));
+
+        set_caller(&accounts(3), 0);
+        contract.accept_ownership();
+        assert_eq!(contract.get_owner(), accounts(3));
+        assert_eq!(contract.get_pending_owner(), None);
+        assert!(contract.has_role(Role::Owner, accounts(3)));
+        assert!(!contract.has_role(Role::Owner, executor()));
+    }
+
+    #[test]
+    fn ownership_transfers_are_announced() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.propose_owner(accounts(3));
+        let logs = near_sdk::test_utils::get_logs();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].contains("\"event\":\"ownership_proposed\""));
+        assert!(logs[0].contains(&format!("\"proposed_owner\":\"{}\"", accounts(3))));
+
+        set_caller(&accounts(3), 0);
+        contract.accept_ownership();
+        let logs = near_sdk::test_utils::get_logs();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].contains("\"event\":\"ownership_transferred\""));
+        assert!(logs[0].contains(&format!("\"previous_owner\":\"{}\"", executor())));
+        assert!(logs[0].contains(&format!("\"new_owner\":\"{}\"", accounts(3))));
+    }
+
+    #[test]
+    #[should_panic(expected = "Method requires role Owner")]
+    fn previous_owners_lose_administration() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.propose_owner(accounts(3));
+        set_caller(&accounts(3), 0);
+        contract.accept_ownership();
+
+        set_caller(&executor(), 0);
+        contract.propose_owner(executor());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed owner can accept ownership")]
+    fn proposals_are_accepted_once() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.propose_owner(accounts(3));
+        set_caller(&accounts(3), 0);
+        contract.accept_ownership();
+        contract.accept_ownership();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed owner can accept ownership")]
+    fn other_accounts_cannot_accept_ownership() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.propose_owner(accounts(3));
+
+        set_caller(&accounts(4), 0);
+        contract.accept_ownership();
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the proposed owner can accept ownership")]
+    fn later_proposals_replace_earlier_ones() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.propose_owner(accounts(3));
+        contract.propose_owner(accounts(4));
+
+        set_caller(&accounts(3), 0);
+        contract.accept_ownership();
+    }
+
+    #[test]
+    #[should_panic(expected = "Method requires role Owner")]
+    fn only_the_owner_proposes_a_successor() {
+        let mut contract = setup();
+        set_caller(&accounts(3), 0);
+        contract.propose_owner(accounts(3));
+    }
+}
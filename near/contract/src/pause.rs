@@ -0,0 +1,147 @@
+//! Emergency circuit breaker.
+//!
+//! Guardians (and the owner) can pause the bridge the moment something looks
+//! wrong; only the owner can resume it.
+
+use near_sdk::near_bindgen;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Halt intent creation and execution
+    pub fn pause(&mut self) {
+        let caller = self.assert_role(&[Role::Guardian, Role::Owner]);
+        require(!self.paused, ContractError::AlreadyPaused);
+        self.paused = true;
+
+        IntentEvent::Paused { by: caller }.emit();
+    }
+
+    /// Resume normal operation
+    pub fn unpause(&mut self) {
+        let caller = self.assert_role(&[Role::Owner]);
+        require(self.paused, ContractError::NotPaused);
+        self.paused = false;
+
+        IntentEvent::Unpaused { by: caller }.emit();
+    }
+
+    /// View method to check if the contract is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+impl CrossChainExecutor {
+    pub(crate) fn assert_not_paused(&self) {
+        require(!self.paused, ContractError::Paused);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{agent, create_approved, executor, intent, set_caller, setup};
+    use near_sdk::test_utils::accounts;
+    use near_sdk::{PromiseOrValue, ONE_NEAR};
+
+    fn guardian() -> near_sdk::AccountId {
+        accounts(3)
+    }
+
+    /// Contract with `guardian()` holding the Guardian role and paused by it
+    fn setup_paused() -> CrossChainExecutor {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.grant_role(Role::Guardian, guardian());
+        set_caller(&guardian(), 0);
+        contract.pause();
+        assert!(contract.is_paused());
+        contract
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn paused_contract_rejects_new_intents() {
+        let mut contract = setup_paused();
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(intent(1_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract is paused")]
+    fn paused_contract_rejects_executions() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&executor(), 0);
+        contract.pause();
+
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "Method requires role Owner")]
+    fn guardians_cannot_unpause() {
+        let mut contract = setup_paused();
+        contract.unpause();
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract already paused")]
+    fn pausing_twice_fails() {
+        let mut contract = setup_paused();
+        contract.pause();
+    }
+
+    #[test]
+    #[should_panic(expected = "Method requires role Guardian")]
+    fn only_guardians_and_the_owner_pause() {
+        let mut contract = setup();
+        set_caller(&agent(), 0);
+        contract.pause();
+    }
+
+    #[test]
+    #[should_panic(expected = "Contract not paused")]
+    fn unpausing_a_running_contract_fails() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.unpause();
+    }
+
+    #[test]
+    fn pausing_and_unpausing_are_announced() {
+        let mut contract = setup_paused();
+        let logs = near_sdk::test_utils::get_logs();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].contains("\"event\":\"paused\""));
+        assert!(logs[0].contains(guardian().as_str()));
+
+        set_caller(&executor(), 0);
+        contract.unpause();
+        let logs = near_sdk::test_utils::get_logs();
+        assert_eq!(logs.len(), 1);
+        assert!(logs[0].contains("\"event\":\"unpaused\""));
+        assert!(logs[0].contains(executor().as_str()));
+    }
+
+    #[test]
+    fn unpaused_contract_executes_again() {
+        let mut contract = setup_paused();
+        set_caller(&executor(), 0);
+        contract.unpause();
+        assert!(!contract.is_paused());
+
+        let intent_id = create_approved(&mut contract, intent(1_000));
+        set_caller(&agent(), 0);
+        assert!(matches!(
+            contract.execute_intent(intent_id),
+            PromiseOrValue::Promise(_)
+        ));
+    }
+}
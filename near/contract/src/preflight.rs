@@ -0,0 +1,43 @@
+//! Dry-run intent validation.
+//!
+//! Agents pre-flight an intent before submitting it, so a bad one is fixed
+//! before it costs a failed transaction. `validate_intent` runs the checks
+//! `create_intent` makes, from the typed ID, proof freshness and token
+//! support to amount limits and the blocked parties, and reports the first
+//! one that fails with the code and reason creation would give. It reads state
+//! only and runs as a view call.
+
+use near_sdk::near_bindgen;
+use near_sdk::serde::Serialize;
+use schemars::JsonSchema;
+
+use crate::errors::ContractError;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// Outcome of validating an intent without creating it
+#[derive(Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidationResult {
+    /// Whether `create_intent` would accept the intent
+    pub valid: bool,
+    /// Code of the reason it would be rejected, if it would
+    pub code: Option<ContractError>,
+    /// Why it would be rejected, if it would
+    pub error: Option<String>,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Run the checks of intent creation against an intent without
+    /// creating it
+    pub fn validate_intent(&self, intent: CrossChainIntent) -> ValidationResult {
+        let checked = self
+            .check_parties_unblocked(&intent)
+            .and_then(|()| self.validate_new_intent(&intent));
+        ValidationResult {
+            valid: checked.is_ok(),
+            code: checked.err(),
+            error: checked.err().map(|error| error.to_string()),
+        }
+    }
+}
@@ -0,0 +1,487 @@
+//! Receipt inclusion proofs for source chain deposits.
+//!
+//! An intent's [`BridgeProof`](crate::BridgeProof) carries the RLP-encoded
+//! receipt of the transaction that locked the tokens on the intent's chain, the Merkle-Patricia
+//! proof nodes linking it to the block's receipts root, and the index of the
+//! `TokensLocked` log inside the receipt, or `NftLocked` log for NFT intents.
+//! Execution walks the proof against a confirmed header from the chain's
+//! light client and checks the log was emitted by the chain's bridge and
+//! matches the intent before any funds move.
+//!
+//! New intents are turned away early when their proof is obviously bogus:
+//! stamped in the future, older than the configured maximum proof age, or
+//! of a block past the chain's latest checkpoint.
+//!
+//! A deposit backs at most one intent. The deposit logs an intent
+//! references are recorded when it is created, so no later intent can be
+//! created against the same deposit. They are released only when the
+//! intent's proof turns out not to prove them at execution, failing the
+//! intent without a refund, so a bogus intent cannot hold a real deposit
+//! hostage. Deposits are identified by the block, receipt and log the proof
+//! locates them at, never by the transaction hash the intent merely claims.
+
+use near_sdk::{env, near_bindgen};
+
+use crate::chains::chain_or_base;
+use crate::errors::{require, ContractError};
+use crate::light_client::BaseHeader;
+use crate::rlp::{self, RlpItem};
+use crate::roles::Role;
+use crate::tokens::{is_native_token, normalize_address};
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// Nanoseconds per second, the unit of source chain block timestamps
+const NANOS_PER_SECOND: u64 = 1_000_000_000;
+
+/// Signature of the bridges' deposit event
+const TOKENS_LOCKED_EVENT: &str = "TokensLocked(string,address,address,string,uint256,uint256)";
+
+/// Signature of the bridges' NFT deposit event, carrying the token ID in
+/// place of the amount
+const NFT_LOCKED_EVENT: &str = "NftLocked(string,address,address,string,uint256,uint256)";
+
+/// Token address the bridges lock native NEAR under
+const NATIVE_TOKEN_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the bridge contract whose `TokensLocked` logs back intents from
+    /// a chain, Base by default
+    pub fn set_base_bridge(&mut self, address: String, chain_id: Option<u64>) {
+        self.assert_role(&[Role::Owner]);
+        require(
+            parse_hex(&address).is_some_and(|bytes| bytes.len() == 20),
+            ContractError::InvalidBridgeAddress,
+        );
+        let chain_id = chain_or_base(chain_id);
+        let mut chain = self.chain(chain_id);
+        chain.bridge = Some(normalize_address(&address));
+        self.chains.insert(chain_id, chain);
+    }
+
+    /// ID of the intent the deposit logged at `log_index` of a block's
+    /// receipt backs, if any. `chain_id` defaults to Base.
+    pub fn get_deposit_intent(
+        &self,
+        block_number: u64,
+        receipt_index: u64,
+        log_index: u64,
+        chain_id: Option<u64>,
+    ) -> Option<String> {
+        self.proofs
+            .get(&deposit_key(
+                chain_or_base(chain_id),
+                block_number,
+                receipt_index,
+                log_index,
+            ))
+            .cloned()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Check none of the deposit logs a new intent references backs another
+    /// intent
+    pub(crate) fn check_deposits_unused(
+        &self,
+        intent: &CrossChainIntent,
+    ) -> Result<(), ContractError> {
+        if deposit_keys(intent).any(|key| self.proofs.contains_key(&key)) {
+            return Err(ContractError::DepositUsed);
+        }
+        Ok(())
+    }
+
+    /// Check a new intent's proof is of a block at or before the chain's
+    /// latest checkpoint, stamped neither in the future nor longer than the
+    /// maximum proof age ago
+    pub(crate) fn check_proof_fresh(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        let proof = &intent.proof;
+        let now = env::block_timestamp();
+        let timestamp = proof
+            .timestamp
+            .checked_mul(NANOS_PER_SECOND)
+            .filter(|timestamp| *timestamp <= now)
+            .ok_or(ContractError::ProofFromFuture)?;
+        if now - timestamp > self.config.max_proof_age.0 {
+            return Err(ContractError::ProofTooOld);
+        }
+        self.check_checkpointed(intent)
+    }
+
+    /// Record the deposit logs of a new intent as backing it
+    pub(crate) fn record_deposits(&mut self, intent: &CrossChainIntent) {
+        for key in deposit_keys(intent) {
+            self.proofs.insert(key, intent.id.clone());
+        }
+    }
+
+    /// Release the deposit logs an intent claimed but its proof failed to
+    /// prove, so the intents they really back can still be created
+    pub(crate) fn release_deposits(&mut self, intent: &CrossChainIntent) {
+        for key in deposit_keys(intent) {
+            if self.proofs.get(&key) == Some(&intent.id) {
+                self.proofs.remove(&key);
+            }
+        }
+    }
+
+    /// Check the intent's receipt proof against a header, returning why it
+    /// does not prove the deposit
+    pub(crate) fn check_receipt_proof(
+        &self,
+        intent: &CrossChainIntent,
+        header: &BaseHeader,
+    ) -> Result<(), ContractError> {
+        let proof = &intent.proof;
+        let bridge = self
+            .chain(intent.chain_id)
+            .bridge
+            .unwrap_or_else(|| ContractError::BridgeNotConfigured.panic());
+        let root = parse_hex(&header.receipts_root)
+            .unwrap_or_else(|| ContractError::InvalidReceiptsRoot.panic());
+
+        let nodes: Vec<Vec<u8>> = proof
+            .proof_nodes
+            .iter()
+            .map(|node| node.0.clone())
+            .collect();
+        let receipt = verify_mpt_proof(&root, &rlp::encode_u64(proof.receipt_index), &nodes)
+            .ok_or(ContractError::InvalidReceiptProof)?;
+        if receipt != proof.receipt.0 {
+            return Err(ContractError::ReceiptMismatch);
+        }
+
+        verify_deposit_log(
+            &receipt,
+            proof.log_index,
+            &bridge,
+            intent,
+            &intent.token,
+            intent
+                .nft_token_id
+                .map_or(intent.amount.0, |token_id| token_id.0),
+        )?;
+        // Every extra basket asset is locked by its own log in the same receipt
+        for asset in &intent.extra_assets {
+            verify_deposit_log(
+                &receipt,
+                asset.log_index,
+                &bridge,
+                intent,
+                &asset.token,
+                asset.amount.0,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Walk a Merkle-Patricia proof from `root` along `key`, returning the value
+/// stored at the key if every node hashes to its parent's reference
+pub fn verify_mpt_proof(root: &[u8], key: &[u8], proof: &[Vec<u8>]) -> Option<Vec<u8>> {
+    let key = to_nibbles(key);
+    let mut key_pos = 0;
+    let mut nodes = proof.iter();
+    // Hash of the next node, or the node itself when embedded in its parent
+    let mut expected = root.to_vec();
+    let mut inline = false;
+
+    loop {
+        let node = if inline {
+            expected.clone()
+        } else {
+            let node = nodes.next()?;
+            if env::keccak256(node) != expected {
+                return None;
+            }
+            node.clone()
+        };
+        let items = rlp::decode_exact(&node)?.items()?;
+
+        let child = match items.len() {
+            // Branch node
+            17 => {
+                if key_pos == key.len() {
+                    let value = items[16].payload;
+                    return (!value.is_empty()).then(|| value.to_vec());
+                }
+                let child = items[usize::from(key[key_pos])];
+                key_pos += 1;
+                child
+            }
+            // Extension or leaf node
+            2 => {
+                let (path, is_leaf) = decode_compact_path(items[0].payload)?;
+                if !key[key_pos..].starts_with(&path) {
+                    return None;
+                }
+                key_pos += path.len();
+                if is_leaf {
+                    return (key_pos == key.len()).then(|| items[1].payload.to_vec());
+                }
+                items[1]
+            }
+            _ => return None,
+        };
+
+        if child.is_list {
+            expected = child.raw.to_vec();
+            inline = true;
+        } else if child.payload.len() == 32 {
+            expected = child.payload.to_vec();
+            inline = false;
+        } else {
+            return None;
+        }
+    }
+}
+
+/// Check that log `log_index` of `receipt` is the bridge's deposit of
+/// `amount` of `token` for `intent`, or of the NFT with ID `amount` for an
+/// NFT intent
+fn verify_deposit_log(
+    receipt: &[u8],
+    log_index: u64,
+    bridge: &str,
+    intent: &CrossChainIntent,
+    token: &str,
+    amount: u128,
+) -> Result<(), ContractError> {
+    // Typed receipts (EIP-2718) are prefixed with their type byte
+    let body = match receipt.first() {
+        Some(receipt_type) if *receipt_type < 0x7f => &receipt[1..],
+        _ => receipt,
+    };
+    let fields = rlp::decode_exact(body)
+        .and_then(|item| item.items())
+        .ok_or(ContractError::InvalidReceiptEncoding)?;
+    if fields.first().map(|status| status.payload) != Some(&[1u8][..]) {
+        return Err(ContractError::SourceTransactionFailed);
+    }
+
+    let logs = fields
+        .get(3)
+        .and_then(|logs| logs.items())
+        .ok_or(ContractError::InvalidReceiptEncoding)?;
+    let log = usize::try_from(log_index)
+        .ok()
+        .and_then(|index| logs.get(index))
+        .and_then(|log| log.items())
+        .ok_or(ContractError::LogNotFound)?;
+    let [address, topics, data]: [RlpItem; 3] = log
+        .try_into()
+        .map_err(|_| ContractError::InvalidLogEncoding)?;
+    let topics = topics.items().ok_or(ContractError::InvalidLogEncoding)?;
+
+    if Some(address.payload.to_vec()) != parse_hex(bridge) {
+        return Err(ContractError::LogNotFromBridge);
+    }
+    let event = if intent.is_nft() {
+        NFT_LOCKED_EVENT
+    } else {
+        TOKENS_LOCKED_EVENT
+    };
+    if topics.len() != 4 || topics[0].payload != env::keccak256(event.as_bytes()) {
+        return Err(ContractError::LogNotDeposit);
+    }
+    if topics[1].payload != env::keccak256(intent.typed_id()?.as_bytes()) {
+        return Err(ContractError::LogIntentMismatch);
+    }
+    let token = if is_native_token(token) {
+        NATIVE_TOKEN_ADDRESS
+    } else {
+        token
+    };
+    if !topic_is_address(&topics[2], token) {
+        return Err(ContractError::LogTokenMismatch);
+    }
+    if !topic_is_address(&topics[3], &intent.sender) {
+        return Err(ContractError::LogSenderMismatch);
+    }
+
+    // data = abi.encode(string nearReceiver, uint256 amount, uint256 timestamp)
+    let data = data.payload;
+    let receiver = abi_word(data, 0)
+        .and_then(word_to_u128)
+        .and_then(|offset| usize::try_from(offset).ok())
+        .and_then(|offset| abi_string(data, offset))
+        .ok_or(ContractError::InvalidLogData)?;
+    let locked_amount = abi_word(data, 1)
+        .and_then(word_to_u128)
+        .ok_or(ContractError::InvalidLogData)?;
+
+    if receiver != intent.receiver.as_str().as_bytes() {
+        return Err(ContractError::LogReceiverMismatch);
+    }
+    if locked_amount != amount {
+        return Err(ContractError::LogAmountMismatch);
+    }
+    Ok(())
+}
+
+/// Key of a deposit log in the proofs map
+fn deposit_key(chain_id: u64, block_number: u64, receipt_index: u64, log_index: u64) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        chain_id, block_number, receipt_index, log_index
+    )
+}
+
+/// Keys of every deposit log an intent references, primary deposit first
+pub(crate) fn deposit_keys(intent: &CrossChainIntent) -> impl Iterator<Item = String> + '_ {
+    let proof = &intent.proof;
+    std::iter::once(proof.log_index)
+        .chain(intent.extra_assets.iter().map(|asset| asset.log_index))
+        .map(|log_index| {
+            deposit_key(
+                intent.chain_id,
+                proof.block_number,
+                proof.receipt_index,
+                log_index,
+            )
+        })
+}
+
+/// Decode a hex string with an optional `0x` prefix
+pub(crate) fn parse_hex(value: &str) -> Option<Vec<u8>> {
+    hex::decode(value.strip_prefix("0x").unwrap_or(value)).ok()
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// Decode a hex-prefix encoded node path into nibbles and a leaf flag
+fn decode_compact_path(path: &[u8]) -> Option<(Vec<u8>, bool)> {
+    let first = *path.first()?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return None;
+    }
+    let mut nibbles = Vec::with_capacity(path.len() * 2);
+    // Odd-length paths carry their first nibble in the prefix byte
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(to_nibbles(&path[1..]));
+    Some((nibbles, flag >= 2))
+}
+
+/// Whether an indexed address topic equals the given hex address
+fn topic_is_address(topic: &RlpItem, address: &str) -> bool {
+    match parse_hex(address) {
+        Some(address) if address.len() == 20 && topic.payload.len() == 32 => {
+            topic.payload[..12].iter().all(|byte| *byte == 0) && topic.payload[12..] == address[..]
+        }
+        _ => false,
+    }
+}
+
+fn abi_word(data: &[u8], index: usize) -> Option<&[u8]> {
+    data.get(index * 32..(index + 1) * 32)
+}
+
+/// Interpret a 32-byte ABI word as an integer that must fit in a u128
+fn word_to_u128(word: &[u8]) -> Option<u128> {
+    if word[..16].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    Some(
+        word[16..]
+            .iter()
+            .fold(0u128, |acc, byte| (acc << 8) | u128::from(*byte)),
+    )
+}
+
+fn abi_string(data: &[u8], offset: usize) -> Option<&[u8]> {
+    let len = data
+        .get(offset..offset.checked_add(32)?)
+        .and_then(word_to_u128)
+        .and_then(|len| usize::try_from(len).ok())?;
+    let start = offset + 32;
+    data.get(start..start.checked_add(len)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{
+        agent, create_approved, deposit_log, deposit_receipt, intent, receipt_holding, rehash,
+        set_caller, setup, submit_proof, BASE_TOKEN,
+    };
+    use crate::tokens::NATIVE_TOKEN;
+    use crate::{CrossChainExecutor, CrossChainIntent, IntentStatus};
+    use near_sdk::json_types::Base64VecU8;
+    use near_sdk::ONE_NEAR;
+
+    /// Execute an intent as the agent, returning its status
+    fn execute(contract: &mut CrossChainExecutor, intent: CrossChainIntent) -> IntentStatus {
+        let intent_id = create_approved(contract, intent);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id.clone());
+        contract.get_intent_status(intent_id).unwrap()
+    }
+
+    fn deposit_intent(contract: &CrossChainExecutor, intent: &CrossChainIntent) -> Option<String> {
+        let proof = &intent.proof;
+        contract.get_deposit_intent(
+            proof.block_number,
+            proof.receipt_index,
+            proof.log_index,
+            None,
+        )
+    }
+
+    #[test]
+    fn native_intents_fail_on_deposits_of_a_token() {
+        let mut contract = setup();
+        let mut intent = intent(ONE_NEAR);
+        intent.token = NATIVE_TOKEN.to_string();
+        rehash(&mut intent);
+        let log = deposit_log(&intent, BASE_TOKEN, ONE_NEAR);
+        intent.proof.receipt = Base64VecU8(receipt_holding(&[log]));
+
+        assert_eq!(
+            execute(&mut contract, intent),
+            IntentStatus::Failed("Log token does not match".to_string())
+        );
+    }
+
+    #[test]
+    fn intents_failing_their_proof_free_the_deposit_they_claimed() {
+        let mut contract = setup();
+        let mut bogus = intent(1_000_000);
+        bogus.proof.receipt = Base64VecU8(deposit_receipt(&bogus, 1_000));
+        let bogus_id = bogus.id.clone();
+        assert!(matches!(
+            execute(&mut contract, bogus.clone()),
+            IntentStatus::Failed(_)
+        ));
+        assert_eq!(deposit_intent(&contract, &bogus), None);
+        assert!(!contract.get_refundable_intents().contains(&bogus_id));
+
+        // The intent the deposit really backs can still be created
+        let mut real = intent(1_000);
+        real.proof.receipt_index = bogus.proof.receipt_index;
+        rehash(&mut real);
+        submit_proof(&mut contract, &mut real);
+        set_caller(&agent(), ONE_NEAR);
+        contract.create_intent(real.clone());
+        assert_eq!(deposit_intent(&contract, &real), Some(real.id));
+    }
+
+    #[test]
+    fn deposits_stay_claimed_by_intents_their_proof_backs() {
+        let mut contract = setup();
+        let intent = intent(1_000);
+        let intent_id = intent.id.clone();
+        assert_eq!(
+            execute(&mut contract, intent.clone()),
+            IntentStatus::Executing
+        );
+        assert_eq!(deposit_intent(&contract, &intent), Some(intent_id));
+    }
+}
@@ -0,0 +1,213 @@
+//! Solver quotes.
+//!
+//! Registered solvers bid for pending intents of their token pairs by
+//! quoting the amount the receiver gets, in the decimals of the NEAR token
+//! delivered. When the intent executes it is bound to the best live quote:
+//! the receiver gets the quoted amount and the quoting solver the rest of
+//! the delivery once the receiver's transfer succeeds. The losing quotes
+//! are pruned. Intents without a live quote deliver in full.
+//!
+//! The spread comes out of the sender's deposit, so only intents whose
+//! sender signed a minimum output take quotes, and no quote below it is
+//! accepted or selected. Intents without one always deliver in full.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Balance, Promise};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::tokens::is_native_token;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, DeliveryKind};
+
+/// Most quotes an intent holds at once, so selection stays cheap
+const MAX_QUOTES_PER_INTENT: usize = 20;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Quote {
+    pub solver: AccountId,
+    /// Amount the receiver gets, in the NEAR token's decimals
+    pub out_amount: U128,
+    /// Block timestamp after which the quote can no longer be selected
+    pub expires_at: U64,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Quote the amount the receiver of a pending intent gets if the caller
+    /// fills it, replacing the caller's previous quote for the intent
+    pub fn submit_quote(&mut self, intent_id: String, out_amount: U128, expiry: U64) {
+        self.assert_not_paused();
+
+        let solver = env::predecessor_account_id();
+        let intent = self.assert_solver_for(&solver, &intent_id);
+        let floor = intent
+            .min_out
+            .unwrap_or_else(|| ContractError::TakesNoQuotes.panic());
+        require(
+            expiry.0 > env::block_timestamp(),
+            ContractError::QuoteExpiryPassed,
+        );
+        require(
+            out_amount.0 > 0 && out_amount.0 <= self.deliverable_amount(&intent),
+            ContractError::QuoteAboveDeliverable,
+        );
+        require(out_amount.0 >= floor.0, ContractError::QuoteBelowMinimum);
+        require(
+            out_amount.0 >= self.current_min_out(&intent_id),
+            ContractError::QuoteBelowAuctionFloor,
+        );
+
+        let mut quotes: Vec<Quote> = self
+            .quotes
+            .get(&intent_id)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|quote| quote.solver != solver && self.is_live_quote(quote))
+            .collect();
+        require(
+            quotes.len() < MAX_QUOTES_PER_INTENT,
+            ContractError::TooManyQuotes,
+        );
+        quotes.push(Quote {
+            solver: solver.clone(),
+            out_amount,
+            expires_at: expiry,
+        });
+        self.quotes.insert(intent_id.clone(), quotes);
+
+        IntentEvent::QuoteSubmitted {
+            intent_id,
+            solver,
+            out_amount,
+            expires_at: expiry,
+        }
+        .emit();
+    }
+
+    /// List the live quotes of an intent
+    pub fn get_quotes(&self, intent_id: String) -> Vec<Quote> {
+        self.quotes
+            .get(&intent_id)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|quote| self.is_live_quote(quote))
+            .collect()
+    }
+
+    /// Get the quote an intent would be bound to if it executed now
+    pub fn get_best_quote(&self, intent_id: String) -> Option<Quote> {
+        let intent = self.load_intent(&intent_id)?;
+        self.best_quote(&intent, self.deliverable_amount(&intent))
+    }
+}
+
+impl CrossChainExecutor {
+    /// Check a new intent's minimum output, if any, is positive and set on
+    /// an intent whose single token is delivered as is
+    pub(crate) fn check_quote_floor(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        let Some(min_out) = intent.min_out else {
+            return Ok(());
+        };
+        if min_out.0 == 0 {
+            return Err(ContractError::QuoteFloorNotPositive);
+        }
+        if !matches!(
+            intent.delivery_kind(),
+            DeliveryKind::Transfer | DeliveryKind::Defuse
+        ) || intent.execute_after.is_some()
+            || intent.depends_on.is_some()
+        {
+            return Err(ContractError::QuotedDelivery);
+        }
+        Ok(())
+    }
+
+    /// Bind an executing intent to its best live quote for `delivered`,
+    /// pruning every quote, and return the amount the receiver gets
+    pub(crate) fn bind_best_quote(
+        &mut self,
+        intent: &CrossChainIntent,
+        delivered: Balance,
+    ) -> Balance {
+        let best = self.best_quote(intent, delivered);
+        self.quotes.remove(&intent.id);
+        self.clear_auction(&intent.id);
+        match best {
+            Some(quote) => {
+                IntentEvent::QuoteSelected {
+                    intent_id: intent.id.clone(),
+                    solver: quote.solver.clone(),
+                    out_amount: quote.out_amount,
+                }
+                .emit();
+                self.bound_quotes.insert(
+                    intent.id.clone(),
+                    (quote.solver, delivered - quote.out_amount.0),
+                );
+                quote.out_amount.0
+            }
+            None => delivered,
+        }
+    }
+
+    /// Pay the solver of an intent's bound quote the rest of the delivery,
+    /// or only drop the binding if the receiver's transfer failed
+    pub(crate) fn settle_bound_quote(&mut self, intent: &CrossChainIntent, succeeded: bool) {
+        let (solver, spread) = match self.bound_quotes.remove(&intent.id) {
+            Some(bound) => bound,
+            None => return,
+        };
+        if !succeeded || spread == 0 {
+            return;
+        }
+        if is_native_token(&intent.token) {
+            Promise::new(solver).transfer(spread);
+        } else {
+            self.deliver_token(intent.chain_id, &intent.token, &solver, U128(spread));
+        }
+    }
+
+    /// Drop the quotes and auction of an intent that will no longer execute
+    pub(crate) fn clear_quotes(&mut self, intent_id: &String) {
+        self.quotes.remove(intent_id);
+        self.clear_auction(intent_id);
+    }
+
+    /// Amount an intent delivers on NEAR after rescaling and the fee
+    pub(crate) fn deliverable_amount(&self, intent: &CrossChainIntent) -> Balance {
+        let amount = self
+            .rescale_amount(intent.chain_id, &intent.token, intent.amount.0)
+            .unwrap_or_else(|| ContractError::AmountOverflow.panic());
+        amount - self.compute_fee(&intent.token, amount)
+    }
+
+    /// Highest live quote of an intent between its minimum output, raised
+    /// by the auction's current one, and `delivered`, the earliest submitted
+    /// winning ties. Intents without a minimum output are never bound.
+    fn best_quote(&self, intent: &CrossChainIntent, delivered: Balance) -> Option<Quote> {
+        let min_out = intent.min_out?.0.max(self.current_min_out(&intent.id));
+        self.quotes
+            .get(&intent.id)
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|quote| {
+                self.is_live_quote(quote)
+                    && quote.out_amount.0 >= min_out
+                    && quote.out_amount.0 <= delivered
+            })
+            .rev()
+            .max_by_key(|quote| quote.out_amount.0)
+    }
+
+    /// Whether a quote has not expired and its solver is still registered
+    fn is_live_quote(&self, quote: &Quote) -> bool {
+        env::block_timestamp() < quote.expires_at.0 && self.solvers.get(&quote.solver).is_some()
+    }
+}
@@ -0,0 +1,107 @@
+//! Execution rate limits.
+//!
+//! Each intent token can be given a window of consecutive blocks together
+//! with the number of executions and the aggregate amount allowed inside one
+//! window. A window of 1 limits each block, 60 roughly limits each minute and
+//! 43,200 matches an epoch. Limits do not roll over between windows.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, Balance};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::roles::Role;
+use crate::tokens::normalize_address;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RateLimit {
+    /// Length of a window in blocks
+    pub window_blocks: u64,
+    pub max_executions: u32,
+    /// Largest aggregate amount executed per window, unlimited when unset
+    pub max_amount: Option<U128>,
+}
+
+/// Usage of the window currently being filled
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RateWindow {
+    /// Index of the window, `block_height / window_blocks`
+    pub window: u64,
+    pub executions: u32,
+    pub amount: U128,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the execution rate limit of an intent token, or remove it
+    pub fn set_rate_limit(&mut self, token: String, limit: Option<RateLimit>) {
+        self.assert_role(&[Role::Owner]);
+        let token = normalize_address(&token);
+        match limit {
+            Some(limit) => {
+                require(limit.window_blocks > 0, ContractError::InvalidRateWindow);
+                self.rate_limits.insert(token.clone(), limit);
+            }
+            None => {
+                self.rate_limits.remove(&token);
+                self.rate_windows.remove(&token);
+            }
+        }
+    }
+
+    /// Get the execution rate limit of an intent token
+    pub fn get_rate_limit(&self, token: String) -> Option<RateLimit> {
+        self.rate_limits.get(&normalize_address(&token)).cloned()
+    }
+
+    /// Get the usage of the most recent rate limit window of an intent token
+    pub fn get_rate_window(&self, token: String) -> Option<RateWindow> {
+        self.rate_windows.get(&normalize_address(&token)).cloned()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Count one execution of `amount` against the token's current window
+    pub(crate) fn consume_rate_limit(&mut self, token: &str, amount: Balance) {
+        let token = normalize_address(token);
+        let limit = match self.rate_limits.get(&token) {
+            Some(limit) => limit,
+            None => return,
+        };
+
+        let current = env::block_height() / limit.window_blocks;
+        let mut window = match self.rate_windows.get(&token) {
+            Some(window) if window.window == current => window.clone(),
+            _ => RateWindow {
+                window: current,
+                executions: 0,
+                amount: U128(0),
+            },
+        };
+
+        window.executions += 1;
+        require(
+            window.executions <= limit.max_executions,
+            ContractError::RateLimitExecutions,
+        );
+        window.amount = U128(
+            window
+                .amount
+                .0
+                .checked_add(amount)
+                .unwrap_or_else(|| ContractError::RateLimitOverflow.panic()),
+        );
+        if let Some(max_amount) = limit.max_amount {
+            require(
+                window.amount.0 <= max_amount.0,
+                ContractError::RateLimitAmount,
+            );
+        }
+        self.rate_windows.insert(token.clone(), window);
+    }
+}
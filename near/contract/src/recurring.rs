@@ -0,0 +1,221 @@
+//! Recurring intent templates.
+//!
+//! Subscription-style payments repeat the same transfer every period. A
+//! template fixes the sender, receiver, token and amount of the transfer and
+//! how often it may be made. Each period the sender's deposit on the source
+//! chain backs one concrete intent, which the agent or the template's keeper
+//! instantiates from the template with the period's nonce, expiry and
+//! deposit proof. The intent is validated and executed like any other, so
+//! its ID is still derived from the period's deposit, whose log names the
+//! typed hash of its terms.
+//!
+//! A period may be instantiated once its due time is reached, which then
+//! moves on by one interval. Templates may run for a set number of periods
+//! and are removed once they are used up or cancelled.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::tokens::normalize_address;
+use crate::{
+    BridgeProof, CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, IntentStatus,
+};
+
+/// Shortest interval between a template's periods
+const MIN_RECURRING_INTERVAL: u64 = 3_600 * 1_000_000_000;
+
+/// Terms of a transfer repeated every period
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RecurringTemplate {
+    /// Source chain address depositing every period
+    pub sender: String,
+    pub receiver: AccountId,
+    /// Token address on the source chain
+    pub token: String,
+    /// Amount moved every period, in source decimals
+    pub amount: U128,
+    /// EIP-155 ID of the chain the deposits are made on
+    pub chain_id: u64,
+    /// Nanoseconds between periods
+    pub interval: U64,
+    /// Block timestamp (ns) from which the next period may be instantiated
+    pub next_due_at: U64,
+    /// Periods left to instantiate, `None` until the template is cancelled
+    pub remaining: Option<u32>,
+    /// Account allowed to instantiate periods besides the agents
+    pub keeper: Option<AccountId>,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Store a recurring transfer template, returning its ID
+    pub fn create_recurring_template(&mut self, template: RecurringTemplate) -> u64 {
+        self.assert_agent();
+        require(template.amount.0 > 0, ContractError::AmountNotPositive);
+        require(
+            template.interval.0 >= MIN_RECURRING_INTERVAL,
+            ContractError::IntervalTooShort,
+        );
+        require(template.remaining != Some(0), ContractError::NoPeriods);
+        require(
+            self.chains.get(&template.chain_id).is_some(),
+            ContractError::ChainNotSupported,
+        );
+        let template = RecurringTemplate {
+            sender: normalize_address(&template.sender),
+            token: normalize_address(&template.token),
+            ..template
+        };
+
+        let template_id = self.next_template_id;
+        self.next_template_id += 1;
+        IntentEvent::RecurringTemplateCreated {
+            template_id,
+            sender: template.sender.clone(),
+            receiver: template.receiver.clone(),
+            token: template.token.clone(),
+            amount: template.amount,
+            interval: template.interval,
+        }
+        .emit();
+        self.recurring_templates.insert(template_id, template);
+        template_id
+    }
+
+    /// Stop a recurring template from being instantiated
+    pub fn cancel_recurring_template(&mut self, template_id: u64) {
+        self.assert_agent();
+        self.recurring_templates
+            .remove(&template_id)
+            .unwrap_or_else(|| ContractError::TemplateNotFound.panic());
+        IntentEvent::RecurringTemplateCancelled { template_id }.emit();
+    }
+
+    /// Create the intent of a template's due period, backed by the period's
+    /// deposit and attaching NEAR for its storage. Returns the intent's ID,
+    /// or `None` if one of its parties is blocked.
+    #[payable]
+    pub fn instantiate_recurring_intent(
+        &mut self,
+        template_id: u64,
+        nonce: u64,
+        expires_at: U64,
+        proof: BridgeProof,
+        sender_signature: Option<Base64VecU8>,
+    ) -> Option<String> {
+        self.assert_not_paused();
+
+        let template = self
+            .recurring_templates
+            .get(&template_id)
+            .cloned()
+            .unwrap_or_else(|| ContractError::TemplateNotFound.panic());
+        let payer = match &template.keeper {
+            Some(keeper) if keeper == &env::predecessor_account_id() => keeper.clone(),
+            _ => self.assert_agent(),
+        };
+        require(
+            env::block_timestamp() >= template.next_due_at.0,
+            ContractError::PeriodNotDue,
+        );
+
+        let mut intent = template.intent(nonce, expires_at, proof, self.current_epoch);
+        intent.sender_signature = sender_signature;
+        intent.id = match intent.derived_id() {
+            Ok(intent_id) => intent_id,
+            Err(reason) => reason.panic(),
+        };
+        // Record blocked attempts and leave the period due
+        if self.screen_intent(&intent, "create").is_err() {
+            self.settle_attached_deposit(0);
+            return None;
+        }
+        if let Err(reason) = self.validate_new_intent(&intent) {
+            reason.panic();
+        }
+
+        let remaining = template.remaining.map(|remaining| remaining - 1);
+        if remaining == Some(0) {
+            self.recurring_templates.remove(&template_id);
+        } else {
+            self.recurring_templates.insert(
+                template_id,
+                RecurringTemplate {
+                    next_due_at: U64(template.next_due_at.0.saturating_add(template.interval.0)),
+                    remaining,
+                    ..template
+                },
+            );
+        }
+
+        let initial_storage = self.storage_usage();
+        let intent_id = intent.id.clone();
+        self.store_new_intent(intent);
+        let cost = self.record_intent_deposit(&intent_id, &payer, initial_storage);
+        self.settle_attached_deposit(cost);
+        IntentEvent::RecurringIntentInstantiated {
+            template_id,
+            intent_id: intent_id.clone(),
+        }
+        .emit();
+        Some(intent_id)
+    }
+
+    /// Get a recurring template
+    pub fn get_recurring_template(&self, template_id: u64) -> Option<RecurringTemplate> {
+        self.recurring_templates.get(&template_id).cloned()
+    }
+
+    /// List recurring templates with their IDs
+    pub fn get_recurring_templates(&self) -> Vec<(u64, RecurringTemplate)> {
+        self.recurring_templates
+            .iter()
+            .map(|(template_id, template)| (*template_id, template.clone()))
+            .collect()
+    }
+}
+
+impl RecurringTemplate {
+    /// Intent of one period, its ID left to be derived from its terms
+    fn intent(
+        &self,
+        nonce: u64,
+        expires_at: U64,
+        proof: BridgeProof,
+        epoch: u64,
+    ) -> CrossChainIntent {
+        CrossChainIntent {
+            id: String::new(),
+            sender: self.sender.clone(),
+            receiver: self.receiver.clone(),
+            token: self.token.clone(),
+            amount: self.amount,
+            proof,
+            status: IntentStatus::Pending,
+            signatures: Vec::new(),
+            expires_at,
+            epoch,
+            nonce,
+            sender_signature: None,
+            chain_id: self.chain_id,
+            settle_in_defuse: false,
+            extra_assets: Vec::new(),
+            nft_token_id: None,
+            referrer: None,
+            swap: None,
+            to_stablecoin: false,
+            aurora_recipient: None,
+            mt_token_id: None,
+            execute_after: None,
+            depends_on: None,
+            actions: Vec::new(),
+            min_out: None,
+        }
+    }
+}
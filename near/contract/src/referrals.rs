@@ -0,0 +1,149 @@
+//! Referral fee shares.
+//!
+//! Wallets and dApps routing users through the bridge name themselves as
+//! the `referrer` of the intents they originate. When such an intent
+//! completes, the referral share of the protocol fee withheld from it is
+//! set aside for the referrer instead of the treasury. Shares accrue per
+//! referrer and intent token, in the decimals of the NEAR token delivered
+//! for it, until the referrer claims them. The referrer is a term of the
+//! intent, part of its typed hash, so whoever submits a signed intent
+//! cannot name themselves to take the share.
+
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, AccountId, Balance, Promise, PromiseError};
+
+use crate::chains::chain_or_base;
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::fees::BPS_DENOMINATOR;
+use crate::tokens::{is_native_token, normalize_address};
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// Largest share of the protocol fee the owner can give referrers (50%)
+const MAX_REFERRAL_SHARE_BPS: u16 = 5_000;
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the share of the protocol fee, in basis points, credited to the
+    /// referrer of an intent
+    pub fn set_referral_share(&mut self, share_bps: u16) {
+        self.assert_untimelocked_owner();
+        self.set_referral_share_bps(share_bps);
+    }
+
+    /// Share of the protocol fee, in basis points, credited to referrers
+    pub fn get_referral_share(&self) -> u16 {
+        self.referral_share_bps
+    }
+
+    /// Send the caller all referral fees it accrued in a token, resolving
+    /// its FT contract on the chain it is registered for, Base by default
+    pub fn claim_referral_fees(&mut self, token: String, chain_id: Option<u64>) -> Promise {
+        let referrer = env::predecessor_account_id();
+        let token = normalize_address(&token);
+        let amount = self
+            .referral_fees
+            .remove(&(referrer.clone(), token.clone()))
+            .unwrap_or(0);
+        require(amount > 0, ContractError::NoReferralFees);
+
+        let transfer = if is_native_token(&token) {
+            self.native_referral_fees -= amount;
+            self.assert_native_liquidity(amount);
+            Promise::new(referrer.clone()).transfer(amount)
+        } else {
+            self.deliver_token(chain_or_base(chain_id), &token, &referrer, U128(amount))
+        };
+
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(self.config.transfer_callback_gas)
+                .on_referral_fees_claimed(referrer, token, U128(amount)),
+        )
+    }
+
+    /// Re-credit referral fees whose claim transfer failed
+    #[private]
+    pub fn on_referral_fees_claimed(
+        &mut self,
+        referrer: AccountId,
+        token: String,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            IntentEvent::ReferralFeesClaimed {
+                referrer,
+                token,
+                amount,
+            }
+            .emit();
+            true
+        } else {
+            self.credit_referral_fee(&referrer, &token, amount.0);
+            false
+        }
+    }
+
+    /// Referral fees a referrer accrued in a token and has not claimed
+    pub fn get_referral_fees(&self, referrer: AccountId, token: String) -> U128 {
+        U128(
+            self.referral_fees
+                .get(&(referrer, normalize_address(&token)))
+                .copied()
+                .unwrap_or(0),
+        )
+    }
+}
+
+impl CrossChainExecutor {
+    /// Set the referral share, capped at [`MAX_REFERRAL_SHARE_BPS`]
+    pub(crate) fn set_referral_share_bps(&mut self, share_bps: u16) {
+        require(
+            share_bps <= MAX_REFERRAL_SHARE_BPS,
+            ContractError::ReferralShareTooHigh,
+        );
+        self.referral_share_bps = share_bps;
+    }
+
+    /// Credit the referral share of a fee withheld from `intent` to its
+    /// referrer, returning the part of the fee left for the protocol
+    pub(crate) fn split_referral_fee(
+        &mut self,
+        intent: &CrossChainIntent,
+        token: &str,
+        fee: Balance,
+    ) -> Balance {
+        let Some(referrer) = &intent.referrer else {
+            return fee;
+        };
+        let share_bps = Balance::from(self.referral_share_bps);
+        let denominator = Balance::from(BPS_DENOMINATOR);
+        let share = fee / denominator * share_bps + fee % denominator * share_bps / denominator;
+        if share == 0 {
+            return fee;
+        }
+        self.credit_referral_fee(referrer, token, share);
+
+        IntentEvent::ReferralFeeAccrued {
+            intent_id: intent.id.clone(),
+            referrer: referrer.clone(),
+            token: normalize_address(token),
+            amount: U128(share),
+        }
+        .emit();
+        fee - share
+    }
+
+    /// Add to the referral fees a referrer accrued in a token
+    fn credit_referral_fee(&mut self, referrer: &AccountId, token: &str, amount: Balance) {
+        let token = normalize_address(token);
+        if is_native_token(&token) {
+            self.native_referral_fees += amount;
+        }
+        *self
+            .referral_fees
+            .entry((referrer.clone(), token))
+            .or_insert(0) += amount;
+    }
+}
@@ -0,0 +1,159 @@
+//! Retries of failed deliveries.
+//!
+//! A transfer can fail for reasons that pass, such as a receiver not yet
+//! registered with the token or a token contract briefly out of gas. Once
+//! the owner allows more than one delivery attempt, an intent none of whose
+//! transfers went through stays executing and waits in a retry queue, its
+//! backoff doubling with every failed attempt. Anyone can re-attempt the
+//! deliveries whose backoff elapsed, and is reimbursed for the gas like any
+//! executor once the intent completes. A retry screens the intent's parties
+//! and tokens again as its execution did, so a delivery whose receiver was
+//! blocked since stays queued. The last allowed attempt failing routes the
+//! intent to refund.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+use schemars::JsonSchema;
+
+use crate::errors::ContractError;
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// Most delivery attempts the owner may allow an intent
+const MAX_DELIVERY_ATTEMPTS: u32 = 10;
+
+/// Nanoseconds a first failed delivery waits before it may be retried
+const RETRY_BACKOFF: u64 = 10 * 60 * 1_000_000_000;
+
+/// Retry of a failed delivery waiting in the queue
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DeliveryRetry {
+    /// Failed deliveries of the intent so far
+    pub attempts: u32,
+    /// Timestamp (ns) from which the delivery may be retried
+    pub next_attempt_at: U64,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set how many deliveries an intent gets before a failed one routes it
+    /// to refund, 1 to refund on the first failure
+    pub fn set_max_delivery_attempts(&mut self, max_attempts: u32) {
+        self.assert_role(&[Role::Owner]);
+        if !(1..=MAX_DELIVERY_ATTEMPTS).contains(&max_attempts) {
+            ContractError::InvalidDeliveryAttempts.panic_with(&format!(
+                "Delivery attempts must be between 1 and {}",
+                MAX_DELIVERY_ATTEMPTS
+            ));
+        }
+        self.max_delivery_attempts = max_attempts;
+    }
+
+    /// Re-attempt the deliveries of up to `limit` queued intents whose
+    /// backoff elapsed, as far as the attached gas allows. Callable by
+    /// anyone. Returns the IDs of the intents retried.
+    pub fn retry_failed_intents(&mut self, limit: u32) -> Vec<String> {
+        self.assert_not_paused();
+
+        let executor = env::predecessor_account_id();
+        let now = env::block_timestamp();
+        let due: Vec<(String, u32)> = self
+            .retry_queue
+            .iter()
+            .filter(|(_, retry)| retry.next_attempt_at.0 <= now)
+            .map(|(intent_id, retry)| (intent_id.clone(), retry.attempts))
+            .take(limit as usize)
+            .collect();
+
+        let mut retried = Vec::new();
+        for (intent_id, attempts) in due {
+            let mut intent = self
+                .load_intent(&intent_id)
+                .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+            let needed = self.delivery_gas(&intent).0 + self.config.transfer_callback_gas.0;
+            if env::prepaid_gas().0.saturating_sub(env::used_gas().0) < needed {
+                break;
+            }
+            // Leave the delivery queued while a party is blocked or a token
+            // or the receiver is no longer allowed
+            if self.screen_intent(&intent, "retry").is_err()
+                || self.check_delivery_allowed(&intent).is_err()
+            {
+                continue;
+            }
+            self.retry_queue.remove(&intent_id);
+
+            // Fail rather than swap past the deadline the sender set
+            if intent
+                .swap_deadline()
+                .is_some_and(|deadline| now > deadline)
+            {
+                self.delivery_attempts.remove(&intent_id);
+                self.fail_intent(&mut intent, "swap deadline passed");
+                continue;
+            }
+
+            let gas_at_start = env::used_gas();
+            let delivery = self.dispatch_delivery(&intent);
+            self.record_execution_gas(&executor, &intent, gas_at_start);
+            self.settle_delivery(intent_id.clone(), !intent.actions.is_empty(), delivery);
+
+            IntentEvent::DeliveryRetried {
+                intent_id: intent_id.clone(),
+                executor: executor.clone(),
+                attempt: attempts + 1,
+            }
+            .emit();
+            retried.push(intent_id);
+        }
+        retried
+    }
+
+    /// Get how many deliveries an intent gets before a failed one routes it
+    /// to refund
+    pub fn get_max_delivery_attempts(&self) -> u32 {
+        self.max_delivery_attempts
+    }
+
+    /// List failed deliveries waiting to be retried
+    pub fn get_retry_queue(&self) -> Vec<(String, DeliveryRetry)> {
+        self.retry_queue
+            .iter()
+            .map(|(intent_id, retry)| (intent_id.clone(), retry.clone()))
+            .collect()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Queue an executing intent whose delivery failed for a retry, or fail
+    /// it for refund once it used up its delivery attempts
+    pub(crate) fn retry_or_fail(&mut self, intent: &mut CrossChainIntent) {
+        let attempts = self.delivery_attempts.get(&intent.id).copied().unwrap_or(0) + 1;
+        if attempts >= self.max_delivery_attempts {
+            self.delivery_attempts.remove(&intent.id);
+            self.fail_intent(intent, "transfer failed");
+            return;
+        }
+        let backoff = RETRY_BACKOFF.saturating_mul(1 << (attempts - 1));
+        let next_attempt_at = U64(env::block_timestamp().saturating_add(backoff));
+        self.delivery_attempts.insert(intent.id.clone(), attempts);
+        self.retry_queue.insert(
+            intent.id.clone(),
+            DeliveryRetry {
+                attempts,
+                next_attempt_at,
+            },
+        );
+
+        IntentEvent::DeliveryRetryQueued {
+            intent_id: intent.id.clone(),
+            attempts,
+            next_attempt_at,
+        }
+        .emit();
+    }
+}
@@ -0,0 +1,159 @@
+//! Minimal RLP decoding for Ethereum/Base trie nodes and receipts.
+//!
+//! Only the subset needed to walk receipt proofs is implemented: items are
+//! borrowed slices of the input and decoding never allocates payload copies.
+
+/// A decoded RLP item borrowing from the encoded input
+#[derive(Clone, Copy, Debug)]
+pub struct RlpItem<'a> {
+    pub is_list: bool,
+    /// Item content without the RLP header
+    pub payload: &'a [u8],
+    /// Full encoding including the header
+    pub raw: &'a [u8],
+}
+
+impl<'a> RlpItem<'a> {
+    /// Decode the elements of a list item
+    pub fn items(&self) -> Option<Vec<RlpItem<'a>>> {
+        if !self.is_list {
+            return None;
+        }
+        let mut items = Vec::new();
+        let mut rest = self.payload;
+        while !rest.is_empty() {
+            let (item, tail) = decode(rest)?;
+            items.push(item);
+            rest = tail;
+        }
+        Some(items)
+    }
+}
+
+/// Decode one item from the front of `data`, returning it and the remaining bytes
+pub fn decode(data: &[u8]) -> Option<(RlpItem<'_>, &[u8])> {
+    let prefix = *data.first()?;
+    let (is_list, header_len, payload_len) = match prefix {
+        0x00..=0x7f => return Some((single_byte(data), &data[1..])),
+        0x80..=0xb7 => (false, 1, usize::from(prefix - 0x80)),
+        0xb8..=0xbf => {
+            let len_of_len = usize::from(prefix - 0xb7);
+            (
+                false,
+                1 + len_of_len,
+                read_length(data.get(1..1 + len_of_len)?)?,
+            )
+        }
+        0xc0..=0xf7 => (true, 1, usize::from(prefix - 0xc0)),
+        0xf8..=0xff => {
+            let len_of_len = usize::from(prefix - 0xf7);
+            (
+                true,
+                1 + len_of_len,
+                read_length(data.get(1..1 + len_of_len)?)?,
+            )
+        }
+    };
+
+    let end = header_len.checked_add(payload_len)?;
+    if end > data.len() {
+        return None;
+    }
+    let item = RlpItem {
+        is_list,
+        payload: &data[header_len..end],
+        raw: &data[..end],
+    };
+    Some((item, &data[end..]))
+}
+
+/// Decode `data` as exactly one item with no trailing bytes
+pub fn decode_exact(data: &[u8]) -> Option<RlpItem<'_>> {
+    match decode(data)? {
+        (item, []) => Some(item),
+        _ => None,
+    }
+}
+
+/// RLP encoding of an unsigned integer, as used for receipt trie keys
+pub fn encode_u64(value: u64) -> Vec<u8> {
+    if value == 0 {
+        return vec![0x80];
+    }
+    if value < 0x80 {
+        return vec![value as u8];
+    }
+    let bytes: Vec<u8> = value
+        .to_be_bytes()
+        .iter()
+        .copied()
+        .skip_while(|byte| *byte == 0)
+        .collect();
+    let mut encoded = vec![0x80 + bytes.len() as u8];
+    encoded.extend(bytes);
+    encoded
+}
+
+fn single_byte(data: &[u8]) -> RlpItem<'_> {
+    RlpItem {
+        is_list: false,
+        payload: &data[..1],
+        raw: &data[..1],
+    }
+}
+
+fn read_length(bytes: &[u8]) -> Option<usize> {
+    if bytes.len() > std::mem::size_of::<usize>() || bytes.first() == Some(&0) {
+        return None;
+    }
+    Some(
+        bytes
+            .iter()
+            .fold(0usize, |acc, byte| (acc << 8) | usize::from(*byte)),
+    )
+}
+
+/// RLP encoders used to build Base transactions and test fixtures
+pub mod encode {
+    /// Unsigned integer as its minimal big-endian byte string
+    pub fn uint(value: u128) -> Vec<u8> {
+        let bytes: Vec<u8> = value
+            .to_be_bytes()
+            .iter()
+            .copied()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        self::bytes(&bytes)
+    }
+
+    pub fn bytes(data: &[u8]) -> Vec<u8> {
+        if data.len() == 1 && data[0] < 0x80 {
+            return data.to_vec();
+        }
+        let mut encoded = header(0x80, data.len());
+        encoded.extend_from_slice(data);
+        encoded
+    }
+
+    pub fn list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload: Vec<u8> = items.concat();
+        let mut encoded = header(0xc0, payload.len());
+        encoded.extend(payload);
+        encoded
+    }
+
+    fn header(offset: u8, len: usize) -> Vec<u8> {
+        if len <= 55 {
+            return vec![offset + len as u8];
+        }
+        let len_bytes: Vec<u8> = len
+            .to_be_bytes()
+            .iter()
+            .copied()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        let mut encoded = vec![offset + 55 + len_bytes.len() as u8];
+        encoded.extend(len_bytes);
+        encoded
+    }
+}
@@ -0,0 +1,153 @@
+//! Role-based access control.
+//!
+//! Every privileged method names the roles allowed to call it through
+//! [`CrossChainExecutor::assert_role`]. Guardian, Agent, FeeCollector and
+//! Arbiter memberships are granted and revoked by the owner; the Owner role follows
+//! the two-step ownership transfer and the Validator role follows the
+//! validator registry, since validators must register a signing key and
+//! a bond.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::store::UnorderedSet;
+use near_sdk::{env, near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, StorageKey};
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Debug,
+    JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    /// Administers the contract
+    Owner,
+    /// May pause the contract
+    Guardian,
+    /// CDP agent that creates, executes, cancels and refunds intents
+    Agent,
+    /// Bridge validator approving intents and attesting headers
+    Validator,
+    /// May collect protocol fees
+    FeeCollector,
+    /// Rules on disputed intents, typically a DAO
+    Arbiter,
+}
+
+/// Roles whose membership is stored in the role registry rather than derived
+pub(crate) const GRANTABLE_ROLES: [Role; 4] = [
+    Role::Guardian,
+    Role::Agent,
+    Role::FeeCollector,
+    Role::Arbiter,
+];
+
+impl Role {
+    fn is_grantable(&self) -> bool {
+        GRANTABLE_ROLES.contains(self)
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Grant a role to an account
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_role(&[Role::Owner]);
+        self.grant(role, &account_id);
+    }
+
+    /// Revoke a role from an account
+    pub fn revoke_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_role(&[Role::Owner]);
+        self.revoke(role, &account_id);
+    }
+
+    /// View method to check if an account holds a role
+    pub fn has_role(&self, role: Role, account_id: AccountId) -> bool {
+        match role {
+            Role::Owner => account_id == self.owner,
+            Role::Validator => self.attesting_key(&account_id).is_some(),
+            _ => self
+                .role_members
+                .get(&role)
+                .is_some_and(|members| members.contains(&account_id)),
+        }
+    }
+
+    /// List accounts granted a Guardian, Agent, FeeCollector or Arbiter role
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        assert_grantable(role);
+        self.role_members
+            .get(&role)
+            .map(|members| members.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Assert the caller holds at least one of `roles`, returning the caller
+    pub(crate) fn assert_role(&self, roles: &[Role]) -> AccountId {
+        let caller = env::predecessor_account_id();
+        if !roles
+            .iter()
+            .any(|role| self.has_role(*role, caller.clone()))
+        {
+            let required = roles
+                .iter()
+                .map(|role| format!("{:?}", role))
+                .collect::<Vec<_>>()
+                .join(" or ");
+            ContractError::MissingRole.panic_with(&format!("Method requires role {}", required));
+        }
+        caller
+    }
+
+    /// Add an account to a grantable role
+    pub(crate) fn grant(&mut self, role: Role, account_id: &AccountId) {
+        assert_grantable(role);
+        self.role_members
+            .entry(role)
+            .or_insert_with(|| UnorderedSet::new(StorageKey::RoleMemberSets { role }))
+            .insert(account_id.clone());
+
+        IntentEvent::RoleGranted {
+            role,
+            account_id: account_id.clone(),
+        }
+        .emit();
+    }
+
+    /// Remove an account from a grantable role
+    pub(crate) fn revoke(&mut self, role: Role, account_id: &AccountId) {
+        assert_grantable(role);
+        require(
+            self.role_members
+                .get_mut(&role)
+                .is_some_and(|members| members.remove(account_id)),
+            ContractError::RoleNotHeld,
+        );
+
+        IntentEvent::RoleRevoked {
+            role,
+            account_id: account_id.clone(),
+        }
+        .emit();
+    }
+}
+
+fn assert_grantable(role: Role) {
+    require(role.is_grantable(), ContractError::RoleNotGrantable);
+}
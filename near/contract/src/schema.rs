@@ -0,0 +1,23 @@
+//! JSON Schemas of the payloads the agent layers exchange with the executor.
+//!
+//! The schemas are derived from the contract's own types, so a payload that
+//! validates against them deserializes on-chain and an event log that
+//! validates matches what the executor emits. `schema-gen/` writes them to
+//! disk for the TypeScript and Python agents.
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use crate::events::EventLog;
+use crate::{BridgeProof, CrossChainIntent, IntentStatus};
+
+/// Schemas keyed by the file stem they are written under
+pub fn schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("cross_chain_intent", schema_for!(CrossChainIntent)),
+        ("bridge_proof", schema_for!(BridgeProof)),
+        ("intent_status", schema_for!(IntentStatus)),
+        // Data of an `EVENT_JSON:` log line, after the prefix
+        ("intent_event", schema_for!(EventLog<'static>)),
+    ]
+}
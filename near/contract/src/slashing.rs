@@ -0,0 +1,107 @@
+//! Slashing for provably bad attestations.
+//!
+//! Anyone may present intents a validator signed that no honest validator
+//! would: two different intents for the same Base deposit, or an intent
+//! whose receipt proof fails against the canonical confirmed header. The
+//! validator's whole bond, bonded and unbonding, is moved to the protocol's
+//! native fees and the validator leaves the set.
+//!
+//! Conflicting header signatures are not slashable, since honest validators
+//! sign both sides of a Base reorg.
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, AccountId, Balance, PublicKey};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::tokens::{normalize_address, NATIVE_TOKEN};
+use crate::{verify_ed25519, CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Misbehavior {
+    /// Two different intents signed for the same intent ID or Base deposit
+    ConflictingIntents {
+        first: Box<CrossChainIntent>,
+        second: Box<CrossChainIntent>,
+    },
+    /// An intent signed with a proof that does not prove its deposit
+    InvalidDeposit { intent: Box<CrossChainIntent> },
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Slash a validator for signing the intents in `proof`, returning the
+    /// amount slashed. Callable by anyone.
+    pub fn submit_misbehavior_proof(&mut self, validator: AccountId, proof: Misbehavior) -> U128 {
+        let public_key = self
+            .bridge_validators
+            .get(&validator)
+            .unwrap_or_else(|| ContractError::ValidatorNotFound.panic());
+
+        let reason = match &proof {
+            Misbehavior::ConflictingIntents { first, second } => {
+                require(
+                    signed_by(first, &validator, public_key)
+                        && signed_by(second, &validator, public_key),
+                    ContractError::IntentsNotSignedByValidator,
+                );
+                let same_deposit = first.id == second.id
+                    || (first.chain_id == second.chain_id
+                        && normalize_address(&first.proof.transaction_hash)
+                            == normalize_address(&second.proof.transaction_hash)
+                        && first.proof.log_index == second.proof.log_index);
+                require(
+                    same_deposit && first.signing_hash() != second.signing_hash(),
+                    ContractError::IntentsDoNotConflict,
+                );
+                "Conflicting intents"
+            }
+            Misbehavior::InvalidDeposit { intent } => {
+                require(
+                    signed_by(intent, &validator, public_key),
+                    ContractError::NotSignedByValidator,
+                );
+                let header = self.confirmed_header(intent.chain_id, intent.proof.block_number);
+                require(
+                    self.check_receipt_proof(intent, &header).is_err(),
+                    ContractError::DepositProofValid,
+                );
+                "Invalid deposit"
+            }
+        };
+
+        let amount = self.slash(&validator);
+        IntentEvent::ValidatorSlashed {
+            validator,
+            amount: U128(amount),
+            reason: reason.to_string(),
+        }
+        .emit();
+        U128(amount)
+    }
+}
+
+impl CrossChainExecutor {
+    /// Remove a validator and move its whole bond to the native fees
+    fn slash(&mut self, validator: &AccountId) -> Balance {
+        self.bridge_validators.remove(validator);
+        let amount = self
+            .validator_bonds
+            .remove(validator)
+            .map_or(0, |bond| bond.bonded.0 + bond.unbonding.0);
+        self.total_bonded -= amount;
+        self.accrue_fee(NATIVE_TOKEN, amount);
+        amount
+    }
+}
+
+/// Whether the intent carries a valid signature from `validator`
+fn signed_by(intent: &CrossChainIntent, validator: &AccountId, public_key: &PublicKey) -> bool {
+    let message = intent.signing_hash();
+    intent.signatures.iter().any(|entry| {
+        &entry.validator == validator && verify_ed25519(public_key, &message, &entry.signature.0)
+    })
+}
@@ -0,0 +1,276 @@
+//! Solver registry.
+//!
+//! Third-party solvers register by attaching a NEAR bond and listing the
+//! token pairs they fill. A registered solver may claim a pending intent of
+//! one of its pairs, reserving the intent's execution for itself for the
+//! claim period: it then executes the intent in place of the CDP agent,
+//! which can only step in once the claim lapses. Unregistering returns the
+//! bond at once and voids the solver's outstanding claims.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::tokens::{is_native_token, normalize_address, NATIVE_TOKEN};
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, IntentStatus};
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Debug, JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenPair {
+    /// EIP-155 ID of the source chain
+    pub chain_id: u64,
+    /// Token address on the source chain, 0x-prefixed hex, or `near`
+    pub source_token: String,
+    /// NEP-141 contract delivered on NEAR, or `near` for native NEAR
+    pub near_token: String,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Solver {
+    /// NEAR backing the solver's claims
+    pub bond: U128,
+    /// Token pairs whose intents the solver may claim
+    pub pairs: Vec<TokenPair>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct IntentClaim {
+    pub solver: AccountId,
+    /// Block timestamp after which the claim lapses
+    pub expires_at: U64,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Register the caller as a solver, or replace its token pairs, bonding
+    /// the attached deposit. The total bond must reach the minimum.
+    #[payable]
+    pub fn register_solver(&mut self, pairs: Vec<TokenPair>) {
+        require(!pairs.is_empty(), ContractError::SolverNeedsPair);
+        let solver = env::predecessor_account_id();
+        let amount = env::attached_deposit();
+        let bond = self
+            .solvers
+            .get(&solver)
+            .map_or(0, |registered| registered.bond.0)
+            + amount;
+        if bond < self.config.min_solver_bond.0 {
+            ContractError::BondBelowMinimum.panic_with(&format!(
+                "Bond is below the minimum of {} yoctoNEAR",
+                self.config.min_solver_bond.0
+            ));
+        }
+        let pairs = pairs
+            .into_iter()
+            .map(|pair| TokenPair {
+                source_token: normalize_address(&pair.source_token),
+                ..pair
+            })
+            .collect();
+        self.solvers.insert(
+            solver.clone(),
+            Solver {
+                bond: U128(bond),
+                pairs,
+            },
+        );
+        self.total_bonded += amount;
+
+        IntentEvent::SolverRegistered {
+            solver,
+            bond: U128(bond),
+        }
+        .emit();
+    }
+
+    /// Unregister the caller as a solver and withdraw its bond
+    pub fn unregister_solver(&mut self) -> Promise {
+        let solver = env::predecessor_account_id();
+        let registered = self
+            .solvers
+            .remove(&solver)
+            .unwrap_or_else(|| ContractError::NotSolver.panic());
+        self.total_bonded -= registered.bond.0;
+
+        IntentEvent::SolverUnregistered {
+            solver: solver.clone(),
+        }
+        .emit();
+
+        Promise::new(solver.clone())
+            .transfer(registered.bond.0)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(self.config.transfer_callback_gas)
+                    .on_solver_bond_withdrawn(solver, registered.bond),
+            )
+    }
+
+    /// Restore the bond of a solver whose withdrawal transfer failed, without
+    /// any token pairs so it cannot claim until it registers again
+    #[private]
+    pub fn on_solver_bond_withdrawn(
+        &mut self,
+        solver: AccountId,
+        bond: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            return true;
+        }
+        let restored = self
+            .solvers
+            .get(&solver)
+            .map_or(0, |registered| registered.bond.0)
+            + bond.0;
+        self.solvers.insert(
+            solver.clone(),
+            Solver {
+                bond: U128(restored),
+                pairs: vec![],
+            },
+        );
+        self.total_bonded += bond.0;
+        false
+    }
+
+    /// Claim a pending intent of one of the caller's token pairs, reserving
+    /// its execution for the caller for the claim period
+    pub fn claim_intent(&mut self, intent_id: String) -> IntentClaim {
+        self.assert_not_paused();
+
+        let solver = env::predecessor_account_id();
+        self.assert_solver_for(&solver, &intent_id);
+        require(
+            self.live_claim(&intent_id).is_none(),
+            ContractError::AlreadyClaimed,
+        );
+        require(
+            self.auctions.get(&intent_id).is_none(),
+            ContractError::BeingAuctioned,
+        );
+
+        let claim = IntentClaim {
+            solver,
+            expires_at: U64(env::block_timestamp() + self.config.solver_claim_period.0),
+        };
+        self.intent_claims.insert(intent_id.clone(), claim.clone());
+
+        IntentEvent::IntentClaimed {
+            intent_id,
+            solver: claim.solver.clone(),
+            expires_at: claim.expires_at,
+        }
+        .emit();
+        claim
+    }
+
+    /// Get a registered solver
+    pub fn get_solver(&self, account_id: AccountId) -> Option<Solver> {
+        self.solvers.get(&account_id).cloned()
+    }
+
+    /// List registered solvers
+    pub fn get_solvers(&self) -> Vec<(AccountId, Solver)> {
+        self.solvers
+            .iter()
+            .map(|(account_id, solver)| (account_id.clone(), solver.clone()))
+            .collect()
+    }
+
+    /// Get the live claim on an intent, if any
+    pub fn get_intent_claim(&self, intent_id: String) -> Option<IntentClaim> {
+        self.live_claim(&intent_id)
+    }
+}
+
+impl CrossChainExecutor {
+    /// Assert the caller may execute an intent, returning the caller: the
+    /// solver holding a live claim on it, or an active agent otherwise
+    pub(crate) fn assert_executor(&self, intent_id: &String) -> AccountId {
+        let caller = env::predecessor_account_id();
+        match self.live_claim(intent_id) {
+            Some(claim) if claim.solver == caller => caller,
+            _ => self.assert_agent(),
+        }
+    }
+
+    /// Check no other solver holds a live claim on an intent
+    pub(crate) fn check_claim_allows(
+        &self,
+        executor: &AccountId,
+        intent_id: &String,
+    ) -> Result<(), ContractError> {
+        match self.live_claim(intent_id) {
+            Some(claim) if &claim.solver != executor => Err(ContractError::Claimed),
+            _ => Ok(()),
+        }
+    }
+
+    /// Assert a registered solver may fill a pending intent of one of its
+    /// token pairs, returning the intent
+    pub(crate) fn assert_solver_for(
+        &self,
+        solver: &AccountId,
+        intent_id: &String,
+    ) -> CrossChainIntent {
+        let registered = self
+            .solvers
+            .get(solver)
+            .filter(|registered| registered.bond.0 >= self.config.min_solver_bond.0)
+            .unwrap_or_else(|| ContractError::NotSolver.panic());
+        let intent = self
+            .load_intent(intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        require(
+            matches!(intent.status, IntentStatus::Pending),
+            ContractError::NotPending,
+        );
+        require(
+            env::block_timestamp() < intent.expires_at.0,
+            ContractError::Expired,
+        );
+
+        let near_token = if is_native_token(&intent.token) {
+            NATIVE_TOKEN.to_string()
+        } else {
+            self.resolve_token(intent.chain_id, &intent.token)
+                .to_string()
+        };
+        let pair = TokenPair {
+            chain_id: intent.chain_id,
+            source_token: normalize_address(&intent.token),
+            near_token,
+        };
+        require(
+            registered.pairs.contains(&pair),
+            ContractError::SolverPairUnsupported,
+        );
+        intent
+    }
+
+    /// Drop the claim on an intent once it no longer needs executing
+    pub(crate) fn clear_claim(&mut self, intent_id: &String) {
+        self.intent_claims.remove(intent_id);
+    }
+
+    /// Claim on an intent that has not lapsed and whose solver is still
+    /// registered
+    pub(crate) fn live_claim(&self, intent_id: &String) -> Option<IntentClaim> {
+        self.intent_claims
+            .get(intent_id)
+            .filter(|claim| {
+                env::block_timestamp() < claim.expires_at.0
+                    && self.solvers.get(&claim.solver).is_some()
+            })
+            .cloned()
+    }
+}
@@ -0,0 +1,266 @@
+//! Validator bonds.
+//!
+//! Validators register themselves by attaching a NEAR bond. Only validators
+//! whose bond is at least the configured minimum count towards quorums, so
+//! unbonding below it immediately stops their attestations from counting.
+//! Unbonded NEAR stays locked for the unbonding period before it can be
+//! withdrawn, keeping it slashable for misbehavior discovered late; the
+//! validator's key is kept until then so its signatures can still be checked.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{
+    env, near_bindgen, AccountId, Balance, CurveType, Promise, PromiseError, PublicKey,
+};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::views::{DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT};
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidatorBond {
+    /// NEAR backing the validator's attestations
+    pub bonded: U128,
+    /// NEAR waiting out the unbonding period
+    pub unbonding: U128,
+    /// Block timestamp after which `unbonding` can be withdrawn
+    pub unlocks_at: U64,
+}
+
+/// Registered validator with its key and bond
+#[derive(Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ValidatorInfo {
+    pub account_id: AccountId,
+    pub public_key: PublicKey,
+    pub bond: ValidatorBond,
+    /// Whether the validator's attestations count towards quorums
+    pub attesting: bool,
+}
+
+impl Default for ValidatorBond {
+    fn default() -> Self {
+        Self {
+            bonded: U128(0),
+            unbonding: U128(0),
+            unlocks_at: U64(0),
+        }
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Register the caller as a bridge validator, or update its key, bonding
+    /// the attached deposit. The total bond must reach the minimum.
+    #[payable]
+    pub fn register_validator(&mut self, public_key: PublicKey) {
+        require(
+            public_key.curve_type() == CurveType::ED25519,
+            ContractError::ValidatorKeyNotEd25519,
+        );
+        let validator = env::predecessor_account_id();
+        let amount = env::attached_deposit();
+        let mut bond = self
+            .validator_bonds
+            .get(&validator)
+            .cloned()
+            .unwrap_or_default();
+        bond.bonded = U128(bond.bonded.0 + amount);
+        if bond.bonded.0 < self.config.min_validator_bond.0 {
+            ContractError::BondBelowMinimum.panic_with(&format!(
+                "Bond is below the minimum of {} yoctoNEAR",
+                self.config.min_validator_bond.0
+            ));
+        }
+        self.validator_bonds.insert(validator.clone(), bond);
+        self.total_bonded += amount;
+
+        let is_new = self
+            .bridge_validators
+            .insert(validator.clone(), public_key)
+            .is_none();
+        if is_new {
+            IntentEvent::ValidatorAdded {
+                validator: validator.clone(),
+            }
+            .emit();
+        }
+        if amount > 0 {
+            IntentEvent::ValidatorBonded {
+                validator,
+                amount: U128(amount),
+            }
+            .emit();
+        }
+    }
+
+    /// Remove a bridge validator, starting to unbond its whole bond
+    pub fn remove_validator(&mut self, validator: &AccountId) {
+        self.assert_untimelocked_owner();
+        self.drop_validator(validator);
+    }
+
+    /// Start unbonding part of the caller's bond. Any amount already
+    /// unbonding is locked again for a full unbonding period.
+    pub fn unbond(&mut self, amount: U128) {
+        let validator = env::predecessor_account_id();
+        let bond = self
+            .validator_bonds
+            .get(&validator)
+            .unwrap_or_else(|| ContractError::NoBond.panic());
+        require(
+            amount.0 > 0 && amount.0 <= bond.bonded.0,
+            ContractError::InvalidUnbondAmount,
+        );
+        self.start_unbonding(&validator, bond.clone(), amount.0);
+    }
+
+    /// Withdraw the caller's unbonded NEAR once the unbonding period is over
+    pub fn withdraw_bond(&mut self) -> Promise {
+        let validator = env::predecessor_account_id();
+        let mut bond = self
+            .validator_bonds
+            .get(&validator)
+            .unwrap_or_else(|| ContractError::NoBond.panic())
+            .clone();
+        let amount = bond.unbonding.0;
+        require(amount > 0, ContractError::NothingToWithdraw);
+        require(
+            env::block_timestamp() >= bond.unlocks_at.0,
+            ContractError::BondUnbonding,
+        );
+
+        bond.unbonding = U128(0);
+        self.save_bond(&validator, &bond);
+        self.total_bonded -= amount;
+
+        Promise::new(validator.clone()).transfer(amount).then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(self.config.transfer_callback_gas)
+                .on_bond_withdrawn(validator, U128(amount)),
+        )
+    }
+
+    /// Re-credit a bond whose withdrawal transfer failed
+    #[private]
+    pub fn on_bond_withdrawn(
+        &mut self,
+        validator: AccountId,
+        amount: U128,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            IntentEvent::BondWithdrawn { validator, amount }.emit();
+            true
+        } else {
+            let mut bond = self
+                .validator_bonds
+                .get(&validator)
+                .cloned()
+                .unwrap_or_default();
+            bond.unbonding = U128(bond.unbonding.0 + amount.0);
+            self.validator_bonds.insert(validator.clone(), bond);
+            self.total_bonded += amount.0;
+            false
+        }
+    }
+
+    /// Get the bond of a validator
+    pub fn get_validator_bond(&self, validator: AccountId) -> Option<ValidatorBond> {
+        self.validator_bonds.get(&validator).cloned()
+    }
+
+    /// View method to check if an account is a validator whose attestations
+    /// count, that is registered with at least the minimum bond
+    pub fn is_validator(&self, account_id: AccountId) -> bool {
+        self.attesting_key(&account_id).is_some()
+    }
+
+    /// List registered validators with their keys and bonds, including
+    /// those whose bond no longer counts
+    pub fn get_validators(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<ValidatorInfo> {
+        let from_index = from_index.map_or(0, |index| index.0);
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+        self.bridge_validators
+            .iter()
+            .skip(usize::try_from(from_index).unwrap_or(usize::MAX))
+            .take(limit as usize)
+            .map(|(account_id, public_key)| ValidatorInfo {
+                account_id: account_id.clone(),
+                public_key: public_key.clone(),
+                bond: self
+                    .validator_bonds
+                    .get(account_id)
+                    .cloned()
+                    .unwrap_or_default(),
+                attesting: self.attesting_key(account_id).is_some(),
+            })
+            .collect()
+    }
+
+    /// Number of registered validators
+    pub fn get_validator_count(&self) -> u64 {
+        u64::from(self.bridge_validators.len())
+    }
+}
+
+impl CrossChainExecutor {
+    /// Public key of a validator whose attestations count, if any
+    pub(crate) fn attesting_key(&self, validator: &AccountId) -> Option<PublicKey> {
+        let bonded = self
+            .validator_bonds
+            .get(validator)
+            .map_or(0, |bond| bond.bonded.0);
+        if bonded < self.config.min_validator_bond.0 {
+            return None;
+        }
+        self.bridge_validators.get(validator).cloned()
+    }
+
+    /// Start unbonding a removed validator's whole bond
+    pub(crate) fn drop_validator(&mut self, validator: &AccountId) {
+        if let Some(bond) = self.validator_bonds.get(validator) {
+            let amount = bond.bonded.0;
+            if amount > 0 {
+                self.start_unbonding(validator, bond.clone(), amount);
+            }
+        }
+
+        IntentEvent::ValidatorRemoved {
+            validator: validator.clone(),
+        }
+        .emit();
+    }
+
+    fn start_unbonding(&mut self, validator: &AccountId, mut bond: ValidatorBond, amount: Balance) {
+        bond.bonded = U128(bond.bonded.0 - amount);
+        bond.unbonding = U128(bond.unbonding.0 + amount);
+        bond.unlocks_at = U64(env::block_timestamp() + self.config.unbonding_period.0);
+        IntentEvent::ValidatorUnbonding {
+            validator: validator.clone(),
+            amount: U128(amount),
+            unlocks_at: bond.unlocks_at,
+        }
+        .emit();
+        self.validator_bonds.insert(validator.clone(), bond);
+    }
+
+    /// Store a bond, dropping it and the validator's key once nothing is
+    /// left in it
+    fn save_bond(&mut self, validator: &AccountId, bond: &ValidatorBond) {
+        if bond.bonded.0 == 0 && bond.unbonding.0 == 0 {
+            self.validator_bonds.remove(validator);
+            self.bridge_validators.remove(validator);
+        } else {
+            self.validator_bonds.insert(validator.clone(), bond.clone());
+        }
+    }
+}
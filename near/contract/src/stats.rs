@@ -0,0 +1,111 @@
+//! Bridge statistics kept in state.
+//!
+//! Counters are updated as intents move through their lifecycle so
+//! dashboards and the MCP agent can report bridge health from a single view
+//! call instead of running an indexer. Intents are counted under their
+//! current status, and pruned intents keep counting under the terminal
+//! status they were pruned in. Volume is the amount delivered per source
+//! chain token, in the token's source decimals. Counters start from zero
+//! when a deployment first tracks them.
+
+use std::collections::BTreeMap;
+
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{near_bindgen, Balance};
+use schemars::JsonSchema;
+
+use crate::tokens::normalize_address;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, IntentStatus};
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BridgeStats {
+    /// Intents ever created
+    pub total_intents: u64,
+    /// Intents in each status any intent is in, failed intents regardless
+    /// of their reason
+    pub intents_by_status: BTreeMap<String, u64>,
+    /// Amount delivered per source chain token
+    pub volume_by_token: BTreeMap<String, U128>,
+    /// Distinct NEAR accounts intents were created for
+    pub unique_receivers: u64,
+    /// Intents failed for each reason
+    pub failures_by_reason: BTreeMap<String, u64>,
+}
+
+impl IntentStatus {
+    /// Name of the status, without the reason of a failure
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            IntentStatus::Pending => "Pending",
+            IntentStatus::Executing => "Executing",
+            IntentStatus::Completed => "Completed",
+            IntentStatus::Failed(_) => "Failed",
+            IntentStatus::Refunded => "Refunded",
+            IntentStatus::Cancelled => "Cancelled",
+            IntentStatus::Disputed => "Disputed",
+        }
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Intent counts, volumes and failures across the bridge's lifetime
+    pub fn get_stats(&self) -> BridgeStats {
+        BridgeStats {
+            total_intents: self.intent_count(),
+            intents_by_status: self
+                .status_counts
+                .iter()
+                .filter(|(_, count)| **count > 0)
+                .map(|(status, count)| (status.clone(), *count))
+                .collect(),
+            volume_by_token: self
+                .token_volumes
+                .iter()
+                .map(|(token, volume)| (token.clone(), U128(*volume)))
+                .collect(),
+            unique_receivers: self.unique_receivers,
+            failures_by_reason: self
+                .failure_counts
+                .iter()
+                .map(|(reason, count)| (reason.clone(), *count))
+                .collect(),
+        }
+    }
+}
+
+impl CrossChainExecutor {
+    /// Move an intent's count from its previous status to `next`, or just
+    /// count it under `next` when it is new
+    pub(crate) fn count_status(&mut self, previous: Option<&IntentStatus>, next: &IntentStatus) {
+        if let Some(previous) = previous {
+            if let Some(count) = self.status_counts.get_mut(previous.name()) {
+                *count = count.saturating_sub(1);
+            }
+        }
+        *self
+            .status_counts
+            .entry(next.name().to_string())
+            .or_insert(0) += 1;
+    }
+
+    /// Count a failure under its reason
+    pub(crate) fn count_failure(&mut self, reason: &str) {
+        *self.failure_counts.entry(reason.to_string()).or_insert(0) += 1;
+    }
+
+    /// Add an amount delivered for an intent to its token's volume
+    pub(crate) fn count_volume(&mut self, intent: &CrossChainIntent, token: &str, amount: Balance) {
+        // NFT intents move a token ID, not an amount
+        if intent.is_nft() {
+            return;
+        }
+        let volume = self
+            .token_volumes
+            .entry(normalize_address(token))
+            .or_insert(0);
+        *volume = volume.saturating_add(amount);
+    }
+}
@@ -0,0 +1,484 @@
+//! Storage accounting.
+//!
+//! Creators pay for the state their intents occupy, so growing state never
+//! silently eats into the contract's own balance. Outbound intents are charged
+//! against NEP-145 storage deposits. Inbound intents are paid for with the
+//! deposit attached to `create_intent`: the bytes each intent adds are
+//! measured, the remainder is refunded straight away, and the intent's share
+//! is returned once the intent reaches a terminal state and is pruned.
+//!
+//! Collections hold their writes until the end of the call, so the bytes
+//! added are measured with [`CrossChainExecutor::storage_usage`], which
+//! flushes them first.
+
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, log, near_bindgen, AccountId, Balance, Promise, StorageUsage,
+};
+
+use crate::errors::{require, ContractError};
+use crate::migration::VersionedIntent;
+use crate::proof::deposit_keys;
+use crate::tokens::normalize_address;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, IntentStatus};
+
+/// Bytes reserved for a storage account entry itself
+const STORAGE_ACCOUNT_BYTES: StorageUsage = 128;
+
+/// Bytes a collection prefixes the keys of its entries with
+const RECORD_KEY_PREFIX: usize = 1;
+
+/// Bytes of an index into a set's members
+const SET_INDEX_BYTES: usize = 8;
+
+/// Bytes the runtime charges for every record besides its key and value
+const RECORD_OVERHEAD_BYTES: usize = 40;
+
+/// Bytes of the longest account ID with its length prefix
+const ACCOUNT_ID_BYTES: usize = 4 + 64;
+
+/// Storage deposit held for one inbound intent
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct IntentDeposit {
+    pub payer: AccountId,
+    pub amount: Balance,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct StorageAccount {
+    /// Total NEAR deposited for storage
+    pub total: Balance,
+    /// Bytes of contract state paid for by this account
+    pub used_bytes: StorageUsage,
+}
+
+impl StorageAccount {
+    fn available(&self) -> Balance {
+        self.total
+            .saturating_sub(Balance::from(self.used_bytes) * env::storage_byte_cost())
+    }
+
+    fn to_balance(&self) -> StorageBalance {
+        StorageBalance {
+            total: self.total.into(),
+            available: self.available().into(),
+        }
+    }
+}
+
+impl CrossChainExecutor {
+    /// Bytes of contract state, counting writes still cached by collections
+    /// and the root as it serializes now, as it is only written once the
+    /// call returns
+    pub(crate) fn storage_usage(&mut self) -> StorageUsage {
+        self.flush_collections();
+        let root = self.try_to_vec().expect("Failed to serialize state");
+        env::storage_usage() + root.len() as StorageUsage
+    }
+
+    /// Write out the cached changes of every collection. Lookup sets write
+    /// straight through, and collections nested in maps are flushed when the
+    /// map drops them.
+    pub(crate) fn flush_collections(&mut self) {
+        self.executed_intents.flush();
+        self.intents.flush();
+        self.approvals.flush();
+        self.refund_queue.flush();
+        self.storage_accounts.flush();
+        self.bridge_validators.flush();
+        self.outbound_intents.flush();
+        self.outbound_approvals.flush();
+        self.role_members.flush();
+        self.agents.flush();
+        self.token_fee_bps.flush();
+        self.accrued_fees.flush();
+        self.rate_limits.flush();
+        self.rate_windows.flush();
+        self.volume_caps.flush();
+        self.volume_buckets.flush();
+        self.intents_by_sender.flush();
+        self.intents_by_receiver.flush();
+        self.intents_by_status.flush();
+        self.intent_positions.flush();
+        self.intent_deposits.flush();
+        self.aggregate_keys.flush();
+        self.aggregate_attestations.flush();
+        self.validator_bonds.flush();
+        self.validator_sets.flush();
+        self.disputes.flush();
+        self.sender_nonces.flush();
+        self.chains.flush();
+        self.chain_heads.flush();
+        self.token_mappings.flush();
+        self.solvers.flush();
+        self.intent_claims.flush();
+        self.quotes.flush();
+        self.bound_quotes.flush();
+        self.auctions.flush();
+        self.escrow_code.flush();
+        self.escrows.flush();
+        self.delegate_keys.flush();
+        self.delegate_nonces.flush();
+        self.release_signatures.flush();
+        self.intent_history.flush();
+        self.emergency_approvals.flush();
+        self.admin_queue.flush();
+        self.nft_collections.flush();
+        self.gas_credits.flush();
+        self.execution_gas.flush();
+        self.referral_fees.flush();
+        self.proofs.flush();
+        self.checkpoints.flush();
+        self.status_counts.flush();
+        self.failure_counts.flush();
+        self.token_volumes.flush();
+        self.allowed_receivers.flush();
+        self.retry_queue.flush();
+        self.delivery_attempts.flush();
+        self.scheduled_intents.flush();
+        self.recurring_templates.flush();
+        self.failed_compensations.flush();
+    }
+
+    /// Charge `account_id` for the state added since `initial_storage`
+    pub(crate) fn charge_storage(&mut self, account_id: &AccountId, initial_storage: StorageUsage) {
+        let added = self.storage_usage().saturating_sub(initial_storage);
+        let account = self
+            .storage_accounts
+            .get_mut(account_id)
+            .unwrap_or_else(|| ContractError::StorageNotRegistered.panic());
+        account.used_bytes += added;
+        require(
+            Balance::from(account.used_bytes) * env::storage_byte_cost() <= account.total,
+            ContractError::InsufficientStorageBalance,
+        );
+    }
+
+    /// Record the cost of the state added for an intent since
+    /// `initial_storage` as a deposit owed back to `payer`, returning it
+    pub(crate) fn record_intent_deposit(
+        &mut self,
+        intent_id: &String,
+        payer: &AccountId,
+        initial_storage: StorageUsage,
+    ) -> Balance {
+        // Insert first so the deposit entry is itself paid for
+        self.intent_deposits.insert(
+            intent_id.clone(),
+            IntentDeposit {
+                payer: payer.clone(),
+                amount: 0,
+            },
+        );
+        let added = self.storage_usage().saturating_sub(initial_storage);
+        let amount = Balance::from(added) * env::storage_byte_cost();
+        self.intent_deposits.get_mut(intent_id).unwrap().amount = amount;
+        amount
+    }
+
+    /// Upper bound of the bytes of state creating an intent adds: its
+    /// record, history, deposit and proof entries, its executed ID, its
+    /// entries in the sender, receiver and status indexes and its sender's
+    /// nonce
+    pub(crate) fn estimate_intent_storage(&self, intent: &CrossChainIntent) -> StorageUsage {
+        let record = |key: usize, value: usize| {
+            (RECORD_KEY_PREFIX + key + value + RECORD_OVERHEAD_BYTES) as StorageUsage
+        };
+        let id = 4 + intent.id.len();
+        let stored = VersionedIntent::from(intent.clone())
+            .try_to_vec()
+            .expect("Failed to serialize intent");
+        // One history step and a deposit, each naming the creating account
+        let history = 4 + 1 + ACCOUNT_ID_BYTES + 8 + 8;
+        let deposit = ACCOUNT_ID_BYTES + 16;
+        let sender = 4 + normalize_address(&intent.sender).len();
+        // New intents are indexed as pending under a hashed key, and their
+        // tree node is linked from its parent
+        let status_key = 4 + IntentStatus::Pending.name().len() + 8;
+        let status_node = 1 + status_key + 1 + 1 + 4;
+        // Sets hold each member under its index and its index under the member
+        let set_member = record(id, SET_INDEX_BYTES) + record(SET_INDEX_BYTES, id);
+
+        let mut bytes = record(id, stored.len())
+            + record(id, history)
+            + record(id, deposit)
+            + set_member
+            // Index entries of new senders and receivers are new records
+            + record(sender, 4 + id)
+            + record(4 + intent.receiver.as_str().len(), 4 + id)
+            + record(1 + 32, id)
+            + record(1 + 4, status_node)
+            + 4
+            + record(id, 8)
+            + record(sender, 8)
+            + record(sender + 8, 0);
+        for key in deposit_keys(intent) {
+            bytes += record(4 + key.len(), id);
+        }
+        if self.croncat_manager.is_some() {
+            bytes += set_member;
+        }
+        bytes
+    }
+
+    /// Require the attached deposit to cover `cost` and refund the rest
+    pub(crate) fn settle_attached_deposit(&self, cost: Balance) {
+        let attached = env::attached_deposit();
+        if attached < cost {
+            ContractError::StorageDepositTooSmall.panic_with(&format!(
+                "Attach at least {} yoctoNEAR to cover intent storage",
+                cost
+            ));
+        }
+        if attached > cost {
+            Promise::new(env::predecessor_account_id()).transfer(attached - cost);
+        }
+    }
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Delete a completed, cancelled or refunded intent and return its
+    /// storage deposit to the payer. The intent ID stays recorded so it can
+    /// never be replayed. Callable by anyone.
+    pub fn prune_intent(&mut self, intent_id: String) {
+        let intent = self
+            .load_intent(&intent_id)
+            .unwrap_or_else(|| ContractError::IntentNotFound.panic());
+        require(intent.status.is_terminal(), ContractError::NotTerminal);
+
+        self.intents.remove(&intent_id);
+        self.unindex_status(&intent);
+        self.approvals.remove(&intent_id);
+        self.aggregate_attestations.remove(&intent_id);
+        self.intent_history.remove(&intent_id);
+        if let Some(deposit) = self.intent_deposits.remove(&intent_id) {
+            if deposit.amount > 0 {
+                Promise::new(deposit.payer).transfer(deposit.amount);
+            }
+        }
+    }
+
+    /// Get the storage deposit held for an inbound intent
+    pub fn get_intent_deposit(&self, intent_id: String) -> Option<U128> {
+        self.intent_deposits
+            .get(&intent_id)
+            .map(|deposit| U128(deposit.amount))
+    }
+}
+
+#[near_bindgen]
+impl StorageManagement for CrossChainExecutor {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+        let min_balance = self.storage_balance_bounds().min.0;
+
+        if let Some(account) = self.storage_accounts.get_mut(&account_id) {
+            if registration_only {
+                log!("The account is already registered, refunding the deposit");
+                if amount > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(amount);
+                }
+            } else {
+                account.total += amount;
+            }
+            return account.to_balance();
+        }
+
+        require(amount >= min_balance, ContractError::StorageBelowMinimum);
+        let total = if registration_only {
+            let refund = amount - min_balance;
+            if refund > 0 {
+                Promise::new(env::predecessor_account_id()).transfer(refund);
+            }
+            min_balance
+        } else {
+            amount
+        };
+        let account = StorageAccount {
+            total,
+            used_bytes: STORAGE_ACCOUNT_BYTES,
+        };
+        let balance = account.to_balance();
+        self.storage_accounts.insert(account_id, account);
+        balance
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let account = self
+            .storage_accounts
+            .get_mut(&account_id)
+            .unwrap_or_else(|| ContractError::StorageNotRegistered.panic());
+
+        let available = account.available();
+        let amount = amount.map(|amount| amount.0).unwrap_or(available);
+        require(amount <= available, ContractError::StorageWithdrawTooLarge);
+
+        if amount > 0 {
+            account.total -= amount;
+            Promise::new(account_id).transfer(amount);
+        }
+        account.to_balance()
+    }
+
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let account = match self.storage_accounts.get(&account_id) {
+            Some(account) => account,
+            None => {
+                log!("The account {} is not registered", &account_id);
+                return false;
+            }
+        };
+
+        // Storage still used by intents is forfeited when forcing
+        let refund = if account.used_bytes > STORAGE_ACCOUNT_BYTES {
+            require(force.unwrap_or(false), ContractError::StorageAccountInUse);
+            account.available()
+        } else {
+            account.total
+        };
+
+        self.storage_accounts.remove(&account_id);
+        if refund > 0 {
+            Promise::new(account_id).transfer(refund);
+        }
+        true
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: (Balance::from(STORAGE_ACCOUNT_BYTES) * env::storage_byte_cost()).into(),
+            max: None,
+        }
+    }
+
+    fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_accounts
+            .get(&account_id)
+            .map(|account| account.to_balance())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{
+        agent, executor, intent, set_caller, setup, token, transfers_to, BASE_TOKEN,
+    };
+    use near_contract_standards::fungible_token::receiver::FungibleTokenReceiver;
+    use near_sdk::json_types::U128;
+    use near_sdk::serde_json::json;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::ONE_NEAR;
+
+    /// Write the contract out as a finished call would and read it back
+    fn reload(contract: CrossChainExecutor) -> CrossChainExecutor {
+        env::state_write(&contract);
+        // Dropping the collections writes out whatever they still cache
+        drop(contract);
+        env::state_read().unwrap()
+    }
+
+    #[test]
+    fn intent_deposits_pay_for_every_entry_creation_writes() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_croncat_manager(Some("manager.croncat.near".parse().unwrap()));
+        // Storage usage is tracked per context, so measure within one
+        set_caller(&agent(), ONE_NEAR);
+        let mut contract = reload(contract);
+        let initial_storage = env::storage_usage();
+
+        let intent = intent(1_000);
+        let intent_id = intent.id.clone();
+        contract.create_intent(intent);
+        let contract = reload(contract);
+
+        let added = env::storage_usage() - initial_storage;
+        assert_eq!(
+            contract.get_intent_deposit(intent_id),
+            Some(U128(Balance::from(added) * env::storage_byte_cost()))
+        );
+    }
+
+    #[test]
+    fn storage_deposits_register_and_top_up_accounts() {
+        let mut contract = setup();
+        let min = contract.storage_balance_bounds().min.0;
+        set_caller(&accounts(3), ONE_NEAR);
+        let balance = contract.storage_deposit(None, None);
+        assert_eq!(balance.total, U128(ONE_NEAR));
+        assert_eq!(balance.available, U128(ONE_NEAR - min));
+
+        set_caller(&accounts(3), ONE_NEAR);
+        contract.storage_deposit(None, None);
+        let balance = contract.storage_balance_of(accounts(3)).unwrap();
+        assert_eq!(balance.total, U128(2 * ONE_NEAR));
+        assert!(contract.storage_balance_of(accounts(4)).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "The attached deposit is less than the minimum storage balance")]
+    fn storage_registration_needs_the_minimum_balance() {
+        let mut contract = setup();
+        let min = contract.storage_balance_bounds().min.0;
+        set_caller(&accounts(3), min - 1);
+        contract.storage_deposit(None, None);
+    }
+
+    #[test]
+    fn storage_withdrawals_return_the_available_balance() {
+        let mut contract = setup();
+        let min = contract.storage_balance_bounds().min.0;
+        set_caller(&accounts(3), ONE_NEAR);
+        contract.storage_deposit(None, None);
+
+        set_caller(&accounts(3), 1);
+        let balance = contract.storage_withdraw(None);
+        assert_eq!(balance.total, U128(min));
+        assert_eq!(balance.available, U128(0));
+        assert_eq!(transfers_to(&accounts(3)), [ONE_NEAR - min]);
+    }
+
+    #[test]
+    #[should_panic(expected = "The amount is greater than the available storage balance")]
+    fn storage_withdrawals_keep_the_used_balance() {
+        let mut contract = setup();
+        set_caller(&accounts(3), ONE_NEAR);
+        contract.storage_deposit(None, None);
+        set_caller(&accounts(3), 1);
+        contract.storage_withdraw(Some(U128(ONE_NEAR)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Can't unregister an account with stored intents without force")]
+    fn accounts_with_outbound_intents_unregister_only_by_force() {
+        let mut contract = setup();
+        set_caller(&token(), 0);
+        let msg = json!({
+            "recipient": "0x71c7656ec7ab88b098defb751b7401b5f6d8976f",
+            "base_token": BASE_TOKEN,
+        });
+        contract.ft_on_transfer(agent(), U128(500), msg.to_string());
+
+        set_caller(&agent(), 1);
+        contract.storage_unregister(None);
+    }
+}
@@ -0,0 +1,216 @@
+//! Ref Finance swaps on arrival.
+//!
+//! An intent may swap the token it delivers into another token listed on
+//! Ref Finance, so a user bridging USDC can receive wNEAR in one flow. On
+//! execution the delivered amount is deposited into the Ref exchange with
+//! `ft_transfer_call`, carrying a single-pool swap action whose output Ref
+//! pays to the receiver, who must be registered with the output token.
+//!
+//! The swap terms are part of the intent's typed hash, so neither the agent
+//! nor a solver can loosen them. Ref rejects a swap paying out less than
+//! `min_amount_out` and hands the input back, and an intent executed after
+//! its swap deadline fails without swapping. The swap callback checks both
+//! again once Ref resolves the deposit, failing a swap Ref did not consume
+//! the whole input of or that resolved after its deadline. Either way the
+//! intent fails and is queued for refund on the source chain.
+//!
+//! Intents may instead ask to be delivered as a stablecoin, shielding the
+//! receiver from price moves of a volatile token while it bridges. The
+//! token's registry mapping names the pool swapping it into the stablecoin
+//! and the least price the swap must fetch, which the owner keeps current.
+//! Such swaps run until the intent expires.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::json;
+use near_sdk::{env, near_bindgen, AccountId, Gas, Promise, PromiseError};
+use schemars::JsonSchema;
+
+use crate::chains::chain_or_base;
+use crate::errors::{require, ContractError};
+use crate::roles::Role;
+use crate::tokens::{is_native_token, normalize_address};
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, DeliveryKind};
+
+/// Gas reserved for checking the swap consumed the whole input
+pub(crate) const ON_SWAPPED_GAS: Gas = Gas(5_000_000_000_000);
+
+/// Swap of the delivered token an intent asks for on arrival
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SwapAction {
+    /// Ref Finance pool swapping the delivered token
+    pub pool_id: u64,
+    /// NEP-141 contract of the token the receiver gets
+    pub token_out: AccountId,
+    /// Least amount of `token_out` the receiver accepts, in its decimals
+    pub min_amount_out: U128,
+    /// Block timestamp (ns) after which the swap may no longer execute
+    pub deadline: U64,
+}
+
+/// Swap a registered token takes to be delivered as a stablecoin
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StablecoinRoute {
+    /// Ref Finance pool swapping the token into the stablecoin
+    pub pool_id: u64,
+    /// NEP-141 contract of the stablecoin
+    pub stablecoin: AccountId,
+    /// Least amount of the stablecoin, in its decimals, the swap must pay
+    /// per whole NEAR token delivered
+    pub min_price: U128,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the Ref Finance exchange contract, or stop accepting swaps
+    pub fn set_ref_contract(&mut self, contract_id: Option<AccountId>) {
+        self.assert_role(&[Role::Owner]);
+        self.ref_contract = contract_id;
+    }
+
+    /// Route a registered token through a swap into a stablecoin for
+    /// intents asking for one, or stop offering stablecoin delivery
+    pub fn set_stablecoin_route(
+        &mut self,
+        base_token: String,
+        route: Option<StablecoinRoute>,
+        chain_id: Option<u64>,
+    ) {
+        self.assert_role(&[Role::Owner]);
+        let chain_id = chain_or_base(chain_id);
+        let base_token = normalize_address(&base_token);
+        self.chain_token_mappings_mut(chain_id)
+            .get_mut(&base_token)
+            .unwrap_or_else(|| ContractError::TokenNotRegistered.panic())
+            .stablecoin_route = route;
+    }
+
+    /// Get the Ref Finance exchange contract
+    pub fn get_ref_contract(&self) -> Option<AccountId> {
+        self.ref_contract.clone()
+    }
+
+    /// Fail the swap unless Ref consumed the whole input, which it refunds
+    /// when the swap would pay out less than its minimum, by `deadline`
+    #[private]
+    pub fn on_ref_swap(
+        &mut self,
+        amount_in: U128,
+        deadline: U64,
+        #[callback_result] used: Result<U128, PromiseError>,
+    ) {
+        require(
+            used.is_ok_and(|used| used == amount_in),
+            ContractError::SwapOutputBelowMinimum,
+        );
+        require(
+            env::block_timestamp() <= deadline.0,
+            ContractError::SwapResolvedLate,
+        );
+    }
+}
+
+impl CrossChainExecutor {
+    /// Check a new intent's swap can be made
+    pub(crate) fn check_swap(&self, intent: &CrossChainIntent) -> Result<(), ContractError> {
+        if intent.swap.is_none() && !intent.to_stablecoin {
+            return Ok(());
+        }
+        if self.ref_contract.is_none() {
+            return Err(ContractError::RefNotConfigured);
+        }
+        if intent.swap.is_some() && intent.to_stablecoin {
+            return Err(ContractError::SwapAndStablecoin);
+        }
+        if !matches!(
+            intent.delivery_kind(),
+            DeliveryKind::Swap | DeliveryKind::Stablecoin
+        ) {
+            return Err(ContractError::SwapDelivery);
+        }
+        if is_native_token(&intent.token) {
+            return Err(ContractError::NativeCannotSwap);
+        }
+        match &intent.swap {
+            Some(swap) if swap.deadline.0 < env::block_timestamp() => {
+                return Err(ContractError::SwapDeadlinePassed)
+            }
+            Some(_) => {}
+            None => {
+                self.token_mapping(intent.chain_id, &intent.token)
+                    .and_then(|mapping| mapping.stablecoin_route.as_ref())
+                    .ok_or(ContractError::NoStablecoinRoute)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Swap an executing intent makes on delivering `amount`, its own or
+    /// the stablecoin route of its token
+    pub(crate) fn intent_swap(
+        &self,
+        intent: &CrossChainIntent,
+        amount: U128,
+    ) -> Option<SwapAction> {
+        if !intent.to_stablecoin {
+            return intent.swap.clone();
+        }
+        let mapping = self
+            .token_mapping(intent.chain_id, &intent.token)
+            .unwrap_or_else(|| ContractError::TokenNotSupported.panic());
+        let route = mapping
+            .stablecoin_route
+            .as_ref()
+            .unwrap_or_else(|| ContractError::NoStablecoinRoute.panic());
+        let unit = 10u128.pow(mapping.decimals.near.into());
+        let min_amount_out = (amount.0 / unit)
+            .saturating_mul(route.min_price.0)
+            .saturating_add((amount.0 % unit).saturating_mul(route.min_price.0) / unit);
+        Some(SwapAction {
+            pool_id: route.pool_id,
+            token_out: route.stablecoin.clone(),
+            min_amount_out: U128(min_amount_out),
+            deadline: intent.expires_at,
+        })
+    }
+
+    /// Deposit `amount` of a source chain token's NEAR counterpart into Ref,
+    /// minting it first if the mapping is mintable, swapping it to the
+    /// receiver. Fails unless the swap went through.
+    pub(crate) fn swap_on_ref(
+        &self,
+        intent: &CrossChainIntent,
+        swap: &SwapAction,
+        amount: U128,
+    ) -> Promise {
+        let exchange = self
+            .ref_contract
+            .clone()
+            .unwrap_or_else(|| ContractError::RefNotConfigured.panic());
+        let msg = json!({
+            "actions": [{
+                "pool_id": swap.pool_id,
+                "token_in": self.resolve_token(intent.chain_id, &intent.token),
+                "token_out": swap.token_out,
+                "amount_in": amount,
+                "min_amount_out": swap.min_amount_out,
+            }],
+            "swap_out_recipient": intent.receiver,
+        });
+        self.transfer_call_token(
+            intent.chain_id,
+            &intent.token,
+            &exchange,
+            amount,
+            msg.to_string(),
+        )
+        .then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(ON_SWAPPED_GAS)
+                .on_ref_swap(amount, swap.deadline),
+        )
+    }
+}
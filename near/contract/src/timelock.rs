@@ -0,0 +1,124 @@
+//! Timelock for privileged changes.
+//!
+//! Once the owner sets the admin timelock, validator removals, token
+//! registry edits, fee changes and changes to the timelock itself can no
+//! longer be made directly. They are queued as a [`GovernanceAction`] and
+//! only applied once the delay has passed, so users see hostile changes
+//! coming and can exit first. Guardians and the owner can cancel a queued
+//! action. The delay can never drop below the minimum of the network the
+//! contract is built for, and the timelock cannot be switched off again.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::governance::GovernanceAction;
+use crate::network::MIN_ADMIN_TIMELOCK;
+use crate::roles::Role;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct QueuedAction {
+    pub id: u64,
+    pub action: GovernanceAction,
+    /// Nanosecond timestamp from which the action can be applied
+    pub executable_at: U64,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Start the admin timelock with a delay in nanoseconds. Once started,
+    /// the delay only changes through a queued action.
+    pub fn set_admin_timelock(&mut self, delay: U64) {
+        self.assert_untimelocked_owner();
+        self.set_timelock_delay(delay.0);
+    }
+
+    /// Get the delay of the admin timelock in nanoseconds, if it is set
+    pub fn get_admin_timelock(&self) -> Option<U64> {
+        self.admin_timelock.map(U64)
+    }
+
+    /// Queue a privileged change, returning its ID
+    pub fn queue_admin_action(&mut self, action: GovernanceAction) -> u64 {
+        self.assert_role(&[Role::Owner]);
+        let delay = self
+            .admin_timelock
+            .unwrap_or_else(|| ContractError::TimelockNotSet.panic());
+        require(action.is_timelocked(), ContractError::NotTimelocked);
+
+        self.admin_action_nonce += 1;
+        let queued = QueuedAction {
+            id: self.admin_action_nonce,
+            action,
+            executable_at: U64(env::block_timestamp() + delay),
+        };
+        IntentEvent::AdminActionQueued {
+            id: queued.id,
+            action: queued.action.clone(),
+            executable_at: queued.executable_at,
+        }
+        .emit();
+        self.admin_queue.insert(queued.id, queued);
+        self.admin_action_nonce
+    }
+
+    /// Apply a queued change whose delay has passed
+    pub fn apply_admin_action(&mut self, id: u64) {
+        self.assert_role(&[Role::Owner]);
+        let queued = self
+            .admin_queue
+            .remove(&id)
+            .unwrap_or_else(|| ContractError::ActionNotQueued.panic());
+        require(
+            env::block_timestamp() >= queued.executable_at.0,
+            ContractError::TimelockPending,
+        );
+        self.apply_governance_action(queued.action);
+
+        IntentEvent::AdminActionApplied { id }.emit();
+    }
+
+    /// Drop a queued change before it is applied
+    pub fn cancel_admin_action(&mut self, id: u64) {
+        let by = self.assert_role(&[Role::Guardian, Role::Owner]);
+        require(
+            self.admin_queue.remove(&id).is_some(),
+            ContractError::ActionNotQueued,
+        );
+
+        IntentEvent::AdminActionCancelled { id, by }.emit();
+    }
+
+    /// List the queued changes, oldest first
+    pub fn get_queued_admin_actions(&self) -> Vec<QueuedAction> {
+        let mut queue: Vec<QueuedAction> = self.admin_queue.values().cloned().collect();
+        queue.sort_by_key(|queued| queued.id);
+        queue
+    }
+}
+
+impl CrossChainExecutor {
+    /// Assert the caller is the owner and the change is not timelocked
+    pub(crate) fn assert_untimelocked_owner(&self) {
+        self.assert_role(&[Role::Owner]);
+        require(
+            self.admin_timelock.is_none(),
+            ContractError::ChangeNotQueued,
+        );
+    }
+
+    /// Set the timelock delay, at least the network's minimum
+    pub(crate) fn set_timelock_delay(&mut self, delay: u64) {
+        if delay < MIN_ADMIN_TIMELOCK {
+            ContractError::TimelockDelayTooShort
+                .panic_with(&format!("Delay must be at least {} ns", MIN_ADMIN_TIMELOCK));
+        }
+        self.admin_timelock = Some(delay);
+    }
+}
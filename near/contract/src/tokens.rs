@@ -0,0 +1,619 @@
+//! Registries mapping each source chain's token addresses to the NEAR FT
+//! contracts they are delivered as, plus native NEAR delivery.
+//!
+//! Each mapping carries the token's symbol and decimals so agents can
+//! discover what is bridgeable, and can be disabled to stop new intents,
+//! executions and transfers while failed intents still refund. Mintable
+//! mappings point at a wrapped token the executor is the minter of, so
+//! deliveries mint instead of drawing on pre-funded liquidity. A token
+//! whose decimals differ from those of its NEAR counterpart has its
+//! amounts rescaled on execution. Scaling up fails on overflow and scaling
+//! down rounds towards zero, leaving the dust locked on the source chain.
+//! A mapping may bound the amount a single intent moves, keeping dust that
+//! would not cover the gas of its delivery out, and any one intent from
+//! draining the token's liquidity. Tokens whose contracts run storage
+//! checks or transfer hooks can carry the gas their deliveries need.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::serde_json::{self, json};
+use near_sdk::{env, near_bindgen, AccountId, Balance, Gas, Promise, PromiseResult, ONE_NEAR};
+use schemars::JsonSchema;
+
+use crate::chains::chain_or_base;
+use crate::config::MAX_CONFIGURED_GAS;
+use crate::errors::{require, ContractError};
+use crate::events::IntentEvent;
+use crate::roles::Role;
+use crate::swap::StablecoinRoute;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent};
+
+/// Intent token value marking native NEAR as the destination asset
+pub const NATIVE_TOKEN: &str = "near";
+
+/// NEAR kept on top of the storage staking requirement for gas and rounding
+const NATIVE_RESERVE_BUFFER: Balance = ONE_NEAR;
+
+/// Gas reserved for checking a deposit was kept in full
+pub(crate) const ON_DEPOSITED_GAS: Gas = Gas(5_000_000_000_000);
+
+/// Most decimals a token may have, as 10^38 is the largest power of ten in a u128
+const MAX_DECIMALS: u8 = 38;
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, Debug, JsonSchema,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenDecimals {
+    /// Decimals of the token on its source chain
+    pub source: u8,
+    /// Decimals of the NEAR token delivered for it
+    pub near: u8,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMapping {
+    /// Token address on the source chain, 0x-prefixed hex
+    pub base_address: String,
+    /// NEP-141 contract delivered for the token
+    pub near_contract: AccountId,
+    pub decimals: TokenDecimals,
+    pub symbol: String,
+    /// Whether new intents and transfers may use the token
+    pub enabled: bool,
+    /// Whether the NEAR contract is a wrapped token the executor mints on
+    /// delivery rather than transferring from its own balance
+    #[serde(default)]
+    pub mintable: bool,
+    /// Smallest amount, in source decimals, a new intent may move
+    #[serde(default)]
+    pub min_amount: Option<U128>,
+    /// Largest amount, in source decimals, a new intent may move
+    #[serde(default)]
+    pub max_amount: Option<U128>,
+    /// Swap delivering the token as a stablecoin to intents asking for one
+    #[serde(default)]
+    pub stablecoin_route: Option<StablecoinRoute>,
+    /// Whether the NEAR contract is a NEP-245 multi-token contract, whose
+    /// intents name the token ID they deliver
+    #[serde(default)]
+    pub multi_token: bool,
+    /// Gas attached to calls delivering the token, for contracts whose
+    /// storage checks or transfer hooks outgrow the configured default
+    #[serde(default)]
+    pub transfer_gas: Option<Gas>,
+}
+
+pub(crate) fn is_native_token(token: &str) -> bool {
+    token == NATIVE_TOKEN
+}
+
+fn assert_transfer_gas(transfer_gas: Option<Gas>) {
+    if let Some(gas) = transfer_gas {
+        require(
+            gas.0 > 0 && gas <= MAX_CONFIGURED_GAS,
+            ContractError::InvalidGas,
+        );
+    }
+}
+
+fn assert_limits_ordered(min_amount: Option<U128>, max_amount: Option<U128>) {
+    if let (Some(min), Some(max)) = (min_amount, max_amount) {
+        require(min.0 <= max.0, ContractError::LimitsNotOrdered);
+    }
+}
+
+/// EVM addresses are hex and case-insensitive, so registry keys are lowercased
+pub(crate) fn normalize_address(address: &str) -> String {
+    address.to_lowercase()
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Map a token on a source chain, Base by default, to the NEAR FT
+    /// contract delivered for it, replacing any existing mapping
+    pub fn register_token(&mut self, mapping: TokenMapping, chain_id: Option<u64>) {
+        self.assert_untimelocked_owner();
+        self.insert_token_mapping(mapping, chain_id);
+    }
+
+    /// Allow or stop new intents and transfers of a registered token
+    pub fn set_token_enabled(&mut self, base_token: String, enabled: bool, chain_id: Option<u64>) {
+        self.assert_role(&[Role::Owner]);
+        let chain_id = chain_or_base(chain_id);
+        let base_token = normalize_address(&base_token);
+        self.chain_token_mappings_mut(chain_id)
+            .get_mut(&base_token)
+            .unwrap_or_else(|| ContractError::TokenNotRegistered.panic())
+            .enabled = enabled;
+    }
+
+    /// Set the smallest and largest amounts new intents of a registered
+    /// token may move, in source decimals, or lift either bound
+    pub fn set_token_limits(
+        &mut self,
+        base_token: String,
+        min_amount: Option<U128>,
+        max_amount: Option<U128>,
+        chain_id: Option<u64>,
+    ) {
+        self.assert_role(&[Role::Owner]);
+        assert_limits_ordered(min_amount, max_amount);
+        let chain_id = chain_or_base(chain_id);
+        let base_token = normalize_address(&base_token);
+        let mapping = self
+            .chain_token_mappings_mut(chain_id)
+            .get_mut(&base_token)
+            .unwrap_or_else(|| ContractError::TokenNotRegistered.panic());
+        mapping.min_amount = min_amount;
+        mapping.max_amount = max_amount;
+    }
+
+    /// Set the gas attached to calls delivering a registered token, or fall
+    /// back to the configured default
+    pub fn set_token_gas(&mut self, base_token: String, gas: Option<Gas>, chain_id: Option<u64>) {
+        self.assert_role(&[Role::Owner]);
+        assert_transfer_gas(gas);
+        let chain_id = chain_or_base(chain_id);
+        let base_token = normalize_address(&base_token);
+        self.chain_token_mappings_mut(chain_id)
+            .get_mut(&base_token)
+            .unwrap_or_else(|| ContractError::TokenNotRegistered.panic())
+            .transfer_gas = gas;
+    }
+
+    /// Remove a token from a source chain's registry, Base by default
+    pub fn remove_token(&mut self, base_token: String, chain_id: Option<u64>) {
+        self.assert_untimelocked_owner();
+        self.remove_token_mapping(base_token, chain_id);
+    }
+
+    /// Get the NEAR FT contract registered for a source chain token, Base
+    /// by default
+    pub fn get_token_contract(
+        &self,
+        base_token: String,
+        chain_id: Option<u64>,
+    ) -> Option<AccountId> {
+        self.get_token_mapping(base_token, chain_id)
+            .map(|mapping| mapping.near_contract)
+    }
+
+    /// Get the mapping registered for a source chain token, Base by default
+    pub fn get_token_mapping(
+        &self,
+        base_token: String,
+        chain_id: Option<u64>,
+    ) -> Option<TokenMapping> {
+        self.token_mapping(chain_or_base(chain_id), &base_token)
+            .cloned()
+    }
+
+    /// List the enabled tokens intents from a chain, Base by default, can
+    /// bridge. Native NEAR is always supported and not listed.
+    pub fn get_supported_tokens(&self, chain_id: Option<u64>) -> Vec<TokenMapping> {
+        self.token_mappings
+            .get(&chain_or_base(chain_id))
+            .map(|mappings| {
+                mappings
+                    .values()
+                    .filter(|mapping| mapping.enabled)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Fail a deposit unless its receiver kept the whole `amount`, reading
+    /// the amount `ft_transfer_call` reports as used. Whatever the receiver
+    /// does not use is refunded to the executor.
+    #[private]
+    pub fn on_token_deposited(&mut self, amount: U128) {
+        let used = match env::promise_result(0) {
+            PromiseResult::Successful(data) => serde_json::from_slice::<U128>(&data).ok(),
+            _ => None,
+        };
+        require(used == Some(amount), ContractError::DepositRefunded);
+    }
+}
+
+impl CrossChainExecutor {
+    /// Store a token mapping, replacing any existing one
+    pub(crate) fn insert_token_mapping(&mut self, mapping: TokenMapping, chain_id: Option<u64>) {
+        let chain_id = chain_or_base(chain_id);
+        require(
+            self.chains.get(&chain_id).is_some(),
+            ContractError::ChainNotSupported,
+        );
+        let mut mapping = mapping;
+        mapping.base_address = normalize_address(&mapping.base_address);
+        require(
+            !is_native_token(&mapping.base_address),
+            ContractError::NativeNeedsNoRegistration,
+        );
+        if mapping.decimals.source > MAX_DECIMALS || mapping.decimals.near > MAX_DECIMALS {
+            ContractError::TooManyDecimals
+                .panic_with(&format!("Decimals must be at most {}", MAX_DECIMALS));
+        }
+        assert_limits_ordered(mapping.min_amount, mapping.max_amount);
+        assert_transfer_gas(mapping.transfer_gas);
+        require(
+            !(mapping.mintable && mapping.multi_token),
+            ContractError::MintableMultiToken,
+        );
+        IntentEvent::TokenRegistered {
+            chain_id,
+            base_token: mapping.base_address.clone(),
+            near_contract: mapping.near_contract.clone(),
+        }
+        .emit();
+        self.chain_token_mappings_mut(chain_id)
+            .insert(mapping.base_address.clone(), mapping);
+    }
+
+    /// Remove a token mapping, panicking if it is not registered
+    pub(crate) fn remove_token_mapping(&mut self, base_token: String, chain_id: Option<u64>) {
+        let chain_id = chain_or_base(chain_id);
+        let base_token = normalize_address(&base_token);
+        require(
+            self.chain_token_mappings_mut(chain_id)
+                .remove(&base_token)
+                .is_some(),
+            ContractError::TokenNotRegistered,
+        );
+
+        IntentEvent::TokenRemoved {
+            chain_id,
+            base_token,
+        }
+        .emit();
+    }
+
+    /// Check a source chain token can back new intents and transfers
+    pub(crate) fn check_token_enabled(
+        &self,
+        chain_id: u64,
+        token: &str,
+    ) -> Result<(), ContractError> {
+        if is_native_token(token) {
+            return Ok(());
+        }
+        match self.token_mapping(chain_id, token) {
+            Some(mapping) if mapping.enabled => Ok(()),
+            Some(_) => Err(ContractError::TokenDisabled),
+            None => Err(ContractError::TokenNotSupported),
+        }
+    }
+
+    /// Check an amount of a token is within its mapping's bounds. Native
+    /// NEAR is unbounded.
+    pub(crate) fn check_amount_limits(
+        &self,
+        chain_id: u64,
+        token: &str,
+        amount: Balance,
+    ) -> Result<(), ContractError> {
+        let Some(mapping) = self.token_mapping(chain_id, token) else {
+            return Ok(());
+        };
+        if mapping.min_amount.is_some_and(|min| amount < min.0) {
+            return Err(ContractError::AmountBelowMinimum);
+        }
+        if mapping.max_amount.is_some_and(|max| amount > max.0) {
+            return Err(ContractError::AmountAboveMaximum);
+        }
+        Ok(())
+    }
+
+    /// Convert a source chain amount into the decimals of the NEAR token
+    /// delivered for it, or `None` if it overflows. Native NEAR is unscaled.
+    pub(crate) fn rescale_amount(
+        &self,
+        chain_id: u64,
+        token: &str,
+        amount: Balance,
+    ) -> Option<Balance> {
+        let decimals = self
+            .token_mapping(chain_id, token)
+            .map(|mapping| mapping.decimals);
+        match decimals {
+            Some(TokenDecimals { source, near }) if near >= source => {
+                amount.checked_mul(10u128.pow(u32::from(near - source)))
+            }
+            Some(TokenDecimals { source, near }) => {
+                Some(amount / 10u128.pow(u32::from(source - near)))
+            }
+            None => Some(amount),
+        }
+    }
+
+    /// Convert an amount in the decimals of a source chain token's NEAR
+    /// counterpart back into the token's own, rounding towards zero
+    pub(crate) fn unscale_amount(&self, chain_id: u64, token: &str, amount: Balance) -> Balance {
+        let decimals = self
+            .token_mapping(chain_id, token)
+            .map(|mapping| mapping.decimals);
+        match decimals {
+            Some(TokenDecimals { source, near }) if near >= source => {
+                amount / 10u128.pow(u32::from(near - source))
+            }
+            Some(TokenDecimals { source, near }) => amount
+                .checked_mul(10u128.pow(u32::from(source - near)))
+                .unwrap_or_else(|| ContractError::AmountOverflow.panic()),
+            None => amount,
+        }
+    }
+
+    /// Deliver `amount` of a source chain token's NEAR counterpart to
+    /// `receiver`, minting it if the mapping is mintable
+    pub(crate) fn deliver_token(
+        &self,
+        chain_id: u64,
+        base_token: &str,
+        receiver: &AccountId,
+        amount: U128,
+    ) -> Promise {
+        let mapping = self
+            .token_mapping(chain_id, base_token)
+            .unwrap_or_else(|| ContractError::TokenNotSupported.panic());
+        let (method, args, deposit) = if mapping.mintable {
+            (
+                "mint",
+                json!({ "account_id": receiver, "amount": amount }),
+                0,
+            )
+        } else {
+            (
+                "ft_transfer",
+                json!({ "receiver_id": receiver, "amount": amount }),
+                self.config.ft_transfer_deposit.0,
+            )
+        };
+        Promise::new(mapping.near_contract.clone()).function_call(
+            method.to_string(),
+            args.to_string().into_bytes(),
+            deposit,
+            mapping.transfer_gas.unwrap_or(self.config.ft_transfer_gas),
+        )
+    }
+
+    /// Deposit `amount` of a source chain token's NEAR counterpart into
+    /// `receiver_id` with `ft_transfer_call`, minting it first if the
+    /// mapping is mintable. Both calls share one receipt so a failed
+    /// deposit also reverts the mint.
+    pub(crate) fn transfer_call_token(
+        &self,
+        chain_id: u64,
+        base_token: &str,
+        receiver_id: &AccountId,
+        amount: U128,
+        msg: String,
+    ) -> Promise {
+        let mapping = self
+            .token_mapping(chain_id, base_token)
+            .unwrap_or_else(|| ContractError::TokenNotSupported.panic());
+        let mut deposit = Promise::new(mapping.near_contract.clone());
+        if mapping.mintable {
+            deposit = deposit.function_call(
+                "mint".to_string(),
+                json!({ "account_id": env::current_account_id(), "amount": amount })
+                    .to_string()
+                    .into_bytes(),
+                0,
+                mapping.transfer_gas.unwrap_or(self.config.ft_transfer_gas),
+            );
+        }
+        deposit.function_call(
+            "ft_transfer_call".to_string(),
+            json!({ "receiver_id": receiver_id, "amount": amount, "msg": msg })
+                .to_string()
+                .into_bytes(),
+            self.config.ft_transfer_deposit.0,
+            self.config.ft_transfer_call_gas,
+        )
+    }
+
+    /// Deposit like [`Self::transfer_call_token`], failing unless
+    /// `receiver_id` kept the whole amount
+    pub(crate) fn deposit_token(
+        &self,
+        chain_id: u64,
+        base_token: &str,
+        receiver_id: &AccountId,
+        amount: U128,
+        msg: String,
+    ) -> Promise {
+        self.transfer_call_token(chain_id, base_token, receiver_id, amount, msg)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(ON_DEPOSITED_GAS)
+                    .on_token_deposited(amount),
+            )
+    }
+
+    /// Gas attached to calls delivering a source chain token
+    pub(crate) fn token_transfer_gas(&self, chain_id: u64, base_token: &str) -> Gas {
+        self.token_mapping(chain_id, base_token)
+            .and_then(|mapping| mapping.transfer_gas)
+            .unwrap_or(self.config.ft_transfer_gas)
+    }
+
+    /// Gas attached to the transfers delivering every asset of an intent,
+    /// or to the chain of its actions
+    pub(crate) fn delivery_gas(&self, intent: &CrossChainIntent) -> Gas {
+        if !intent.actions.is_empty() {
+            return self.actions_gas(intent);
+        }
+        Gas(intent
+            .assets()
+            .map(|(token, _)| self.token_transfer_gas(intent.chain_id, token).0)
+            .sum())
+    }
+
+    /// Resolve the NEAR FT contract for a source chain token, panicking if unsupported
+    pub(crate) fn resolve_token(&self, chain_id: u64, base_token: &str) -> AccountId {
+        self.token_mapping(chain_id, base_token)
+            .map(|mapping| mapping.near_contract.clone())
+            .unwrap_or_else(|| ContractError::TokenNotSupported.panic())
+    }
+
+    /// Assert the contract can send `amount` of native NEAR without dropping
+    /// below the balance its storage, accrued fees, validator bonds and gas
+    /// reimbursements require
+    pub(crate) fn assert_native_liquidity(&self, amount: Balance) {
+        let reserve = Balance::from(env::storage_usage()) * env::storage_byte_cost()
+            + NATIVE_RESERVE_BUFFER
+            + self.accrued_native_fees()
+            + self.total_bonded
+            + self.reserved_gas_funds()
+            + self.native_referral_fees;
+        let available = env::account_balance().saturating_sub(reserve);
+        require(
+            amount <= available,
+            ContractError::InsufficientNativeBalance,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{
+        agent, assert_schema_describes, create_approved, executor, intent, mapping, rehash,
+        set_caller, setup, token, transfers_to, BASE_TOKEN, VALIDATOR_BOND,
+    };
+    use near_sdk::mock::VmAction;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    const WETH: &str = "0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2";
+
+    /// Be called by the agent while holding `balance`
+    fn set_balance(balance: Balance) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(executor())
+            .predecessor_account_id(agent())
+            .account_balance(balance)
+            .build());
+    }
+
+    fn native_intent(amount: Balance) -> CrossChainIntent {
+        let mut intent = intent(amount);
+        intent.token = NATIVE_TOKEN.to_string();
+        rehash(&mut intent);
+        intent
+    }
+
+    #[test]
+    fn intents_deliver_the_contract_registered_for_their_token() {
+        let mut contract = setup();
+        let mut weth = mapping(WETH, 18, 18);
+        weth.near_contract = accounts(5);
+        set_caller(&executor(), 0);
+        contract.register_token(weth, None);
+        assert_eq!(
+            contract.get_token_contract(WETH.to_lowercase(), None),
+            Some(accounts(5))
+        );
+        assert_eq!(
+            contract.get_token_contract(BASE_TOKEN.to_string(), None),
+            Some(token())
+        );
+
+        let mut intent = intent(1_000);
+        intent.token = WETH.to_lowercase();
+        rehash(&mut intent);
+        let intent_id = create_approved(&mut contract, intent);
+        set_caller(&agent(), 0);
+        contract.execute_intent(intent_id);
+
+        let receipt = get_created_receipts()
+            .into_iter()
+            .find(|receipt| receipt.receiver_id == accounts(5))
+            .expect("No transfer of the registered contract");
+        assert!(matches!(
+            &receipt.actions[0],
+            VmAction::FunctionCall { function_name, .. } if function_name == "ft_transfer"
+        ));
+    }
+
+    #[test]
+    fn removed_tokens_are_no_longer_supported() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.remove_token(BASE_TOKEN.to_uppercase(), None);
+
+        assert_eq!(
+            contract.get_token_contract(BASE_TOKEN.to_string(), None),
+            None
+        );
+        assert!(contract.get_supported_tokens(None).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Method requires role")]
+    fn only_the_owner_registers_tokens() {
+        let mut contract = setup();
+        set_caller(&agent(), 0);
+        contract.register_token(mapping(WETH, 18, 18), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Token not registered")]
+    fn removing_an_unregistered_token_fails() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.remove_token(WETH.to_string(), None);
+    }
+
+    #[test]
+    fn native_intents_transfer_near_to_their_receiver() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, native_intent(ONE_NEAR));
+        set_balance(1_000 * ONE_NEAR);
+        contract.execute_intent(intent_id);
+
+        assert_eq!(transfers_to(&accounts(2)), [ONE_NEAR]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Insufficient NEAR balance for native transfer")]
+    fn native_transfers_leave_the_reserve_untouched() {
+        let mut contract = setup();
+        let intent_id = create_approved(&mut contract, native_intent(ONE_NEAR));
+        // The validator's bond alone takes up the whole balance
+        set_balance(VALIDATOR_BOND);
+        contract.execute_intent(intent_id);
+    }
+
+    #[test]
+    fn native_intents_withhold_the_fee_in_near() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_fee(30);
+        let intent_id = create_approved(&mut contract, native_intent(10 * ONE_NEAR));
+        set_balance(1_000 * ONE_NEAR);
+        contract.execute_intent(intent_id);
+
+        assert_eq!(transfers_to(&accounts(2)), [10 * ONE_NEAR / 10_000 * 9_970]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Native NEAR cannot settle in NEAR Intents")]
+    fn native_intents_cannot_settle_in_near_intents() {
+        let mut contract = setup();
+        set_caller(&executor(), 0);
+        contract.set_defuse_contract(Some(accounts(5)));
+        let mut settled = native_intent(ONE_NEAR);
+        settled.settle_in_defuse = true;
+        rehash(&mut settled);
+        create_approved(&mut contract, settled);
+    }
+
+    #[test]
+    fn token_mapping_schema_lists_its_fields() {
+        assert_schema_describes(&mapping(WETH, 18, 18));
+    }
+}
@@ -0,0 +1,211 @@
+//! Intent lookups and paginated enumeration for indexers, wallets and the MCP agent.
+//!
+//! Intents are listed in creation order from an index of their IDs, by
+//! sender, receiver or status, so a page skips `from_index` IDs without
+//! loading the intents before it. Pruned intents are left out of the page
+//! holding their ID.
+
+use near_sdk::json_types::{U128, U64};
+use near_sdk::serde::Serialize;
+use near_sdk::{near_bindgen, AccountId};
+use schemars::JsonSchema;
+
+use crate::errors::{require, ContractError};
+use crate::tokens::normalize_address;
+use crate::{CrossChainExecutor, CrossChainExecutorExt, CrossChainIntent, IntentStatus};
+
+pub(crate) const DEFAULT_PAGE_LIMIT: u64 = 50;
+pub(crate) const MAX_PAGE_LIMIT: u64 = 200;
+
+/// Pending intent with the time it becomes eligible to execute
+#[derive(Serialize, JsonSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingIntent {
+    #[serde(flatten)]
+    pub intent: CrossChainIntent,
+    /// Block timestamp (ns) from which the intent may execute, `None` once
+    /// it may
+    pub eligible_at: Option<U64>,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Get the full stored record of an intent
+    pub fn get_intent(&self, intent_id: String) -> Option<CrossChainIntent> {
+        self.load_intent(&intent_id)
+    }
+
+    /// Get the records of several intents, in the order of `intent_ids`,
+    /// with `None` for unknown IDs
+    pub fn get_intents_bulk(&self, intent_ids: Vec<String>) -> Vec<Option<CrossChainIntent>> {
+        require(
+            intent_ids.len() as u64 <= MAX_PAGE_LIMIT,
+            ContractError::TooManyIntentIds,
+        );
+        intent_ids
+            .iter()
+            .map(|intent_id| self.load_intent(intent_id))
+            .collect()
+    }
+
+    /// List intents in creation order
+    pub fn get_intents(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<CrossChainIntent> {
+        self.page_intents(self.intent_ids(), from_index, limit)
+    }
+
+    /// List intents with a given status in creation order. Any `Failed`
+    /// status matches failed intents regardless of the reason.
+    pub fn get_intents_by_status(
+        &self,
+        status: IntentStatus,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<CrossChainIntent> {
+        self.page_intents(self.status_intent_ids(&status), from_index, limit)
+    }
+
+    /// List pending intents in creation order, with when each becomes
+    /// eligible to execute
+    pub fn get_pending_intents(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<PendingIntent> {
+        self.page_intents(
+            self.status_intent_ids(&IntentStatus::Pending),
+            from_index,
+            limit,
+        )
+        .into_iter()
+        .map(|intent| PendingIntent {
+            eligible_at: intent.execute_after.filter(|_| !intent.is_eligible()),
+            intent,
+        })
+        .collect()
+    }
+
+    /// List intents deposited by a Base address, oldest first
+    pub fn get_intents_by_sender(
+        &self,
+        sender: String,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<CrossChainIntent> {
+        let intent_ids = self
+            .intents_by_sender
+            .get(&normalize_address(&sender))
+            .into_iter()
+            .flatten()
+            .cloned();
+        self.page_intents(intent_ids, from_index, limit)
+    }
+
+    /// List intents delivering to a NEAR account, oldest first
+    pub fn get_intents_by_receiver(
+        &self,
+        receiver: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<CrossChainIntent> {
+        let intent_ids = self
+            .intents_by_receiver
+            .get(&receiver)
+            .into_iter()
+            .flatten()
+            .cloned();
+        self.page_intents(intent_ids, from_index, limit)
+    }
+
+    /// Highest intent nonce a Base sender has used, 0 before its first
+    /// intent. Lower nonces the sender has not used yet remain valid.
+    pub fn get_sender_nonce(&self, sender: String) -> u64 {
+        self.sender_nonces
+            .get(&normalize_address(&sender))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Number of intents ever created
+    pub fn get_intent_count(&self) -> u64 {
+        self.intent_count()
+    }
+}
+
+impl CrossChainExecutor {
+    /// Add a new intent to the sender and receiver indexes
+    pub(crate) fn index_intent(&mut self, intent: &CrossChainIntent) {
+        self.intents_by_sender
+            .entry(normalize_address(&intent.sender))
+            .or_default()
+            .push(intent.id.clone());
+        let receiver_intents = self
+            .intents_by_receiver
+            .entry(intent.receiver.clone())
+            .or_default();
+        if receiver_intents.is_empty() {
+            self.unique_receivers += 1;
+        }
+        receiver_intents.push(intent.id.clone());
+    }
+
+    /// Move an intent to the index of its next status, placing a new one
+    /// at the next creation position
+    pub(crate) fn index_status(
+        &mut self,
+        intent_id: &String,
+        previous: Option<&IntentStatus>,
+        next: &IntentStatus,
+    ) {
+        let position = match previous {
+            Some(previous) => {
+                let position = self.intent_positions[intent_id];
+                self.intents_by_status
+                    .remove(&(previous.name().to_string(), position));
+                position
+            }
+            None => {
+                let position = self.intent_count();
+                self.intent_positions.insert(intent_id.clone(), position);
+                position
+            }
+        };
+        self.intents_by_status
+            .insert((next.name().to_string(), position), intent_id.clone());
+    }
+
+    /// Remove a pruned intent from the status index
+    pub(crate) fn unindex_status(&mut self, intent: &CrossChainIntent) {
+        if let Some(position) = self.intent_positions.remove(&intent.id) {
+            self.intents_by_status
+                .remove(&(intent.status.name().to_string(), position));
+        }
+    }
+
+    /// IDs of the intents in a status, any `Failed` status matching every
+    /// failed intent, in creation order
+    fn status_intent_ids(&self, status: &IntentStatus) -> impl Iterator<Item = String> + '_ {
+        let name = status.name().to_string();
+        self.intents_by_status
+            .range((name.clone(), 0)..=(name, u64::MAX))
+            .map(|(_, intent_id)| intent_id.clone())
+    }
+
+    fn page_intents(
+        &self,
+        intent_ids: impl Iterator<Item = String>,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<CrossChainIntent> {
+        let from_index = from_index.map_or(0, |index| index.0);
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+        intent_ids
+            .skip(usize::try_from(from_index).unwrap_or(usize::MAX))
+            .take(limit as usize)
+            .filter_map(|intent_id| self.load_intent(&intent_id))
+            .collect()
+    }
+}
@@ -0,0 +1,124 @@
+//! Rolling 24-hour volume caps.
+//!
+//! Executed amounts are accumulated per destination token in hourly buckets.
+//! An execution that would push the volume of the last 24 buckets over the
+//! token's cap is refused: the intent stays pending and a `cap_reached` event
+//! alerts operators.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::{env, near_bindgen, Balance};
+
+use crate::roles::Role;
+use crate::tokens::normalize_address;
+use crate::{CrossChainExecutor, CrossChainExecutorExt};
+
+const NANOS_PER_BUCKET: u64 = 3_600 * 1_000_000_000;
+
+/// Buckets making up the rolling window
+pub(crate) const BUCKETS_PER_WINDOW: u64 = 24;
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct VolumeBucket {
+    /// Hour since the Unix epoch
+    pub hour: u64,
+    pub amount: Balance,
+}
+
+#[near_bindgen]
+impl CrossChainExecutor {
+    /// Set the rolling 24h volume cap of a destination token, or remove it
+    pub fn set_volume_cap(&mut self, token: String, cap: Option<U128>) {
+        self.assert_role(&[Role::Owner]);
+        let token = normalize_address(&token);
+        match cap {
+            Some(cap) => self.volume_caps.insert(token, cap.0),
+            None => self.volume_caps.remove(&token),
+        };
+    }
+
+    /// Get the rolling 24h volume cap of a destination token
+    pub fn get_volume_cap(&self, token: String) -> Option<U128> {
+        self.volume_caps
+            .get(&normalize_address(&token))
+            .copied()
+            .map(U128)
+    }
+
+    /// Amount of a token executed in the last 24 hours
+    pub fn get_daily_volume(&self, token: String) -> U128 {
+        U128(self.daily_volume(&normalize_address(&token)))
+    }
+}
+
+impl CrossChainExecutor {
+    /// Whether executing `amount` keeps the token under its cap, returning
+    /// the cap and current volume when it would not
+    pub(crate) fn check_volume_cap(
+        &self,
+        token: &str,
+        amount: Balance,
+    ) -> Result<(), (Balance, Balance)> {
+        let token = normalize_address(token);
+        let cap = match self.volume_caps.get(&token) {
+            Some(cap) => *cap,
+            None => return Ok(()),
+        };
+        let volume = self.daily_volume(&token);
+        match volume.checked_add(amount) {
+            Some(total) if total <= cap => Ok(()),
+            _ => Err((cap, volume)),
+        }
+    }
+
+    /// Add an executed amount to the token's current bucket
+    pub(crate) fn record_volume(&mut self, token: &str, amount: Balance) {
+        let token = normalize_address(token);
+        let hour = current_hour();
+        let buckets = self.volume_buckets.entry(token).or_default();
+        buckets.retain(|bucket| is_live(bucket, hour));
+        match buckets.last_mut() {
+            Some(bucket) if bucket.hour == hour => {
+                bucket.amount = bucket.amount.saturating_add(amount)
+            }
+            _ => buckets.push(VolumeBucket { hour, amount }),
+        }
+    }
+
+    /// Volume of a token in the current hour and in the rest of the window
+    pub(crate) fn hourly_volumes(&self, token: &str) -> (Balance, Balance) {
+        let hour = current_hour();
+        self.volume_buckets
+            .get(&normalize_address(token))
+            .into_iter()
+            .flatten()
+            .filter(|bucket| is_live(bucket, hour))
+            .fold((0, 0), |(current, trailing): (Balance, Balance), bucket| {
+                if bucket.hour == hour {
+                    (current.saturating_add(bucket.amount), trailing)
+                } else {
+                    (current, trailing.saturating_add(bucket.amount))
+                }
+            })
+    }
+
+    fn daily_volume(&self, token: &str) -> Balance {
+        let hour = current_hour();
+        self.volume_buckets
+            .get(token)
+            .into_iter()
+            .flatten()
+            .filter(|bucket| is_live(bucket, hour))
+            .fold(0, |total: Balance, bucket| {
+                total.saturating_add(bucket.amount)
+            })
+    }
+}
+
+fn current_hour() -> u64 {
+    env::block_timestamp() / NANOS_PER_BUCKET
+}
+
+fn is_live(bucket: &VolumeBucket, hour: u64) -> bool {
+    bucket.hour + BUCKETS_PER_WINDOW > hour
+}
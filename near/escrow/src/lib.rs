@@ -0,0 +1,121 @@
+//! Per-intent escrow.
+//!
+//! Deployed by the CrossChainExecutor on a subaccount of its own for each
+//! high-value intent. The escrow holds the intent's funds apart from the
+//! executor's pool while it settles, pays them out on the executor's
+//! instruction and deletes itself once the intent is final, returning its
+//! remaining NEAR to the executor.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::json_types::U128;
+use near_sdk::serde_json::json;
+use near_sdk::{env, near_bindgen, AccountId, Gas, PanicOnDefault, Promise};
+
+/// Gas attached to `ft_transfer` calls paying out tokens
+const FT_TRANSFER_GAS: Gas = Gas(5_000_000_000_000);
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Escrow {
+    /// Account allowed to release funds and close the escrow, the
+    /// CrossChainExecutor
+    executor: AccountId,
+}
+
+#[near_bindgen]
+impl Escrow {
+    #[init]
+    pub fn new(executor: AccountId) -> Self {
+        assert!(!env::state_exists(), "Already initialized");
+        Self { executor }
+    }
+
+    /// Pay `amount` of a NEP-141 token, or of native NEAR when `token` is
+    /// unset, to `receiver`. Only callable by the executor.
+    pub fn release(
+        &mut self,
+        token: Option<AccountId>,
+        receiver: AccountId,
+        amount: U128,
+    ) -> Promise {
+        self.assert_executor();
+        match token {
+            Some(token) => Promise::new(token).function_call(
+                "ft_transfer".to_string(),
+                json!({ "receiver_id": receiver, "amount": amount })
+                    .to_string()
+                    .into_bytes(),
+                1,
+                FT_TRANSFER_GAS,
+            ),
+            None => Promise::new(receiver).transfer(amount.0),
+        }
+    }
+
+    /// Delete the escrow, sending its remaining NEAR to the executor. Only
+    /// callable by the executor.
+    pub fn close(&mut self) -> Promise {
+        self.assert_executor();
+        Promise::new(env::current_account_id()).delete_account(self.executor.clone())
+    }
+
+    /// Get the account allowed to release funds
+    pub fn get_executor(&self) -> AccountId {
+        self.executor.clone()
+    }
+}
+
+impl Escrow {
+    fn assert_executor(&self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.executor,
+            "Only the executor can manage the escrow"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, get_created_receipts, VMContextBuilder};
+    use near_sdk::{mock::VmAction, testing_env};
+
+    fn executor() -> AccountId {
+        "executor.near".parse().unwrap()
+    }
+
+    fn set_caller(account_id: &AccountId) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("0a1b2c.executor.near".parse().unwrap())
+            .predecessor_account_id(account_id.clone())
+            .build());
+    }
+
+    #[test]
+    fn executor_releases_tokens_and_closes() {
+        set_caller(&executor());
+        let mut escrow = Escrow::new(executor());
+        escrow.release(Some("usdc.near".parse().unwrap()), accounts(1), U128(1_000));
+        escrow.close();
+
+        let receipts = get_created_receipts();
+        assert_eq!(
+            receipts[0].receiver_id,
+            "usdc.near".parse::<AccountId>().unwrap()
+        );
+        assert!(matches!(
+            &receipts[1].actions[0],
+            VmAction::DeleteAccount { beneficiary_id } if *beneficiary_id == executor()
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the executor can manage the escrow")]
+    fn release_rejects_other_callers() {
+        set_caller(&executor());
+        let mut escrow = Escrow::new(executor());
+        set_caller(&accounts(1));
+        escrow.release(None, accounts(1), U128(1_000));
+    }
+}
@@ -0,0 +1,107 @@
+//! Gas benchmark of the intent lifecycle against stored state size.
+//!
+//! For each size, deploys the executor to a sandbox with that many pending
+//! intents already stored, then runs one intent through `create_intent`,
+//! `approve_intent` and `execute_intent` and reports the gas each burnt,
+//! the transfer and its callback included. Gas that grows with the number
+//! of stored intents means a method touches a collection it should not
+//! iterate or deserialize whole; the run fails if any method costs more
+//! than `--max-growth` percent over its cost at the smallest size.
+//!
+//! Build the contracts first with `contract/build.sh` and
+//! `wrapped-token/build.sh`.
+
+use clap::Parser;
+use intents_integration_tests::setup_seeded;
+
+const METHODS: [&str; 3] = ["create_intent", "approve_intent", "execute_intent"];
+
+#[derive(Parser)]
+#[command(about = "Measure the executor's gas at varying state sizes")]
+struct Args {
+    /// Numbers of stored intents to measure at
+    #[arg(long, value_delimiter = ',', default_value = "10,10000,1000000")]
+    sizes: Vec<u32>,
+    /// Allowed growth in percent over the gas at the smallest size
+    #[arg(long, default_value_t = 25)]
+    max_growth: u64,
+}
+
+/// Gas burnt by each of `METHODS` with `intents` stored
+async fn measure(intents: u32) -> anyhow::Result<[u64; 3]> {
+    let env = setup_seeded(intents).await?;
+    let receiver = env.receiver.id().to_string();
+    let intent = env.prove_deposit(env.intent(&receiver, 1_000, 1)).await?;
+    let intent_id = intent["id"].as_str().unwrap().to_string();
+
+    let results = [
+        env.create(&intent).await?,
+        env.approve(&intent_id).await?,
+        env.execute(&intent_id).await?,
+    ];
+    let mut gas = [0; 3];
+    for (index, result) in results.iter().enumerate() {
+        if !result.is_success() {
+            anyhow::bail!("{} failed: {:?}", METHODS[index], result.receipt_failures());
+        }
+        gas[index] = result.total_gas_burnt.as_gas();
+    }
+    Ok(gas)
+}
+
+fn tgas(gas: u64) -> String {
+    format!("{:.3}", gas as f64 / 1e12)
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let mut args = Args::parse();
+    args.sizes.sort_unstable();
+    args.sizes.dedup();
+    if args.sizes.is_empty() {
+        anyhow::bail!("No sizes to measure");
+    }
+
+    let mut rows = Vec::new();
+    for &size in &args.sizes {
+        eprintln!("Measuring with {} stored intents", size);
+        rows.push((size, measure(size).await?));
+    }
+
+    println!(
+        "{:>12} {:>16} {:>16} {:>16}",
+        "intents", METHODS[0], METHODS[1], METHODS[2]
+    );
+    for (size, gas) in &rows {
+        println!(
+            "{:>12} {:>16} {:>16} {:>16}",
+            size,
+            tgas(gas[0]),
+            tgas(gas[1]),
+            tgas(gas[2])
+        );
+    }
+    println!("(Tgas)");
+
+    let baseline = rows[0].1;
+    let mut regressions = Vec::new();
+    for (size, gas) in &rows[1..] {
+        for (index, method) in METHODS.iter().enumerate() {
+            let limit = baseline[index] + baseline[index] * args.max_growth / 100;
+            if gas[index] > limit {
+                regressions.push(format!(
+                    "{} burns {} Tgas with {} intents stored, over {} Tgas at {}",
+                    method,
+                    tgas(gas[index]),
+                    size,
+                    tgas(baseline[index]),
+                    rows[0].0
+                ));
+            }
+        }
+    }
+    if !regressions.is_empty() {
+        anyhow::bail!("Gas grows with state size:\n{}", regressions.join("\n"));
+    }
+    Ok(())
+}
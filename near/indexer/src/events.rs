@@ -0,0 +1,107 @@
+//! Decoding of the executor's NEP-297 event logs.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// NEP-297 standard the executor logs its events under
+pub const EVENT_STANDARD: &str = "near-intents";
+
+const EVENT_PREFIX: &str = "EVENT_JSON:";
+
+/// An event logged by the executor
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutorEvent {
+    pub event: String,
+    pub data: Value,
+}
+
+/// An event with the receipt that logged it
+#[derive(Debug, Clone)]
+pub struct LoggedEvent {
+    pub receipt_id: String,
+    /// Position of the log among the receipt's logs
+    pub log_index: u32,
+    pub event: ExecutorEvent,
+}
+
+/// Executor events of one block, in execution order
+#[derive(Debug, Clone)]
+pub struct IndexedBlock {
+    pub height: u64,
+    /// Block timestamp in nanoseconds
+    pub timestamp: u64,
+    pub events: Vec<LoggedEvent>,
+}
+
+#[derive(Deserialize)]
+struct EventLog {
+    standard: String,
+    event: String,
+    #[serde(default)]
+    data: Value,
+}
+
+/// Decode a log line, ignoring anything but `near-intents` events
+pub fn decode(log: &str) -> Option<ExecutorEvent> {
+    let log: EventLog = serde_json::from_str(log.strip_prefix(EVENT_PREFIX)?).ok()?;
+    (log.standard == EVENT_STANDARD).then_some(ExecutorEvent {
+        event: log.event,
+        data: log.data,
+    })
+}
+
+impl ExecutorEvent {
+    /// Intent the event is about, if any
+    pub fn intent_id(&self) -> Option<&str> {
+        self.data.get("intent_id")?.as_str()
+    }
+
+    /// Status the event moves its intent to, if it is a transition
+    pub fn status(&self) -> Option<&'static str> {
+        match self.event.as_str() {
+            "intent_created" | "outbound_intent_created" => Some("Pending"),
+            "intent_executed" => Some("Executing"),
+            "intent_completed" | "outbound_intent_completed" => Some("Completed"),
+            "intent_failed" => Some("Failed"),
+            "intent_cancelled" => Some("Cancelled"),
+            "intent_refunded" => Some("Refunded"),
+            "intent_disputed" => Some("Disputed"),
+            "dispute_resolved" => match self.data.get("ruling")?.as_str()? {
+                "Release" => Some("Pending"),
+                "Cancel" => Some("Cancelled"),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// String field of the event's data
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.data.get(name)?.as_str()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_executor_events() {
+        let event = decode(
+            r#"EVENT_JSON:{"standard":"near-intents","version":"1.1.0","event":"intent_failed","data":{"intent_id":"0xab","reason":"Transfer failed"}}"#,
+        )
+        .unwrap();
+        assert_eq!(event.intent_id(), Some("0xab"));
+        assert_eq!(event.status(), Some("Failed"));
+        assert_eq!(event.field("reason"), Some("Transfer failed"));
+    }
+
+    #[test]
+    fn ignores_other_logs() {
+        assert!(decode("Transfer 10 from alice to bob").is_none());
+        assert!(decode(
+            r#"EVENT_JSON:{"standard":"nep141","version":"1.0.0","event":"ft_transfer","data":[]}"#
+        )
+        .is_none());
+    }
+}
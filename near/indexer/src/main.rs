@@ -0,0 +1,157 @@
+//! NEAR Lake indexer for the CrossChainExecutor.
+//!
+//! Streams blocks from NEAR Lake, decodes the `EVENT_JSON` logs of the
+//! executor's successful receipts and keeps a SQLite store of every intent,
+//! its current status and the events that moved it there. Indexing resumes
+//! after the last stored block, so the indexer can be restarted at will.
+//!
+//! ```text
+//! intents-indexer --db intents.sqlite run --contract executor.testnet --start-block 150000000
+//! intents-indexer --db intents.sqlite show 0x5c..e1
+//! ```
+//!
+//! Reading NEAR Lake requires AWS credentials in the environment, as for any
+//! near-lake-framework indexer.
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Parser, Subcommand, ValueEnum};
+use near_lake_framework::near_indexer_primitives::views::ExecutionStatusView;
+use near_lake_framework::near_indexer_primitives::StreamerMessage;
+use near_lake_framework::LakeConfigBuilder;
+
+mod events;
+mod store;
+
+use events::{IndexedBlock, LoggedEvent};
+use store::Store;
+
+#[derive(Parser)]
+#[command(about = "Index CrossChainExecutor intents from NEAR Lake")]
+struct Args {
+    /// SQLite database holding the indexed intents
+    #[arg(long, default_value = "intents.sqlite")]
+    db: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Stream blocks and index the executor's events
+    Run {
+        /// Account the executor is deployed on
+        #[arg(long)]
+        contract: String,
+        #[arg(long, value_enum, default_value_t = Network::Testnet)]
+        network: Network,
+        /// Block to start from when the database is empty
+        #[arg(long)]
+        start_block: Option<u64>,
+    },
+    /// Print an indexed intent and its events as JSON
+    Show { intent_id: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Network {
+    Mainnet,
+    Testnet,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let store =
+        Store::open(&args.db).with_context(|| format!("Failed to open {}", args.db.display()))?;
+
+    match args.command {
+        Command::Run {
+            contract,
+            network,
+            start_block,
+        } => run(store, contract, network, start_block).await,
+        Command::Show { intent_id } => {
+            let intent = store
+                .intent(&intent_id)?
+                .with_context(|| format!("Intent {} is not indexed", intent_id))?;
+            println!("{}", serde_json::to_string_pretty(&intent)?);
+            Ok(())
+        }
+    }
+}
+
+async fn run(
+    mut store: Store,
+    contract: String,
+    network: Network,
+    start_block: Option<u64>,
+) -> anyhow::Result<()> {
+    let start = match store.last_block()? {
+        Some(height) => height + 1,
+        None => start_block.context("Pass --start-block to index into an empty database")?,
+    };
+    let config = match network {
+        Network::Mainnet => LakeConfigBuilder::default().mainnet(),
+        Network::Testnet => LakeConfigBuilder::default().testnet(),
+    }
+    .start_block_height(start)
+    .build()?;
+
+    let (streamer, mut messages) = near_lake_framework::streamer(config);
+    while let Some(message) = messages.recv().await {
+        let block = decode_block(&message, &contract);
+        store
+            .apply_block(&block)
+            .with_context(|| format!("Failed to index block {}", block.height))?;
+        for logged in &block.events {
+            println!(
+                "#{} {} {}",
+                block.height,
+                logged.event.event,
+                logged.event.intent_id().unwrap_or("-")
+            );
+        }
+    }
+    streamer.await?
+}
+
+/// Executor events logged by successful receipts in a block
+fn decode_block(message: &StreamerMessage, contract: &str) -> IndexedBlock {
+    let events = message
+        .shards
+        .iter()
+        .flat_map(|shard| &shard.receipt_execution_outcomes)
+        .filter(|outcome| outcome.receipt.receiver_id.as_str() == contract)
+        // Failed receipts are reverted, and with them what they logged
+        .filter(|outcome| {
+            !matches!(
+                outcome.execution_outcome.outcome.status,
+                ExecutionStatusView::Failure(_)
+            )
+        })
+        .flat_map(|outcome| {
+            let receipt_id = outcome.receipt.receipt_id.to_string();
+            outcome
+                .execution_outcome
+                .outcome
+                .logs
+                .iter()
+                .enumerate()
+                .filter_map(move |(index, log)| {
+                    Some(LoggedEvent {
+                        receipt_id: receipt_id.clone(),
+                        log_index: index as u32,
+                        event: events::decode(log)?,
+                    })
+                })
+        })
+        .collect();
+
+    IndexedBlock {
+        height: message.block.header.height,
+        timestamp: message.block.header.timestamp,
+        events,
+    }
+}
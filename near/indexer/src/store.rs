@@ -0,0 +1,311 @@
+//! SQLite store of indexed intents.
+//!
+//! `intents` holds the latest state of every inbound and outbound intent,
+//! `events` every executor event about an intent in the order it was logged,
+//! and `cursor` the last fully indexed block. Each block is applied in one
+//! transaction, so a restart resumes from a consistent state and replaying
+//! a block is harmless.
+
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::events::{ExecutorEvent, IndexedBlock};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS intents (
+        intent_id TEXT PRIMARY KEY,
+        kind TEXT NOT NULL,
+        chain_id INTEGER,
+        sender TEXT,
+        receiver TEXT,
+        token TEXT,
+        amount TEXT,
+        status TEXT NOT NULL,
+        failure_reason TEXT,
+        created_block INTEGER NOT NULL,
+        updated_block INTEGER NOT NULL,
+        updated_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS events (
+        receipt_id TEXT NOT NULL,
+        log_index INTEGER NOT NULL,
+        block_height INTEGER NOT NULL,
+        block_timestamp INTEGER NOT NULL,
+        intent_id TEXT NOT NULL,
+        event TEXT NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (receipt_id, log_index)
+    );
+    CREATE INDEX IF NOT EXISTS events_by_intent ON events (intent_id, block_height, log_index);
+    CREATE TABLE IF NOT EXISTS cursor (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        block_height INTEGER NOT NULL
+    );
+";
+
+/// An intent with its events, oldest first
+#[derive(Serialize)]
+pub struct IntentRecord {
+    pub intent_id: String,
+    /// `inbound` or `outbound`
+    pub kind: String,
+    pub chain_id: Option<u64>,
+    pub sender: Option<String>,
+    pub receiver: Option<String>,
+    pub token: Option<String>,
+    pub amount: Option<String>,
+    pub status: String,
+    pub failure_reason: Option<String>,
+    pub created_block: u64,
+    pub updated_block: u64,
+    pub events: Vec<EventRecord>,
+}
+
+#[derive(Serialize)]
+pub struct EventRecord {
+    pub block_height: u64,
+    pub block_timestamp: u64,
+    pub receipt_id: String,
+    pub event: String,
+    pub data: Value,
+}
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Open the store at `path`, creating its tables on first use
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    fn with_connection(conn: Connection) -> rusqlite::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Height of the last fully indexed block
+    pub fn last_block(&self) -> rusqlite::Result<Option<u64>> {
+        self.conn
+            .query_row("SELECT block_height FROM cursor WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()
+    }
+
+    /// Record a block's events and advance the cursor past it
+    pub fn apply_block(&mut self, block: &IndexedBlock) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        for logged in &block.events {
+            let intent_id = match logged.event.intent_id() {
+                Some(intent_id) => intent_id,
+                None => continue,
+            };
+            let inserted = tx.execute(
+                "INSERT OR IGNORE INTO events
+                    (receipt_id, log_index, block_height, block_timestamp, intent_id, event, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    logged.receipt_id,
+                    logged.log_index,
+                    block.height,
+                    block.timestamp,
+                    intent_id,
+                    logged.event.event,
+                    logged.event.data.to_string(),
+                ],
+            )?;
+            // Already applied when the block was indexed before
+            if inserted == 0 {
+                continue;
+            }
+            apply_event(&tx, block, intent_id, &logged.event)?;
+        }
+        tx.execute(
+            "INSERT INTO cursor (id, block_height) VALUES (0, ?1)
+             ON CONFLICT (id) DO UPDATE SET block_height = excluded.block_height",
+            params![block.height],
+        )?;
+        tx.commit()
+    }
+
+    /// Get an intent with its events
+    pub fn intent(&self, intent_id: &str) -> rusqlite::Result<Option<IntentRecord>> {
+        let record = self
+            .conn
+            .query_row(
+                "SELECT kind, chain_id, sender, receiver, token, amount, status,
+                        failure_reason, created_block, updated_block
+                 FROM intents WHERE intent_id = ?1",
+                params![intent_id],
+                |row| {
+                    Ok(IntentRecord {
+                        intent_id: intent_id.to_string(),
+                        kind: row.get(0)?,
+                        chain_id: row.get(1)?,
+                        sender: row.get(2)?,
+                        receiver: row.get(3)?,
+                        token: row.get(4)?,
+                        amount: row.get(5)?,
+                        status: row.get(6)?,
+                        failure_reason: row.get(7)?,
+                        created_block: row.get(8)?,
+                        updated_block: row.get(9)?,
+                        events: vec![],
+                    })
+                },
+            )
+            .optional()?;
+        let mut record = match record {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+
+        let mut statement = self.conn.prepare(
+            "SELECT block_height, block_timestamp, receipt_id, event, data
+             FROM events WHERE intent_id = ?1
+             ORDER BY block_height, receipt_id, log_index",
+        )?;
+        record.events = statement
+            .query_map(params![intent_id], |row| {
+                let data: String = row.get(4)?;
+                Ok(EventRecord {
+                    block_height: row.get(0)?,
+                    block_timestamp: row.get(1)?,
+                    receipt_id: row.get(2)?,
+                    event: row.get(3)?,
+                    data: serde_json::from_str(&data).unwrap_or(Value::Null),
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(Some(record))
+    }
+}
+
+/// Create or update the intent an event is about
+fn apply_event(
+    tx: &Connection,
+    block: &IndexedBlock,
+    intent_id: &str,
+    event: &ExecutorEvent,
+) -> rusqlite::Result<()> {
+    let status = match event.status() {
+        Some(status) => status,
+        None => return Ok(()),
+    };
+    match event.event.as_str() {
+        "intent_created" => {
+            tx.execute(
+                "INSERT OR IGNORE INTO intents
+                    (intent_id, kind, chain_id, sender, receiver, token, amount, status,
+                     created_block, updated_block, updated_at)
+                 VALUES (?1, 'inbound', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?8, ?9)",
+                params![
+                    intent_id,
+                    event.data.get("chain_id").and_then(Value::as_u64),
+                    event.field("sender"),
+                    event.field("receiver"),
+                    event.field("token"),
+                    event.field("amount"),
+                    status,
+                    block.height,
+                    block.timestamp,
+                ],
+            )?;
+        }
+        "outbound_intent_created" => {
+            tx.execute(
+                "INSERT OR IGNORE INTO intents
+                    (intent_id, kind, sender, receiver, token, amount, status,
+                     created_block, updated_block, updated_at)
+                 VALUES (?1, 'outbound', ?2, ?3, ?4, ?5, ?6, ?7, ?7, ?8)",
+                params![
+                    intent_id,
+                    event.field("sender"),
+                    event.field("recipient"),
+                    event.field("base_token"),
+                    event.field("amount"),
+                    status,
+                    block.height,
+                    block.timestamp,
+                ],
+            )?;
+        }
+        _ => {
+            tx.execute(
+                "UPDATE intents
+                 SET status = ?2, failure_reason = COALESCE(?3, failure_reason),
+                     updated_block = ?4, updated_at = ?5
+                 WHERE intent_id = ?1",
+                params![
+                    intent_id,
+                    status,
+                    event.field("reason"),
+                    block.height,
+                    block.timestamp,
+                ],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{decode, LoggedEvent};
+
+    fn block(height: u64, logs: &[&str]) -> IndexedBlock {
+        IndexedBlock {
+            height,
+            timestamp: height * 1_000,
+            events: logs
+                .iter()
+                .enumerate()
+                .map(|(index, log)| LoggedEvent {
+                    receipt_id: format!("receipt-{}", height),
+                    log_index: index as u32,
+                    event: decode(&format!(
+                        r#"EVENT_JSON:{{"standard":"near-intents","version":"1.1.0",{}}}"#,
+                        log
+                    ))
+                    .unwrap(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn tracks_intent_status_and_events() {
+        let mut store = Store::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+        let created = block(
+            10,
+            &[
+                r#""event":"intent_created","data":{"intent_id":"0xab","chain_id":8453,"sender":"0x11","receiver":"alice.near","token":"0x22","amount":"1000"}"#,
+            ],
+        );
+        let failed = block(
+            11,
+            &[
+                r#""event":"intent_executed","data":{"intent_id":"0xab","receiver":"alice.near","amount":"1000"}"#,
+                r#""event":"intent_failed","data":{"intent_id":"0xab","reason":"Transfer failed"}"#,
+            ],
+        );
+        store.apply_block(&created).unwrap();
+        store.apply_block(&failed).unwrap();
+        // Replaying a block changes nothing
+        store.apply_block(&failed).unwrap();
+
+        let intent = store.intent("0xab").unwrap().unwrap();
+        assert_eq!(intent.kind, "inbound");
+        assert_eq!(intent.chain_id, Some(8453));
+        assert_eq!(intent.status, "Failed");
+        assert_eq!(intent.failure_reason.as_deref(), Some("Transfer failed"));
+        assert_eq!(intent.events.len(), 3);
+        assert_eq!(store.last_block().unwrap(), Some(11));
+    }
+}
@@ -0,0 +1,481 @@
+//! Sandbox fixtures for end-to-end tests of the CrossChainExecutor.
+//!
+//! Deploys the executor and a wrapped token to a local sandbox and drives
+//! intents through the real promise path: the executor's `ft_transfer` runs
+//! on the token contract and its result reaches `on_transfer_complete`. The
+//! deposit proofs are built here the way the Base bridge and the relayer
+//! would produce them.
+//!
+//! Build the contracts first with `contract/build.sh` and
+//! `wrapped-token/build.sh`.
+
+use anyhow::Context;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ed25519_dalek::Signer;
+use near_intents::bench::SeedParams;
+use near_workspaces::network::Sandbox;
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::types::{AccountDetailsPatch, NearToken};
+use near_workspaces::{Account, Contract, Worker};
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+
+pub const BASE_CHAIN_ID: u64 = 8453;
+pub const BASE_BRIDGE: &str = "0x1111111111111111111111111111111111111111";
+pub const BASE_TOKEN: &str = "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+const SENDER: &str = "0x71c7656ec7ab88b098defb751b7401b5f6d8976f";
+
+/// Tokens minted to the executor for it to deliver
+pub const LIQUIDITY: u128 = 1_000_000_000;
+
+/// yoctoNEAR staked per byte of account storage
+const STORAGE_PRICE_PER_BYTE: u128 = 10_000_000_000_000_000_000;
+
+/// Storage records patched into the sandbox per request
+const PATCH_BATCH: usize = 10_000;
+
+pub struct TestEnv {
+    pub worker: Worker<Sandbox>,
+    pub executor: Contract,
+    pub token: Contract,
+    pub agent: Account,
+    pub validator: Account,
+    /// Receiver registered with the token
+    pub receiver: Account,
+}
+
+fn wasm(path: &str) -> anyhow::Result<Vec<u8>> {
+    let path = format!("{}/../{}", env!("CARGO_MANIFEST_DIR"), path);
+    std::fs::read(&path).with_context(|| format!("Failed to read {}, run its build.sh", path))
+}
+
+/// Deploy the executor with one validator and a token mapping for
+/// `BASE_TOKEN`, delivered from liquidity the executor holds
+pub async fn setup() -> anyhow::Result<TestEnv> {
+    deploy(None).await
+}
+
+/// Deploy the executor as `setup` does, with `intents` pending intents
+/// already stored. The state is built natively and patched into the
+/// sandbox, see `near_intents::bench`.
+pub async fn setup_seeded(intents: u32) -> anyhow::Result<TestEnv> {
+    deploy(Some(intents)).await
+}
+
+async fn deploy(seeded_intents: Option<u32>) -> anyhow::Result<TestEnv> {
+    let worker = near_workspaces::sandbox().await?;
+    let root = worker.root_account()?;
+    let create = |name: &'static str, near: u128| {
+        root.create_subaccount(name)
+            .initial_balance(NearToken::from_near(near))
+            .transact()
+    };
+    let agent = create("agent", 10).await?.into_result()?;
+    let validator = create("validator", 200).await?.into_result()?;
+    let receiver = create("receiver", 10).await?.into_result()?;
+    let executor = create("executor", 50)
+        .await?
+        .into_result()?
+        .deploy(&wasm("contract/res/near_intents.wasm")?)
+        .await?
+        .into_result()?;
+    let token = create("token", 10)
+        .await?
+        .into_result()?
+        .deploy(&wasm("wrapped-token/res/wrapped_token.wasm")?)
+        .await?
+        .into_result()?;
+
+    match seeded_intents {
+        None => init_executor(&executor, &token, &agent, &validator).await?,
+        Some(intents) => {
+            let state = near_intents::bench::seeded_state(&SeedParams {
+                executor: executor.id().as_str().parse()?,
+                agent: agent.id().as_str().parse()?,
+                validator: validator.id().as_str().parse()?,
+                validator_key: validator
+                    .secret_key()
+                    .public_key()
+                    .to_string()
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid validator key"))?,
+                token: token.id().as_str().parse()?,
+                base_token: BASE_TOKEN.to_string(),
+                base_bridge: BASE_BRIDGE.to_string(),
+                receiver: receiver.id().as_str().parse()?,
+                intents,
+            });
+            patch_state(&worker, &executor, &state).await?;
+        }
+    }
+
+    // The root account mints the executor's liquidity
+    token
+        .call("new")
+        .args_json(json!({
+            "minter": root.id(),
+            "metadata": {
+                "spec": "ft-1.0.0",
+                "name": "USD Coin",
+                "symbol": "USDC",
+                "decimals": 6,
+            },
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+    root.call(token.id(), "mint")
+        .args_json(json!({ "account_id": executor.id(), "amount": LIQUIDITY.to_string() }))
+        .transact()
+        .await?
+        .into_result()?;
+    receiver
+        .call(token.id(), "storage_deposit")
+        .args_json(json!({}))
+        .deposit(NearToken::from_millinear(10))
+        .transact()
+        .await?
+        .into_result()?;
+
+    Ok(TestEnv {
+        worker,
+        executor,
+        token,
+        agent,
+        validator,
+        receiver,
+    })
+}
+
+/// Initialize the executor through its methods, as an operator would
+async fn init_executor(
+    executor: &Contract,
+    token: &Contract,
+    agent: &Account,
+    validator: &Account,
+) -> anyhow::Result<()> {
+    executor
+        .call("new")
+        .args_json(json!({ "required_signatures": 1, "agent_account": agent.id() }))
+        .transact()
+        .await?
+        .into_result()?;
+    executor
+        .call("register_token")
+        .args_json(json!({
+            "mapping": {
+                "base_address": BASE_TOKEN,
+                "near_contract": token.id(),
+                "decimals": { "source": 6, "near": 6 },
+                "symbol": "USDC",
+                "enabled": true,
+                "mintable": false,
+            },
+        }))
+        .transact()
+        .await?
+        .into_result()?;
+    executor
+        .call("set_base_bridge")
+        .args_json(json!({ "address": BASE_BRIDGE }))
+        .transact()
+        .await?
+        .into_result()?;
+    validator
+        .call(executor.id(), "register_validator")
+        .args_json(json!({ "public_key": validator.secret_key().public_key().to_string() }))
+        .deposit(NearToken::from_near(100))
+        .transact()
+        .await?
+        .into_result()?;
+    agent
+        .call(executor.id(), "storage_deposit")
+        .args_json(json!({}))
+        .deposit(NearToken::from_near(1))
+        .transact()
+        .await?
+        .into_result()?;
+    Ok(())
+}
+
+/// Write storage records into the executor's account, funding the storage
+/// they take
+async fn patch_state(
+    worker: &Worker<Sandbox>,
+    executor: &Contract,
+    state: &[(Vec<u8>, Vec<u8>)],
+) -> anyhow::Result<()> {
+    let bytes: usize = state
+        .iter()
+        .map(|(key, value)| key.len() + value.len())
+        .sum();
+    worker
+        .patch(executor.id())
+        .account(
+            AccountDetailsPatch::default().balance(NearToken::from_yoctonear(
+                (bytes as u128 + 1_000_000) * STORAGE_PRICE_PER_BYTE,
+            )),
+        )
+        .transact()
+        .await?;
+    for records in state.chunks(PATCH_BATCH) {
+        worker
+            .patch(executor.id())
+            .states(
+                records
+                    .iter()
+                    .map(|(key, value)| (key.as_slice(), value.as_slice())),
+            )
+            .transact()
+            .await?;
+    }
+    Ok(())
+}
+
+impl TestEnv {
+    /// Intent delivering `amount` of `BASE_TOKEN` to `receiver`, with an ID
+    /// and proof still to be filled in by `create_approved`
+    pub fn intent(&self, receiver: &str, amount: u128, nonce: u64) -> Value {
+        json!({
+            "id": "",
+            "sender": SENDER,
+            "receiver": receiver,
+            "token": BASE_TOKEN,
+            "amount": amount.to_string(),
+            "proof": {
+                "block_number": 1,
+                "timestamp": 1,
+                "transaction_hash": format!("0x{:064x}", nonce),
+                "receipt": "",
+                "receipt_index": 0,
+                "proof_nodes": [],
+                "log_index": 0,
+            },
+            "status": "Pending",
+            "signatures": [],
+            "expires_at": u64::MAX.to_string(),
+            "nonce": nonce,
+            "chain_id": BASE_CHAIN_ID,
+        })
+    }
+
+    /// Prove an intent's deposit under a trusted Base header, create it as
+    /// the agent and approve it as the validator, returning its ID
+    pub async fn create_approved(&self, intent: Value) -> anyhow::Result<String> {
+        let intent = self.prove_deposit(intent).await?;
+        let intent_id = intent["id"].as_str().unwrap().to_string();
+        self.create(&intent).await?.into_result()?;
+        self.approve(&intent_id).await?.into_result()?;
+        Ok(intent_id)
+    }
+
+    /// Fill in an intent's ID and deposit proof, trusting a Base header
+    /// whose receipts trie holds only its deposit and checkpointing its block
+    pub async fn prove_deposit(&self, mut intent: Value) -> anyhow::Result<Value> {
+        let terms_hash: String = self
+            .executor
+            .view("compute_intent_hash")
+            .args_json(json!({ "intent": intent }))
+            .await?
+            .json()?;
+        let intent_id: String = self
+            .executor
+            .view("compute_intent_id")
+            .args_json(json!({ "intent": intent }))
+            .await?
+            .json()?;
+        intent["id"] = json!(intent_id);
+
+        let receipt = deposit_receipt(&intent, &terms_hash);
+        // Single leaf keyed by rlp(0), hex-prefix encoded as an even-length leaf path
+        let leaf = rlp::list(&[rlp::bytes(&[0x20, 0x80]), rlp::bytes(&receipt)]);
+        let mut header = vec![rlp::bytes(&[]); 15];
+        header[0] = rlp::bytes(&[0; 32]);
+        header[5] = rlp::bytes(&keccak(&leaf));
+        header[8] = rlp::bytes(&[1]);
+        intent["proof"]["receipt"] = json!(BASE64.encode(&receipt));
+        intent["proof"]["proof_nodes"] = json!([BASE64.encode(&leaf)]);
+
+        self.executor
+            .call("set_trusted_header")
+            .args_json(json!({
+                "header_rlp": BASE64.encode(rlp::list(&header)),
+                "confirmations": 0,
+            }))
+            .transact()
+            .await?
+            .into_result()?;
+        self.checkpoint_deposit_block().await?;
+        Ok(intent)
+    }
+
+    /// Checkpoint Base block 1, which every proven deposit is in, signed by
+    /// the validator
+    async fn checkpoint_deposit_block(&self) -> anyhow::Result<()> {
+        if !self.view("get_checkpoint", json!({})).await?.is_null() {
+            return Ok(());
+        }
+        let state_root = [0u8; 32];
+        let message = near_intents::bench::checkpoint_hash(BASE_CHAIN_ID, 1, &state_root);
+        // Secret keys are the base58 secret and public key halves
+        let secret_key = self.validator.secret_key().to_string();
+        let keypair = ed25519_dalek::Keypair::from_bytes(
+            &bs58::decode(secret_key.trim_start_matches("ed25519:")).into_vec()?,
+        )?;
+        self.validator
+            .call(self.executor.id(), "submit_checkpoint")
+            .args_json(json!({
+                "block_number": 1,
+                "state_root": format!("0x{}", hex::encode(state_root)),
+                "signatures": [{
+                    "validator": self.validator.id(),
+                    "signature": BASE64.encode(keypair.sign(&message).to_bytes()),
+                }],
+            }))
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(())
+    }
+
+    /// Create a proven intent as the agent
+    pub async fn create(&self, intent: &Value) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(self
+            .agent
+            .call(self.executor.id(), "create_intent")
+            .args_json(json!({ "intent": intent }))
+            .deposit(NearToken::from_near(1))
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    /// Approve an intent as the validator
+    pub async fn approve(&self, intent_id: &str) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(self
+            .validator
+            .call(self.executor.id(), "approve_intent")
+            .args_json(json!({ "intent_id": intent_id }))
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    /// Execute an intent as the agent, waiting for its callback
+    pub async fn execute(&self, intent_id: &str) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(self
+            .agent
+            .call(self.executor.id(), "execute_intent")
+            .args_json(json!({ "intent_id": intent_id }))
+            .max_gas()
+            .transact()
+            .await?)
+    }
+
+    pub async fn ft_balance_of(&self, account_id: &str) -> anyhow::Result<u128> {
+        let balance: String = self
+            .token
+            .view("ft_balance_of")
+            .args_json(json!({ "account_id": account_id }))
+            .await?
+            .json()?;
+        Ok(balance.parse()?)
+    }
+
+    pub async fn view(&self, method: &str, args: Value) -> anyhow::Result<Value> {
+        Ok(self.executor.view(method).args_json(args).await?.json()?)
+    }
+}
+
+/// NEP-297 events logged by any receipt of a transaction, as
+/// `(standard, event, data)`
+pub fn events(result: &ExecutionFinalResult) -> Vec<(String, String, Value)> {
+    result
+        .logs()
+        .into_iter()
+        .filter_map(|log| log.strip_prefix("EVENT_JSON:"))
+        .filter_map(|json| serde_json::from_str::<Value>(json).ok())
+        .map(|event| {
+            (
+                event["standard"].as_str().unwrap_or_default().to_string(),
+                event["event"].as_str().unwrap_or_default().to_string(),
+                event["data"].clone(),
+            )
+        })
+        .collect()
+}
+
+fn keccak(data: &[u8]) -> Vec<u8> {
+    Keccak256::digest(data).to_vec()
+}
+
+fn address_topic(address: &str) -> Vec<u8> {
+    let mut topic = vec![0u8; 12];
+    topic.extend(hex::decode(&address[2..]).unwrap());
+    topic
+}
+
+fn abi_word(value: u128) -> Vec<u8> {
+    let mut word = vec![0u8; 16];
+    word.extend(value.to_be_bytes());
+    word
+}
+
+/// RLP receipt holding the Base bridge's TokensLocked log for `intent`,
+/// naming the typed hash of its terms
+fn deposit_receipt(intent: &Value, terms_hash: &str) -> Vec<u8> {
+    let amount: u128 = intent["amount"].as_str().unwrap().parse().unwrap();
+    let receiver = intent["receiver"].as_str().unwrap().as_bytes();
+    let mut data = [abi_word(96), abi_word(amount), abi_word(1)].concat();
+    data.extend(abi_word(receiver.len() as u128));
+    data.extend(receiver);
+    data.resize(data.len() + (32 - receiver.len() % 32) % 32, 0);
+
+    let topics = [
+        keccak(b"TokensLocked(string,address,address,string,uint256,uint256)"),
+        keccak(terms_hash.as_bytes()),
+        address_topic(intent["token"].as_str().unwrap()),
+        address_topic(intent["sender"].as_str().unwrap()),
+    ];
+    let log = rlp::list(&[
+        rlp::bytes(&hex::decode(&BASE_BRIDGE[2..]).unwrap()),
+        rlp::list(
+            &topics
+                .iter()
+                .map(|topic| rlp::bytes(topic))
+                .collect::<Vec<_>>(),
+        ),
+        rlp::bytes(&data),
+    ]);
+    rlp::list(&[
+        rlp::bytes(&[1]),
+        rlp::bytes(&[0x52, 0x08]),
+        rlp::bytes(&[0u8; 256]),
+        rlp::list(&[log]),
+    ])
+}
+
+/// Minimal RLP encoding of byte strings and lists
+mod rlp {
+    fn length_prefix(len: usize, short: u8, long: u8) -> Vec<u8> {
+        if len < 56 {
+            return vec![short + len as u8];
+        }
+        let len = len.to_be_bytes();
+        let len = &len[len.iter().position(|&byte| byte != 0).unwrap()..];
+        [&[long + len.len() as u8], len].concat()
+    }
+
+    pub fn bytes(data: &[u8]) -> Vec<u8> {
+        if data.len() == 1 && data[0] < 0x80 {
+            return data.to_vec();
+        }
+        [length_prefix(data.len(), 0x80, 0xb7), data.to_vec()].concat()
+    }
+
+    pub fn list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload = items.concat();
+        [length_prefix(payload.len(), 0xc0, 0xf7), payload].concat()
+    }
+}
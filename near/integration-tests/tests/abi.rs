@@ -0,0 +1,29 @@
+use intents_integration_tests::setup;
+use serde_json::Value;
+
+#[tokio::test]
+async fn embedded_abi_describes_the_contract() -> anyhow::Result<()> {
+    let env = setup().await?;
+    let compressed = env.executor.view("__contract_abi").await?.result;
+    let abi: Value = serde_json::from_slice(&zstd::decode_all(compressed.as_slice())?)?;
+
+    assert_eq!(abi["metadata"]["name"], "near-intents");
+    let functions: Vec<&str> = abi["body"]["functions"]
+        .as_array()
+        .expect("ABI lists no functions")
+        .iter()
+        .filter_map(|function| function["name"].as_str())
+        .collect();
+    for name in [
+        "new",
+        "create_intent",
+        "approve_intent",
+        "execute_intent",
+        "get_intent",
+        "storage_deposit",
+    ] {
+        assert!(functions.contains(&name), "{} is missing from the ABI", name);
+    }
+    assert!(abi["body"]["root_schema"]["definitions"]["CrossChainIntent"].is_object());
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use intents_integration_tests::{events, setup, LIQUIDITY};
+use serde_json::json;
+
+#[tokio::test]
+async fn executed_intents_deliver_tokens_through_the_callback() -> anyhow::Result<()> {
+    let env = setup().await?;
+    let receiver = env.receiver.id().to_string();
+    let intent_id = env.create_approved(env.intent(&receiver, 1_000, 1)).await?;
+
+    let result = env.execute(&intent_id).await?;
+    assert!(result.is_success(), "{:?}", result.receipt_failures());
+    assert!(result.json::<bool>()?);
+
+    assert_eq!(env.ft_balance_of(&receiver).await?, 1_000);
+    assert_eq!(
+        env.ft_balance_of(env.executor.id().as_str()).await?,
+        LIQUIDITY - 1_000
+    );
+    assert_eq!(
+        env.view("get_intent_status", json!({ "intent_id": intent_id }))
+            .await?,
+        json!("Completed")
+    );
+
+    // The transfer's own event is logged between the executor's two
+    let events = events(&result);
+    let names: Vec<_> = events
+        .iter()
+        .map(|(standard, event, _)| (standard.as_str(), event.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("near-intents", "intent_executed"),
+            ("nep141", "ft_transfer"),
+            ("near-intents", "intent_completed"),
+        ]
+    );
+    assert_eq!(events[0].2["intent_id"], intent_id);
+    assert_eq!(events[0].2["receiver"], receiver);
+    assert_eq!(events[1].2[0]["new_owner_id"], receiver);
+    assert_eq!(events[1].2[0]["amount"], "1000");
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_transfers_fail_the_intent_in_the_callback() -> anyhow::Result<()> {
+    let env = setup().await?;
+    // Not registered with the token, so its ft_transfer panics
+    let receiver = "unregistered.test.near";
+    let intent_id = env.create_approved(env.intent(receiver, 1_000, 1)).await?;
+
+    let result = env.execute(&intent_id).await?;
+    assert!(result.is_success(), "{:?}", result.receipt_failures());
+    assert!(!result.json::<bool>()?);
+
+    assert_eq!(
+        env.ft_balance_of(env.executor.id().as_str()).await?,
+        LIQUIDITY
+    );
+    assert_eq!(
+        env.view("get_intent_status", json!({ "intent_id": intent_id }))
+            .await?,
+        json!({ "Failed": "transfer failed" })
+    );
+    assert_eq!(
+        env.view("get_refundable_intents", json!({})).await?,
+        json!([intent_id])
+    );
+
+    let events = events(&result);
+    let (_, event, data) = events.last().unwrap();
+    assert_eq!(event, "intent_failed");
+    assert_eq!(data["reason"], "transfer failed");
+    Ok(())
+}
@@ -0,0 +1,204 @@
+//! Model Context Protocol server for the CrossChainExecutor.
+//!
+//! Speaks MCP over stdio, one JSON-RPC message per line, and exposes the
+//! executor to agents as tools: creating intents, reading their status,
+//! listing pending intents and the supported tokens. Reads go to the
+//! executor's final state; `create_intent` is signed with the agent key,
+//! and without one the server is read-only. Diagnostics go to stderr, as
+//! stdout carries the protocol.
+//!
+//! Configured through `NEAR_NODE_URL` and `NEAR_EXECUTOR_ID`, plus
+//! `NEAR_ACCOUNT_ID` and `NEAR_PRIVATE_KEY` for the agent.
+
+use anyhow::Context;
+use near_crypto::InMemorySigner;
+use near_primitives::types::AccountId;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+mod near;
+mod tools;
+
+use near::NearClient;
+
+/// Protocol revision the server implements
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Deserialize)]
+struct Request {
+    /// Absent on notifications, which get no response
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct Server {
+    near: NearClient,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let rpc_url = std::env::var("NEAR_NODE_URL").context("NEAR_NODE_URL is not set")?;
+    let executor_id: AccountId = std::env::var("NEAR_EXECUTOR_ID")
+        .context("NEAR_EXECUTOR_ID is not set")?
+        .parse()?;
+    let signer = match (
+        std::env::var("NEAR_ACCOUNT_ID"),
+        std::env::var("NEAR_PRIVATE_KEY"),
+    ) {
+        (Ok(account_id), Ok(private_key)) => Some(InMemorySigner::from_secret_key(
+            account_id.parse()?,
+            private_key.parse()?,
+        )),
+        _ => {
+            eprintln!("No agent key configured, serving read-only");
+            None
+        }
+    };
+    let server = Server {
+        near: NearClient::new(&rpc_url, executor_id, signer),
+    };
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_message(&line).await {
+            stdout.write_all(response.to_string().as_bytes()).await?;
+            stdout.write_all(b"\n").await?;
+            stdout.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+impl Server {
+    /// Handle one JSON-RPC message, returning the response to write if any
+    async fn handle_message(&self, message: &str) -> Option<Value> {
+        let request: Request = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(error) => {
+                return Some(error_response(Value::Null, PARSE_ERROR, &error.to_string()))
+            }
+        };
+        let id = request.id?;
+        Some(match self.handle(&request.method, &request.params).await {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    async fn handle(&self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tools::definitions() })),
+            "tools/call" => {
+                let name = params
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| (INVALID_PARAMS, "Missing tool name".to_string()))?;
+                let args = params.get("arguments").cloned().unwrap_or(json!({}));
+                // Tool failures are results the model should see, not
+                // protocol errors
+                Ok(match tools::call(&self.near, name, &args).await {
+                    Ok(result) => json!({
+                        "content": [{ "type": "text", "text": result.to_string() }],
+                        "isError": false,
+                    }),
+                    Err(error) => json!({
+                        "content": [{ "type": "text", "text": format!("{:#}", error) }],
+                        "isError": true,
+                    }),
+                })
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method {}", method))),
+        }
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> Server {
+        Server {
+            near: NearClient::new(
+                "http://127.0.0.1:1",
+                "executor.testnet".parse().unwrap(),
+                None,
+            ),
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_tools_after_initializing() {
+        let server = server();
+        let init = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#)
+            .await
+            .unwrap();
+        assert_eq!(init["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert!(server
+            .handle_message(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+            .await
+            .is_none());
+
+        let list = server
+            .handle_message(r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#)
+            .await
+            .unwrap();
+        let names: Vec<_> = list["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tool| tool["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "create_intent",
+                "get_intent_status",
+                "list_pending_intents",
+                "get_supported_tokens"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn tool_failures_are_reported_as_results() {
+        let server = server();
+        let response = server
+            .handle_message(
+                r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"create_intent","arguments":{"intent":{}}}}"#,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+        let text = response["result"]["content"][0]["text"].as_str().unwrap();
+        assert!(text.contains("No agent key configured"));
+    }
+}
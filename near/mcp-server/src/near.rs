@@ -0,0 +1,100 @@
+//! Views and signed calls on the executor.
+
+use anyhow::{bail, Context};
+use near_crypto::InMemorySigner;
+use near_jsonrpc_client::{methods, JsonRpcClient};
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::transaction::{Action, FunctionCallAction, Transaction};
+use near_primitives::types::{AccountId, Balance, BlockReference, Finality, FunctionArgs, Gas};
+use near_primitives::views::{FinalExecutionStatus, QueryRequest};
+use serde_json::Value;
+
+pub struct NearClient {
+    client: JsonRpcClient,
+    /// Agent account signing calls, if the server may change state
+    signer: Option<InMemorySigner>,
+    contract_id: AccountId,
+}
+
+impl NearClient {
+    pub fn new(rpc_url: &str, contract_id: AccountId, signer: Option<InMemorySigner>) -> Self {
+        Self {
+            client: JsonRpcClient::connect(rpc_url),
+            signer,
+            contract_id,
+        }
+    }
+
+    /// Call a view method of the executor on final state
+    pub async fn view(&self, method: &str, args: Value) -> anyhow::Result<Value> {
+        let response = self
+            .client
+            .call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+                request: QueryRequest::CallFunction {
+                    account_id: self.contract_id.clone(),
+                    method_name: method.to_string(),
+                    args: FunctionArgs::from(args.to_string().into_bytes()),
+                },
+            })
+            .await?;
+        match response.kind {
+            QueryResponseKind::CallResult(result) => Ok(serde_json::from_slice(&result.result)?),
+            _ => bail!("Unexpected response to {}", method),
+        }
+    }
+
+    /// Sign and send a call to the executor as the agent, returning the
+    /// transaction hash once it completes
+    pub async fn call(
+        &self,
+        method: &str,
+        args: Value,
+        deposit: Balance,
+        gas: Gas,
+    ) -> anyhow::Result<String> {
+        let signer = self
+            .signer
+            .as_ref()
+            .context("No agent key configured, set NEAR_ACCOUNT_ID and NEAR_PRIVATE_KEY")?;
+        let access_key = self
+            .client
+            .call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+                request: QueryRequest::ViewAccessKey {
+                    account_id: signer.account_id.clone(),
+                    public_key: signer.public_key.clone(),
+                },
+            })
+            .await?;
+        let nonce = match access_key.kind {
+            QueryResponseKind::AccessKey(key) => key.nonce,
+            _ => bail!("Unexpected access key response"),
+        };
+
+        let transaction = Transaction {
+            signer_id: signer.account_id.clone(),
+            public_key: signer.public_key.clone(),
+            nonce: nonce + 1,
+            receiver_id: self.contract_id.clone(),
+            block_hash: access_key.block_hash,
+            actions: vec![Action::FunctionCall(FunctionCallAction {
+                method_name: method.to_string(),
+                args: args.to_string().into_bytes(),
+                gas,
+                deposit,
+            })],
+        };
+        let outcome = self
+            .client
+            .call(methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest {
+                signed_transaction: transaction.sign(signer),
+            })
+            .await?;
+        match outcome.status {
+            FinalExecutionStatus::SuccessValue(_) => Ok(outcome.transaction.hash.to_string()),
+            FinalExecutionStatus::Failure(error) => bail!("{} failed: {}", method, error),
+            status => bail!("{} did not complete: {:?}", method, status),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Tools the server exposes, each backed by the executor.
+
+use anyhow::Context;
+use near_primitives::types::{Balance, Gas};
+use serde_json::{json, Value};
+
+use crate::near::NearClient;
+
+/// Gas attached to `create_intent`
+const CREATE_INTENT_GAS: Gas = 50_000_000_000_000;
+
+/// NEAR attached to `create_intent` for storage when the caller sets none;
+/// the executor refunds what it does not use
+const DEFAULT_STORAGE_DEPOSIT: Balance = 100_000_000_000_000_000_000_000;
+
+/// Most intents `list_pending_intents` returns in one page
+const MAX_PAGE_LIMIT: u64 = 200;
+
+/// Tool descriptions returned by `tools/list`
+pub fn definitions() -> Value {
+    json!([
+        {
+            "name": "create_intent",
+            "description": "Submit a cross-chain intent to the executor as the agent. The intent's `id` must be derived from its deposit, see `compute_intent_id` on the executor.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "intent": {
+                        "type": "object",
+                        "description": "CrossChainIntent as the executor's create_intent takes it"
+                    },
+                    "storage_deposit": {
+                        "type": "string",
+                        "description": "yoctoNEAR attached for the intent's storage, 0.1 NEAR by default"
+                    }
+                },
+                "required": ["intent"]
+            }
+        },
+        {
+            "name": "get_intent_status",
+            "description": "Get the status of an intent, or null if it does not exist.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "intent_id": { "type": "string" } },
+                "required": ["intent_id"]
+            }
+        },
+        {
+            "name": "list_pending_intents",
+            "description": "List pending intents in creation order.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "from_index": { "type": "integer", "minimum": 0 },
+                    "limit": { "type": "integer", "minimum": 1, "maximum": MAX_PAGE_LIMIT }
+                }
+            }
+        },
+        {
+            "name": "get_supported_tokens",
+            "description": "List the source chain tokens the executor maps to NEAR tokens.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "chain_id": {
+                        "type": "integer",
+                        "description": "EIP-155 ID of the source chain, all chains when unset"
+                    }
+                }
+            }
+        }
+    ])
+}
+
+/// Run a tool, returning its result
+pub async fn call(near: &NearClient, name: &str, args: &Value) -> anyhow::Result<Value> {
+    match name {
+        "create_intent" => {
+            let intent = args.get("intent").context("Missing intent")?;
+            let deposit = match args.get("storage_deposit").and_then(Value::as_str) {
+                Some(deposit) => deposit.parse().context("Invalid storage_deposit")?,
+                None => DEFAULT_STORAGE_DEPOSIT,
+            };
+            let transaction_hash = near
+                .call(
+                    "create_intent",
+                    json!({ "intent": intent }),
+                    deposit,
+                    CREATE_INTENT_GAS,
+                )
+                .await?;
+            Ok(json!({ "intent_id": intent.get("id"), "transaction_hash": transaction_hash }))
+        }
+        "get_intent_status" => {
+            let intent_id = args
+                .get("intent_id")
+                .and_then(Value::as_str)
+                .context("Missing intent_id")?;
+            near.view("get_intent_status", json!({ "intent_id": intent_id }))
+                .await
+        }
+        "list_pending_intents" => {
+            let from_index = args.get("from_index").and_then(Value::as_u64);
+            let limit = args
+                .get("limit")
+                .and_then(Value::as_u64)
+                .map(|limit| limit.min(MAX_PAGE_LIMIT));
+            near.view(
+                "get_intents_by_status",
+                json!({
+                    "status": "Pending",
+                    // U128 indexes are passed as strings
+                    "from_index": from_index.map(|index| index.to_string()),
+                    "limit": limit,
+                }),
+            )
+            .await
+        }
+        "get_supported_tokens" => {
+            let chain_id = args.get("chain_id").and_then(Value::as_u64);
+            near.view("get_supported_tokens", json!({ "chain_id": chain_id }))
+                .await
+        }
+        _ => anyhow::bail!("Unknown tool {}", name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn near() -> NearClient {
+        NearClient::new(
+            "http://127.0.0.1:1",
+            "executor.testnet".parse().unwrap(),
+            None,
+        )
+    }
+
+    #[test]
+    fn definitions_require_only_declared_properties() {
+        for tool in definitions().as_array().unwrap() {
+            let schema = &tool["inputSchema"];
+            assert_eq!(schema["type"], "object", "{}", tool["name"]);
+            assert!(tool["description"].is_string(), "{}", tool["name"]);
+            for required in schema["required"].as_array().into_iter().flatten() {
+                let required = required.as_str().unwrap();
+                assert!(
+                    schema["properties"].get(required).is_some(),
+                    "{} requires undeclared {}",
+                    tool["name"],
+                    required
+                );
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn calls_with_invalid_arguments_fail_before_reaching_the_node() {
+        let near = near();
+        let error = call(&near, "get_intent_status", &json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Missing intent_id");
+
+        let intent = json!({ "intent": {}, "storage_deposit": "lots" });
+        let error = call(&near, "create_intent", &intent).await.unwrap_err();
+        assert_eq!(error.to_string(), "Invalid storage_deposit");
+    }
+
+    #[tokio::test]
+    async fn unknown_tools_are_rejected() {
+        let error = call(&near(), "drain_bridge", &json!({})).await.unwrap_err();
+        assert_eq!(error.to_string(), "Unknown tool drain_bridge");
+    }
+}
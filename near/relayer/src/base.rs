@@ -0,0 +1,148 @@
+//! Base side: bridge deposits and the receipt proofs backing them.
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use ethers::abi::AbiDecode;
+use ethers::contract::abigen;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, H256, U256};
+use ethers::utils::keccak256;
+
+use crate::proof;
+
+abigen!(
+    Bridge,
+    r#"[
+        event TokensLocked(string indexed intentId, address indexed token, address indexed from, string nearReceiver, uint256 amount, uint256 timestamp)
+        function lockTokens(string intentId, address token, address from, uint256 amount, string nearReceiver)
+    ]"#
+);
+
+/// A `TokensLocked` log of the bridge
+pub struct Deposit {
+    /// Typed hash of the intent's terms, as passed to `lockTokens`
+    pub intent_id: String,
+    pub sender: Address,
+    pub token: Address,
+    pub receiver: String,
+    pub amount: U256,
+    pub block_number: u64,
+    pub transaction_hash: H256,
+    /// Position of the log in the block
+    pub block_log_index: U256,
+}
+
+/// Receipt proof of a deposit, as the executor's `BridgeProof` holds it
+pub struct ReceiptProof {
+    pub block_number: u64,
+    pub timestamp: u64,
+    pub transaction_hash: H256,
+    pub receipt: Vec<u8>,
+    pub receipt_index: u64,
+    pub proof_nodes: Vec<Vec<u8>>,
+    /// Position of the log in the receipt
+    pub log_index: u64,
+}
+
+pub struct BaseClient {
+    provider: Arc<Provider<Http>>,
+    bridge: Bridge<Provider<Http>>,
+}
+
+impl BaseClient {
+    pub fn new(rpc_url: &str, bridge_address: Address) -> anyhow::Result<Self> {
+        let provider = Arc::new(Provider::<Http>::try_from(rpc_url)?);
+        let bridge = Bridge::new(bridge_address, provider.clone());
+        Ok(Self { provider, bridge })
+    }
+
+    pub async fn block_number(&self) -> anyhow::Result<u64> {
+        Ok(self.provider.get_block_number().await?.as_u64())
+    }
+
+    /// Bridge deposits logged in blocks `from..=to`. The intent ID is only
+    /// logged hashed, so it is read from the `lockTokens` call itself;
+    /// deposits made through another contract are skipped.
+    pub async fn deposits(&self, from: u64, to: u64) -> anyhow::Result<Vec<Deposit>> {
+        let logs = self
+            .bridge
+            .tokens_locked_filter()
+            .from_block(from)
+            .to_block(to)
+            .query_with_meta()
+            .await?;
+
+        let mut deposits = Vec::with_capacity(logs.len());
+        for (event, meta) in logs {
+            let transaction = self
+                .provider
+                .get_transaction(meta.transaction_hash)
+                .await?
+                .context("Deposit transaction not found")?;
+            let call = match LockTokensCall::decode(&transaction.input) {
+                Ok(call) if keccak256(call.intent_id.as_bytes()) == event.intent_id.0 => call,
+                _ => {
+                    eprintln!(
+                        "Skipping deposit in {:?}: not a direct lockTokens call",
+                        meta.transaction_hash
+                    );
+                    continue;
+                }
+            };
+            deposits.push(Deposit {
+                intent_id: call.intent_id,
+                sender: event.from,
+                token: event.token,
+                receiver: event.near_receiver,
+                amount: event.amount,
+                block_number: meta.block_number.as_u64(),
+                transaction_hash: meta.transaction_hash,
+                block_log_index: meta.log_index,
+            });
+        }
+        Ok(deposits)
+    }
+
+    /// Prove a deposit's receipt against its block's receipts root
+    pub async fn receipt_proof(&self, deposit: &Deposit) -> anyhow::Result<ReceiptProof> {
+        let block = self
+            .provider
+            .get_block(deposit.block_number)
+            .await?
+            .context("Deposit block not found")?;
+        let mut receipts = self
+            .provider
+            .get_block_receipts(deposit.block_number)
+            .await?;
+        receipts.sort_by_key(|receipt| receipt.transaction_index);
+
+        let index = receipts
+            .iter()
+            .position(|receipt| receipt.transaction_hash == deposit.transaction_hash)
+            .context("Deposit receipt not found in its block")?;
+        let log_index = receipts[index]
+            .logs
+            .iter()
+            .position(|log| log.log_index == Some(deposit.block_log_index))
+            .context("Deposit log not found in its receipt")?;
+
+        let encoded: Vec<Vec<u8>> = receipts.iter().map(proof::encode_receipt).collect();
+        let (root, proof_nodes) = proof::receipt_proof(&encoded, index)?;
+        anyhow::ensure!(
+            root == block.receipts_root.0,
+            "Receipts of block {} do not match its receipts root",
+            deposit.block_number
+        );
+
+        Ok(ReceiptProof {
+            block_number: deposit.block_number,
+            timestamp: block.timestamp.as_u64(),
+            transaction_hash: deposit.transaction_hash,
+            receipt: encoded[index].clone(),
+            receipt_index: index as u64,
+            proof_nodes,
+            log_index: log_index as u64,
+        })
+    }
+}
@@ -0,0 +1,321 @@
+//! Base to NEAR relayer for the CrossChainExecutor.
+//!
+//! Polls the Base bridge for `TokensLocked` logs and, once a deposit is
+//! `CONFIRMATIONS` blocks deep and the executor's light client has reached
+//! its block, submits its intent to the executor with `create_intent`,
+//! carrying a proof of the deposit receipt built from the block's receipts. Intents that already exist on NEAR are skipped, so
+//! relaying the same blocks twice is harmless, and failed submissions are
+//! retried with backoff. The next block to relay is kept in
+//! `RELAYER_CURSOR_FILE` across restarts.
+//!
+//! Configured through the environment, or a `.env` file:
+//!
+//! | Variable | |
+//! |---|---|
+//! | `BASE_RPC_URL`, `BRIDGE_ADDRESS` | Base endpoint and bridge contract |
+//! | `NEAR_NODE_URL`, `NEAR_EXECUTOR_ID` | NEAR endpoint and executor account |
+//! | `NEAR_ACCOUNT_ID`, `NEAR_PRIVATE_KEY` | Agent account submitting intents |
+//! | `INTENT_TERMS_URL` | Service issuing intent IDs, see [`terms`] |
+//! | `START_BLOCK` | First Base block to relay when there is no cursor |
+//! | `CONFIRMATIONS` | Blocks a deposit must be buried under, 12 by default |
+
+use std::fmt::Display;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Context;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ethers::types::Address;
+use near_primitives::types::{AccountId, Balance, Gas};
+use serde_json::{json, Value};
+
+mod base;
+mod near;
+mod proof;
+mod terms;
+
+use base::{BaseClient, Deposit, ReceiptProof};
+use near::{error_code, CallOutcome, NearClient};
+use terms::{IntentTerms, TermsClient};
+
+/// EIP-155 ID of Base mainnet
+const BASE_CHAIN_ID: u64 = 8453;
+
+/// Gas attached to `create_intent`
+const CREATE_INTENT_GAS: Gas = 50_000_000_000_000;
+
+/// Most blocks read in one `eth_getLogs` request
+const MAX_BLOCK_RANGE: u64 = 2_000;
+
+/// Attempts at an RPC step before giving up on it
+const MAX_ATTEMPTS: u32 = 5;
+
+struct Config {
+    base_rpc_url: String,
+    bridge_address: Address,
+    near_node_url: String,
+    executor_id: AccountId,
+    account_id: AccountId,
+    private_key: near_crypto::SecretKey,
+    intent_terms_url: String,
+    start_block: u64,
+    confirmations: u64,
+    /// NEAR attached to `create_intent` for the intent's storage; the
+    /// executor refunds what it does not use
+    storage_deposit: Balance,
+    poll_interval: Duration,
+    cursor_file: PathBuf,
+}
+
+impl Config {
+    fn from_env() -> anyhow::Result<Self> {
+        Ok(Self {
+            base_rpc_url: env_var("BASE_RPC_URL")?,
+            bridge_address: parse_env("BRIDGE_ADDRESS")?,
+            near_node_url: env_var("NEAR_NODE_URL")?,
+            executor_id: parse_env("NEAR_EXECUTOR_ID")?,
+            account_id: parse_env("NEAR_ACCOUNT_ID")?,
+            private_key: parse_env("NEAR_PRIVATE_KEY")?,
+            intent_terms_url: env_var("INTENT_TERMS_URL")?,
+            start_block: parse_env("START_BLOCK")?,
+            confirmations: parse_env_or("CONFIRMATIONS", 12)?,
+            storage_deposit: parse_env_or("STORAGE_DEPOSIT", 100_000_000_000_000_000_000_000)?,
+            poll_interval: Duration::from_secs(parse_env_or("POLL_INTERVAL_SECS", 4)?),
+            cursor_file: parse_env_or("RELAYER_CURSOR_FILE", PathBuf::from("relayer.cursor"))?,
+        })
+    }
+}
+
+fn env_var(name: &str) -> anyhow::Result<String> {
+    std::env::var(name).with_context(|| format!("{} is not set", name))
+}
+
+fn parse_env<T: FromStr>(name: &str) -> anyhow::Result<T>
+where
+    T::Err: Display,
+{
+    env_var(name)?
+        .parse()
+        .map_err(|error| anyhow::anyhow!("Invalid {}: {}", name, error))
+}
+
+fn parse_env_or<T: FromStr>(name: &str, default: T) -> anyhow::Result<T>
+where
+    T::Err: Display,
+{
+    match std::env::var(name) {
+        Ok(_) => parse_env(name),
+        Err(_) => Ok(default),
+    }
+}
+
+struct Relayer {
+    config: Config,
+    base: BaseClient,
+    near: NearClient,
+    terms: TermsClient,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    let config = Config::from_env()?;
+    let relayer = Relayer {
+        base: BaseClient::new(&config.base_rpc_url, config.bridge_address)?,
+        near: NearClient::new(
+            &config.near_node_url,
+            config.executor_id.clone(),
+            config.account_id.clone(),
+            config.private_key.clone(),
+        ),
+        terms: TermsClient::new(&config.intent_terms_url),
+        config,
+    };
+    relayer.run().await
+}
+
+impl Relayer {
+    async fn run(&self) -> anyhow::Result<()> {
+        let mut cursor = read_cursor(&self.config.cursor_file)?.unwrap_or(self.config.start_block);
+        println!("Relaying Base deposits from block {}", cursor);
+        loop {
+            let head = retry("Reading the Base head", || self.base.block_number()).await?;
+            let confirmed = head.saturating_sub(self.config.confirmations);
+            if cursor > confirmed {
+                tokio::time::sleep(self.config.poll_interval).await;
+                continue;
+            }
+
+            let to = confirmed.min(cursor + MAX_BLOCK_RANGE - 1);
+            let deposits =
+                retry("Reading bridge deposits", || self.base.deposits(cursor, to)).await?;
+            for deposit in &deposits {
+                self.relay(deposit).await?;
+            }
+            cursor = to + 1;
+            write_cursor(&self.config.cursor_file, cursor)?;
+        }
+    }
+
+    /// Submit a deposit's intent unless it already exists. Errors only when
+    /// retries are exhausted, so the deposit is relayed again on restart.
+    async fn relay(&self, deposit: &Deposit) -> anyhow::Result<()> {
+        let terms_hash = &deposit.intent_id;
+        let terms = match retry("Fetching intent terms", || self.terms.fetch(terms_hash)).await? {
+            Some(terms) => terms,
+            None => {
+                eprintln!("Skipping {}: no terms for the intent", terms_hash);
+                return Ok(());
+            }
+        };
+        let proof = retry("Building the receipt proof", || {
+            self.base.receipt_proof(deposit)
+        })
+        .await?;
+        let epoch: u64 = retry("Reading the validator epoch", || {
+            self.near.view("get_current_epoch", json!({}))
+        })
+        .await?;
+        let mut intent = intent_json(deposit, &proof, &terms, epoch)?;
+        // The executor derives the ID from the deposit the proof locates
+        let intent_id: String = retry("Deriving the intent ID", || {
+            self.near
+                .view("compute_intent_id", json!({ "intent": intent }))
+        })
+        .await?;
+        intent["id"] = json!(intent_id);
+
+        let status: Option<Value> = retry("Reading the intent status", || {
+            self.near
+                .view("get_intent_status", json!({ "intent_id": intent_id }))
+        })
+        .await?;
+        if status.is_some() {
+            println!("{} already relayed", intent_id);
+            return Ok(());
+        }
+        // The executor turns away proofs of blocks past its latest checkpoint
+        self.wait_for_checkpoint(proof.block_number).await?;
+
+        let outcome = retry("Submitting the intent", || {
+            self.near.call(
+                "create_intent",
+                json!({ "intent": intent }),
+                self.config.storage_deposit,
+                CREATE_INTENT_GAS,
+            )
+        })
+        .await?;
+        match outcome {
+            CallOutcome::Succeeded => println!("Relayed {}", intent_id),
+            // Submitted by an earlier attempt whose outcome was lost
+            CallOutcome::Failed(error) if error_code(&error) == Some("ALREADY_EXECUTED") => {
+                println!("{} already relayed", intent_id)
+            }
+            CallOutcome::Failed(error) => eprintln!("Executor rejected {}: {}", intent_id, error),
+        }
+        Ok(())
+    }
+
+    /// Wait until the executor's latest Base checkpoint reaches `block_number`
+    async fn wait_for_checkpoint(&self, block_number: u64) -> anyhow::Result<()> {
+        loop {
+            let checkpoint: Option<Value> = retry("Reading the latest checkpoint", || {
+                self.near.view("get_checkpoint", json!({}))
+            })
+            .await?;
+            let checkpointed =
+                checkpoint.and_then(|checkpoint| checkpoint["block_number"].as_u64());
+            if checkpointed.is_some_and(|checkpointed| checkpointed >= block_number) {
+                return Ok(());
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+        }
+    }
+}
+
+/// `CrossChainIntent` argument of `create_intent` for a deposit, its ID
+/// left for the executor to derive
+fn intent_json(
+    deposit: &Deposit,
+    proof: &ReceiptProof,
+    terms: &IntentTerms,
+    epoch: u64,
+) -> anyhow::Result<Value> {
+    let amount = u128::try_from(deposit.amount)
+        .map_err(|_| anyhow::anyhow!("Deposit amount of {} overflows", deposit.intent_id))?;
+    Ok(json!({
+        "id": "",
+        "sender": format!("{:?}", deposit.sender),
+        "receiver": deposit.receiver,
+        "token": format!("{:?}", deposit.token),
+        "amount": amount.to_string(),
+        "proof": {
+            "block_number": proof.block_number,
+            "timestamp": proof.timestamp,
+            "transaction_hash": format!("{:?}", proof.transaction_hash),
+            "receipt": BASE64.encode(&proof.receipt),
+            "receipt_index": proof.receipt_index,
+            "proof_nodes": proof.proof_nodes.iter().map(|node| BASE64.encode(node)).collect::<Vec<_>>(),
+            "log_index": proof.log_index,
+        },
+        "status": "Pending",
+        "signatures": [],
+        "expires_at": terms.expires_at,
+        "epoch": epoch,
+        "nonce": terms.nonce,
+        "sender_signature": terms.sender_signature,
+        "chain_id": BASE_CHAIN_ID,
+        "settle_in_defuse": terms.settle_in_defuse,
+        "referrer": terms.referrer,
+        "swap": terms.swap,
+        "to_stablecoin": terms.to_stablecoin,
+        "aurora_recipient": terms.aurora_recipient,
+        "mt_token_id": terms.mt_token_id,
+        "execute_after": terms.execute_after,
+        "depends_on": terms.depends_on,
+        "actions": terms.actions,
+        "min_out": terms.min_out,
+    }))
+}
+
+/// Run an RPC step, retrying failures with exponential backoff
+async fn retry<T, F, Fut>(what: &str, mut step: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let mut delay = Duration::from_secs(1);
+    let mut attempt = 1;
+    loop {
+        match step().await {
+            Ok(value) => return Ok(value),
+            Err(error) if attempt < MAX_ATTEMPTS => {
+                eprintln!("{} failed (attempt {}): {:#}", what, attempt, error);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(error) => return Err(error.context(format!("{} failed", what))),
+        }
+    }
+}
+
+fn read_cursor(path: &Path) -> anyhow::Result<Option<u64>> {
+    match std::fs::read_to_string(path) {
+        Ok(cursor) => Ok(Some(cursor.trim().parse().context("Invalid cursor file")?)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+fn write_cursor(path: &Path, cursor: u64) -> anyhow::Result<()> {
+    // Write then rename so a crash never leaves a torn cursor
+    let temporary = path.with_extension("tmp");
+    std::fs::write(&temporary, cursor.to_string())?;
+    std::fs::rename(&temporary, path)?;
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! NEAR side: views and signed calls on the executor.
+
+use anyhow::bail;
+use near_crypto::{InMemorySigner, SecretKey};
+use near_jsonrpc_client::{methods, JsonRpcClient};
+use near_jsonrpc_primitives::types::query::QueryResponseKind;
+use near_primitives::transaction::{Action, FunctionCallAction, Transaction};
+use near_primitives::types::{AccountId, Balance, BlockReference, Finality, FunctionArgs, Gas};
+use near_primitives::views::{FinalExecutionStatus, QueryRequest};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Outcome of a call that reached the chain
+pub enum CallOutcome {
+    Succeeded,
+    /// The contract rejected the call
+    Failed(String),
+}
+
+/// Stable code of the contract error a failed call panicked with, if any
+pub fn error_code(error: &str) -> Option<&str> {
+    let start = error.find("\"code\":\"")? + "\"code\":\"".len();
+    let len = error[start..].find('"')?;
+    Some(&error[start..start + len])
+}
+
+pub struct NearClient {
+    client: JsonRpcClient,
+    signer: InMemorySigner,
+    contract_id: AccountId,
+}
+
+impl NearClient {
+    pub fn new(
+        rpc_url: &str,
+        contract_id: AccountId,
+        account_id: AccountId,
+        secret_key: SecretKey,
+    ) -> Self {
+        Self {
+            client: JsonRpcClient::connect(rpc_url),
+            signer: InMemorySigner::from_secret_key(account_id, secret_key),
+            contract_id,
+        }
+    }
+
+    /// Call a view method of the executor on final state
+    pub async fn view<T: DeserializeOwned>(&self, method: &str, args: Value) -> anyhow::Result<T> {
+        let response = self
+            .client
+            .call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+                request: QueryRequest::CallFunction {
+                    account_id: self.contract_id.clone(),
+                    method_name: method.to_string(),
+                    args: FunctionArgs::from(args.to_string().into_bytes()),
+                },
+            })
+            .await?;
+        match response.kind {
+            QueryResponseKind::CallResult(result) => Ok(serde_json::from_slice(&result.result)?),
+            _ => bail!("Unexpected response to {}", method),
+        }
+    }
+
+    /// Sign and send a call to the executor, waiting for its outcome
+    pub async fn call(
+        &self,
+        method: &str,
+        args: Value,
+        deposit: Balance,
+        gas: Gas,
+    ) -> anyhow::Result<CallOutcome> {
+        let access_key = self
+            .client
+            .call(methods::query::RpcQueryRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+                request: QueryRequest::ViewAccessKey {
+                    account_id: self.signer.account_id.clone(),
+                    public_key: self.signer.public_key.clone(),
+                },
+            })
+            .await?;
+        let nonce = match access_key.kind {
+            QueryResponseKind::AccessKey(key) => key.nonce,
+            _ => bail!("Unexpected access key response"),
+        };
+
+        let transaction = Transaction {
+            signer_id: self.signer.account_id.clone(),
+            public_key: self.signer.public_key.clone(),
+            nonce: nonce + 1,
+            receiver_id: self.contract_id.clone(),
+            block_hash: access_key.block_hash,
+            actions: vec![Action::FunctionCall(FunctionCallAction {
+                method_name: method.to_string(),
+                args: args.to_string().into_bytes(),
+                gas,
+                deposit,
+            })],
+        };
+        let outcome = self
+            .client
+            .call(methods::broadcast_tx_commit::RpcBroadcastTxCommitRequest {
+                signed_transaction: transaction.sign(&self.signer),
+            })
+            .await?;
+        Ok(match outcome.status {
+            FinalExecutionStatus::SuccessValue(_) => CallOutcome::Succeeded,
+            FinalExecutionStatus::Failure(error) => CallOutcome::Failed(error.to_string()),
+            status => bail!("Call did not complete: {:?}", status),
+        })
+    }
+}
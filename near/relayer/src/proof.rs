@@ -0,0 +1,82 @@
+//! Receipt inclusion proofs, in the form the executor verifies them.
+
+use std::sync::Arc;
+
+use eth_trie::{EthTrie, MemoryDB, Trie};
+use ethers::types::TransactionReceipt;
+use ethers::utils::rlp::{self, RlpStream};
+
+/// Consensus encoding of a receipt: `rlp([status, cumulative_gas, bloom,
+/// logs])`, prefixed with the transaction type for typed transactions
+pub fn encode_receipt(receipt: &TransactionReceipt) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(4);
+    stream.append(&receipt.status.unwrap_or_default());
+    stream.append(&receipt.cumulative_gas_used);
+    stream.append(&receipt.logs_bloom);
+    stream.begin_list(receipt.logs.len());
+    for log in &receipt.logs {
+        stream.begin_list(3);
+        stream.append(&log.address);
+        stream.append_list(&log.topics);
+        stream.append(&log.data.to_vec());
+    }
+
+    let tx_type = receipt.transaction_type.unwrap_or_default().as_u64();
+    let mut encoded = Vec::new();
+    if tx_type > 0 {
+        encoded.push(tx_type as u8);
+    }
+    encoded.extend_from_slice(&stream.out());
+    encoded
+}
+
+/// Root of the receipts trie of a block's encoded receipts, in transaction
+/// order, and the proof nodes of the receipt at `index`
+pub fn receipt_proof(
+    receipts: &[Vec<u8>],
+    index: usize,
+) -> anyhow::Result<([u8; 32], Vec<Vec<u8>>)> {
+    let mut trie = EthTrie::new(Arc::new(MemoryDB::new(true)));
+    for (position, receipt) in receipts.iter().enumerate() {
+        trie.insert(&rlp::encode(&(position as u64)), receipt)?;
+    }
+    let root = trie.root_hash()?;
+    let nodes = trie.get_proof(&rlp::encode(&(index as u64)))?;
+    Ok((root.0, nodes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::U64;
+
+    #[test]
+    fn typed_receipts_are_prefixed_with_their_type() {
+        let mut receipt = TransactionReceipt {
+            status: Some(U64::from(1)),
+            ..Default::default()
+        };
+        let legacy = encode_receipt(&receipt);
+        receipt.transaction_type = Some(U64::from(2));
+        let typed = encode_receipt(&receipt);
+
+        assert_eq!(typed[0], 2);
+        assert_eq!(&typed[1..], &legacy[..]);
+        // Status is the first field of the receipt list
+        let fields = rlp::Rlp::new(&legacy);
+        assert_eq!(fields.at(0).unwrap().data().unwrap(), &[1]);
+    }
+
+    #[test]
+    fn proofs_end_at_the_proven_receipt() {
+        let receipts: Vec<Vec<u8>> = (0..20u8).map(|seed| vec![seed; 40]).collect();
+        let (root, nodes) = receipt_proof(&receipts, 7).unwrap();
+        let (other_root, _) = receipt_proof(&receipts[..19], 7).unwrap();
+
+        assert_ne!(root, other_root);
+        let leaf = nodes.last().unwrap();
+        assert!(leaf
+            .windows(receipts[7].len())
+            .any(|window| window == &receipts[7][..]));
+    }
+}
@@ -0,0 +1,87 @@
+//! Intent terms that deposits do not carry.
+//!
+//! A deposit names the typed hash of terms the bridge never sees: the
+//! sender's nonce, the expiry and whether it settles into NEAR Intents. The
+//! relayer fetches them from the service that issued the hash, at
+//! `{INTENT_TERMS_URL}/{terms_hash}`, and the executor rejects any terms that
+//! do not hash to the one the deposit names, including the Ref Finance swap
+//! some intents make on arrival. The service also names the wallet or dApp that
+//! referred the intent, if any.
+
+use anyhow::Context;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct IntentTerms {
+    pub nonce: u64,
+    /// Block timestamp (ns) after which the intent can no longer execute,
+    /// as a decimal string
+    pub expires_at: String,
+    #[serde(default)]
+    pub settle_in_defuse: bool,
+    /// Base64 `r || s || v` signature of the sender over the typed hash
+    #[serde(default)]
+    pub sender_signature: Option<String>,
+    /// NEAR account of the wallet or dApp that routed the intent
+    #[serde(default)]
+    pub referrer: Option<String>,
+    /// Swap terms, passed through to the executor as is
+    #[serde(default)]
+    pub swap: Option<serde_json::Value>,
+    /// Whether the intent is delivered as its token's routed stablecoin
+    #[serde(default)]
+    pub to_stablecoin: bool,
+    /// Aurora EVM address the intent delivers to, if not a NEAR account
+    #[serde(default)]
+    pub aurora_recipient: Option<String>,
+    /// NEP-245 token ID delivered for multi-token deposits
+    #[serde(default)]
+    pub mt_token_id: Option<String>,
+    /// Block timestamp (ns) before which the intent may not execute, as a
+    /// decimal string
+    #[serde(default)]
+    pub execute_after: Option<String>,
+    /// ID of the intent that must complete before this one executes
+    #[serde(default)]
+    pub depends_on: Option<String>,
+    /// Actions the delivered tokens are split across, passed through to the
+    /// executor as is
+    #[serde(default)]
+    pub actions: Vec<serde_json::Value>,
+    /// Least the receiver takes from a solver quote, as a decimal string
+    #[serde(default)]
+    pub min_out: Option<String>,
+}
+
+pub struct TermsClient {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl TermsClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Terms hashing to `terms_hash`, `None` if the service does not know them
+    pub async fn fetch(&self, terms_hash: &str) -> anyhow::Result<Option<IntentTerms>> {
+        let response = self
+            .http
+            .get(format!("{}/{}", self.url, terms_hash))
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let terms = response
+            .error_for_status()?
+            .json()
+            .await
+            .context("Invalid intent terms")?;
+        Ok(Some(terms))
+    }
+}
@@ -0,0 +1,20 @@
+//! Writes the JSON Schemas of the executor's intent, proof and event
+//! payloads, one `<name>.json` per schema, for the agent layers to validate
+//! against.
+//!
+//! Usage: `cargo run -- [OUT_DIR]`, writing to `schemas/` by default.
+
+use std::path::PathBuf;
+use std::{env, fs, io};
+
+fn main() -> io::Result<()> {
+    let out_dir = PathBuf::from(env::args().nth(1).unwrap_or_else(|| "schemas".to_string()));
+    fs::create_dir_all(&out_dir)?;
+    for (name, schema) in near_intents::schema::schemas() {
+        let path = out_dir.join(format!("{}.json", name));
+        let json = serde_json::to_string_pretty(&schema).expect("Schemas serialize to JSON");
+        fs::write(&path, json + "\n")?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,18 @@
+#!/bin/bash
+
+# Exit on error
+set -e
+
+echo "Building wrapped token contract..."
+
+# Build the contract
+RUSTFLAGS='-C link-arg=-s' cargo build --target wasm32-unknown-unknown --release
+
+# Create res directory if it doesn't exist
+mkdir -p res
+
+# Copy wasm file to res directory
+cp ../target/wasm32-unknown-unknown/release/wrapped_token.wasm res/
+
+echo "Build complete! Contract binary is in res/wrapped_token.wasm"
+echo "To deploy, run: near deploy --accountId your.testnet.near --wasmFile res/wrapped_token.wasm"
\ No newline at end of file
@@ -0,0 +1,157 @@
+//! Wrapped source chain asset.
+//!
+//! A mintable and burnable NEP-141 token standing for an asset locked on a
+//! source chain. Only the minter, the CrossChainExecutor, can create tokens,
+//! which it does when an intent for the asset executes, so deliveries need no
+//! pre-funded liquidity. Holders burn their tokens to release the asset on
+//! the source chain.
+
+use near_contract_standards::fungible_token::events::{FtBurn, FtMint};
+use near_contract_standards::fungible_token::metadata::{
+    FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
+};
+use near_contract_standards::fungible_token::FungibleToken;
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::LazyOption;
+use near_sdk::json_types::U128;
+use near_sdk::{
+    assert_one_yocto, env, near_bindgen, AccountId, BorshStorageKey, PanicOnDefault, PromiseOrValue,
+};
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    FungibleToken,
+    Metadata,
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct WrappedToken {
+    /// Account allowed to mint, the CrossChainExecutor
+    minter: AccountId,
+    token: FungibleToken,
+    metadata: LazyOption<FungibleTokenMetadata>,
+}
+
+#[near_bindgen]
+impl WrappedToken {
+    #[init]
+    pub fn new(minter: AccountId, metadata: FungibleTokenMetadata) -> Self {
+        assert!(!env::state_exists(), "Already initialized");
+        assert_eq!(metadata.spec, FT_METADATA_SPEC, "Unsupported metadata spec");
+        Self {
+            minter,
+            token: FungibleToken::new(StorageKey::FungibleToken),
+            metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
+        }
+    }
+
+    /// Mint tokens to an account, registering it at the contract's expense
+    /// if needed. Only callable by the minter.
+    pub fn mint(&mut self, account_id: AccountId, amount: U128, memo: Option<String>) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.minter,
+            "Only the minter can mint"
+        );
+        assert!(amount.0 > 0, "Amount must be positive");
+        if !self.token.accounts.contains_key(&account_id) {
+            self.token.internal_register_account(&account_id);
+        }
+        self.token.internal_deposit(&account_id, amount.0);
+
+        FtMint {
+            owner_id: &account_id,
+            amount: &amount,
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+
+    /// Burn tokens held by the caller
+    #[payable]
+    pub fn burn(&mut self, amount: U128, memo: Option<String>) {
+        assert_one_yocto();
+        assert!(amount.0 > 0, "Amount must be positive");
+        let account_id = env::predecessor_account_id();
+        self.token.internal_withdraw(&account_id, amount.0);
+
+        FtBurn {
+            owner_id: &account_id,
+            amount: &amount,
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+
+    /// Get the account allowed to mint
+    pub fn get_minter(&self) -> AccountId {
+        self.minter.clone()
+    }
+}
+
+near_contract_standards::impl_fungible_token_core!(WrappedToken, token);
+near_contract_standards::impl_fungible_token_storage!(WrappedToken, token);
+
+#[near_bindgen]
+impl FungibleTokenMetadataProvider for WrappedToken {
+    fn ft_metadata(&self) -> FungibleTokenMetadata {
+        self.metadata.get().expect("Metadata not set")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_contract_standards::fungible_token::core::FungibleTokenCore;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    fn minter() -> AccountId {
+        "executor.near".parse().unwrap()
+    }
+
+    fn set_caller(account_id: &AccountId, deposit: u128) {
+        testing_env!(VMContextBuilder::new()
+            .current_account_id("wusdc.near".parse().unwrap())
+            .predecessor_account_id(account_id.clone())
+            .attached_deposit(deposit)
+            .build());
+    }
+
+    fn setup() -> WrappedToken {
+        set_caller(&minter(), 0);
+        WrappedToken::new(
+            minter(),
+            FungibleTokenMetadata {
+                spec: FT_METADATA_SPEC.to_string(),
+                name: "Wrapped Base USDC".to_string(),
+                symbol: "wUSDC".to_string(),
+                icon: None,
+                reference: None,
+                reference_hash: None,
+                decimals: 6,
+            },
+        )
+    }
+
+    #[test]
+    fn minter_mints_to_unregistered_accounts_and_holders_burn() {
+        let mut contract = setup();
+        contract.mint(accounts(1), U128(1_000), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(1_000));
+
+        set_caller(&accounts(1), 1);
+        contract.burn(U128(400), None);
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(600));
+        assert_eq!(contract.ft_total_supply(), U128(600));
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the minter can mint")]
+    fn mint_rejects_other_callers() {
+        let mut contract = setup();
+        set_caller(&accounts(1), 0);
+        contract.mint(accounts(1), U128(1_000), None);
+    }
+}